@@ -0,0 +1,57 @@
+use colored::*;
+use gramr::{ContractType, GenerationPlan, Manifest, PlannedFile, ProjectType, Result};
+use std::path::Path;
+
+/// Generate every resource in the manifest at `path` that is not already in the project
+pub fn execute_apply(path: &Path, dry_run: bool) -> Result<()> {
+    let manifest = Manifest::load(path)?;
+
+    for resource in &manifest.resources {
+        if let ContractType::MultiInheritance { base_type, extensions } = &resource.contract_type {
+            manifest.defaults.extensions.check_compatible(base_type, extensions)?;
+        }
+    }
+
+    let mut preview = GenerationPlan::new();
+    let mut generated = 0;
+    let mut up_to_date = 0;
+
+    for resource in &manifest.resources {
+        let mut plan = manifest.plan(resource, ProjectType::detect(&manifest.language)?)?;
+
+        if !plan.files.iter().any(PlannedFile::is_new) {
+            println!("{} Up to date: {} {}", "✓".green(), resource.kind.as_str(), resource.name);
+            up_to_date += 1;
+        } else if dry_run {
+            plan.files.retain(|file| file.is_new() || file.edit);
+            for dependency in plan.dependencies {
+                if !preview.dependencies.contains(&dependency) {
+                    preview.dependencies.push(dependency);
+                }
+            }
+            preview.files.extend(plan.files);
+        } else {
+            println!("{} Generating {} {}", "→".yellow(), resource.kind.as_str(), resource.name);
+            manifest.generate(resource, ProjectType::detect(&manifest.language)?)?;
+            generated += 1;
+        }
+    }
+
+    if dry_run {
+        if !preview.files.is_empty() {
+            if up_to_date > 0 {
+                println!();
+            }
+            preview.print_dry_run();
+        }
+        return Ok(());
+    }
+
+    println!(
+        "\n{} {} generated, {} up to date",
+        "🎉".bold(),
+        generated,
+        up_to_date
+    );
+    Ok(())
+}
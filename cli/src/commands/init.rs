@@ -0,0 +1,20 @@
+use colored::*;
+use gramr::{CargoProject, GramrError, Result};
+use std::path::Path;
+
+/// Create a Stylus-ready crate at `path`, named `name` or after the directory
+pub fn execute_init(path: &Path, name: Option<String>) -> Result<()> {
+    let root = std::path::absolute(path)?;
+    let name = match name {
+        Some(name) => name,
+        None => root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .ok_or_else(|| GramrError::Other(format!("Cannot name a crate after {}; pass --name", root.display())))?,
+    };
+
+    CargoProject::init(&root, &name)?;
+    println!("{} Created Stylus crate {} in {}", "✓".green(), name.cyan(), root.display());
+    println!("  Add a contract with: gramr new contract <Name> --rust-stylus");
+    Ok(())
+}
@@ -1,3 +1,9 @@
+pub mod apply;
+pub mod init;
 pub mod new;
+pub mod template;
 
-pub use new::execute_new;
\ No newline at end of file
+pub use apply::execute_apply;
+pub use init::execute_init;
+pub use new::execute_new;
+pub use template::{execute_template_add, execute_template_list};
\ No newline at end of file
@@ -1,9 +1,11 @@
 use gramr::{
-    AbstractContractGenerator, ConfigGenerator, ContractType, GenericContractGenerator, GramrError,
-    InterfaceGenerator, Language, LibraryGenerator, ProjectType, Result, ScriptGenerator,
-    TestGenerator, TokenExtension,
+    AbstractContractGenerator, AccessControl, ConfigGenerator, ContractType, Defaults, GenerationPlan, GenericContractGenerator, GramrError,
+    InterfaceGenerator, Language, LibraryGenerator, OverwritePolicy, ProjectType, Result, ScriptGenerator,
+    TestGenerator, TokenMetadata,
 };
+use std::path::PathBuf;
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_new(
     resource_type: &str,
     name: String,
@@ -14,19 +16,33 @@ pub fn execute_new(
     oz_erc1155: bool,
     upgradeable: bool,
     extensions: Vec<String>,
+    token_metadata: TokenMetadata,
     with_test: bool,
     with_script: bool,
     pragma: String,
     license: String,
+    oz_version: Option<String>,
+    access: Option<AccessControl>,
+    from: Option<PathBuf>,
+    into: Option<String>,
     with_section_markers: bool,
+    dry_run: bool,
+    overwrite: OverwritePolicy,
+    defaults: Defaults,
 ) -> Result<()> {
     let language = Language::from_flags(solidity, rust_stylus)?;
     let project = ProjectType::detect(&language)?;
+    if from.is_some() && resource_type != "interface" {
+        return Err(GramrError::Other("--from only applies to interfaces".to_string()));
+    }
+    if into.is_some() && resource_type != "abstract" {
+        return Err(GramrError::Other("--into only applies to abstract contracts".to_string()));
+    }
 
     match resource_type {
         "contract" => {
-            let contract_type = determine_contract_type(oz_erc20, oz_erc721, oz_erc1155, upgradeable, &extensions, &language)?;
-            let generator = GenericContractGenerator::new(
+            let contract_type = determine_contract_type(oz_erc20, oz_erc721, oz_erc1155, upgradeable, &extensions, &defaults)?;
+            let mut generator = GenericContractGenerator::new(
                 project,
                 language,
                 name,
@@ -35,11 +51,24 @@ pub fn execute_new(
                 with_script,
                 Some(pragma),
                 Some(license),
-            );
-            generator.generate()
+            )
+            .with_overwrite_policy(overwrite)
+            .with_defaults(defaults)
+            .with_token_metadata(token_metadata);
+            if let Some(version) = oz_version {
+                generator = generator.with_openzeppelin_version(version);
+            }
+            if let Some(access) = access {
+                generator = generator.with_access_control(access);
+            }
+            if dry_run {
+                preview(generator.plan()?)
+            } else {
+                generator.generate()
+            }
         }
         "library" => {
-            if oz_erc20 || oz_erc721 || oz_erc1155 || upgradeable || !extensions.is_empty() || with_test || with_script || with_section_markers {
+            if oz_erc20 || oz_erc721 || oz_erc1155 || upgradeable || !extensions.is_empty() || with_test || with_script || with_section_markers || oz_version.is_some() || !token_metadata.is_empty() {
                 return Err(GramrError::Other(
                     "Library generation doesn't support contract-specific flags".to_string()
                 ));
@@ -50,8 +79,14 @@ pub fn execute_new(
                 name,
                 Some(pragma),
                 Some(license),
-            );
-            generator.generate()
+            )
+            .with_overwrite_policy(overwrite)
+            .with_defaults(defaults);
+            if dry_run {
+                preview(generator.plan()?)
+            } else {
+                generator.generate()
+            }
         }
         "script" => {
             if rust_stylus {
@@ -59,15 +94,20 @@ pub fn execute_new(
                     "Script generation is not supported for Rust/Stylus projects".to_string()
                 ));
             }
-            if oz_erc20 || oz_erc721 || oz_erc1155 || upgradeable || !extensions.is_empty() || with_test || with_script || with_section_markers {
+            if oz_erc20 || oz_erc721 || oz_erc1155 || upgradeable || !extensions.is_empty() || with_test || with_script || with_section_markers || oz_version.is_some() || !token_metadata.is_empty() {
                 return Err(GramrError::Other(
                     "Script generation doesn't support contract-specific flags".to_string()
                 ));
             }
             match project {
                 ProjectType::Foundry(foundry_project) => {
-                    let generator = ScriptGenerator::new(foundry_project, name, pragma, license);
-                    generator.generate()
+                    let generator = ScriptGenerator::new(foundry_project, name, pragma, license)
+                        .with_overwrite_policy(overwrite);
+                    if dry_run {
+                        preview(generator.plan()?)
+                    } else {
+                        generator.generate()
+                    }
                 }
                 _ => Err(GramrError::Other("Script generation is only supported for Foundry projects".to_string()))
             }
@@ -78,51 +118,74 @@ pub fn execute_new(
                     "Test generation is not supported for Rust/Stylus projects".to_string()
                 ));
             }
-            if oz_erc20 || oz_erc721 || oz_erc1155 || upgradeable || !extensions.is_empty() || with_test || with_script || with_section_markers {
+            if oz_erc20 || oz_erc721 || oz_erc1155 || upgradeable || !extensions.is_empty() || with_test || with_script || with_section_markers || oz_version.is_some() || !token_metadata.is_empty() {
                 return Err(GramrError::Other(
                     "Test generation doesn't support contract-specific flags".to_string()
                 ));
             }
             match project {
                 ProjectType::Foundry(foundry_project) => {
-                    let generator = TestGenerator::new(foundry_project, name, pragma, license);
-                    generator.generate()
+                    let generator = TestGenerator::new(foundry_project, name, pragma, license)
+                        .with_overwrite_policy(overwrite);
+                    if dry_run {
+                        preview(generator.plan()?)
+                    } else {
+                        generator.generate()
+                    }
                 }
                 _ => Err(GramrError::Other("Test generation is only supported for Foundry projects".to_string()))
             }
         }
         "interface" => {
-            if oz_erc20 || oz_erc721 || oz_erc1155 || upgradeable || !extensions.is_empty() || with_test || with_script || with_section_markers {
+            if oz_erc20 || oz_erc721 || oz_erc1155 || upgradeable || !extensions.is_empty() || with_test || with_script || with_section_markers || oz_version.is_some() || !token_metadata.is_empty() {
                 return Err(GramrError::Other(
                     "Interface generation doesn't support contract-specific flags".to_string()
                 ));
             }
-            let generator = InterfaceGenerator::new(
+            let mut generator = InterfaceGenerator::new(
                 project,
                 language,
                 name,
                 Some(pragma),
                 Some(license),
-            );
-            generator.generate()
+            )
+            .with_overwrite_policy(overwrite)
+            .with_defaults(defaults);
+            if let Some(path) = from {
+                generator = generator.with_source(path);
+            }
+            if dry_run {
+                preview(generator.plan()?)
+            } else {
+                generator.generate()
+            }
         }
         "abstract" => {
-            if oz_erc20 || oz_erc721 || oz_erc1155 || upgradeable || !extensions.is_empty() || with_test || with_script {
+            if oz_erc20 || oz_erc721 || oz_erc1155 || upgradeable || !extensions.is_empty() || with_test || with_script || oz_version.is_some() || !token_metadata.is_empty() {
                 return Err(GramrError::Other(
                     "Abstract contract generation doesn't support contract-specific flags (except --with-section-markers)".to_string()
                 ));
             }
-            let generator = AbstractContractGenerator::new(
+            let mut generator = AbstractContractGenerator::new(
                 project,
                 language,
                 name,
                 Some(pragma),
                 Some(license),
-            );
-            generator.generate()
+            )
+            .with_overwrite_policy(overwrite)
+            .with_defaults(defaults);
+            if let Some(contract) = into {
+                generator = generator.with_inherited_by(contract);
+            }
+            if dry_run {
+                preview(generator.plan()?)
+            } else {
+                generator.generate()
+            }
         }
         "config" => {
-            if oz_erc20 || oz_erc721 || oz_erc1155 || upgradeable || !extensions.is_empty() || with_test || with_script || with_section_markers {
+            if oz_erc20 || oz_erc721 || oz_erc1155 || upgradeable || !extensions.is_empty() || with_test || with_script || with_section_markers || oz_version.is_some() || !token_metadata.is_empty() {
                 return Err(GramrError::Other(
                     "Config generation doesn't support contract-specific flags".to_string()
                 ));
@@ -132,7 +195,11 @@ pub fn execute_new(
                 language,
                 name,
             );
-            generator.generate()
+            if dry_run {
+                preview(generator.plan()?)
+            } else {
+                generator.generate()
+            }
         }
         _ => Err(GramrError::Other(
             format!("Unsupported resource type: {}. Supported types: contract, library, script, test, interface, abstract, config", resource_type)
@@ -140,13 +207,19 @@ pub fn execute_new(
     }
 }
 
+/// Print the planned changes instead of writing them
+fn preview(plan: GenerationPlan) -> Result<()> {
+    plan.print_dry_run();
+    Ok(())
+}
+
 fn determine_contract_type(
     oz_erc20: bool,
     oz_erc721: bool,
     oz_erc1155: bool,
     upgradeable: bool,
     extensions: &[String],
-    language: &Language,
+    defaults: &Defaults,
 ) -> Result<ContractType> {
     // Count how many base token types are specified
     let base_count = [oz_erc20, oz_erc721, oz_erc1155]
@@ -167,19 +240,8 @@ fn determine_contract_type(
         ));
     }
 
-    // Check if upgradeable is supported for the language
-    if upgradeable && *language == Language::RustStylus {
-        return Err(GramrError::Other(
-            "Upgradeable contracts are not yet supported for Rust/Stylus. OpenZeppelin Stylus library doesn't include upgradeable patterns yet.".to_string()
-        ));
-    }
-
-    // Parse extensions using the library function
-    let parsed_extensions = if !extensions.is_empty() {
-        gramr::parse_extensions(extensions)?
-    } else {
-        Vec::new()
-    };
+    // Parse extensions against the built-in and registered extensions
+    let parsed_extensions = defaults.extensions.parse(extensions)?;
 
     // If extensions are specified, we need a base type
     if !parsed_extensions.is_empty() && base_count == 0 {
@@ -205,145 +267,10 @@ fn determine_contract_type(
         Ok(base_type)
     } else {
         // Validate extensions are compatible with base type
-        validate_extensions_compatibility(&base_type, &parsed_extensions)?;
+        defaults.extensions.check_compatible(&base_type, &parsed_extensions)?;
         Ok(ContractType::MultiInheritance {
             base_type: Box::new(base_type),
             extensions: parsed_extensions,
         })
     }
 }
-
-fn validate_extensions_compatibility(
-    base_type: &ContractType,
-    extensions: &[TokenExtension],
-) -> Result<()> {
-    for extension in extensions {
-        let is_compatible = match (base_type, extension) {
-            // ERC20 extensions
-            (ContractType::ERC20 | ContractType::ERC20Upgradeable, TokenExtension::ERC20Permit) => {
-                true
-            }
-            (
-                ContractType::ERC20 | ContractType::ERC20Upgradeable,
-                TokenExtension::ERC20Burnable,
-            ) => true,
-            (ContractType::ERC20 | ContractType::ERC20Upgradeable, TokenExtension::ERC20Capped) => {
-                true
-            }
-            (
-                ContractType::ERC20 | ContractType::ERC20Upgradeable,
-                TokenExtension::ERC20Pausable,
-            ) => true,
-            (ContractType::ERC20 | ContractType::ERC20Upgradeable, TokenExtension::ERC20Votes) => {
-                true
-            }
-            (
-                ContractType::ERC20 | ContractType::ERC20Upgradeable,
-                TokenExtension::ERC20Wrapper,
-            ) => true,
-            (
-                ContractType::ERC20 | ContractType::ERC20Upgradeable,
-                TokenExtension::ERC20FlashMint,
-            ) => true,
-            (
-                ContractType::ERC20 | ContractType::ERC20Upgradeable,
-                TokenExtension::ERC20TemporaryApproval,
-            ) => true,
-            (
-                ContractType::ERC20 | ContractType::ERC20Upgradeable,
-                TokenExtension::ERC20Bridgeable,
-            ) => true,
-            (ContractType::ERC20 | ContractType::ERC20Upgradeable, TokenExtension::ERC1363) => true,
-            (ContractType::ERC20 | ContractType::ERC20Upgradeable, TokenExtension::ERC4626) => true,
-
-            // ERC721 extensions
-            (
-                ContractType::ERC721 | ContractType::ERC721Upgradeable,
-                TokenExtension::ERC721Pausable,
-            ) => true,
-            (
-                ContractType::ERC721 | ContractType::ERC721Upgradeable,
-                TokenExtension::ERC721Burnable,
-            ) => true,
-            (
-                ContractType::ERC721 | ContractType::ERC721Upgradeable,
-                TokenExtension::ERC721Consecutive,
-            ) => true,
-            (
-                ContractType::ERC721 | ContractType::ERC721Upgradeable,
-                TokenExtension::ERC721URIStorage,
-            ) => true,
-            (
-                ContractType::ERC721 | ContractType::ERC721Upgradeable,
-                TokenExtension::ERC721Votes,
-            ) => true,
-            (
-                ContractType::ERC721 | ContractType::ERC721Upgradeable,
-                TokenExtension::ERC721Royalty,
-            ) => true,
-            (
-                ContractType::ERC721 | ContractType::ERC721Upgradeable,
-                TokenExtension::ERC721Wrapper,
-            ) => true,
-            (
-                ContractType::ERC721 | ContractType::ERC721Upgradeable,
-                TokenExtension::ERC721Enumerable,
-            ) => true,
-
-            // ERC1155 extensions
-            (
-                ContractType::ERC1155 | ContractType::ERC1155Upgradeable,
-                TokenExtension::ERC1155Pausable,
-            ) => true,
-            (
-                ContractType::ERC1155 | ContractType::ERC1155Upgradeable,
-                TokenExtension::ERC1155Burnable,
-            ) => true,
-            (
-                ContractType::ERC1155 | ContractType::ERC1155Upgradeable,
-                TokenExtension::ERC1155Supply,
-            ) => true,
-            (
-                ContractType::ERC1155 | ContractType::ERC1155Upgradeable,
-                TokenExtension::ERC1155URIStorage,
-            ) => true,
-
-            // Cross-compatible extensions (burnable, pausable, wrapper, uristorage work with multiple types)
-            (
-                ContractType::ERC721 | ContractType::ERC721Upgradeable,
-                TokenExtension::ERC20Burnable,
-            ) => true, // Will be converted to ERC721Burnable
-            (
-                ContractType::ERC721 | ContractType::ERC721Upgradeable,
-                TokenExtension::ERC20Pausable,
-            ) => true, // Will be converted to ERC721Pausable
-            (
-                ContractType::ERC721 | ContractType::ERC721Upgradeable,
-                TokenExtension::ERC20Wrapper,
-            ) => true, // Will be converted to ERC721Wrapper
-            (
-                ContractType::ERC1155 | ContractType::ERC1155Upgradeable,
-                TokenExtension::ERC20Burnable,
-            ) => true, // Will be converted to ERC1155Burnable
-            (
-                ContractType::ERC1155 | ContractType::ERC1155Upgradeable,
-                TokenExtension::ERC20Pausable,
-            ) => true, // Will be converted to ERC1155Pausable
-            (
-                ContractType::ERC1155 | ContractType::ERC1155Upgradeable,
-                TokenExtension::ERC721URIStorage,
-            ) => true, // Will be converted to ERC1155URIStorage
-
-            _ => false,
-        };
-
-        if !is_compatible {
-            return Err(GramrError::Other(format!(
-                "Extension {:?} is not compatible with base type {:?}",
-                extension, base_type
-            )));
-        }
-    }
-
-    Ok(())
-}
@@ -0,0 +1,41 @@
+use colored::*;
+use gramr::{GramrError, Result, TemplatePack};
+use std::path::PathBuf;
+
+/// Install the template pack at `source` (a local directory or git URL) into the pack cache
+pub fn execute_template_add(source: &str) -> Result<()> {
+    let cache_dir = cache_dir()?;
+
+    println!("{} Fetching template pack from {}", "→".yellow(), source);
+    let existing: Vec<String> = TemplatePack::list(&cache_dir)?.into_iter().map(|pack| pack.name).collect();
+    let pack = TemplatePack::install(source, &cache_dir)?;
+
+    let action = if existing.contains(&pack.name) { "Updated" } else { "Installed" };
+    println!("{} {} template pack {}", "✓".green(), action, pack.name.cyan());
+    println!("  Use it with: gramr new contract <Name> --template-pack {}", pack.name);
+    Ok(())
+}
+
+/// Print the installed template packs and what they cover
+pub fn execute_template_list() -> Result<()> {
+    let packs = TemplatePack::list(&cache_dir()?)?;
+    if packs.is_empty() {
+        println!("No template packs installed. Add one with `gramr template add <git-url|path>`");
+        return Ok(());
+    }
+
+    for pack in packs {
+        let resources: Vec<&str> = pack.resources.iter().map(|kind| kind.as_str()).collect();
+        println!("{} {}", pack.name.cyan().bold(), pack.description.as_deref().unwrap_or(""));
+        println!("    resources: {}", resources.join(", "));
+        if !pack.contract_types.is_empty() {
+            println!("    contract types: {}", pack.contract_types.join(", "));
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn cache_dir() -> Result<PathBuf> {
+    TemplatePack::cache_dir()
+        .ok_or_else(|| GramrError::Other("Cannot locate the template pack cache: HOME is not set".to_string()))
+}
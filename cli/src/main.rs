@@ -2,7 +2,8 @@ mod commands;
 
 use clap::{Args, Parser, Subcommand};
 use colored::*;
-use gramr::Result;
+use gramr::{AccessControl, Defaults, OverwritePolicy, Result, TemplatePack, TokenMetadata};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(
@@ -18,8 +19,15 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
+    /// Create a project ready for gramr to generate into
+    Init(InitArgs),
     /// Create a new contract, test, or script
-    New(NewArgs),
+    New(Box<NewArgs>),
+    /// Generate every resource listed in gramr.toml that does not exist yet
+    Apply(ApplyArgs),
+    /// Manage template packs
+    #[command(subcommand)]
+    Template(TemplateCommand),
     /// Launch interactive wizard (calls wotan)
     Wizard,
     /// Show version information
@@ -62,6 +70,30 @@ struct NewArgs {
     #[arg(long = "extensions", value_delimiter = ',')]
     extensions: Vec<String>,
 
+    /// Token name, if different from the contract name
+    #[arg(long = "token-name", value_name = "NAME")]
+    token_name: Option<String>,
+
+    /// Token symbol [default: capitals of the contract name]
+    #[arg(long = "symbol")]
+    symbol: Option<String>,
+
+    /// ERC20 decimals [default: 18]
+    #[arg(long = "decimals")]
+    decimals: Option<u8>,
+
+    /// ERC20 supply the generated test and script mint, in whole tokens [default: 1000000]
+    #[arg(long = "initial-supply", value_name = "AMOUNT")]
+    initial_supply: Option<String>,
+
+    /// ERC721 base URI or ERC1155 URI
+    #[arg(long = "base-uri", value_name = "URI")]
+    base_uri: Option<String>,
+
+    /// Who may mint, pause and upgrade: ownable, ownable2step, access-control, access-managed or none [default: access_control from .gramr.toml or the user config, else ownable]
+    #[arg(long = "access", value_name = "MODEL")]
+    access: Option<String>,
+
     /// Generate corresponding test file
     #[arg(long = "with-test")]
     with_test: bool,
@@ -70,17 +102,101 @@ struct NewArgs {
     #[arg(long = "with-script")]
     with_script: bool,
 
-    /// Solidity pragma version
-    #[arg(long = "pragma", default_value = "0.8.30")]
-    pragma: String,
+    /// Solidity pragma version [default: from .gramr.toml or the user config, else 0.8.30]
+    #[arg(long = "pragma")]
+    pragma: Option<String>,
+
+    /// SPDX License Identifier [default: from .gramr.toml or the user config, else UNLICENSED]
+    #[arg(long = "license")]
+    license: Option<String>,
+
+    /// OpenZeppelin Contracts release to install and target (e.g. 4.9.6 or 5.4.0)
+    #[arg(long = "oz-version", value_name = "VERSION")]
+    oz_version: Option<String>,
 
-    /// SPDX License Identifier
-    #[arg(long = "license", default_value = "UNLICENSED")]
-    license: String,
+    /// Declare an interface's functions from a Solidity interface (.sol) or ABI (.json) file
+    #[arg(long = "from", value_name = "PATH")]
+    from: Option<PathBuf>,
+
+    /// Build this existing Stylus contract (src/<CONTRACT>.rs) on the generated abstract contract
+    #[arg(long = "into", value_name = "CONTRACT")]
+    into: Option<String>,
+
+    /// Render from an installed template pack [default: template_pack from .gramr.toml or the user config]
+    #[arg(long = "template-pack", value_name = "NAME")]
+    template_pack: Option<String>,
 
     /// Include section markers (comment blocks for organizing contract code)
     #[arg(long = "with-section-markers")]
     with_section_markers: bool,
+
+    /// Show the files that would be written, with a diff against existing ones, without writing anything
+    #[arg(long = "dry-run")]
+    dry_run: bool,
+
+    /// Overwrite files that already exist
+    #[arg(long = "force", conflicts_with_all = ["interactive", "skip_existing"])]
+    force: bool,
+
+    /// Ask before overwriting each file that already exists
+    #[arg(long = "interactive", conflicts_with = "skip_existing")]
+    interactive: bool,
+
+    /// Keep files that already exist and only write new ones
+    #[arg(long = "skip-existing")]
+    skip_existing: bool,
+}
+
+#[derive(Args)]
+struct InitArgs {
+    /// Directory to create the project in [default: current directory]
+    #[arg(default_value = ".")]
+    path: PathBuf,
+
+    /// Crate name [default: the directory name]
+    #[arg(long = "name")]
+    name: Option<String>,
+
+    /// Create a Cargo crate set up for Arbitrum Stylus (Foundry projects come from `forge init`)
+    #[arg(long = "rust-stylus", required = true)]
+    rust_stylus: bool,
+}
+
+#[derive(Args)]
+struct ApplyArgs {
+    /// Path to the project manifest
+    #[arg(long = "manifest", value_name = "PATH", default_value = gramr::manifest::MANIFEST_FILE)]
+    manifest: PathBuf,
+
+    /// Show the files that would be written without writing anything
+    #[arg(long = "dry-run")]
+    dry_run: bool,
+}
+
+#[derive(Subcommand)]
+enum TemplateCommand {
+    /// Install a template pack from a local directory or git URL, replacing any pack of the same name
+    Add {
+        /// Directory or git URL (https://, ssh or file://) of the pack
+        #[arg(value_name = "GIT_URL|PATH")]
+        source: String,
+    },
+    /// List installed template packs
+    List,
+}
+
+impl NewArgs {
+    fn overwrite_policy(&self) -> OverwritePolicy {
+        if self.force {
+            OverwritePolicy::Force
+        } else if self.interactive {
+            OverwritePolicy::Prompt
+        } else if self.skip_existing {
+            OverwritePolicy::SkipExisting
+        } else {
+            OverwritePolicy::Error
+        }
+    }
 }
 
 fn main() {
@@ -94,22 +210,59 @@ fn run() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::New(args) => commands::execute_new(
-            &args.resource_type,
-            args.name,
-            args.solidity,
-            args.rust_stylus,
-            args.oz_erc20,
-            args.oz_erc721,
-            args.oz_erc1155,
-            args.upgradeable,
-            args.extensions,
-            args.with_test,
-            args.with_script,
-            args.pragma,
-            args.license,
-            args.with_section_markers,
-        ),
+        Commands::Init(args) => commands::execute_init(&args.path, args.name),
+        Commands::New(args) => {
+            let overwrite = args.overwrite_policy();
+            let mut defaults = Defaults::load()?;
+            if let Some(name) = &args.template_pack {
+                defaults.template_pack = Some(TemplatePack::find(name, &commands::template::cache_dir()?)?);
+            }
+            let access = args
+                .access
+                .as_deref()
+                .map(|access| {
+                    AccessControl::from_name(access).ok_or_else(|| {
+                        gramr::GramrError::Other(format!(
+                            "Unknown access control `{}` (expected ownable, ownable2step, access-control, access-managed or none)",
+                            access
+                        ))
+                    })
+                })
+                .transpose()?;
+            commands::execute_new(
+                &args.resource_type,
+                args.name,
+                args.solidity,
+                args.rust_stylus,
+                args.oz_erc20,
+                args.oz_erc721,
+                args.oz_erc1155,
+                args.upgradeable,
+                args.extensions,
+                TokenMetadata {
+                    name: args.token_name,
+                    symbol: args.symbol,
+                    decimals: args.decimals,
+                    initial_supply: args.initial_supply,
+                    base_uri: args.base_uri,
+                },
+                args.with_test,
+                args.with_script,
+                args.pragma.unwrap_or_else(|| defaults.pragma.clone()),
+                args.license.unwrap_or_else(|| defaults.license.clone()),
+                args.oz_version,
+                access,
+                args.from,
+                args.into,
+                args.with_section_markers,
+                args.dry_run,
+                overwrite,
+                defaults,
+            )
+        }
+        Commands::Apply(args) => commands::execute_apply(&args.manifest, args.dry_run),
+        Commands::Template(TemplateCommand::Add { source }) => commands::execute_template_add(&source),
+        Commands::Template(TemplateCommand::List) => commands::execute_template_list(),
         Commands::Wizard => launch_wizard(),
         Commands::Version => {
             println!("⚔️  Gramr v{}", env!("CARGO_PKG_VERSION"));
@@ -271,7 +271,7 @@ fn install_from_local() -> Result<()> {
 
     // Build the project
     let output = Command::new("cargo")
-        .args(&["build", "--release", "--package", "gramr-cli"])
+        .args(["build", "--release", "--package", "gramr-cli"])
         .status()
         .context("Failed to build Gramr")?;
 
@@ -281,7 +281,7 @@ fn install_from_local() -> Result<()> {
 
     // Install the CLI binary
     let output = Command::new("cargo")
-        .args(&["install", "--path", "cli", "--force"])
+        .args(["install", "--path", "cli", "--force"])
         .status()
         .context("Failed to install Gramr CLI")?;
 
@@ -291,7 +291,7 @@ fn install_from_local() -> Result<()> {
 
     // Install Wotan wizard
     let output = Command::new("cargo")
-        .args(&["install", "--path", "wotan", "--force"])
+        .args(["install", "--path", "wotan", "--force"])
         .status()
         .context("Failed to install Wotan wizard")?;
 
@@ -318,7 +318,7 @@ fn install_from_github() -> Result<()> {
     let _ = std::fs::remove_dir_all(&temp_dir);
 
     let output = Command::new("git")
-        .args(&["clone", "https://github.com/pxlvre/gramr.git"])
+        .args(["clone", "https://github.com/pxlvre/gramr.git"])
         .arg(&temp_dir)
         .status()
         .context("Failed to clone Gramr repository")?;
@@ -329,9 +329,9 @@ fn install_from_github() -> Result<()> {
 
     // Build and install Gramr CLI
     let output = Command::new("cargo")
-        .args(&["install", "--path"])
+        .args(["install", "--path"])
         .arg(temp_dir.join("cli"))
-        .args(&["--force"])
+        .args(["--force"])
         .status()
         .context("Failed to install Gramr CLI")?;
 
@@ -341,9 +341,9 @@ fn install_from_github() -> Result<()> {
 
     // Build and install Wotan wizard
     let output = Command::new("cargo")
-        .args(&["install", "--path"])
+        .args(["install", "--path"])
         .arg(temp_dir.join("wotan"))
-        .args(&["--force"])
+        .args(["--force"])
         .status()
         .context("Failed to install Wotan wizard")?;
 
@@ -392,7 +392,7 @@ fn uninstall() -> Result<()> {
 
     // Uninstall using cargo
     let output = Command::new("cargo")
-        .args(&["uninstall", "gramr-cli"])
+        .args(["uninstall", "gramr-cli"])
         .status()
         .context("Failed to uninstall Gramr")?;
 
@@ -443,7 +443,7 @@ fn self_uninstall() -> Result<()> {
 
         // Make script executable
         Command::new("chmod")
-            .args(&["+x"])
+            .args(["+x"])
             .arg(&temp_script)
             .status()
             .context("Failed to make uninstall script executable")?;
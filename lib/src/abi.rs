@@ -0,0 +1,388 @@
+//! Function declarations read from a Solidity interface or an ABI JSON file, for
+//! generating interfaces that match an existing contract
+
+use crate::error::{GramrError, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// The external function declarations in `path`: a `.sol` file holding an
+/// interface (`I<name>` or `<name>` when there are several) or a `.json` ABI
+pub fn interface_functions(path: &Path, name: &str) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| GramrError::Other(format!("Failed to read {}: {}", path.display(), e)))?;
+
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("sol") => functions_from_solidity(&content, name),
+        Some("json") => functions_from_abi(&content),
+        _ => Err(GramrError::InvalidConfig(format!(
+            "{} is neither a Solidity interface (.sol) nor an ABI (.json)",
+            path.display()
+        ))),
+    }
+}
+
+/// Declarations of the ABI's functions. Accepts a bare ABI array or a compiler
+/// artifact with an `abi` key; events and errors are left out, and functions
+/// that take or return structs become comments
+pub fn functions_from_abi(json: &str) -> Result<Vec<String>> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| GramrError::InvalidConfig(format!("Invalid ABI JSON: {}", e)))?;
+    let entries = value
+        .as_array()
+        .or_else(|| value.get("abi").and_then(Value::as_array))
+        .ok_or_else(|| GramrError::InvalidConfig("ABI JSON must be an array or have an `abi` array".to_string()))?;
+
+    let functions = entries
+        .iter()
+        .filter(|entry| entry.get("type").and_then(Value::as_str).unwrap_or("function") == "function")
+        .map(abi_function)
+        .collect::<Result<Vec<_>>>()?;
+
+    if functions.is_empty() {
+        return Err(GramrError::InvalidConfig("The ABI has no functions".to_string()));
+    }
+    Ok(functions)
+}
+
+fn abi_function(entry: &Value) -> Result<String> {
+    let name = entry
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| GramrError::InvalidConfig("ABI function without a name".to_string()))?;
+    let params = |key: &str| entry.get(key).and_then(Value::as_array).cloned().unwrap_or_default();
+    let (inputs, outputs) = (params("inputs"), params("outputs"));
+
+    if inputs.iter().chain(&outputs).any(|param| param_type(param).starts_with("tuple")) {
+        return Ok(format!("// {}: takes or returns a struct, declare it by hand", name));
+    }
+
+    let mutability = match entry.get("stateMutability").and_then(Value::as_str) {
+        Some(mutability @ ("view" | "pure" | "payable")) => Some(mutability),
+        Some(_) => None,
+        // Pre-0.5 ABIs
+        None if entry.get("constant").and_then(Value::as_bool) == Some(true) => Some("view"),
+        None if entry.get("payable").and_then(Value::as_bool) == Some(true) => Some("payable"),
+        None => None,
+    };
+
+    let mut declaration = format!("function {}({}) external", name, abi_params(&inputs, "calldata"));
+    if let Some(mutability) = mutability {
+        declaration.push(' ');
+        declaration.push_str(mutability);
+    }
+    if !outputs.is_empty() {
+        declaration.push_str(&format!(" returns ({})", abi_params(&outputs, "memory")));
+    }
+    declaration.push(';');
+    Ok(declaration)
+}
+
+fn param_type(param: &Value) -> &str {
+    param.get("type").and_then(Value::as_str).unwrap_or("")
+}
+
+/// `type [location] [name]` for each parameter, with `location` on the dynamic types that need one
+fn abi_params(params: &[Value], location: &str) -> String {
+    params
+        .iter()
+        .map(|param| {
+            let kind = param_type(param);
+            let mut declaration = kind.to_string();
+            if kind == "string" || kind == "bytes" || kind.ends_with(']') {
+                declaration.push(' ');
+                declaration.push_str(location);
+            }
+            if let Some(name) = param.get("name").and_then(Value::as_str).filter(|name| !name.is_empty()) {
+                declaration.push(' ');
+                declaration.push_str(name);
+            }
+            declaration
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The function declarations of the interface in a Solidity source, one per line.
+/// Struct and enum definitions are left out, so functions that take or return
+/// them, or any other user-defined type, become comments
+pub fn functions_from_solidity(source: &str, name: &str) -> Result<Vec<String>> {
+    let source = strip_comments(source);
+    let interfaces = interface_bodies(&source);
+
+    let body = match interfaces.as_slice() {
+        [] => return Err(GramrError::InvalidConfig("No interface found in the Solidity source".to_string())),
+        [(_, body)] => body,
+        _ => interfaces
+            .iter()
+            .find(|(interface, _)| *interface == format!("I{}", name) || *interface == name)
+            .map(|(_, body)| body)
+            .ok_or_else(|| {
+                let names: Vec<&str> = interfaces.iter().map(|(interface, _)| interface.as_str()).collect();
+                GramrError::InvalidConfig(format!(
+                    "The Solidity source declares {}; name the interface I{} or {} to pick one",
+                    names.join(", "),
+                    name,
+                    name
+                ))
+            })?,
+    };
+
+    let functions: Vec<String> = statements(body)
+        .into_iter()
+        .filter(|statement| statement.starts_with("function "))
+        .map(|statement| solidity_function(&statement))
+        .collect();
+
+    if functions.is_empty() {
+        return Err(GramrError::InvalidConfig("The interface declares no functions".to_string()));
+    }
+    Ok(functions)
+}
+
+fn solidity_function(statement: &str) -> String {
+    let types_are_elementary = parameter_lists(statement)
+        .iter()
+        .flat_map(|list| list.split(','))
+        .map(str::trim)
+        .filter(|param| !param.is_empty())
+        .all(|param| is_elementary(param.split_whitespace().next().unwrap_or(param)));
+    if types_are_elementary {
+        return format!("{};", statement);
+    }
+
+    let name = statement["function ".len()..].split('(').next().unwrap_or_default().trim();
+    format!("// {}: takes or returns a user-defined type, declare it by hand", name)
+}
+
+/// The contents of each top-level `( ... )` in a declaration: its parameters and return values
+fn parameter_lists(statement: &str) -> Vec<&str> {
+    let mut lists = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in statement.char_indices() {
+        match c {
+            '(' => {
+                if depth == 0 {
+                    start = i + 1;
+                }
+                depth += 1;
+            }
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    lists.push(&statement[start..i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    lists
+}
+
+/// Whether `kind`, e.g. `uint256[]`, is a built-in Solidity type rather than a
+/// struct, enum, contract or user-defined value type
+fn is_elementary(kind: &str) -> bool {
+    let base = kind.split('[').next().unwrap_or(kind);
+    let sized = |prefix: &str| {
+        base.strip_prefix(prefix).is_some_and(|size| size.chars().all(|c| c.is_ascii_digit() || c == 'x'))
+    };
+    matches!(base, "address" | "bool" | "string")
+        || sized("uint")
+        || sized("int")
+        || sized("bytes")
+        || sized("ufixed")
+        || sized("fixed")
+}
+
+fn strip_comments(source: &str) -> String {
+    let mut stripped = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(start) = rest.find('/') {
+        stripped.push_str(&rest[..start]);
+        let after = &rest[start..];
+        if after.starts_with("//") {
+            rest = after.find('\n').map_or("", |end| &after[end..]);
+        } else if let Some(comment) = after.strip_prefix("/*") {
+            rest = comment.find("*/").map_or("", |end| &comment[end + 2..]);
+            stripped.push(' ');
+        } else {
+            stripped.push('/');
+            rest = &after[1..];
+        }
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+/// Each `interface Name ... { body }` in the source, with its name
+fn interface_bodies(source: &str) -> Vec<(String, String)> {
+    let mut interfaces = Vec::new();
+    let mut rest = source;
+    while let Some(start) = find_keyword(rest, "interface") {
+        let after = &rest[start + "interface".len()..];
+        let name: String = after
+            .trim_start()
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+            .collect();
+        let Some(open) = after.find('{') else { break };
+
+        let mut depth = 0;
+        let mut close = None;
+        for (i, c) in after[open..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        close = Some(open + i);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let Some(close) = close else { break };
+
+        interfaces.push((name, after[open + 1..close].to_string()));
+        rest = &after[close + 1..];
+    }
+    interfaces
+}
+
+/// Position of `keyword` as a whole word
+fn find_keyword(source: &str, keyword: &str) -> Option<usize> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    source.match_indices(keyword).map(|(i, _)| i).find(|&i| {
+        let before = source[..i].chars().next_back();
+        let after = source[i + keyword.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
+
+/// Top-level statements of an interface body with whitespace collapsed; struct
+/// and enum definitions end at their closing brace
+fn statements(body: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+
+    for c in body.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 && (c == ';' || c == '}') {
+            if c == '}' {
+                current.push(c);
+            }
+            let statement = current.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !statement.is_empty() {
+                statements.push(statement.replace("( ", "(").replace(" )", ")").replace(" ,", ","));
+            }
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const ERC20_ABI: &str = r#"[
+        {"type": "function", "name": "balanceOf", "stateMutability": "view",
+         "inputs": [{"name": "account", "type": "address"}], "outputs": [{"name": "", "type": "uint256"}]},
+        {"type": "function", "name": "transfer", "stateMutability": "nonpayable",
+         "inputs": [{"name": "to", "type": "address"}, {"name": "value", "type": "uint256"}],
+         "outputs": [{"name": "", "type": "bool"}]},
+        {"type": "function", "name": "setNames", "stateMutability": "nonpayable",
+         "inputs": [{"name": "names", "type": "string[]"}], "outputs": []},
+        {"type": "function", "name": "position", "stateMutability": "view",
+         "inputs": [], "outputs": [{"name": "", "type": "tuple", "components": []}]},
+        {"type": "event", "name": "Transfer", "inputs": [], "anonymous": false}
+    ]"#;
+
+    #[test]
+    fn test_functions_from_abi() {
+        let functions = functions_from_abi(ERC20_ABI).unwrap();
+
+        assert_eq!(
+            functions,
+            vec![
+                "function balanceOf(address account) external view returns (uint256);",
+                "function transfer(address to, uint256 value) external returns (bool);",
+                "function setNames(string[] calldata names) external;",
+                "// position: takes or returns a struct, declare it by hand",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_functions_from_artifact_and_legacy_abi() {
+        let artifact = r#"{"abi": [{"name": "total", "constant": true, "inputs": [], "outputs": [{"type": "string"}]}]}"#;
+
+        assert_eq!(
+            functions_from_abi(artifact).unwrap(),
+            vec!["function total() external view returns (string memory);"]
+        );
+        assert!(functions_from_abi("{}").is_err());
+        assert!(functions_from_abi(r#"[{"type": "event", "name": "E"}]"#).is_err());
+    }
+
+    #[test]
+    fn test_functions_from_solidity() {
+        let source = r#"
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// Some other interface
+interface IOther { function other() external; }
+
+interface ICounter is IOther {
+    struct Snapshot { uint256 value; uint256 at; }
+    event Incremented(uint256 value);
+
+    /// Current count
+    function number() external view returns (uint256);
+    /* multi-line
+       declaration */
+    function setNumber(
+        uint256 newNumber // the new value
+    ) external;
+    function snapshot(uint256 at) external view returns (Snapshot memory);
+    function pay(address payable to, bytes32[2] calldata ids) external payable;
+}
+"#;
+
+        assert_eq!(
+            functions_from_solidity(source, "Counter").unwrap(),
+            vec![
+                "function number() external view returns (uint256);",
+                "function setNumber(uint256 newNumber) external;",
+                "// snapshot: takes or returns a user-defined type, declare it by hand",
+                "function pay(address payable to, bytes32[2] calldata ids) external payable;",
+            ]
+        );
+        let ambiguous = functions_from_solidity(source, "Missing");
+        assert!(matches!(ambiguous, Err(GramrError::InvalidConfig(msg)) if msg.contains("IOther, ICounter")));
+        assert!(functions_from_solidity("contract C {}", "C").is_err());
+    }
+
+    #[test]
+    fn test_interface_functions_by_extension() {
+        let temp_dir = TempDir::new().unwrap();
+        let abi = temp_dir.path().join("Token.json");
+        fs::write(&abi, ERC20_ABI).unwrap();
+        let text = temp_dir.path().join("Token.txt");
+        fs::write(&text, ERC20_ABI).unwrap();
+
+        assert_eq!(interface_functions(&abi, "Token").unwrap().len(), 4);
+        assert!(matches!(interface_functions(&text, "Token"), Err(GramrError::InvalidConfig(_))));
+    }
+}
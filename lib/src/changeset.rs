@@ -0,0 +1,286 @@
+//! File writes that land together or not at all.
+//!
+//! A [`Changeset`] stages every output next to its destination first, so
+//! failures such as a full disk or a missing permission surface before any
+//! real file is touched. Committing moves the staged files into place; if a
+//! move fails, or the changeset is dropped without being committed, every
+//! staged file, replaced file and created directory is put back as it was.
+//! Files that are edited in place by other means, such as a dependency
+//! install rewriting Cargo.toml, can be snapshotted to be restored as well.
+
+use crate::error::Result;
+use crate::plan::GenerationPlan;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A set of staged file writes, committed atomically as a group
+#[derive(Debug, Default)]
+pub struct Changeset {
+    entries: Vec<Entry>,
+    /// Directories created while staging, shallowest first
+    created_dirs: Vec<PathBuf>,
+    /// Files edited outside the changeset, with their content beforehand
+    /// (`None` for files that did not exist)
+    snapshots: Vec<(PathBuf, Option<Vec<u8>>)>,
+    committed: bool,
+}
+
+#[derive(Debug)]
+struct Entry {
+    target: PathBuf,
+    staged: PathBuf,
+    /// Where the file being replaced was moved during commit
+    backup: Option<PathBuf>,
+    moved: bool,
+}
+
+impl Changeset {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stage every file in `plan`
+    pub fn stage(plan: &GenerationPlan) -> Result<Self> {
+        let mut changeset = Self::new();
+        for file in &plan.files {
+            changeset.stage_file(&file.path, &file.content)?;
+        }
+        Ok(changeset)
+    }
+
+    /// Write `content` beside `path` without touching `path` itself
+    pub fn stage_file(&mut self, path: &Path, content: &str) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            self.create_dirs(parent)?;
+        }
+
+        let staged = sibling(path, "staged");
+        fs::write(&staged, content)?;
+        self.entries.push(Entry {
+            target: path.to_path_buf(),
+            staged,
+            backup: None,
+            moved: false,
+        });
+        Ok(())
+    }
+
+    /// Remember `path` as it is now, so that a rollback undoes whatever is
+    /// written to it before the changeset is committed
+    pub fn snapshot(&mut self, path: &Path) -> Result<()> {
+        if self.snapshots.iter().any(|(snapshotted, _)| snapshotted == path) {
+            return Ok(());
+        }
+        let content = match fs::read(path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        self.snapshots.push((path.to_path_buf(), content));
+        Ok(())
+    }
+
+    /// Make the staged copy of `path` executable, so it lands with the mode set
+    #[cfg(unix)]
+    pub fn set_executable(&self, path: &Path) -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        if let Some(entry) = self.entries.iter().find(|entry| entry.target == path) {
+            fs::set_permissions(&entry.staged, fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn set_executable(&self, _path: &Path) -> Result<()> {
+        Ok(())
+    }
+
+    /// Destinations of the staged files
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.entries.iter().map(|entry| entry.target.as_path())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Move every staged file into place, restoring the previous state if any move fails
+    pub fn commit(mut self) -> Result<()> {
+        for index in 0..self.entries.len() {
+            if let Err(e) = self.move_into_place(index) {
+                self.rollback();
+                return Err(e.into());
+            }
+        }
+
+        for entry in &self.entries {
+            if let Some(backup) = &entry.backup {
+                let _ = fs::remove_file(backup);
+            }
+        }
+        self.snapshots.clear();
+        self.committed = true;
+        Ok(())
+    }
+
+    fn move_into_place(&mut self, index: usize) -> io::Result<()> {
+        let entry = &mut self.entries[index];
+
+        if entry.target.exists() {
+            let backup = sibling(&entry.target, "backup");
+            fs::rename(&entry.target, &backup)?;
+            entry.backup = Some(backup);
+        }
+
+        fs::rename(&entry.staged, &entry.target)?;
+        entry.moved = true;
+        Ok(())
+    }
+
+    fn create_dirs(&mut self, dir: &Path) -> Result<()> {
+        let missing: Vec<PathBuf> = dir
+            .ancestors()
+            .take_while(|ancestor| !ancestor.as_os_str().is_empty() && !ancestor.exists())
+            .map(Path::to_path_buf)
+            .collect();
+
+        fs::create_dir_all(dir)?;
+        self.created_dirs.extend(missing.into_iter().rev());
+        Ok(())
+    }
+
+    /// Put back replaced files and remove everything this changeset created.
+    /// Best effort: rollback runs while another error is already being reported.
+    fn rollback(&mut self) {
+        for entry in self.entries.drain(..).rev() {
+            if entry.moved {
+                let _ = fs::remove_file(&entry.target);
+            }
+            if let Some(backup) = &entry.backup {
+                let _ = fs::rename(backup, &entry.target);
+            }
+            let _ = fs::remove_file(&entry.staged);
+        }
+
+        for (path, content) in self.snapshots.drain(..).rev() {
+            let _ = match content {
+                Some(content) => fs::write(&path, content),
+                None => fs::remove_file(&path),
+            };
+        }
+
+        // Only removes directories that are still empty
+        for dir in self.created_dirs.drain(..).rev() {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
+impl Drop for Changeset {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.rollback();
+        }
+    }
+}
+
+/// Hidden file next to `path`, e.g. `src/.Token.sol.gramr-staged`
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.gramr-{}", name, suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_commit_writes_and_replaces_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = temp_dir.path().join("Token.sol");
+        fs::write(&existing, "old").unwrap();
+
+        let mut changeset = Changeset::new();
+        changeset.stage_file(&existing, "new").unwrap();
+        changeset.stage_file(&temp_dir.path().join("test/Token.t.sol"), "test").unwrap();
+
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "old");
+        changeset.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "new");
+        assert_eq!(fs::read_to_string(temp_dir.path().join("test/Token.t.sol")).unwrap(), "test");
+        let leftovers: Vec<_> = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".gramr-"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn test_drop_without_commit_rolls_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = temp_dir.path().join("Token.sol");
+        fs::write(&existing, "old").unwrap();
+
+        {
+            let mut changeset = Changeset::new();
+            changeset.stage_file(&existing, "new").unwrap();
+            changeset.stage_file(&temp_dir.path().join("script/deploy/Deploy.s.sol"), "script").unwrap();
+            assert!(temp_dir.path().join("script/deploy").exists());
+        }
+
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "old");
+        assert!(!temp_dir.path().join("script").exists());
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_failed_commit_restores_earlier_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("A.sol");
+        let second = temp_dir.path().join("B.sol");
+        fs::write(&first, "original").unwrap();
+
+        let mut changeset = Changeset::new();
+        changeset.stage_file(&first, "replacement").unwrap();
+        changeset.stage_file(&second, "new").unwrap();
+        // Make the second move fail after the first has gone through
+        fs::remove_file(&changeset.entries[1].staged).unwrap();
+
+        assert!(changeset.commit().is_err());
+        assert_eq!(fs::read_to_string(&first).unwrap(), "original");
+        assert!(!second.exists());
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_rollback_restores_snapshotted_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest = temp_dir.path().join("Cargo.toml");
+        let toolchain = temp_dir.path().join("rust-toolchain.toml");
+        fs::write(&manifest, "[package]\n").unwrap();
+
+        {
+            let mut changeset = Changeset::new();
+            changeset.stage_file(&temp_dir.path().join("src/lib.rs"), "mod token;\n").unwrap();
+            changeset.snapshot(&manifest).unwrap();
+            changeset.snapshot(&toolchain).unwrap();
+            fs::write(&manifest, "[package]\n[dependencies]\n").unwrap();
+            fs::write(&toolchain, "[toolchain]\n").unwrap();
+        }
+        assert_eq!(fs::read_to_string(&manifest).unwrap(), "[package]\n");
+        assert!(!toolchain.exists());
+
+        let mut changeset = Changeset::new();
+        changeset.snapshot(&manifest).unwrap();
+        fs::write(&manifest, "[package]\n[dependencies]\n").unwrap();
+        changeset.commit().unwrap();
+        assert_eq!(fs::read_to_string(&manifest).unwrap(), "[package]\n[dependencies]\n");
+    }
+}
@@ -0,0 +1,362 @@
+//! Generation defaults read from user and project configuration.
+//!
+//! Every setting is resolved through one chain, highest priority first:
+//!
+//! 1. command-line flags (or explicit builder calls)
+//! 2. the `[project]` table of a `gramr.toml` manifest, for `gramr apply`
+//! 3. `.gramr.toml` in the project directory
+//! 4. `config.toml` in the user config directory (`~/.config/gramr/`)
+//! 5. built-in defaults
+//!
+//! Templates are overridden the same way: a file in the project's
+//! `.gramr/templates/` wins over one in the selected template pack, then one in
+//! `~/.config/gramr/templates/`, then the built-in template of the same name
+//! (see [`crate::templates::engine`] and [`crate::pack`]). Extensions defined in
+//! `~/.config/gramr/extensions/` and then `.gramr/extensions/` are added to the
+//! built-in ones (see [`crate::extensions`]).
+//!
+//! ```toml
+//! pragma = "0.8.26"
+//! license = "MIT"
+//! author = "Acme Labs"
+//! natspec = "minimal"        # full, minimal or none
+//! access_control = "ownable" # ownable, ownable2step, access-control, access-managed or none
+//! template_pack = "acme"     # installed with `gramr template add`
+//!
+//! [paths]
+//! libraries = "lib"          # relative to the source directory
+//! interfaces = "interfaces"
+//! abstracts = "base"
+//! ```
+
+use crate::error::{GramrError, Result};
+use crate::extensions::{ExtensionRegistry, PROJECT_EXTENSIONS_DIR};
+use crate::manifest::ResourceKind;
+use crate::pack::TemplatePack;
+use crate::templates::engine::PROJECT_TEMPLATES_DIR;
+use crate::templates::{AccessControl, ContractType, NatSpecStyle};
+use colored::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Table;
+
+/// Project-local defaults file, read from the directory gramr runs in
+pub const PROJECT_DEFAULTS_FILE: &str = ".gramr.toml";
+
+pub const DEFAULT_PRAGMA: &str = "0.8.30";
+pub const DEFAULT_LICENSE: &str = "UNLICENSED";
+
+const KEYS: &[&str] = &["pragma", "license", "author", "natspec", "access_control", "template_pack", "paths"];
+const PATH_KEYS: &[&str] = &["libraries", "interfaces", "abstracts"];
+
+/// Where libraries, interfaces and abstract contracts go, relative to the source directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputPaths {
+    pub libraries: String,
+    pub interfaces: String,
+    pub abstracts: String,
+}
+
+impl Default for OutputPaths {
+    fn default() -> Self {
+        Self {
+            libraries: "libraries".to_string(),
+            interfaces: "interfaces".to_string(),
+            abstracts: "abstracts".to_string(),
+        }
+    }
+}
+
+/// Resolved defaults for everything a command does not set explicitly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Defaults {
+    pub pragma: String,
+    pub license: String,
+    /// `@author` for generated NatSpec headers
+    pub author: Option<String>,
+    pub natspec: NatSpecStyle,
+    pub access_control: AccessControl,
+    pub paths: OutputPaths,
+    /// The project's `.gramr/templates`, when it exists
+    pub project_templates: Option<PathBuf>,
+    /// `templates` in the user config directory, when it exists
+    pub user_templates: Option<PathBuf>,
+    pub template_pack: Option<TemplatePack>,
+    /// Built-in extensions plus those registered in the user and project extension directories
+    pub extensions: ExtensionRegistry,
+}
+
+impl Default for Defaults {
+    fn default() -> Self {
+        Self {
+            pragma: DEFAULT_PRAGMA.to_string(),
+            license: DEFAULT_LICENSE.to_string(),
+            author: None,
+            natspec: NatSpecStyle::default(),
+            access_control: AccessControl::default(),
+            paths: OutputPaths::default(),
+            project_templates: None,
+            user_templates: None,
+            template_pack: None,
+            extensions: ExtensionRegistry::builtin(),
+        }
+    }
+}
+
+impl Defaults {
+    /// Load the user config, then the project's `.gramr.toml` from the current directory
+    pub fn load() -> Result<Self> {
+        let current_dir = std::env::current_dir()
+            .map_err(|e| GramrError::Other(format!("Failed to get current directory: {}", e)))?;
+
+        Self::load_from(
+            Self::user_config_path().as_deref(),
+            TemplatePack::cache_dir().as_deref(),
+            &current_dir,
+        )
+    }
+
+    /// Like [`Defaults::load`], but falls back to the built-in defaults with a
+    /// warning when a config file cannot be read
+    pub fn load_or_default() -> Self {
+        Self::load().unwrap_or_else(|e| {
+            eprintln!("{} Ignoring gramr configuration: {}", "!".yellow(), e);
+            Self::default()
+        })
+    }
+
+    /// Layer `user_config` (if given) and then `project_dir/.gramr.toml` over the built-in defaults.
+    ///
+    /// Template overrides are looked up in `project_dir/.gramr/templates` and then in
+    /// `templates` next to `user_config`, whichever of them exist. Extensions are
+    /// registered from `extensions` next to `user_config` and then from
+    /// `project_dir/.gramr/extensions`. A `template_pack` setting names a pack installed
+    /// in `pack_cache`.
+    pub fn load_from(user_config: Option<&Path>, pack_cache: Option<&Path>, project_dir: &Path) -> Result<Self> {
+        let extension_dirs: Vec<PathBuf> = [
+            user_config.and_then(Path::parent).map(|dir| dir.join("extensions")),
+            Some(project_dir.join(PROJECT_EXTENSIONS_DIR)),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|dir| dir.is_dir())
+        .collect();
+
+        let mut defaults = Self {
+            project_templates: Some(project_dir.join(PROJECT_TEMPLATES_DIR)).filter(|dir| dir.is_dir()),
+            user_templates: user_config
+                .and_then(Path::parent)
+                .map(|dir| dir.join("templates"))
+                .filter(|dir| dir.is_dir()),
+            extensions: ExtensionRegistry::load(&extension_dirs)?,
+            ..Self::default()
+        };
+
+        let project_config = project_dir.join(PROJECT_DEFAULTS_FILE);
+        for path in user_config.into_iter().chain([project_config.as_path()]) {
+            if path.exists() {
+                let content = fs::read_to_string(path)?;
+                defaults.apply(&content, &path.display().to_string(), pack_cache)?;
+            }
+        }
+
+        Ok(defaults)
+    }
+
+    /// Template override directories, highest priority first
+    pub fn template_dirs(&self) -> Vec<PathBuf> {
+        [
+            self.project_templates.clone(),
+            self.template_pack.as_ref().map(|pack| pack.path.clone()),
+            self.user_templates.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Error unless the selected template pack, if any, covers `kind` (and `contract_type`)
+    pub fn check_template_pack(&self, kind: ResourceKind, contract_type: Option<&ContractType>) -> Result<()> {
+        match &self.template_pack {
+            Some(pack) => pack.check_supports(kind, contract_type),
+            None => Ok(()),
+        }
+    }
+
+    /// `config.toml` in `$XDG_CONFIG_HOME/gramr`, or `~/.config/gramr` when it is unset.
+    pub fn user_config_path() -> Option<PathBuf> {
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(config_dir.join("gramr").join("config.toml"))
+    }
+
+    /// Overlay the settings in one config file; `source` names the file in errors,
+    /// and `template_pack` is looked up in `pack_cache`
+    pub fn apply(&mut self, content: &str, source: &str, pack_cache: Option<&Path>) -> Result<()> {
+        let table: Table = content
+            .parse()
+            .map_err(|e| GramrError::InvalidConfig(format!("{}: {}", source, e)))?;
+        check_keys(&table, KEYS, source, "")?;
+
+        if let Some(pragma) = string_key(&table, "pragma", source)? {
+            self.pragma = pragma;
+        }
+        if let Some(license) = string_key(&table, "license", source)? {
+            self.license = license;
+        }
+        if let Some(author) = string_key(&table, "author", source)? {
+            self.author = Some(author);
+        }
+        if let Some(natspec) = string_key(&table, "natspec", source)? {
+            self.natspec = NatSpecStyle::from_name(&natspec).ok_or_else(|| {
+                GramrError::InvalidConfig(format!(
+                    "{}: unknown natspec style `{}` (expected full, minimal or none)",
+                    source, natspec
+                ))
+            })?;
+        }
+        if let Some(access_control) = string_key(&table, "access_control", source)? {
+            self.access_control = AccessControl::from_name(&access_control).ok_or_else(|| {
+                GramrError::InvalidConfig(format!(
+                    "{}: unknown access_control `{}` (expected ownable, ownable2step, access-control, access-managed or none)",
+                    source, access_control
+                ))
+            })?;
+        }
+        if let Some(name) = string_key(&table, "template_pack", source)? {
+            let pack_cache = pack_cache.ok_or_else(|| {
+                GramrError::InvalidConfig(format!("{}: cannot locate the template pack cache", source))
+            })?;
+            self.template_pack = Some(TemplatePack::find(&name, pack_cache)?);
+        }
+
+        if let Some(paths) = table.get("paths") {
+            let paths = paths.as_table().ok_or_else(|| invalid_type("paths", "a table", source))?;
+            check_keys(paths, PATH_KEYS, source, " in [paths]")?;
+
+            if let Some(libraries) = string_key(paths, "libraries", source)? {
+                self.paths.libraries = libraries;
+            }
+            if let Some(interfaces) = string_key(paths, "interfaces", source)? {
+                self.paths.interfaces = interfaces;
+            }
+            if let Some(abstracts) = string_key(paths, "abstracts", source)? {
+                self.paths.abstracts = abstracts;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn check_keys(table: &Table, allowed: &[&str], source: &str, context: &str) -> Result<()> {
+    match table.keys().find(|key| !allowed.contains(&key.as_str())) {
+        Some(key) => Err(GramrError::InvalidConfig(format!("{}: unknown key `{}`{}", source, key, context))),
+        None => Ok(()),
+    }
+}
+
+fn string_key(table: &Table, key: &str, source: &str) -> Result<Option<String>> {
+    match table.get(key) {
+        Some(value) => value
+            .as_str()
+            .map(|s| Some(s.to_string()))
+            .ok_or_else(|| invalid_type(key, "a string", source)),
+        None => Ok(None),
+    }
+}
+
+fn invalid_type(key: &str, expected: &str, source: &str) -> GramrError {
+    GramrError::InvalidConfig(format!("{}: `{}` must be {}", source, key, expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_builtin_defaults_without_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let defaults = Defaults::load_from(None, None, temp_dir.path()).unwrap();
+
+        assert_eq!(defaults, Defaults::default());
+        assert_eq!(defaults.pragma, "0.8.30");
+        assert_eq!(defaults.license, "UNLICENSED");
+    }
+
+    #[test]
+    fn test_project_config_overrides_user_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_config = temp_dir.path().join("config.toml");
+        fs::write(
+            &user_config,
+            "pragma = \"0.8.24\"\nlicense = \"MIT\"\nauthor = \"Alice\"\n\n[paths]\nlibraries = \"lib\"\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join(PROJECT_DEFAULTS_FILE),
+            "license = \"Apache-2.0\"\nnatspec = \"minimal\"\naccess_control = \"access-control\"\n",
+        )
+        .unwrap();
+
+        let defaults = Defaults::load_from(Some(&user_config), None, temp_dir.path()).unwrap();
+
+        assert_eq!(defaults.pragma, "0.8.24");
+        assert_eq!(defaults.license, "Apache-2.0");
+        assert_eq!(defaults.author.as_deref(), Some("Alice"));
+        assert_eq!(defaults.natspec, NatSpecStyle::Minimal);
+        assert_eq!(defaults.access_control, AccessControl::Roles);
+        assert_eq!(defaults.paths.libraries, "lib");
+        assert_eq!(defaults.paths.interfaces, "interfaces");
+        assert!(defaults.template_dirs().is_empty());
+
+        fs::create_dir_all(temp_dir.path().join("templates")).unwrap();
+        fs::create_dir_all(temp_dir.path().join(PROJECT_TEMPLATES_DIR)).unwrap();
+        let defaults = Defaults::load_from(Some(&user_config), None, temp_dir.path()).unwrap();
+
+        assert_eq!(
+            defaults.template_dirs(),
+            vec![temp_dir.path().join(PROJECT_TEMPLATES_DIR), temp_dir.path().join("templates")]
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_settings() {
+        let cases = [
+            ("pragm = \"0.8.30\"", "unknown key `pragm`"),
+            ("natspec = \"verbose\"", "unknown natspec style"),
+            ("access_control = \"roles\"", "unknown access_control"),
+            ("license = 3", "`license` must be a string"),
+            ("[paths]\nsrc = \"contracts\"", "unknown key `src` in [paths]"),
+        ];
+
+        for (content, expected) in cases {
+            let err = Defaults::default().apply(content, ".gramr.toml", None).unwrap_err().to_string();
+            assert!(err.contains(expected), "{:?} gave {:?}", content, err);
+            assert!(err.contains(".gramr.toml"));
+        }
+    }
+
+    #[test]
+    fn test_template_pack_is_found_in_the_given_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let pack_cache = temp_dir.path().join("packs");
+        fs::create_dir_all(pack_cache.join("acme")).unwrap();
+        fs::write(
+            pack_cache.join("acme").join(crate::pack::PACK_MANIFEST),
+            "name = \"acme\"\nresources = [\"library\"]\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join(PROJECT_DEFAULTS_FILE), "template_pack = \"acme\"\n").unwrap();
+
+        let defaults = Defaults::load_from(None, Some(&pack_cache), temp_dir.path()).unwrap();
+        assert_eq!(defaults.template_pack.map(|pack| pack.path), Some(pack_cache.join("acme")));
+
+        let err = Defaults::load_from(None, None, temp_dir.path()).unwrap_err().to_string();
+        assert!(err.contains("cannot locate the template pack cache"), "{}", err);
+    }
+}
@@ -23,6 +23,12 @@ pub enum GramrError {
     #[error("Invalid contract name: {0}")]
     InvalidContractName(String),
 
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("Template error: {0}")]
+    TemplateError(String),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
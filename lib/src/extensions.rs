@@ -0,0 +1,1001 @@
+//! Token extensions and what each one contributes to a generated contract.
+//!
+//! The built-in OpenZeppelin extensions are read from `extensions/openzeppelin.toml`
+//! in this crate. More can be registered without touching Rust code by dropping
+//! TOML files of the same shape into `~/.config/gramr/extensions/` or the
+//! project's `.gramr/extensions/`:
+//!
+//! ```toml
+//! [[extension]]
+//! id = "ERC20Blocklist"                 # contract name unless `contract` is set
+//! name = "blocklist"                    # accepted by --extensions and gramr.toml
+//! description = "Block transfers from listed accounts"
+//! base_types = ["erc20"]                # erc20, erc721 and/or erc1155
+//! oz_versions = [5]                     # default: [4, 5]
+//! import = "@acme/contracts/ERC20Blocklist.sol"
+//! constructor_args = '"{{ token_name }}", admin'
+//! parameters = [
+//!     { name = "admin", type = "address", description = "Blocklist admin", default = "msg.sender" },
+//! ]
+//! constructor_body = ["        _grantBlocklistAdmin(admin);"]
+//! state_variables = ["    uint256 private _blocked;"]
+//! functions = ['''
+//!     function blocked() public view returns (uint256) {
+//!         return _blocked;
+//!     }''']
+//! role = "blocker"                      # functions use {{ access_modifier }}, e.g. onlyRole(BLOCKER_ROLE)
+//! overrides = ["_update"]               # overridable functions it declares
+//! implies = ["ERC20Pausable"]           # placed before this one, added when missing
+//! provides = []                         # ids this one stands in for
+//! conflicts = ["ERC20Wrapper"]
+//! ```
+//!
+//! An `import` starting with `@` is used as is; anything else is relative to the
+//! OpenZeppelin Contracts root. Snippets are MiniJinja templates receiving
+//! `name` (the contract's), `token_name`, `symbol`, `upgradeable`, `oz_version`,
+//! `interface_suffix` (`Upgradeable` for 4.x upgradeable contracts) and
+//! `access_modifier`, the modifier restricting a function to the extension's
+//! `role` (or to the admin when it has none) under the chosen access control.
+//! An entry whose `id` matches a built-in (or one from a lower-priority
+//! directory) replaces it.
+//!
+//! Each of `parameters` becomes a constructor (or `initialize`) parameter that
+//! snippets refer to by name. Values come from `--extensions admin=0x...` style
+//! arguments, `:`-separated in declaration order; generated tests and scripts
+//! deploy with them, or with each parameter's `default` expression.
+//!
+//! `overrides` names the `virtual` functions the extension declares, so that a
+//! contract inheriting another declaration of one gets the `override(A, B)`
+//! Solidity requires. Names apply to the base type and release line they exist
+//! in (`_update` on 5.x, `_beforeTokenTransfer` on 4.x) and are ignored
+//! elsewhere; `Nonces.nonces` names a function one of the extension's own
+//! bases declares.
+
+use crate::error::{GramrError, Result};
+use crate::templates::solidity::OVERRIDABLE_FUNCTIONS;
+use crate::templates::{ContractType, OpenZeppelinVersion, TokenExtension};
+use minijinja::Environment;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tiny_keccak::{Hasher, Keccak};
+use toml::{Table, Value};
+
+/// Project extension directory, relative to the project root
+pub const PROJECT_EXTENSIONS_DIR: &str = ".gramr/extensions";
+
+const BUILTIN_EXTENSIONS: &str = include_str!("../extensions/openzeppelin.toml");
+
+const KEYS: &[&str] = &[
+    "id",
+    "name",
+    "description",
+    "base_types",
+    "oz_versions",
+    "contract",
+    "import",
+    "constructor_args",
+    "parameters",
+    "constructor_body",
+    "state_variables",
+    "functions",
+    "role",
+    "overrides",
+    "provides",
+    "implies",
+    "conflicts",
+];
+const PARAMETER_KEYS: &[&str] = &["name", "type", "description", "default"];
+const BASE_TYPES: &[&str] = &["erc20", "erc721", "erc1155"];
+
+/// One extension contract and the pieces it adds to a token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionSpec {
+    pub id: String,
+    /// Name accepted on the command line; `None` for extensions that are only
+    /// ever pulled in by others, such as `EIP712`
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// `erc20`, `erc721` and/or `erc1155`
+    pub base_types: Vec<String>,
+    pub oz_versions: Vec<OpenZeppelinVersion>,
+    /// Contract to inherit, without the `Upgradeable` suffix
+    pub contract: String,
+    /// Path relative to the OpenZeppelin contracts root, or a remapped `@...` path
+    pub import: String,
+    /// Arguments of the base constructor call (or `__X_init` call); `None` for no call
+    pub constructor_args: Option<String>,
+    /// Values the extension takes at deployment, in the order they are given
+    pub parameters: Vec<ExtensionParameter>,
+    /// Statements run in the constructor (or `initialize`), indented as they should appear
+    pub constructor_body: Vec<String>,
+    pub state_variables: Vec<String>,
+    pub functions: Vec<String>,
+    /// Role `functions` are restricted to through the `access_modifier` snippet
+    /// variable, e.g. `pauser`; contracts without access control reject the extension
+    pub role: Option<String>,
+    /// Overridable functions the contract declares, e.g. `_update`, or
+    /// `Nonces.nonces` for one declared by one of its own bases
+    pub overrides: Vec<String>,
+    /// Ids of extensions this one makes redundant, e.g. `EIP712` for `ERC20Permit`
+    pub provides: Vec<String>,
+    /// Ids of extensions this one needs, added ahead of it when missing
+    pub implies: Vec<String>,
+    /// Ids of extensions this one cannot be combined with
+    pub conflicts: Vec<String>,
+}
+
+impl ExtensionSpec {
+    /// Whether the extension can be added to `base_type`
+    pub fn supports_base(&self, base_type: &ContractType) -> bool {
+        self.base_types.iter().any(|t| t == base_type.type_name())
+    }
+
+    pub fn supports_version(&self, oz_version: OpenZeppelinVersion) -> bool {
+        self.oz_versions.contains(&oz_version)
+    }
+
+    /// `args` checked against `parameters`, with addresses checksummed;
+    /// no values at all means the defaults apply
+    pub fn check_args(&self, args: &[String]) -> Result<Vec<String>> {
+        let label = self.name.as_deref().unwrap_or(&self.id);
+        if args.is_empty() {
+            return Ok(Vec::new());
+        }
+        if self.parameters.is_empty() {
+            return Err(GramrError::Other(format!("Extension `{}` takes no values", label)));
+        }
+        if args.len() != self.parameters.len() {
+            let names: Vec<&str> = self.parameters.iter().map(|p| p.name.as_str()).collect();
+            return Err(GramrError::Other(format!(
+                "Extension `{}` takes {} value{} ({}), got {}",
+                label,
+                names.len(),
+                if names.len() == 1 { "" } else { "s" },
+                names.join(":"),
+                args.len()
+            )));
+        }
+
+        self.parameters
+            .iter()
+            .zip(args)
+            .map(|(parameter, value)| {
+                parameter.normalize(value).ok_or_else(|| {
+                    GramrError::Other(format!(
+                        "Invalid value `{}` for `{}` of `{}`: expected {}",
+                        value,
+                        parameter.name,
+                        label,
+                        if parameter.solidity_type == "address" { "an address" } else { "a whole number" }
+                    ))
+                })
+            })
+            .collect()
+    }
+}
+
+/// A value an extension takes at deployment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionParameter {
+    /// Solidity parameter name, which snippets refer to
+    pub name: String,
+    /// `address` or an unsigned integer type such as `uint256`
+    pub solidity_type: String,
+    pub description: Option<String>,
+    /// Expression generated tests and scripts pass when no value is given
+    pub default: String,
+}
+
+impl ExtensionParameter {
+    /// `value` as a Solidity literal of this parameter's type, if it is one
+    pub fn normalize(&self, value: &str) -> Option<String> {
+        if self.solidity_type == "address" {
+            let hex = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X"))?;
+            return (hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit())).then(|| checksum_address(hex));
+        }
+
+        is_number_literal(value).then(|| value.to_string())
+    }
+}
+
+/// Whether `value` is a whole-number Solidity literal: decimal digits with
+/// optional `_` separators and exponent, like `1_000e18`
+pub(crate) fn is_number_literal(value: &str) -> bool {
+    let (mantissa, exponent) = value.split_once(['e', 'E']).unwrap_or((value, "0"));
+    let is_digits = |digits: &str| {
+        digits.starts_with(|c: char| c.is_ascii_digit())
+            && digits.ends_with(|c: char| c.is_ascii_digit())
+            && digits.chars().all(|c| c.is_ascii_digit() || c == '_')
+    };
+    is_digits(mantissa) && is_digits(exponent)
+}
+
+/// The extensions a token ends up with, from [`ExtensionRegistry::resolve_all`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResolvedExtensions {
+    pub extensions: Vec<TokenExtension>,
+    /// One line for each extension dropped or added, explaining why
+    pub changes: Vec<String>,
+}
+
+/// Every extension gramr knows about: the built-ins plus any registered from TOML
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionRegistry {
+    specs: Vec<ExtensionSpec>,
+}
+
+impl Default for ExtensionRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl ExtensionRegistry {
+    /// The built-in OpenZeppelin extensions only
+    pub fn builtin() -> Self {
+        static BUILTIN: OnceLock<ExtensionRegistry> = OnceLock::new();
+        BUILTIN
+            .get_or_init(|| {
+                let mut registry = Self { specs: Vec::new() };
+                registry
+                    .register(BUILTIN_EXTENSIONS, "built-in extensions")
+                    .expect("built-in extensions are valid");
+                registry
+            })
+            .clone()
+    }
+
+    /// The built-ins, then every `*.toml` file in each of `dirs` in turn (by file
+    /// name), so later directories take precedence
+    pub fn load(dirs: &[PathBuf]) -> Result<Self> {
+        let mut registry = Self::builtin();
+
+        for dir in dirs {
+            let mut files = fs::read_dir(dir)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<std::io::Result<Vec<_>>>()?;
+            files.retain(|path| path.extension().is_some_and(|ext| ext == "toml"));
+            files.sort();
+
+            for path in files {
+                let content = fs::read_to_string(&path)?;
+                registry.register(&content, &path.display().to_string())?;
+            }
+        }
+
+        Ok(registry)
+    }
+
+    /// Add the `[[extension]]` entries in `content`; `source` names it in errors.
+    ///
+    /// Entries replace every existing entry with the same id, in its place.
+    pub fn register(&mut self, content: &str, source: &str) -> Result<()> {
+        let table: Table = content
+            .parse()
+            .map_err(|e| GramrError::InvalidConfig(format!("{}: {}", source, e)))?;
+        if let Some(key) = table.keys().find(|key| *key != "extension") {
+            return Err(GramrError::InvalidConfig(format!("{}: unknown key `{}`", source, key)));
+        }
+
+        let entries = match table.get("extension") {
+            Some(Value::Array(entries)) => entries.as_slice(),
+            Some(_) => return Err(invalid_type("extension", "an array of tables", source)),
+            None => &[],
+        };
+
+        let mut specs: Vec<ExtensionSpec> = Vec::new();
+        for entry in entries {
+            let entry = entry
+                .as_table()
+                .ok_or_else(|| invalid_type("extension", "an array of tables", source))?;
+            specs.push(parse_spec(entry, source)?);
+        }
+
+        for spec in &specs {
+            let clash = self
+                .specs
+                .iter()
+                .chain(&specs)
+                .find(|other| {
+                    other.id != spec.id
+                        && other.name.is_some()
+                        && other.name == spec.name
+                        && other.base_types.iter().any(|t| spec.base_types.contains(t))
+                });
+            if let Some(other) = clash {
+                return Err(GramrError::InvalidConfig(format!(
+                    "{}: `{}` and `{}` are both named `{}` for the same base type",
+                    source,
+                    spec.id,
+                    other.id,
+                    spec.name.as_deref().unwrap_or_default()
+                )));
+            }
+        }
+
+        let mut ids: Vec<&str> = Vec::new();
+        for spec in &specs {
+            if !ids.contains(&spec.id.as_str()) {
+                ids.push(&spec.id);
+            }
+        }
+        for id in ids {
+            let replacements = specs.iter().filter(|spec| spec.id == id).cloned();
+            match self.specs.iter().position(|spec| spec.id == id) {
+                Some(index) => {
+                    self.specs.retain(|spec| spec.id != id);
+                    self.specs.splice(index..index, replacements);
+                }
+                None => self.specs.extend(replacements),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Every registered entry, in registration order
+    pub fn specs(&self) -> &[ExtensionSpec] {
+        &self.specs
+    }
+
+    /// The entry for `extension` in the `oz_version` release line
+    pub fn get(&self, extension: &TokenExtension, oz_version: OpenZeppelinVersion) -> Option<&ExtensionSpec> {
+        self.specs
+            .iter()
+            .find(|spec| spec.id == extension.id() && spec.supports_version(oz_version))
+    }
+
+    /// Parse command-line extension names, case-insensitively, each optionally
+    /// followed by `=` and its parameter values separated by `:`
+    /// (`royalty=0x...:500`)
+    pub fn parse(&self, names: &[String]) -> Result<Vec<TokenExtension>> {
+        names
+            .iter()
+            .map(|argument| {
+                let (name, values) = match argument.split_once('=') {
+                    Some((name, values)) => (name.trim(), values.split(':').map(|v| v.trim().to_string()).collect()),
+                    None => (argument.as_str(), Vec::new()),
+                };
+                let spec = self
+                    .specs
+                    .iter()
+                    .find(|spec| spec.name.as_deref().is_some_and(|n| n.eq_ignore_ascii_case(name)))
+                    .ok_or_else(|| {
+                        GramrError::Other(format!(
+                            "Unknown extension: {}. Available extensions: {}",
+                            name,
+                            self.names().join(", ")
+                        ))
+                    })?;
+                Ok(TokenExtension::from_id(&spec.id).with_args(spec.check_args(&values)?))
+            })
+            .collect()
+    }
+
+    /// `extension` as it applies to `base_type`: itself when compatible,
+    /// otherwise the extension of the same name for that base type (so
+    /// `burnable` on an ERC721 becomes `ERC721Burnable`)
+    pub fn resolve(&self, base_type: &ContractType, extension: &TokenExtension) -> Result<TokenExtension> {
+        let spec = self.find(extension)?;
+        let spec = if spec.supports_base(base_type) {
+            spec
+        } else {
+            spec.name
+                .as_ref()
+                .and_then(|name| {
+                    self.specs
+                        .iter()
+                        .find(|other| other.name.as_ref() == Some(name) && other.supports_base(base_type))
+                })
+                .ok_or_else(|| {
+                    GramrError::Other(format!(
+                        "Extension {} is not compatible with base type {:?}",
+                        extension, base_type
+                    ))
+                })?
+        };
+
+        let args = spec.check_args(extension.args())?;
+        Ok(TokenExtension::from_id(&spec.id).with_args(args))
+    }
+
+    /// Error unless every one of `extensions` can be added to `base_type` and
+    /// none of them conflict with each other
+    pub fn check_compatible(&self, base_type: &ContractType, extensions: &[TokenExtension]) -> Result<()> {
+        self.resolve_requested(base_type, extensions).map(|_| ())
+    }
+
+    /// The extensions a `base_type` token built against `oz_version` ends up
+    /// with: each one resolved for the base type, duplicates dropped and
+    /// implied extensions added when missing. Every extension comes after the
+    /// ones it implies, requested or not, so the inheritance list linearizes.
+    /// Errors when two of them conflict.
+    pub fn resolve_all(
+        &self,
+        base_type: &ContractType,
+        extensions: &[TokenExtension],
+        oz_version: OpenZeppelinVersion,
+    ) -> Result<ResolvedExtensions> {
+        let mut resolved = self.resolve_requested(base_type, extensions)?;
+        let requested = std::mem::take(&mut resolved.extensions);
+
+        let mut visiting: Vec<String> = Vec::new();
+        for extension in &requested {
+            self.push_with_implied(extension, oz_version, &requested, &mut visiting, &mut resolved)?;
+        }
+
+        self.check_conflicts(&resolved.extensions)?;
+        Ok(resolved)
+    }
+
+    /// Named extensions that can be added to `base_type`, one per name
+    pub fn available(&self, base_type: &ContractType) -> Vec<&ExtensionSpec> {
+        let mut available: Vec<&ExtensionSpec> = Vec::new();
+        for spec in &self.specs {
+            if spec.name.is_some()
+                && spec.supports_base(base_type)
+                && !available.iter().any(|other| other.name == spec.name)
+            {
+                available.push(spec);
+            }
+        }
+        available
+    }
+
+    /// Pushes `extension` after what it implies: a requested extension with
+    /// the implied id (or providing it) is moved ahead, anything else is added
+    fn push_with_implied(
+        &self,
+        extension: &TokenExtension,
+        oz_version: OpenZeppelinVersion,
+        requested: &[TokenExtension],
+        visiting: &mut Vec<String>,
+        resolved: &mut ResolvedExtensions,
+    ) -> Result<()> {
+        let spec = self.require(extension, oz_version)?;
+        // Already placed, or part of a cycle that is left in the order given
+        if resolved.extensions.iter().any(|other| other.id() == extension.id()) || visiting.contains(&spec.id) {
+            return Ok(());
+        }
+        visiting.push(spec.id.clone());
+
+        for implied in &spec.implies {
+            if self.find_provider(&resolved.extensions, implied, oz_version)?.is_some() {
+                continue;
+            }
+            match self.find_provider(requested, implied, oz_version)? {
+                Some(provider) => self.push_with_implied(provider, oz_version, requested, visiting, resolved)?,
+                None => {
+                    let implied = TokenExtension::from_id(implied);
+                    resolved.changes.push(format!(
+                        "Adding {}, which {} requires",
+                        self.describe(&implied),
+                        self.describe(extension)
+                    ));
+                    self.push_with_implied(&implied, oz_version, requested, visiting, resolved)?;
+                }
+            }
+        }
+
+        visiting.retain(|id| id != &spec.id);
+        resolved.extensions.push(extension.clone());
+        Ok(())
+    }
+
+    /// The first of `extensions` that is, or stands in for, the extension with `id`
+    fn find_provider<'a>(
+        &self,
+        extensions: &'a [TokenExtension],
+        id: &str,
+        oz_version: OpenZeppelinVersion,
+    ) -> Result<Option<&'a TokenExtension>> {
+        for extension in extensions {
+            let spec = self.require(extension, oz_version)?;
+            if spec.id == id || spec.provides.iter().any(|provided| provided == id) {
+                return Ok(Some(extension));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Each of `extensions` resolved for `base_type`, without duplicates or
+    /// conflicts; unlike [`ExtensionRegistry::resolve_all`], implied extensions
+    /// are not added
+    pub fn resolve_requested(&self, base_type: &ContractType, extensions: &[TokenExtension]) -> Result<ResolvedExtensions> {
+        let mut resolved = ResolvedExtensions::default();
+        for extension in extensions {
+            let converted = self.resolve(base_type, extension)?;
+            if resolved.extensions.iter().any(|other| other.id() == converted.id()) {
+                resolved
+                    .changes
+                    .push(format!("Ignoring duplicate extension {}", self.describe(&converted)));
+            } else {
+                resolved.extensions.push(converted);
+            }
+        }
+
+        self.check_conflicts(&resolved.extensions)?;
+        Ok(resolved)
+    }
+
+    fn check_conflicts(&self, extensions: &[TokenExtension]) -> Result<()> {
+        for (index, first) in extensions.iter().enumerate() {
+            let first_spec = self.find(first)?;
+            for second in &extensions[index + 1..] {
+                let second_spec = self.find(second)?;
+                if first_spec.conflicts.contains(&second_spec.id) || second_spec.conflicts.contains(&first_spec.id) {
+                    return Err(GramrError::Other(format!(
+                        "Extensions {} and {} cannot be combined",
+                        self.describe(first),
+                        self.describe(second)
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `` `burnable` (ERC721Burnable) ``, or just the id for unnamed extensions
+    fn describe(&self, extension: &TokenExtension) -> String {
+        match self.find(extension).ok().and_then(|spec| spec.name.as_ref()) {
+            Some(name) => format!("`{}` ({})", name, extension),
+            None => extension.to_string(),
+        }
+    }
+
+    /// Any entry for `extension`, regardless of release line
+    fn find(&self, extension: &TokenExtension) -> Result<&ExtensionSpec> {
+        self.specs
+            .iter()
+            .find(|spec| spec.id == extension.id())
+            .ok_or_else(|| GramrError::Other(format!("Unknown extension: {}", extension)))
+    }
+
+    /// Like [`ExtensionRegistry::get`], but an error explaining why there is no entry
+    pub fn require(&self, extension: &TokenExtension, oz_version: OpenZeppelinVersion) -> Result<&ExtensionSpec> {
+        let any_version = self.find(extension)?;
+        self.get(extension, oz_version).ok_or_else(|| {
+            GramrError::Other(format!(
+                "{} is not available in OpenZeppelin Contracts {}.x; use a {}.x release",
+                extension,
+                oz_version.major(),
+                any_version.oz_versions[0].major()
+            ))
+        })
+    }
+
+    fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for name in self.specs.iter().filter_map(|spec| spec.name.as_deref()) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+}
+
+fn parse_spec(entry: &Table, source: &str) -> Result<ExtensionSpec> {
+    let invalid = |message: String| GramrError::InvalidConfig(format!("{}: {}", source, message));
+
+    let id = string_key(entry, "id", source)?.ok_or_else(|| invalid("every extension needs an `id`".to_string()))?;
+    if !is_identifier(&id) {
+        return Err(invalid(format!("invalid extension id `{}`", id)));
+    }
+    let context = format!(" in extension `{}`", id);
+    if let Some(key) = entry.keys().find(|key| !KEYS.contains(&key.as_str())) {
+        return Err(invalid(format!("unknown key `{}`{}", key, context)));
+    }
+
+    let name = string_key(entry, "name", source)?;
+    if let Some(name) = &name {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_') {
+            return Err(invalid(format!(
+                "invalid extension name `{}`{} (use lowercase letters, digits, `-` and `_`)",
+                name, context
+            )));
+        }
+    }
+
+    let role = string_key(entry, "role", source)?;
+    if let Some(role) = &role {
+        if role.is_empty() || !role.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+            return Err(invalid(format!(
+                "invalid role `{}`{} (use lowercase letters and `_`, e.g. `pauser`)",
+                role, context
+            )));
+        }
+    }
+
+    let overrides = string_array_key(entry, "overrides", source)?.unwrap_or_default();
+    for name in &overrides {
+        let (declared_in, function) = name.split_once('.').unwrap_or(("", name));
+        if !(declared_in.is_empty() || is_identifier(declared_in)) || !OVERRIDABLE_FUNCTIONS.contains(&function) {
+            return Err(invalid(format!(
+                "unknown override `{}`{} (expected one of {}, optionally prefixed with the declaring contract)",
+                name,
+                context,
+                OVERRIDABLE_FUNCTIONS.join(", ")
+            )));
+        }
+    }
+
+    let base_types = string_array_key(entry, "base_types", source)?
+        .ok_or_else(|| invalid(format!("missing `base_types`{}", context)))?;
+    if let Some(base_type) = base_types.iter().find(|t| !BASE_TYPES.contains(&t.as_str())) {
+        return Err(invalid(format!(
+            "unknown base type `{}`{} (expected {})",
+            base_type,
+            context,
+            BASE_TYPES.join(", ")
+        )));
+    }
+
+    let oz_versions = match entry.get("oz_versions") {
+        None => vec![OpenZeppelinVersion::V4, OpenZeppelinVersion::V5],
+        Some(Value::Array(versions)) => versions
+            .iter()
+            .map(|version| match version.as_integer() {
+                Some(4) => Ok(OpenZeppelinVersion::V4),
+                Some(5) => Ok(OpenZeppelinVersion::V5),
+                _ => Err(invalid(format!("`oz_versions`{} may only contain 4 and 5", context))),
+            })
+            .collect::<Result<_>>()?,
+        Some(_) => return Err(invalid_type("oz_versions", "an array of integers", source)),
+    };
+    if oz_versions.is_empty() {
+        return Err(invalid(format!("`oz_versions`{} cannot be empty", context)));
+    }
+
+    let spec = ExtensionSpec {
+        contract: string_key(entry, "contract", source)?.unwrap_or_else(|| id.clone()),
+        import: string_key(entry, "import", source)?
+            .ok_or_else(|| invalid(format!("missing `import`{}", context)))?,
+        description: string_key(entry, "description", source)?,
+        constructor_args: string_key(entry, "constructor_args", source)?,
+        parameters: parse_parameters(entry, &context, source)?,
+        constructor_body: string_array_key(entry, "constructor_body", source)?.unwrap_or_default(),
+        state_variables: string_array_key(entry, "state_variables", source)?.unwrap_or_default(),
+        functions: string_array_key(entry, "functions", source)?.unwrap_or_default(),
+        role,
+        overrides,
+        provides: string_array_key(entry, "provides", source)?.unwrap_or_default(),
+        implies: string_array_key(entry, "implies", source)?.unwrap_or_default(),
+        conflicts: string_array_key(entry, "conflicts", source)?.unwrap_or_default(),
+        id,
+        name,
+        base_types,
+        oz_versions,
+    };
+
+    // Catch template syntax errors here rather than halfway through generation
+    let env = Environment::new();
+    let snippets = spec
+        .constructor_args
+        .iter()
+        .chain(&spec.constructor_body)
+        .chain(&spec.state_variables)
+        .chain(&spec.functions);
+    for snippet in snippets {
+        env.template_from_str(snippet)
+            .map_err(|e| invalid(format!("{}{}", e, context)))?;
+    }
+
+    Ok(spec)
+}
+
+fn parse_parameters(entry: &Table, context: &str, source: &str) -> Result<Vec<ExtensionParameter>> {
+    let invalid = |message: String| GramrError::InvalidConfig(format!("{}: {}", source, message));
+    let parameters = match entry.get("parameters") {
+        Some(Value::Array(parameters)) => parameters,
+        Some(_) => return Err(invalid_type("parameters", "an array of tables", source)),
+        None => return Ok(Vec::new()),
+    };
+
+    parameters
+        .iter()
+        .map(|parameter| {
+            let parameter = parameter
+                .as_table()
+                .ok_or_else(|| invalid_type("parameters", "an array of tables", source))?;
+            if let Some(key) = parameter.keys().find(|key| !PARAMETER_KEYS.contains(&key.as_str())) {
+                return Err(invalid(format!("unknown parameter key `{}`{}", key, context)));
+            }
+
+            let name = string_key(parameter, "name", source)?
+                .ok_or_else(|| invalid(format!("every parameter needs a `name`{}", context)))?;
+            if !is_identifier(&name) {
+                return Err(invalid(format!("invalid parameter name `{}`{}", name, context)));
+            }
+            let solidity_type = string_key(parameter, "type", source)?
+                .ok_or_else(|| invalid(format!("missing `type` for parameter `{}`{}", name, context)))?;
+            if !is_parameter_type(&solidity_type) {
+                return Err(invalid(format!(
+                    "unsupported type `{}` for parameter `{}`{} (use address or uint8 to uint256)",
+                    solidity_type, name, context
+                )));
+            }
+
+            Ok(ExtensionParameter {
+                default: string_key(parameter, "default", source)?
+                    .ok_or_else(|| invalid(format!("missing `default` for parameter `{}`{}", name, context)))?,
+                description: string_key(parameter, "description", source)?,
+                name,
+                solidity_type,
+            })
+        })
+        .collect()
+}
+
+fn is_parameter_type(solidity_type: &str) -> bool {
+    match solidity_type.strip_prefix("uint") {
+        Some("") => true,
+        Some(bits) => bits.parse::<u16>().is_ok_and(|bits| bits % 8 == 0 && (8..=256).contains(&bits)),
+        None => solidity_type == "address",
+    }
+}
+
+/// EIP-55 mixed-case encoding of a 40-digit hex address, which Solidity
+/// requires of address literals
+fn checksum_address(hex: &str) -> String {
+    let hex = hex.to_ascii_lowercase();
+    let mut hash = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(hex.as_bytes());
+    keccak.finalize(&mut hash);
+
+    let mut checksummed = String::from("0x");
+    for (index, c) in hex.chars().enumerate() {
+        let nibble = if index % 2 == 0 { hash[index / 2] >> 4 } else { hash[index / 2] & 0x0f };
+        checksummed.push(if nibble >= 8 { c.to_ascii_uppercase() } else { c });
+    }
+    checksummed
+}
+
+fn is_identifier(id: &str) -> bool {
+    id.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn string_key(table: &Table, key: &str, source: &str) -> Result<Option<String>> {
+    match table.get(key) {
+        Some(value) => value
+            .as_str()
+            .map(|s| Some(s.to_string()))
+            .ok_or_else(|| invalid_type(key, "a string", source)),
+        None => Ok(None),
+    }
+}
+
+fn string_array_key(table: &Table, key: &str, source: &str) -> Result<Option<Vec<String>>> {
+    let Some(value) = table.get(key) else {
+        return Ok(None);
+    };
+    value
+        .as_array()
+        .and_then(|items| items.iter().map(|item| item.as_str().map(str::to_string)).collect())
+        .map(Some)
+        .ok_or_else(|| invalid_type(key, "an array of strings", source))
+}
+
+fn invalid_type(key: &str, expected: &str, source: &str) -> GramrError {
+    GramrError::InvalidConfig(format!("{}: `{}` must be {}", source, key, expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_builtin_resolves_by_base_type() {
+        let registry = ExtensionRegistry::builtin();
+
+        assert_eq!(
+            registry.resolve(&ContractType::ERC721Upgradeable, &TokenExtension::ERC20Burnable).unwrap(),
+            TokenExtension::ERC721Burnable
+        );
+        assert_eq!(
+            registry.resolve(&ContractType::ERC1155, &TokenExtension::ERC721URIStorage).unwrap(),
+            TokenExtension::ERC1155URIStorage
+        );
+        assert_eq!(
+            registry.resolve(&ContractType::ERC721, &TokenExtension::ERC20Votes).unwrap(),
+            TokenExtension::ERC721Votes
+        );
+        let err = registry.resolve(&ContractType::ERC1155, &TokenExtension::ERC20Permit).unwrap_err();
+        assert!(err.to_string().contains("Extension ERC20Permit is not compatible with base type ERC1155"));
+
+        let erc1155: Vec<_> = registry
+            .available(&ContractType::ERC1155)
+            .iter()
+            .filter_map(|spec| spec.name.as_deref())
+            .collect();
+        assert_eq!(erc1155, ["pausable", "burnable", "supply", "uristorage"]);
+    }
+
+    #[test]
+    fn test_implied_extensions_follow_version() {
+        let registry = ExtensionRegistry::builtin();
+        let votes = [TokenExtension::ERC20Votes];
+        let resolve = |extensions: &[TokenExtension], oz_version| {
+            registry.resolve_all(&ContractType::ERC20, extensions, oz_version)
+        };
+
+        let v5 = resolve(&votes, OpenZeppelinVersion::V5).unwrap();
+        assert_eq!(v5.extensions, [TokenExtension::Custom("EIP712".to_string(), Vec::new()), TokenExtension::ERC20Votes]);
+        assert_eq!(v5.changes, ["Adding EIP712, which `votes` (ERC20Votes) requires"]);
+        assert_eq!(
+            resolve(&votes, OpenZeppelinVersion::V4).unwrap().extensions,
+            [TokenExtension::ERC20Permit, TokenExtension::ERC20Votes]
+        );
+        // ERC20Permit already provides the EIP712 domain, so it goes first
+        let with_permit = resolve(&[TokenExtension::ERC20Votes, TokenExtension::ERC20Permit], OpenZeppelinVersion::V5).unwrap();
+        assert_eq!(with_permit.extensions, [TokenExtension::ERC20Permit, TokenExtension::ERC20Votes]);
+        assert!(with_permit.changes.is_empty());
+        // In 4.x ERC20Votes extends ERC20Permit, which has to come before it even when listed after
+        let v4_with_permit = resolve(
+            &[TokenExtension::ERC20Pausable, TokenExtension::ERC20Votes, TokenExtension::ERC20Permit],
+            OpenZeppelinVersion::V4,
+        )
+        .unwrap();
+        assert_eq!(
+            v4_with_permit.extensions,
+            [TokenExtension::ERC20Pausable, TokenExtension::ERC20Permit, TokenExtension::ERC20Votes]
+        );
+        assert!(v4_with_permit.changes.is_empty());
+
+        let err = resolve(&[TokenExtension::ERC1363], OpenZeppelinVersion::V4).unwrap_err();
+        assert!(err.to_string().contains("ERC1363 is not available in OpenZeppelin Contracts 4.x"));
+    }
+
+    #[test]
+    fn test_duplicates_dropped_and_conflicts_rejected() {
+        let registry = ExtensionRegistry::builtin();
+
+        let resolved = registry
+            .resolve_all(
+                &ContractType::ERC721,
+                &[TokenExtension::ERC20Burnable, TokenExtension::ERC721Burnable, TokenExtension::ERC721Votes],
+                OpenZeppelinVersion::V5,
+            )
+            .unwrap();
+        assert_eq!(
+            resolved.extensions,
+            [TokenExtension::ERC721Burnable, TokenExtension::Custom("EIP712".to_string(), Vec::new()), TokenExtension::ERC721Votes]
+        );
+        assert_eq!(
+            resolved.changes,
+            [
+                "Ignoring duplicate extension `burnable` (ERC721Burnable)",
+                "Adding EIP712, which `votes` (ERC721Votes) requires",
+            ]
+        );
+
+        let err = registry
+            .check_compatible(&ContractType::ERC721, &[TokenExtension::ERC721Enumerable, TokenExtension::ERC721Consecutive])
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Extensions `enumerable` (ERC721Enumerable) and `consecutive` (ERC721Consecutive) cannot be combined"));
+        let err = registry
+            .resolve_all(&ContractType::ERC20, &[TokenExtension::ERC4626(Vec::new()), TokenExtension::ERC20Wrapper(Vec::new())], OpenZeppelinVersion::V4)
+            .unwrap_err();
+        assert!(err.to_string().contains("cannot be combined"));
+    }
+
+    #[test]
+    fn test_parse_extension_parameters() {
+        let registry = ExtensionRegistry::builtin();
+
+        let parsed = registry
+            .parse(&names(&["capped=1_000_000e18", "Royalty=0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed:500", "burnable"]))
+            .unwrap();
+        assert_eq!(
+            parsed,
+            [
+                TokenExtension::ERC20Capped(names(&["1_000_000e18"])),
+                // Checksummed, as Solidity rejects address literals otherwise
+                TokenExtension::ERC721Royalty(names(&["0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed", "500"])),
+                TokenExtension::ERC20Burnable,
+            ]
+        );
+        // Values follow the extension to the base type it resolves to
+        let wrapper = registry.parse(&names(&["wrapper=0x0000000000000000000000000000000000000001"])).unwrap();
+        assert_eq!(
+            registry.resolve(&ContractType::ERC721, &wrapper[0]).unwrap(),
+            TokenExtension::ERC721Wrapper(names(&["0x0000000000000000000000000000000000000001"]))
+        );
+
+        let err = registry.parse(&names(&["royalty=0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"])).unwrap_err();
+        assert!(err.to_string().contains("`royalty` takes 2 values (royaltyReceiver:royaltyFee), got 1"), "{}", err);
+        let err = registry.parse(&names(&["capped=lots"])).unwrap_err();
+        assert!(err.to_string().contains("Invalid value `lots` for `cap_` of `capped`: expected a whole number"));
+        assert!(registry.parse(&names(&["wrapper=0x1234"])).is_err());
+        assert!(registry.parse(&names(&["burnable=1"])).unwrap_err().to_string().contains("takes no values"));
+    }
+
+    #[test]
+    fn test_load_registers_and_replaces_extensions() {
+        let user = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        fs::write(
+            user.path().join("acme.toml"),
+            r#"
+[[extension]]
+id = "ERC20Blocklist"
+name = "blocklist"
+base_types = ["erc20"]
+import = "@acme/contracts/ERC20Blocklist.sol"
+implies = ["ERC20Pausable"]
+"#,
+        )
+        .unwrap();
+        fs::write(
+            project.path().join("burnable.toml"),
+            r#"
+[[extension]]
+id = "ERC20Burnable"
+name = "burnable"
+base_types = ["erc20"]
+import = "token/ERC20/extensions/ERC20Burnable.sol"
+functions = ["    // burnable for {{ name }}"]
+"#,
+        )
+        .unwrap();
+        fs::write(project.path().join("README.md"), "not an extension").unwrap();
+
+        let registry = ExtensionRegistry::load(&[user.path().to_path_buf(), project.path().to_path_buf()]).unwrap();
+
+        let parsed = registry.parse(&names(&["blocklist", "burnable"])).unwrap();
+        assert_eq!(parsed, [TokenExtension::Custom("ERC20Blocklist".to_string(), Vec::new()), TokenExtension::ERC20Burnable]);
+        assert_eq!(
+            registry
+                .resolve_all(&ContractType::ERC20, &parsed[..1], OpenZeppelinVersion::V5)
+                .unwrap()
+                .extensions,
+            [TokenExtension::ERC20Pausable, TokenExtension::Custom("ERC20Blocklist".to_string(), Vec::new())]
+        );
+        let burnable = registry.get(&TokenExtension::ERC20Burnable, OpenZeppelinVersion::V4).unwrap();
+        assert_eq!(burnable.functions, ["    // burnable for {{ name }}"]);
+        // Replaced in place, so `burnable` still means the ERC20 flavour first
+        assert_eq!(registry.parse(&names(&["burnable"])).unwrap(), [TokenExtension::ERC20Burnable]);
+        assert!(registry.parse(&names(&["nope"])).unwrap_err().to_string().contains("blocklist"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_extension_files() {
+        let mut registry = ExtensionRegistry::builtin();
+
+        let missing_import = "[[extension]]\nid = \"Foo\"\nbase_types = [\"erc20\"]\n";
+        let err = registry.register(missing_import, "foo.toml").unwrap_err().to_string();
+        assert!(err.contains("foo.toml: missing `import` in extension `Foo`"), "{}", err);
+
+        let bad_base = "[[extension]]\nid = \"Foo\"\nbase_types = [\"erc777\"]\nimport = \"Foo.sol\"\n";
+        assert!(registry.register(bad_base, "foo.toml").unwrap_err().to_string().contains("erc777"));
+
+        let bad_parameter = "[[extension]]\nid = \"Foo\"\nbase_types = [\"erc20\"]\nimport = \"Foo.sol\"\nparameters = [{ name = \"owner\", type = \"string\", default = \"msg.sender\" }]\n";
+        let err = registry.register(bad_parameter, "foo.toml").unwrap_err().to_string();
+        assert!(err.contains("unsupported type `string` for parameter `owner`"), "{}", err);
+
+        let bad_role = "[[extension]]\nid = \"Foo\"\nbase_types = [\"erc20\"]\nimport = \"Foo.sol\"\nrole = \"Pauser\"\n";
+        let err = registry.register(bad_role, "foo.toml").unwrap_err().to_string();
+        assert!(err.contains("invalid role `Pauser`"), "{}", err);
+
+        let bad_override = "[[extension]]\nid = \"Foo\"\nbase_types = [\"erc20\"]\nimport = \"Foo.sol\"\noverrides = [\"transfer\"]\n";
+        let err = registry.register(bad_override, "foo.toml").unwrap_err().to_string();
+        assert!(err.contains("unknown override `transfer` in extension `Foo`"), "{}", err);
+
+        let broken_snippet = "[[extension]]\nid = \"Foo\"\nbase_types = [\"erc20\"]\nimport = \"Foo.sol\"\nconstructor_args = \"{{ name\"\n";
+        assert!(registry.register(broken_snippet, "foo.toml").is_err());
+
+        let clashing_name = "[[extension]]\nid = \"Foo\"\nname = \"permit\"\nbase_types = [\"erc20\"]\nimport = \"Foo.sol\"\n";
+        let err = registry.register(clashing_name, "foo.toml").unwrap_err().to_string();
+        assert!(err.contains("`Foo` and `ERC20Permit` are both named `permit`"), "{}", err);
+    }
+}
@@ -0,0 +1,278 @@
+use crate::error::{GramrError, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use toml::{Table, Value};
+
+/// Environment variable Foundry uses to select the active profile
+pub const FOUNDRY_PROFILE_ENV: &str = "FOUNDRY_PROFILE";
+
+const DEFAULT_PROFILE: &str = "default";
+
+/// Project layout and remapping settings read from `foundry.toml`.
+///
+/// Paths are kept exactly as written in the file (relative to the project
+/// root); `FoundryProject` is responsible for joining them onto the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoundryConfig {
+    pub src: String,
+    pub test: String,
+    pub script: String,
+    pub libs: Vec<String>,
+    pub remappings: Vec<String>,
+    /// Soldeer dependencies from the top-level `[dependencies]` table, by name and version
+    pub dependencies: BTreeMap<String, String>,
+    /// Whether the file configures Soldeer through `[dependencies]` or `[soldeer]`
+    pub uses_soldeer: bool,
+}
+
+impl Default for FoundryConfig {
+    fn default() -> Self {
+        Self {
+            src: "src".to_string(),
+            test: "test".to_string(),
+            script: "script".to_string(),
+            libs: vec!["lib".to_string()],
+            remappings: Vec::new(),
+            dependencies: BTreeMap::new(),
+            uses_soldeer: false,
+        }
+    }
+}
+
+impl FoundryConfig {
+    /// Load `foundry.toml` from `root` using the profile named by `FOUNDRY_PROFILE`
+    pub fn load(root: &Path) -> Result<Self> {
+        let profile = std::env::var(FOUNDRY_PROFILE_ENV)
+            .ok()
+            .filter(|p| !p.is_empty())
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+
+        Self::load_with_profile(root, &profile)
+    }
+
+    /// Load `foundry.toml` from `root` using an explicit profile.
+    ///
+    /// Like Foundry itself, a named profile inherits every setting it does not
+    /// override from `[profile.default]`.
+    pub fn load_with_profile(root: &Path, profile: &str) -> Result<Self> {
+        let path = root.join("foundry.toml");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        Self::parse(&content, profile)
+    }
+
+    /// Parse the contents of a `foundry.toml` file for the given profile
+    pub fn parse(content: &str, profile: &str) -> Result<Self> {
+        let table: Table = content
+            .parse()
+            .map_err(|e| GramrError::InvalidConfig(format!("foundry.toml: {}", e)))?;
+
+        let profiles = table.get("profile").and_then(Value::as_table);
+
+        let mut config = Self::default();
+        if let Some(default) = profiles.and_then(|p| p.get(DEFAULT_PROFILE)).and_then(Value::as_table) {
+            config.apply(default)?;
+        }
+
+        if profile != DEFAULT_PROFILE {
+            if let Some(selected) = profiles.and_then(|p| p.get(profile)).and_then(Value::as_table) {
+                config.apply(selected)?;
+            }
+        }
+
+        if let Some(dependencies) = table.get("dependencies") {
+            let dependencies = dependencies
+                .as_table()
+                .ok_or_else(|| invalid_type("dependencies", "a table"))?;
+            for (name, spec) in dependencies {
+                config.dependencies.insert(name.clone(), dependency_version(name, spec)?);
+            }
+        }
+        config.uses_soldeer = table.contains_key("dependencies") || table.contains_key("soldeer");
+
+        Ok(config)
+    }
+
+    /// Overlay the keys set in a single `[profile.*]` table.
+    ///
+    /// Foundry accepts `contracts`, `tests` and `libraries` as aliases.
+    fn apply(&mut self, profile: &Table) -> Result<()> {
+        if let Some(src) = string_key(profile, &["src", "contracts"])? {
+            self.src = src;
+        }
+        if let Some(test) = string_key(profile, &["test", "tests"])? {
+            self.test = test;
+        }
+        if let Some(script) = string_key(profile, &["script"])? {
+            self.script = script;
+        }
+        if let Some(libs) = string_array_key(profile, &["libs", "libraries"])? {
+            self.libs = libs;
+        }
+        if let Some(remappings) = string_array_key(profile, &["remappings"])? {
+            self.remappings = remappings;
+        }
+        Ok(())
+    }
+}
+
+fn first_key<'a>(table: &'a Table, keys: &[&str]) -> Option<&'a Value> {
+    keys.iter().find_map(|key| table.get(*key))
+}
+
+fn string_key(table: &Table, keys: &[&str]) -> Result<Option<String>> {
+    match first_key(table, keys) {
+        Some(value) => value
+            .as_str()
+            .map(|s| Some(s.to_string()))
+            .ok_or_else(|| invalid_type(keys[0], "a string")),
+        None => Ok(None),
+    }
+}
+
+fn string_array_key(table: &Table, keys: &[&str]) -> Result<Option<Vec<String>>> {
+    let Some(value) = first_key(table, keys) else {
+        return Ok(None);
+    };
+
+    value
+        .as_array()
+        .and_then(|values| {
+            values
+                .iter()
+                .map(|v| v.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+        })
+        .map(Some)
+        .ok_or_else(|| invalid_type(keys[0], "an array of strings"))
+}
+
+/// Version of a Soldeer dependency, written either as `"1.0.0"` or `{ version = "1.0.0", ... }`
+fn dependency_version(name: &str, spec: &Value) -> Result<String> {
+    spec.as_str()
+        .or_else(|| spec.get("version").and_then(Value::as_str))
+        .map(str::to_string)
+        .ok_or_else(|| {
+            GramrError::InvalidConfig(format!("foundry.toml: dependency `{}` has no version", name))
+        })
+}
+
+fn invalid_type(key: &str, expected: &str) -> GramrError {
+    GramrError::InvalidConfig(format!("foundry.toml: `{}` must be {}", key, expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_defaults_without_foundry_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = FoundryConfig::load_with_profile(temp_dir.path(), "default").unwrap();
+
+        assert_eq!(config, FoundryConfig::default());
+        assert_eq!(config.libs, vec!["lib"]);
+    }
+
+    #[test]
+    fn test_reads_default_profile() {
+        let config = FoundryConfig::parse(
+            r#"
+[profile.default]
+src = "contracts"
+test = "tests"
+script = "scripts"
+libs = ["node_modules", "lib"]
+"#,
+            "default",
+        )
+        .unwrap();
+
+        assert_eq!(config.src, "contracts");
+        assert_eq!(config.test, "tests");
+        assert_eq!(config.script, "scripts");
+        assert_eq!(config.libs, vec!["node_modules", "lib"]);
+    }
+
+    #[test]
+    fn test_named_profile_inherits_from_default() {
+        let content = r#"
+[profile.default]
+src = "contracts"
+libs = ["dependencies"]
+
+[profile.ci]
+test = "test/integration"
+"#;
+        let config = FoundryConfig::parse(content, "ci").unwrap();
+
+        assert_eq!(config.src, "contracts");
+        assert_eq!(config.test, "test/integration");
+        assert_eq!(config.script, "script");
+        assert_eq!(config.libs, vec!["dependencies"]);
+    }
+
+    #[test]
+    fn test_reads_remappings() {
+        let content = r#"
+[profile.default]
+remappings = ["oz/=lib/openzeppelin-contracts/contracts/"]
+"#;
+        let config = FoundryConfig::parse(content, "default").unwrap();
+
+        assert_eq!(config.remappings, vec!["oz/=lib/openzeppelin-contracts/contracts/"]);
+    }
+
+    #[test]
+    fn test_reads_soldeer_dependencies() {
+        let content = r#"
+[profile.default]
+libs = ["dependencies"]
+
+[dependencies]
+forge-std = "1.9.4"
+"@openzeppelin-contracts" = { version = "5.1.0" }
+"#;
+        let config = FoundryConfig::parse(content, "default").unwrap();
+
+        assert!(config.uses_soldeer);
+        assert_eq!(config.dependencies["forge-std"], "1.9.4");
+        assert_eq!(config.dependencies["@openzeppelin-contracts"], "5.1.0");
+
+        let config = FoundryConfig::parse("[profile.default]\n", "default").unwrap();
+        assert!(!config.uses_soldeer);
+        assert!(config.dependencies.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_profile_falls_back_to_default() {
+        let content = "[profile.default]\nsrc = \"contracts\"\n";
+        let config = FoundryConfig::parse(content, "missing").unwrap();
+
+        assert_eq!(config.src, "contracts");
+    }
+
+    #[test]
+    fn test_key_aliases() {
+        let content = "[profile.default]\ncontracts = \"sol\"\ntests = \"spec\"\nlibraries = [\"deps\"]\n";
+        let config = FoundryConfig::parse(content, "default").unwrap();
+
+        assert_eq!(config.src, "sol");
+        assert_eq!(config.test, "spec");
+        assert_eq!(config.libs, vec!["deps"]);
+    }
+
+    #[test]
+    fn test_invalid_toml_is_reported() {
+        let result = FoundryConfig::parse("[profile.default\nsrc = ", "default");
+        assert!(matches!(result, Err(GramrError::InvalidConfig(_))));
+
+        let result = FoundryConfig::parse("[profile.default]\nlibs = \"lib\"\n", "default");
+        assert!(matches!(result, Err(GramrError::InvalidConfig(msg)) if msg.contains("libs")));
+    }
+}
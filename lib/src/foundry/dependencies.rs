@@ -0,0 +1,219 @@
+use super::config::FoundryConfig;
+use super::remappings::Package;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// OpenZeppelin release gramr pins when it installs the contracts
+pub const OPENZEPPELIN_VERSION: &str = "5.4.0";
+
+/// forge-std release gramr pins when it installs the standard library
+pub const FORGE_STD_VERSION: &str = "1.9.7";
+
+/// How a Foundry project manages its Solidity dependencies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyManager {
+    /// Git submodules through `forge install`, checked out under `lib/`
+    Git,
+    /// Soldeer packages through `forge soldeer install`, unpacked under `dependencies/`
+    Soldeer,
+}
+
+impl DependencyManager {
+    /// Soldeer is in use when `foundry.toml` configures it or a `soldeer.lock` exists
+    pub fn detect(root: &Path, config: &FoundryConfig) -> Self {
+        if config.uses_soldeer || root.join("soldeer.lock").exists() {
+            DependencyManager::Soldeer
+        } else {
+            DependencyManager::Git
+        }
+    }
+
+    /// Arguments for `forge` that install `package` pinned to `version`
+    pub fn install_args(self, package: Package, version: &str) -> Vec<String> {
+        match self {
+            DependencyManager::Git => vec![
+                "install".to_string(),
+                format!("{}@v{}", package.git_repository(), version),
+            ],
+            DependencyManager::Soldeer => vec![
+                "soldeer".to_string(),
+                "install".to_string(),
+                format!("{}~{}", package.soldeer_name(), version),
+            ],
+        }
+    }
+}
+
+/// A dependency found on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledPackage {
+    pub path: PathBuf,
+    pub manager: DependencyManager,
+    pub version: Option<String>,
+}
+
+/// Find `package` in the project's `libs` or in Soldeer's `dependencies/` directory,
+/// preferring the version pinned in `dependencies` (`foundry.toml`'s `[dependencies]`)
+pub fn find_installed(
+    root: &Path,
+    libs: &[PathBuf],
+    dependencies: &BTreeMap<String, String>,
+    package: Package,
+) -> Option<InstalledPackage> {
+    if let Some(path) = libs
+        .iter()
+        .map(|lib| lib.join(package.lib_name()))
+        .find(|path| path.exists())
+    {
+        let version = package_json_version(&path);
+        return Some(InstalledPackage {
+            path,
+            manager: DependencyManager::Git,
+            version,
+        });
+    }
+
+    let pinned = dependencies.get(package.soldeer_name());
+    if let Some(version) = pinned {
+        let path = root
+            .join("dependencies")
+            .join(format!("{}-{}", package.soldeer_name(), version));
+        if path.exists() {
+            return Some(InstalledPackage {
+                path,
+                manager: DependencyManager::Soldeer,
+                version: Some(version.clone()),
+            });
+        }
+    }
+
+    let (version, path) = fs::read_dir(root.join("dependencies"))
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?;
+            let version = name
+                .strip_prefix(package.soldeer_name())?
+                .strip_prefix('-')
+                .filter(|_| package.matches_segment(name) == Some(true))?
+                .to_string();
+            Some((version, path))
+        })
+        .max_by_key(|(version, _)| version_key(version))?;
+
+    Some(InstalledPackage {
+        path,
+        manager: DependencyManager::Soldeer,
+        version: Some(version),
+    })
+}
+
+/// Numeric components of a version string, so `10.0.0` sorts after `5.1.0`
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
+
+/// `version` field of a package's `package.json`, which git checkouts of OpenZeppelin ship
+fn package_json_version(package_dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(package_dir.join("package.json")).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    json.get("version")?.as_str().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_dependency_manager() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let config = FoundryConfig::default();
+        assert_eq!(DependencyManager::detect(root, &config), DependencyManager::Git);
+
+        let soldeer_config = FoundryConfig {
+            uses_soldeer: true,
+            ..FoundryConfig::default()
+        };
+        assert_eq!(
+            DependencyManager::detect(root, &soldeer_config),
+            DependencyManager::Soldeer
+        );
+
+        fs::write(root.join("soldeer.lock"), "").unwrap();
+        assert_eq!(DependencyManager::detect(root, &config), DependencyManager::Soldeer);
+    }
+
+    #[test]
+    fn test_install_args_pin_version() {
+        assert_eq!(
+            DependencyManager::Git.install_args(Package::OpenZeppelin, "5.4.0"),
+            vec!["install", "OpenZeppelin/openzeppelin-contracts@v5.4.0"]
+        );
+        assert_eq!(
+            DependencyManager::Soldeer.install_args(Package::OpenZeppelinUpgradeable, "5.4.0"),
+            vec!["soldeer", "install", "@openzeppelin-contracts-upgradeable~5.4.0"]
+        );
+        assert_eq!(
+            DependencyManager::Git.install_args(Package::ForgeStd, "1.9.7"),
+            vec!["install", "foundry-rs/forge-std@v1.9.7"]
+        );
+    }
+
+    #[test]
+    fn test_find_git_checkout_with_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let package_dir = root.join("lib/openzeppelin-contracts");
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::write(package_dir.join("package.json"), r#"{"name": "openzeppelin-solidity", "version": "5.1.0"}"#).unwrap();
+
+        let installed = find_installed(
+            root,
+            &[root.join("lib")],
+            &BTreeMap::new(),
+            Package::OpenZeppelin,
+        )
+        .unwrap();
+
+        assert_eq!(installed.manager, DependencyManager::Git);
+        assert_eq!(installed.path, package_dir);
+        assert_eq!(installed.version.as_deref(), Some("5.1.0"));
+        assert!(find_installed(root, &[root.join("lib")], &BTreeMap::new(), Package::OpenZeppelinUpgradeable).is_none());
+    }
+
+    #[test]
+    fn test_find_soldeer_dependency_prefers_pinned_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("dependencies/@openzeppelin-contracts-4.9.6")).unwrap();
+        fs::create_dir_all(root.join("dependencies/@openzeppelin-contracts-5.1.0")).unwrap();
+        fs::create_dir_all(root.join("dependencies/@openzeppelin-contracts-upgradeable-5.1.0")).unwrap();
+
+        let mut config = FoundryConfig {
+            uses_soldeer: true,
+            ..FoundryConfig::default()
+        };
+        config
+            .dependencies
+            .insert("@openzeppelin-contracts".to_string(), "4.9.6".to_string());
+
+        let installed = find_installed(root, &[], &config.dependencies, Package::OpenZeppelin).unwrap();
+        assert_eq!(installed.manager, DependencyManager::Soldeer);
+        assert_eq!(installed.version.as_deref(), Some("4.9.6"));
+
+        let installed =
+            find_installed(root, &[], &BTreeMap::new(), Package::OpenZeppelin).unwrap();
+        assert_eq!(installed.version.as_deref(), Some("5.1.0"));
+
+        let installed = find_installed(root, &[], &config.dependencies, Package::OpenZeppelinUpgradeable).unwrap();
+        assert_eq!(installed.path, root.join("dependencies/@openzeppelin-contracts-upgradeable-5.1.0"));
+    }
+}
@@ -1,6 +1,13 @@
+use super::config::FoundryConfig;
+use super::dependencies::{self, DependencyManager, InstalledPackage, OPENZEPPELIN_VERSION};
+use super::remappings::{self, ImportPaths, Package, Remappings};
 use crate::error::{GramrError, Result};
-use crate::project::Project;
+use crate::project::{relative_path, Project};
+use crate::prompt;
+use colored::*;
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 use which::which;
 
@@ -10,6 +17,13 @@ pub struct FoundryProject {
     pub src_dir: PathBuf,
     pub test_dir: PathBuf,
     pub script_dir: PathBuf,
+    pub libs: Vec<PathBuf>,
+    /// Soldeer pins from `foundry.toml`'s `[dependencies]`, as loaded with the project
+    dependencies: BTreeMap<String, String>,
+    /// The `forge` executable that installs dependencies
+    forge: PathBuf,
+    /// Whether to ask on stdin before changing remappings
+    interactive: bool,
 }
 
 impl FoundryProject {
@@ -22,16 +36,45 @@ impl FoundryProject {
 
         let root = Self::find_foundry_root(&current_dir)?;
 
-        let src_dir = root.join("src");
-        let test_dir = root.join("test");
-        let script_dir = root.join("script");
+        Self::from_root(root)
+    }
+
+    /// Build a project from its root, taking directory layout from `foundry.toml`
+    pub fn from_root(root: PathBuf) -> Result<Self> {
+        let config = FoundryConfig::load(&root)?;
+        Ok(Self::with_config(root, &config))
+    }
+
+    /// Build a project from an already loaded `foundry.toml` configuration
+    pub fn with_config(root: PathBuf, config: &FoundryConfig) -> Self {
+        let src_dir = root.join(&config.src);
+        let test_dir = root.join(&config.test);
+        let script_dir = root.join(&config.script);
+        let libs = config.libs.iter().map(|lib| root.join(lib)).collect();
 
-        Ok(Self {
+        Self {
             root,
             src_dir,
             test_dir,
             script_dir,
-        })
+            libs,
+            dependencies: config.dependencies.clone(),
+            forge: PathBuf::from("forge"),
+            interactive: std::io::stdin().is_terminal(),
+        }
+    }
+
+    /// Install dependencies with the `forge` at `forge` rather than the one on `PATH`
+    pub fn with_forge(mut self, forge: impl Into<PathBuf>) -> Self {
+        self.forge = forge.into();
+        self
+    }
+
+    /// Set whether to ask before changing remappings; without asking, nothing is changed.
+    /// Defaults to whether stdin is a terminal.
+    pub fn with_interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self
     }
 
     fn has_forge() -> Result<bool> {
@@ -60,20 +103,49 @@ impl FoundryProject {
     }
 
     pub fn has_openzeppelin(&self) -> bool {
-        self.root.join("lib/openzeppelin-contracts").exists()
+        self.installed_package(Package::OpenZeppelin).is_some()
     }
 
     pub fn has_openzeppelin_upgradeable(&self) -> bool {
-        self.root
-            .join("lib/openzeppelin-contracts-upgradeable")
-            .exists()
+        self.installed_package(Package::OpenZeppelinUpgradeable).is_some()
     }
 
     pub fn install_openzeppelin(&self) -> Result<()> {
+        self.install_package(Package::OpenZeppelin, OPENZEPPELIN_VERSION)
+    }
+
+    pub fn install_openzeppelin_upgradeable(&self) -> Result<()> {
+        self.install_package(Package::OpenZeppelinUpgradeable, OPENZEPPELIN_VERSION)
+    }
+
+    /// Version of OpenZeppelin installed in the project, if any
+    pub fn openzeppelin_version(&self) -> Option<String> {
+        self.installed_package(Package::OpenZeppelin)
+            .or_else(|| self.installed_package(Package::OpenZeppelinUpgradeable))
+            .and_then(|installed| installed.version)
+    }
+
+    /// Whether the project installs dependencies with `forge install` or Soldeer
+    pub fn dependency_manager(&self) -> Result<DependencyManager> {
+        let config = FoundryConfig::load(&self.root)?;
+        Ok(DependencyManager::detect(&self.root, &config))
+    }
+
+    /// Locate a dependency in `libs` or Soldeer's `dependencies/` directory
+    pub fn installed_package(&self, package: Package) -> Option<InstalledPackage> {
+        dependencies::find_installed(&self.root, &self.libs, &self.dependencies, package)
+    }
+
+    /// Install `package` pinned to `version` through the project's dependency manager.
+    ///
+    /// Soldeer records the pin in `foundry.toml` and `soldeer.lock`; `forge install`
+    /// checks out the matching release tag.
+    pub fn install_package(&self, package: Package, version: &str) -> Result<()> {
         use std::process::Command;
 
-        let output = Command::new("forge")
-            .args(&["install", "OpenZeppelin/openzeppelin-contracts"])
+        let manager = self.dependency_manager()?;
+        let output = Command::new(&self.forge)
+            .args(manager.install_args(package, version))
             .current_dir(&self.root)
             .output()?;
 
@@ -83,21 +155,44 @@ impl FoundryProject {
             ));
         }
 
-        Ok(())
+        self.offer_remapping(package)
     }
 
-    pub fn install_openzeppelin_upgradeable(&self) -> Result<()> {
-        use std::process::Command;
+    /// Remappings configured in `foundry.toml` and `remappings.txt`
+    pub fn remappings(&self) -> Result<Remappings> {
+        let config = FoundryConfig::load(&self.root)?;
+        Remappings::load(&self.root, &config.remappings)
+    }
 
-        let output = Command::new("forge")
-            .args(&["install", "OpenZeppelin/openzeppelin-contracts-upgradeable"])
-            .current_dir(&self.root)
-            .output()?;
+    /// Import prefixes generated code should use in this project
+    pub fn import_paths(&self) -> Result<ImportPaths> {
+        Ok(self.remappings()?.import_paths())
+    }
 
-        if !output.status.success() {
-            return Err(GramrError::ForgeCommandError(
-                String::from_utf8_lossy(&output.stderr).to_string(),
-            ));
+    /// Offer to add a remapping for a freshly installed package that has none
+    fn offer_remapping(&self, package: Package) -> Result<()> {
+        if self.remappings()?.contains(package) {
+            return Ok(());
+        }
+
+        let Some(installed) = self.installed_package(package) else {
+            return Ok(());
+        };
+
+        let remapping = package.default_remapping(
+            &relative_path(&self.root, &installed.path),
+            installed.manager == DependencyManager::Soldeer,
+        );
+        println!(
+            "{} No remapping found for {}",
+            "!".yellow(),
+            package.default_prefix().cyan()
+        );
+
+        let question = format!("  Add `{}` to remappings.txt?", remapping);
+        if prompt::confirm(&mut std::io::stdin().lock(), self.interactive, &question, false) {
+            remappings::add_remapping(&self.root, &remapping)?;
+            println!("{} Added remapping {}", "✓".green(), remapping);
         }
 
         Ok(())
@@ -137,3 +232,122 @@ impl Project for FoundryProject {
         self.install_openzeppelin_upgradeable()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_with_config_uses_foundry_toml_layout() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        fs::write(
+            root.join("foundry.toml"),
+            "[profile.default]\nsrc = \"contracts\"\nlibs = [\"dependencies\", \"lib\"]\n\n[profile.ci]\nscript = \"deploy\"\n",
+        )
+        .unwrap();
+
+        let config = FoundryConfig::load_with_profile(&root, "ci").unwrap();
+        let project = FoundryProject::with_config(root.clone(), &config);
+
+        assert_eq!(project.src_dir, root.join("contracts"));
+        assert_eq!(project.test_dir, root.join("test"));
+        assert_eq!(project.script_dir, root.join("deploy"));
+        assert_eq!(project.libs, vec![root.join("dependencies"), root.join("lib")]);
+    }
+
+    #[test]
+    fn test_has_openzeppelin_checks_every_lib() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        let config = FoundryConfig {
+            libs: vec!["lib".to_string(), "vendor".to_string()],
+            ..FoundryConfig::default()
+        };
+        let project = FoundryProject::with_config(root.clone(), &config);
+
+        assert!(!project.has_openzeppelin());
+
+        fs::create_dir_all(root.join("vendor/openzeppelin-contracts")).unwrap();
+        assert!(project.has_openzeppelin());
+        assert!(!project.has_openzeppelin_upgradeable());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_install_openzeppelin_with_forge_install() {
+        use crate::foundry::testing::StubForge;
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        fs::write(root.join("foundry.toml"), "[profile.default]\nsrc = \"src\"\n").unwrap();
+        let forge = StubForge::install();
+        let project = forge.project(&root);
+
+        assert!(!project.has_openzeppelin());
+        project.install_openzeppelin().unwrap();
+
+        assert_eq!(project.dependency_manager().unwrap(), DependencyManager::Git);
+        assert_eq!(
+            forge.calls(),
+            vec![format!("install OpenZeppelin/openzeppelin-contracts@v{}", OPENZEPPELIN_VERSION)]
+        );
+        assert!(project.has_openzeppelin());
+        assert_eq!(project.openzeppelin_version().as_deref(), Some(OPENZEPPELIN_VERSION));
+        // Without prompting, remappings are left alone
+        assert!(!root.join("remappings.txt").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_install_openzeppelin_with_soldeer() {
+        use crate::foundry::testing::StubForge;
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        fs::write(
+            root.join("foundry.toml"),
+            "[profile.default]\nlibs = [\"dependencies\"]\n\n[dependencies]\nforge-std = \"1.9.7\"\n",
+        )
+        .unwrap();
+        let forge = StubForge::install();
+        let project = forge.project(&root);
+
+        assert_eq!(project.dependency_manager().unwrap(), DependencyManager::Soldeer);
+        assert!(!project.has_openzeppelin_upgradeable());
+
+        project.install_openzeppelin_upgradeable().unwrap();
+
+        assert_eq!(
+            forge.calls(),
+            vec![format!(
+                "soldeer install @openzeppelin-contracts-upgradeable~{}",
+                OPENZEPPELIN_VERSION
+            )]
+        );
+        let installed = project
+            .installed_package(Package::OpenZeppelinUpgradeable)
+            .unwrap();
+        assert_eq!(installed.manager, DependencyManager::Soldeer);
+        assert_eq!(installed.version.as_deref(), Some(OPENZEPPELIN_VERSION));
+        assert!(!project.has_openzeppelin());
+
+        let config = FoundryConfig::load_with_profile(&root, "default").unwrap();
+        assert_eq!(
+            config.dependencies["@openzeppelin-contracts-upgradeable"],
+            OPENZEPPELIN_VERSION
+        );
+    }
+
+    #[test]
+    fn test_has_openzeppelin_detects_soldeer_dependencies() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        fs::create_dir_all(root.join("dependencies/@openzeppelin-contracts-5.1.0")).unwrap();
+        let project = FoundryProject::with_config(root, &FoundryConfig::default());
+
+        assert!(project.has_openzeppelin());
+        assert_eq!(project.openzeppelin_version().as_deref(), Some("5.1.0"));
+    }
+}
@@ -1,3 +1,11 @@
+pub mod config;
+pub mod dependencies;
 pub mod detector;
+pub mod remappings;
+#[cfg(test)]
+pub(crate) mod testing;
 
-pub use detector::FoundryProject;
\ No newline at end of file
+pub use config::FoundryConfig;
+pub use dependencies::{DependencyManager, InstalledPackage};
+pub use detector::FoundryProject;
+pub use remappings::{ImportPaths, Package, Remapping, Remappings};
\ No newline at end of file
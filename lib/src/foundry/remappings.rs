@@ -0,0 +1,491 @@
+use crate::error::{GramrError, Result};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A single Solidity import remapping: `[context:]name=path`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Remapping {
+    pub context: Option<String>,
+    pub name: String,
+    pub path: String,
+}
+
+impl Remapping {
+    pub fn parse(line: &str) -> Result<Self> {
+        let (lhs, path) = line
+            .trim()
+            .split_once('=')
+            .ok_or_else(|| GramrError::InvalidConfig(format!("invalid remapping `{}`", line.trim())))?;
+
+        let (context, name) = match lhs.split_once(':') {
+            Some((context, name)) => (Some(context.to_string()), name),
+            None => (None, lhs),
+        };
+
+        if name.is_empty() {
+            return Err(GramrError::InvalidConfig(format!(
+                "invalid remapping `{}`",
+                line.trim()
+            )));
+        }
+
+        Ok(Self {
+            context,
+            name: name.to_string(),
+            path: path.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Remapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(context) = &self.context {
+            write!(f, "{}:", context)?;
+        }
+        write!(f, "{}={}", self.name, self.path)
+    }
+}
+
+/// Dependencies whose import paths gramr emits in generated code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Package {
+    OpenZeppelin,
+    OpenZeppelinUpgradeable,
+    ForgeStd,
+}
+
+impl Package {
+    /// Import prefix used when no remapping says otherwise
+    pub fn default_prefix(self) -> &'static str {
+        match self {
+            Package::OpenZeppelin => "@openzeppelin/contracts/",
+            Package::OpenZeppelinUpgradeable => "@openzeppelin/contracts-upgradeable/",
+            Package::ForgeStd => "forge-std/",
+        }
+    }
+
+    /// Directory name used by `forge install`
+    pub fn lib_name(self) -> &'static str {
+        match self {
+            Package::OpenZeppelin => "openzeppelin-contracts",
+            Package::OpenZeppelinUpgradeable => "openzeppelin-contracts-upgradeable",
+            Package::ForgeStd => "forge-std",
+        }
+    }
+
+    /// GitHub repository `forge install` fetches the package from
+    pub fn git_repository(self) -> &'static str {
+        match self {
+            Package::OpenZeppelin => "OpenZeppelin/openzeppelin-contracts",
+            Package::OpenZeppelinUpgradeable => "OpenZeppelin/openzeppelin-contracts-upgradeable",
+            Package::ForgeStd => "foundry-rs/forge-std",
+        }
+    }
+
+    /// Package name used by Soldeer, which suffixes it with the version on disk
+    pub fn soldeer_name(self) -> &'static str {
+        match self {
+            Package::OpenZeppelin => "@openzeppelin-contracts",
+            Package::OpenZeppelinUpgradeable => "@openzeppelin-contracts-upgradeable",
+            Package::ForgeStd => "forge-std",
+        }
+    }
+
+    /// Directory holding the Solidity sources, relative to the package root.
+    ///
+    /// Soldeer publishes OpenZeppelin from npm, where sources sit at the root,
+    /// while git checkouts keep them under `contracts/`.
+    fn source_dir(self, soldeer: bool) -> &'static str {
+        match self {
+            Package::OpenZeppelin | Package::OpenZeppelinUpgradeable if soldeer => "",
+            Package::OpenZeppelin | Package::OpenZeppelinUpgradeable => "contracts/",
+            Package::ForgeStd => "src/",
+        }
+    }
+
+    /// Whether a path segment names this package, returning `Some(true)` for Soldeer layouts
+    pub(crate) fn matches_segment(self, segment: &str) -> Option<bool> {
+        if segment == self.lib_name() {
+            return Some(false);
+        }
+        if segment == self.soldeer_name() {
+            return Some(true);
+        }
+        segment
+            .strip_prefix(self.soldeer_name())
+            .and_then(|rest| rest.strip_prefix('-'))
+            .filter(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+            .map(|_| true)
+    }
+
+    /// Conventional remapping for a copy of the package installed at `package_dir`
+    pub fn default_remapping(self, package_dir: &str, soldeer: bool) -> Remapping {
+        Remapping {
+            context: None,
+            name: self.default_prefix().to_string(),
+            path: format!(
+                "{}/{}",
+                package_dir.trim_end_matches('/'),
+                self.source_dir(soldeer)
+            ),
+        }
+    }
+
+    /// Import prefix that reaches this package's sources through `remapping`, if it does
+    fn prefix_via(self, remapping: &Remapping) -> Option<String> {
+        if remapping.context.is_some() {
+            return None;
+        }
+
+        let path = remapping.path.trim_end_matches('/');
+        let segments: Vec<&str> = path.split('/').collect();
+        let (index, mut soldeer) = segments
+            .iter()
+            .enumerate()
+            .find_map(|(i, segment)| self.matches_segment(segment).map(|s| (i, s)))?;
+        soldeer |= segments[..index].contains(&"dependencies");
+
+        let rest = segments[index + 1..].join("/");
+        let rest = if rest.is_empty() { rest } else { format!("{}/", rest) };
+        let source_dir = self.source_dir(soldeer);
+
+        let mut prefix = remapping.name.clone();
+        if !prefix.ends_with('/') {
+            prefix.push('/');
+        }
+
+        if rest == source_dir {
+            Some(prefix)
+        } else if rest.is_empty() {
+            Some(format!("{}{}", prefix, source_dir))
+        } else {
+            None
+        }
+    }
+}
+
+/// Remappings explicitly configured for a project, from `foundry.toml` and `remappings.txt`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Remappings {
+    entries: Vec<Remapping>,
+}
+
+impl Remappings {
+    /// Combine `foundry.toml` remappings with those in `root/remappings.txt`.
+    ///
+    /// As in Foundry, entries from `foundry.toml` take precedence.
+    pub fn load(root: &Path, config_remappings: &[String]) -> Result<Self> {
+        let mut lines: Vec<String> = config_remappings.to_vec();
+
+        let file = root.join("remappings.txt");
+        if file.exists() {
+            lines.extend(fs::read_to_string(&file)?.lines().map(str::to_string));
+        }
+
+        Self::parse(lines.iter().map(String::as_str))
+    }
+
+    pub fn parse<'a>(lines: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        let mut entries: Vec<Remapping> = Vec::new();
+        for line in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let remapping = Remapping::parse(line)?;
+            if !entries
+                .iter()
+                .any(|r| r.context == remapping.context && r.name == remapping.name)
+            {
+                entries.push(remapping);
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn entries(&self) -> &[Remapping] {
+        &self.entries
+    }
+
+    /// Import prefix for a package, preferring its conventional name when it is remapped
+    pub fn prefix_for(&self, package: Package) -> Option<String> {
+        let candidates: Vec<(&Remapping, String)> = self
+            .entries
+            .iter()
+            .filter_map(|r| package.prefix_via(r).map(|prefix| (r, prefix)))
+            .collect();
+
+        candidates
+            .iter()
+            .find(|(r, _)| r.name.trim_end_matches('/') == package.default_prefix().trim_end_matches('/'))
+            .or_else(|| candidates.first())
+            .map(|(_, prefix)| prefix.clone())
+    }
+
+    pub fn contains(&self, package: Package) -> bool {
+        self.prefix_for(package).is_some()
+    }
+
+    /// Resolve the import prefixes generated code should use
+    pub fn import_paths(&self) -> ImportPaths {
+        let defaults = ImportPaths::default();
+        ImportPaths {
+            openzeppelin: self
+                .prefix_for(Package::OpenZeppelin)
+                .unwrap_or(defaults.openzeppelin),
+            openzeppelin_upgradeable: self
+                .prefix_for(Package::OpenZeppelinUpgradeable)
+                .unwrap_or(defaults.openzeppelin_upgradeable),
+            forge_std: self
+                .prefix_for(Package::ForgeStd)
+                .unwrap_or(defaults.forge_std),
+        }
+    }
+}
+
+/// Import prefixes for the dependencies generated Solidity code pulls in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportPaths {
+    pub openzeppelin: String,
+    pub openzeppelin_upgradeable: String,
+    pub forge_std: String,
+}
+
+impl Default for ImportPaths {
+    fn default() -> Self {
+        Self {
+            openzeppelin: Package::OpenZeppelin.default_prefix().to_string(),
+            openzeppelin_upgradeable: Package::OpenZeppelinUpgradeable.default_prefix().to_string(),
+            forge_std: Package::ForgeStd.default_prefix().to_string(),
+        }
+    }
+}
+
+impl ImportPaths {
+    /// Rewrite the default import prefixes in `source` to the resolved ones
+    pub fn rewrite(&self, source: &str) -> String {
+        if *self == Self::default() {
+            return source.to_string();
+        }
+
+        let replacements = [
+            (Package::OpenZeppelin, &self.openzeppelin),
+            (Package::OpenZeppelinUpgradeable, &self.openzeppelin_upgradeable),
+            (Package::ForgeStd, &self.forge_std),
+        ];
+
+        let mut lines: Vec<String> = source
+            .lines()
+            .map(|line| {
+                if !line.trim_start().starts_with("import ") {
+                    return line.to_string();
+                }
+                replacements.iter().fold(line.to_string(), |line, (package, prefix)| {
+                    line.replace(
+                        &format!("\"{}", package.default_prefix()),
+                        &format!("\"{}", prefix),
+                    )
+                })
+            })
+            .collect();
+
+        if source.ends_with('\n') {
+            lines.push(String::new());
+        }
+        lines.join("\n")
+    }
+}
+
+/// Append a remapping to `root/remappings.txt`, creating the file if needed
+pub fn add_remapping(root: &Path, remapping: &Remapping) -> Result<()> {
+    let path = root.join("remappings.txt");
+    let mut content = if path.exists() {
+        fs::read_to_string(&path)?
+    } else {
+        String::new()
+    };
+
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&remapping.to_string());
+    content.push('\n');
+
+    fs::write(&path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn remappings(lines: &[&str]) -> Remappings {
+        Remappings::parse(lines.iter().copied()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_remapping() {
+        let remapping = Remapping::parse("src/:oz/=lib/openzeppelin-contracts/contracts/").unwrap();
+
+        assert_eq!(remapping.context.as_deref(), Some("src/"));
+        assert_eq!(remapping.name, "oz/");
+        assert_eq!(remapping.path, "lib/openzeppelin-contracts/contracts/");
+        assert_eq!(remapping.to_string(), "src/:oz/=lib/openzeppelin-contracts/contracts/");
+
+        assert!(Remapping::parse("no-equals-sign").is_err());
+    }
+
+    #[test]
+    fn test_default_remapping() {
+        assert_eq!(
+            Package::OpenZeppelin
+                .default_remapping("lib/openzeppelin-contracts", false)
+                .to_string(),
+            "@openzeppelin/contracts/=lib/openzeppelin-contracts/contracts/"
+        );
+        assert_eq!(
+            Package::OpenZeppelin
+                .default_remapping("dependencies/@openzeppelin-contracts-5.4.0/", true)
+                .to_string(),
+            "@openzeppelin/contracts/=dependencies/@openzeppelin-contracts-5.4.0/"
+        );
+        assert_eq!(
+            Package::ForgeStd.default_remapping("vendor/forge-std/", false).to_string(),
+            "forge-std/=vendor/forge-std/src/"
+        );
+    }
+
+    #[test]
+    fn test_no_remappings_uses_defaults() {
+        assert_eq!(remappings(&[]).import_paths(), ImportPaths::default());
+    }
+
+    #[test]
+    fn test_custom_oz_alias() {
+        let paths = remappings(&["oz/=lib/openzeppelin-contracts/contracts/"]).import_paths();
+
+        assert_eq!(paths.openzeppelin, "oz/");
+        assert_eq!(paths.openzeppelin_upgradeable, "@openzeppelin/contracts-upgradeable/");
+        assert_eq!(paths.forge_std, "forge-std/");
+    }
+
+    #[test]
+    fn test_alias_to_package_root_adds_source_dir() {
+        let paths = remappings(&[
+            "oz/=lib/openzeppelin-contracts/",
+            "std/=lib/forge-std/",
+        ])
+        .import_paths();
+
+        assert_eq!(paths.openzeppelin, "oz/contracts/");
+        assert_eq!(paths.forge_std, "std/src/");
+    }
+
+    #[test]
+    fn test_soldeer_dependencies() {
+        let paths = remappings(&[
+            "@openzeppelin-contracts-5.1.0/=dependencies/@openzeppelin-contracts-5.1.0/",
+            "@openzeppelin-contracts-upgradeable-5.1.0/=dependencies/@openzeppelin-contracts-upgradeable-5.1.0/",
+            "forge-std-1.9.4/=dependencies/forge-std-1.9.4/",
+        ])
+        .import_paths();
+
+        assert_eq!(paths.openzeppelin, "@openzeppelin-contracts-5.1.0/");
+        assert_eq!(
+            paths.openzeppelin_upgradeable,
+            "@openzeppelin-contracts-upgradeable-5.1.0/"
+        );
+        assert_eq!(paths.forge_std, "forge-std-1.9.4/src/");
+    }
+
+    #[test]
+    fn test_conventional_name_preferred() {
+        let paths = remappings(&[
+            "oz/=lib/openzeppelin-contracts/contracts/",
+            "@openzeppelin/contracts/=dependencies/@openzeppelin-contracts-5.1.0/",
+        ])
+        .import_paths();
+
+        assert_eq!(paths.openzeppelin, "@openzeppelin/contracts/");
+    }
+
+    #[test]
+    fn test_context_and_partial_remappings_ignored() {
+        let set = remappings(&[
+            "src/:oz/=lib/openzeppelin-contracts/contracts/",
+            "tokens/=lib/openzeppelin-contracts/contracts/token/",
+        ]);
+
+        assert!(!set.contains(Package::OpenZeppelin));
+        assert_eq!(set.import_paths(), ImportPaths::default());
+    }
+
+    #[test]
+    fn test_first_definition_wins() {
+        let set = remappings(&[
+            "oz/=lib/openzeppelin-contracts/contracts/",
+            "oz/=lib/other/",
+        ]);
+
+        assert_eq!(set.entries().len(), 1);
+        assert_eq!(set.entries()[0].path, "lib/openzeppelin-contracts/contracts/");
+    }
+
+    #[test]
+    fn test_rewrite_only_touches_imports() {
+        let paths = ImportPaths {
+            openzeppelin: "oz/".to_string(),
+            openzeppelin_upgradeable: "ozu/".to_string(),
+            forge_std: "std/src/".to_string(),
+        };
+        let source = "import \"@openzeppelin/contracts/token/ERC20/ERC20.sol\";\n\
+import \"@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol\";\n\
+import \"forge-std/Test.sol\";\n\
+// see \"@openzeppelin/contracts/\"\n";
+
+        let rewritten = paths.rewrite(source);
+
+        assert!(rewritten.contains("import \"oz/token/ERC20/ERC20.sol\";"));
+        assert!(rewritten.contains("import \"ozu/proxy/utils/Initializable.sol\";"));
+        assert!(rewritten.contains("import \"std/src/Test.sol\";"));
+        assert!(rewritten.contains("// see \"@openzeppelin/contracts/\""));
+        assert!(rewritten.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_load_merges_config_and_file() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("remappings.txt"),
+            "# comment\nforge-std/=lib/forge-std/src/\noz/=lib/other/\n",
+        )
+        .unwrap();
+
+        let set = Remappings::load(
+            temp_dir.path(),
+            &["oz/=lib/openzeppelin-contracts/contracts/".to_string()],
+        )
+        .unwrap();
+
+        assert_eq!(set.entries().len(), 2);
+        assert_eq!(set.import_paths().openzeppelin, "oz/");
+    }
+
+    #[test]
+    fn test_add_remapping_appends_line() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("remappings.txt");
+        fs::write(&path, "forge-std/=lib/forge-std/src/").unwrap();
+
+        let remapping = Remapping::parse("@openzeppelin/contracts/=lib/openzeppelin-contracts/contracts/").unwrap();
+        add_remapping(temp_dir.path(), &remapping).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "forge-std/=lib/forge-std/src/\n@openzeppelin/contracts/=lib/openzeppelin-contracts/contracts/\n"
+        );
+    }
+}
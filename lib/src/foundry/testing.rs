@@ -0,0 +1,97 @@
+//! A stand-in `forge` binary for tests that exercise dependency installation.
+
+use super::FoundryProject;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const STUB_SCRIPT: &str = r#"#!/bin/sh
+echo "$@" >> "__LOG__"
+case "$1" in
+  install)
+    spec="$2"
+    repo="${spec%@*}"
+    version="${spec##*@v}"
+    dest="lib/${repo##*/}"
+    mkdir -p "$dest/contracts"
+    printf '{"version": "%s"}\n' "$version" > "$dest/package.json"
+    ;;
+  soldeer)
+    spec="$3"
+    name="${spec%~*}"
+    version="${spec##*~}"
+    mkdir -p "dependencies/$name-$version"
+    if grep -q '^\[dependencies\]' foundry.toml; then
+      awk -v line="\"$name\" = \"$version\"" '{ print } /^\[dependencies\]$/ { print line }' foundry.toml > foundry.toml.tmp
+      mv foundry.toml.tmp foundry.toml
+    else
+      printf '\n[dependencies]\n"%s" = "%s"\n' "$name" "$version" >> foundry.toml
+    fi
+    ;;
+  *)
+    echo "unsupported stub command: $*" >&2
+    exit 1
+    ;;
+esac
+"#;
+
+const FAILING_SCRIPT: &str = r#"#!/bin/sh
+echo "$@" >> "__LOG__"
+echo "stub install failure" >&2
+exit 1
+"#;
+
+/// A fake `forge` for projects built with [`StubForge::project`].
+///
+/// The stub mimics `forge install` and `forge soldeer install` by creating the
+/// directories they would, and logs every invocation.
+pub(crate) struct StubForge {
+    _dir: TempDir,
+    path: PathBuf,
+    log: PathBuf,
+}
+
+impl StubForge {
+    pub(crate) fn install() -> Self {
+        Self::with_script(STUB_SCRIPT)
+    }
+
+    /// A stub whose every invocation fails, as a broken network or tag would
+    pub(crate) fn failing() -> Self {
+        Self::with_script(FAILING_SCRIPT)
+    }
+
+    fn with_script(script: &str) -> Self {
+        let dir = TempDir::new().unwrap();
+        let log = dir.path().join("forge.log");
+        let forge = dir.path().join("forge");
+
+        fs::write(&forge, script.replace("__LOG__", &log.display().to_string())).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&forge, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        Self {
+            _dir: dir,
+            path: forge,
+            log,
+        }
+    }
+
+    /// The Foundry project at `root`, installing through this stub and never prompting
+    pub(crate) fn project(&self, root: &Path) -> FoundryProject {
+        FoundryProject::from_root(root.to_path_buf())
+            .unwrap()
+            .with_forge(&self.path)
+            .with_interactive(false)
+    }
+
+    /// Arguments of every `forge` invocation so far, one line per call
+    pub(crate) fn calls(&self) -> Vec<String> {
+        fs::read_to_string(&self.log)
+            .map(|log| log.lines().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+}
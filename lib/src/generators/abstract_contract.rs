@@ -1,14 +1,20 @@
 use crate::{
-    project::Project, templates::SolidityTemplate, GramrError, Language, ProjectType, Result,
+    changeset::Changeset, defaults::Defaults, project::Project, templates::{SolidityTemplate, StylusTemplate, Template, Templates}, GenerationPlan, GramrError, OverwritePolicy, Language, ProjectType, Result,
 };
+use colored::*;
+use std::fs;
 
-/// Abstract contract generator for creating empty abstract contracts
+/// Abstract contract generator for creating empty abstract contracts, or their
+/// Stylus equivalent: a storage struct and a trait with default methods
 pub struct AbstractContractGenerator {
     project: ProjectType,
     language: Language,
     name: String,
     pragma: Option<String>,
     license: Option<String>,
+    overwrite: OverwritePolicy,
+    defaults: Defaults,
+    inherited_by: Option<String>,
 }
 
 impl AbstractContractGenerator {
@@ -26,40 +32,94 @@ impl AbstractContractGenerator {
             name,
             pragma,
             license,
+            overwrite: OverwritePolicy::default(),
+            defaults: Defaults::default(),
+            inherited_by: None,
         }
     }
 
+    /// Set what happens when the abstract contract file already exists
+    pub fn with_overwrite_policy(mut self, overwrite: OverwritePolicy) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Set the defaults used for anything not given explicitly, e.g. from [`Defaults::load`]
+    pub fn with_defaults(mut self, defaults: Defaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Build the existing Stylus contract `contract` on the generated abstract contract
+    pub fn with_inherited_by(mut self, contract: String) -> Self {
+        self.inherited_by = Some(contract);
+        self
+    }
+
     /// Generate the abstract contract
     pub fn generate(&self) -> Result<()> {
+        let plan = self.plan()?.resolve_conflicts(self.overwrite)?;
+        if plan.files.is_empty() {
+            return Ok(());
+        }
+        // A failed commit undoes the Stylus setup as well as the staged files
+        let mut changeset = Changeset::stage(&plan)?;
+        if let ProjectType::Cargo(project) = &self.project {
+            for path in project.setup_files() {
+                changeset.snapshot(&path)?;
+            }
+            for prerequisite in project.repair_stylus_prerequisites()? {
+                println!("{} Added {}", "✓".green(), prerequisite.description());
+            }
+        }
+        changeset.commit()?;
+
+        if plan.files.iter().any(|file| file.label == "abstract contract") {
+            println!("✅ Abstract contract {} created successfully!", self.name);
+        }
+        if let Some(contract) = plan.files.iter().find(|file| file.label == "contract").and(self.inherited_by.as_ref()) {
+            println!("{} {} now inherits {}", "✓".green(), contract, self.name);
+        }
+        Ok(())
+    }
+
+    /// The abstract contract file `generate` would write, without writing it
+    pub fn plan(&self) -> Result<GenerationPlan> {
+        self.defaults.check_template_pack(crate::manifest::ResourceKind::Abstract, None)?;
+        if self.inherited_by.is_some() && self.language == Language::Solidity {
+            return Err(GramrError::Other(
+                "Wiring an abstract contract into a contract is only supported for Rust/Stylus".to_string(),
+            ));
+        }
+
         match self.language {
-            Language::Solidity => self.generate_solidity_abstract(),
-            Language::RustStylus => self.generate_rust_abstract(),
+            Language::Solidity => self.plan_solidity_abstract(),
+            Language::RustStylus => self.plan_rust_abstract(),
         }
     }
 
-    fn generate_solidity_abstract(&self) -> Result<()> {
-        let pragma = self.pragma.as_deref().unwrap_or("0.8.30");
-        let license = self.license.as_deref().unwrap_or("UNLICENSED");
+    fn plan_solidity_abstract(&self) -> Result<GenerationPlan> {
+        let pragma = self.pragma.as_deref().unwrap_or(&self.defaults.pragma);
+        let license = self.license.as_deref().unwrap_or(&self.defaults.license);
 
         let template = SolidityTemplate::new(
             self.name.clone(),
             crate::templates::ContractType::Abstract,
             pragma.to_string(),
             license.to_string(),
-        );
+        )
+        .with_natspec(self.defaults.natspec, self.defaults.author.clone())
+        .with_templates(Templates::load(&self.defaults.template_dirs())?);
 
-        let content = template.generate_abstract_contract();
+        let content = template.generate_abstract_contract()?;
 
         match &self.project {
             ProjectType::Foundry(foundry) => {
-                let abstracts_dir = foundry.src_dir().join("abstracts");
-                std::fs::create_dir_all(&abstracts_dir)?;
-
-                let file_path = abstracts_dir.join(format!("{}.sol", self.name));
-                std::fs::write(file_path, content)?;
+                let file_path = foundry.src_dir().join(&self.defaults.paths.abstracts).join(format!("{}.sol", self.name));
 
-                println!("✅ Abstract contract {} created successfully!", self.name);
-                Ok(())
+                let mut plan = GenerationPlan::new();
+                plan.add_file("abstract contract", file_path, content);
+                Ok(plan)
             }
             ProjectType::Cargo(_) => Err(GramrError::Other(
                 "Abstract contract generation for Rust/Stylus projects is not yet supported"
@@ -68,10 +128,102 @@ impl AbstractContractGenerator {
         }
     }
 
-    fn generate_rust_abstract(&self) -> Result<()> {
-        // For now, return an error since Rust/Stylus abstract contracts might work differently
-        Err(GramrError::Other(
-            "Abstract contract generation for Rust/Stylus projects is not yet supported. Use traits for similar patterns.".to_string()
-        ))
+    /// The storage struct and trait under the abstracts directory, declared from
+    /// `lib.rs` down, and the edits that build the requested contract on them
+    fn plan_rust_abstract(&self) -> Result<GenerationPlan> {
+        let ProjectType::Cargo(project) = &self.project else {
+            return Err(GramrError::Other(
+                "Rust/Stylus abstract contracts can only be generated in a Cargo project".to_string()
+            ));
+        };
+
+        let content = StylusTemplate::new(self.name.clone(), crate::templates::ContractType::Abstract)
+            .with_templates(Templates::load(&self.defaults.template_dirs())?)
+            .generate_abstract_contract()?;
+        let file_path = project
+            .src_dir()
+            .join(&self.defaults.paths.abstracts)
+            .join(format!("{}.rs", crate::stylus::module_name(&self.name)));
+
+        let mut plan = GenerationPlan::new();
+        plan.add_file("abstract contract", file_path.clone(), content);
+        for (path, content) in project.module_declarations(&file_path)? {
+            plan.add_edit("module", path, content);
+        }
+
+        if let Some(contract) = &self.inherited_by {
+            let contract_path = project.src_dir().join(format!("{}.rs", crate::stylus::module_name(contract)));
+            let source = fs::read_to_string(&contract_path).map_err(|_| {
+                GramrError::Other(format!("No contract {} at {}", contract, contract_path.display()))
+            })?;
+            let module = project.module_path(&file_path)?;
+            if let Some(updated) = crate::stylus::inherit_abstract(&source, contract, &self.name, &module)? {
+                plan.add_edit("contract", contract_path, updated);
+            }
+        }
+
+        plan.dependencies = project
+            .missing_stylus_prerequisites()?
+            .into_iter()
+            .map(|prerequisite| prerequisite.description().to_string())
+            .collect();
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::CargoProject;
+    use tempfile::TempDir;
+
+    fn generator(project: CargoProject, name: &str) -> AbstractContractGenerator {
+        AbstractContractGenerator::new(ProjectType::Cargo(project), Language::RustStylus, name.to_string(), None, None)
+    }
+
+    #[test]
+    fn test_stylus_abstract_is_a_storage_struct_and_trait() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = CargoProject::init(temp_dir.path(), "app").unwrap();
+
+        generator(project, "FeeBase").generate().unwrap();
+
+        let base = fs::read_to_string(temp_dir.path().join("src/abstracts/fee_base.rs")).unwrap();
+        assert!(base.contains("#[storage]\npub struct FeeBase {"));
+        assert!(base.contains("#[public]\nimpl FeeBase {"));
+        assert!(base.contains("pub trait IFeeBase {\n    /// The FeeBase state the contract embeds\n    fn fee_base(&self) -> &FeeBase;"));
+        assert!(base.contains("self.fee_base().admin.get()"));
+        assert_eq!(fs::read_to_string(temp_dir.path().join("src/abstracts/mod.rs")).unwrap(), "pub mod fee_base;\n");
+    }
+
+    #[test]
+    fn test_stylus_abstract_wired_into_contract() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = CargoProject::init(temp_dir.path(), "app").unwrap();
+        let contract = temp_dir.path().join("src/counter.rs");
+        fs::write(
+            &contract,
+            "use stylus_sdk::prelude::*;\n\n#[entrypoint]\n#[storage]\npub struct Counter {\n    count: StorageU256,\n}\n\n#[public]\nimpl Counter {\n}\n",
+        )
+        .unwrap();
+
+        let plan = generator(project, "FeeBase").with_inherited_by("Counter".to_string()).plan().unwrap();
+        assert!(plan.files.iter().any(|file| file.label == "contract" && file.edit));
+        plan.apply_with(OverwritePolicy::Error).unwrap();
+
+        let wired = fs::read_to_string(&contract).unwrap();
+        assert!(wired.contains("use crate::abstracts::fee_base::{FeeBase, IFeeBase};"));
+        assert!(wired.contains("#[inherit(FeeBase)]"));
+        assert!(wired.contains("impl IFeeBase for Counter {"));
+    }
+
+    #[test]
+    fn test_wiring_requires_an_existing_stylus_contract() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = CargoProject::init(temp_dir.path(), "app").unwrap();
+
+        let result = generator(project, "FeeBase").with_inherited_by("Missing".to_string()).plan();
+
+        assert!(matches!(result, Err(GramrError::Other(msg)) if msg.contains("No contract Missing")));
     }
 }
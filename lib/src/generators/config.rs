@@ -1,4 +1,4 @@
-use crate::{Result, GramrError, ProjectType, Language};
+use crate::{Result, GramrError, GenerationPlan, ProjectType, Language};
 
 /// Config generator for creating configuration files (placeholder for now)
 pub struct ConfigGenerator {
@@ -23,6 +23,11 @@ impl ConfigGenerator {
 
     /// Generate the config (placeholder)
     pub fn generate(&self) -> Result<()> {
+        self.plan()?.apply()
+    }
+
+    /// The config files `generate` would write (placeholder)
+    pub fn plan(&self) -> Result<GenerationPlan> {
         Err(GramrError::Other(
             format!("Config generation for '{}' is not yet implemented! This will be used to quickly scaffold config files like slither.json, foundry.toml, etc.", self.name)
         ))
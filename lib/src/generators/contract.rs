@@ -1,8 +1,11 @@
+use crate::changeset::Changeset;
 use crate::error::{GramrError, Result};
-use crate::foundry::FoundryProject;
-use crate::templates::{ContractType, SolidityTemplate};
+use crate::extensions::ExtensionRegistry;
+use crate::foundry::{FoundryProject, Package};
+use crate::plan::{GenerationPlan, OverwritePolicy, PlannedFile};
+use crate::project::relative_path;
+use crate::templates::{AccessControl, ContractType, OpenZeppelinVersion, SolidityTemplate, TokenMetadata};
 use colored::*;
-use std::fs;
 
 pub struct ContractGenerator {
     project: FoundryProject,
@@ -12,6 +15,9 @@ pub struct ContractGenerator {
     with_script: bool,
     pragma: String,
     license: String,
+    overwrite: OverwritePolicy,
+    token_metadata: TokenMetadata,
+    access_control: AccessControl,
 }
 
 impl ContractGenerator {
@@ -32,36 +38,107 @@ impl ContractGenerator {
             with_script,
             pragma,
             license,
+            overwrite: OverwritePolicy::default(),
+            token_metadata: TokenMetadata::default(),
+            access_control: AccessControl::default(),
         }
     }
 
+    /// Set what happens when a file to generate already exists
+    pub fn with_overwrite_policy(mut self, overwrite: OverwritePolicy) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Set the token name, symbol, decimals, initial supply and base URI
+    pub fn with_token_metadata(mut self, token_metadata: TokenMetadata) -> Self {
+        self.token_metadata = token_metadata;
+        self
+    }
+
+    /// Set who may call the privileged functions of the generated token
+    pub fn with_access_control(mut self, access_control: AccessControl) -> Self {
+        self.access_control = access_control;
+        self
+    }
+
     pub fn generate(&self) -> Result<()> {
         self.validate_name()?;
+        self.token_metadata.check(&self.contract_type)?;
+        self.access_control.check(&self.contract_type, &ExtensionRegistry::builtin(), self.oz_version())?;
 
-        self.project.ensure_directories()?;
+        let plan = self.build_plan()?.resolve_conflicts(self.overwrite)?;
+
+        let changeset = Changeset::stage(&plan)?;
 
         self.check_and_install_dependencies()?;
 
-        let template = SolidityTemplate::new(
-            self.contract_name.clone(),
-            self.contract_type.clone(),
-            self.pragma.clone(),
-            self.license.clone(),
-        );
+        changeset.commit()?;
 
-        self.create_contract_file(&template)?;
+        self.project.ensure_directories()?;
 
-        if self.with_test {
-            self.create_test_file(&template)?;
+        for file in &plan.files {
+            println!("{} Created {}: {}", "✓".green(), file.label, file.path.display());
         }
 
+        self.print_success();
+
+        Ok(())
+    }
+
+    /// The files `generate` would write, without installing or writing anything
+    pub fn plan(&self) -> Result<GenerationPlan> {
+        self.validate_name()?;
+        self.token_metadata.check(&self.contract_type)?;
+        self.access_control.check(&self.contract_type, &ExtensionRegistry::builtin(), self.oz_version())?;
+
+        let mut plan = self.build_plan()?;
+        plan.dependencies = self
+            .missing_dependency()
+            .map(|(_, lib_name)| lib_name.to_string())
+            .into_iter()
+            .collect();
+        Ok(plan)
+    }
+
+    fn build_plan(&self) -> Result<GenerationPlan> {
+        let template = self.template()?;
+
+        let mut plan = GenerationPlan::new();
+        plan.files.push(self.contract_file(&template)?);
+        if self.with_test {
+            plan.files.push(self.test_file(&template)?);
+        }
         if self.with_script {
-            self.create_script_file(&template)?;
+            plan.files.push(self.script_file(&template)?);
         }
+        Ok(plan)
+    }
 
-        self.print_success();
+    fn template(&self) -> Result<SolidityTemplate> {
+        Ok(SolidityTemplate::new(
+            self.contract_name.clone(),
+            self.contract_type.clone(),
+            self.pragma.clone(),
+            self.license.clone(),
+        )
+        .with_src_paths(
+            relative_path(&self.project.test_dir, &self.project.src_dir),
+            relative_path(&self.project.script_dir, &self.project.src_dir),
+        )
+        .with_import_paths(self.project.import_paths()?)
+        .with_oz_version(self.oz_version())
+        .with_token_metadata(self.token_metadata.clone())
+        .with_access_control(self.access_control))
+    }
 
-        Ok(())
+    /// The OpenZeppelin release line the project has installed
+    fn oz_version(&self) -> OpenZeppelinVersion {
+        self.project
+            .openzeppelin_version()
+            .as_deref()
+            .and_then(OpenZeppelinVersion::from_release)
+            .unwrap_or_default()
     }
 
     fn validate_name(&self) -> Result<()> {
@@ -90,109 +167,66 @@ impl ContractGenerator {
         Ok(())
     }
 
-    fn check_and_install_dependencies(&self) -> Result<()> {
-        match &self.contract_type {
-            ContractType::ERC20 | ContractType::ERC721 | ContractType::ERC1155 => {
-                if !self.project.has_openzeppelin() {
-                    println!("{} Installing OpenZeppelin contracts...", "→".yellow());
-                    self.project.install_openzeppelin()?;
-                    println!("{} OpenZeppelin contracts installed", "✓".green());
-                }
+    /// The OpenZeppelin package the contract needs but the project lacks, with its display name
+    fn missing_dependency(&self) -> Option<(Package, &'static str)> {
+        let base_type = match &self.contract_type {
+            ContractType::MultiInheritance { base_type, .. } => &**base_type,
+            other => other,
+        };
+
+        match base_type {
+            ContractType::ERC20 | ContractType::ERC721 | ContractType::ERC1155
+                if !self.project.has_openzeppelin() =>
+            {
+                Some((Package::OpenZeppelin, "OpenZeppelin contracts"))
             }
             ContractType::ERC20Upgradeable
             | ContractType::ERC721Upgradeable
-            | ContractType::ERC1155Upgradeable => {
-                if !self.project.has_openzeppelin_upgradeable() {
-                    println!(
-                        "{} Installing OpenZeppelin upgradeable contracts...",
-                        "→".yellow()
-                    );
-                    self.project.install_openzeppelin_upgradeable()?;
-                    println!(
-                        "{} OpenZeppelin upgradeable contracts installed",
-                        "✓".green()
-                    );
-                }
+            | ContractType::ERC1155Upgradeable
+                if !self.project.has_openzeppelin_upgradeable() =>
+            {
+                Some((Package::OpenZeppelinUpgradeable, "OpenZeppelin upgradeable contracts"))
             }
-            ContractType::MultiInheritance { base_type, .. } => match **base_type {
-                ContractType::ERC20 | ContractType::ERC721 | ContractType::ERC1155 => {
-                    if !self.project.has_openzeppelin() {
-                        println!("{} Installing OpenZeppelin contracts...", "→".yellow());
-                        self.project.install_openzeppelin()?;
-                        println!("{} OpenZeppelin contracts installed", "✓".green());
-                    }
-                }
-                ContractType::ERC20Upgradeable
-                | ContractType::ERC721Upgradeable
-                | ContractType::ERC1155Upgradeable => {
-                    if !self.project.has_openzeppelin_upgradeable() {
-                        println!(
-                            "{} Installing OpenZeppelin upgradeable contracts...",
-                            "→".yellow()
-                        );
-                        self.project.install_openzeppelin_upgradeable()?;
-                        println!(
-                            "{} OpenZeppelin upgradeable contracts installed",
-                            "✓".green()
-                        );
-                    }
-                }
-                _ => {}
-            },
-            ContractType::Basic | ContractType::Interface | ContractType::Abstract => {}
+            _ => None,
         }
-        Ok(())
     }
 
-    fn create_contract_file(&self, template: &SolidityTemplate) -> Result<()> {
-        let file_path = self
-            .project
-            .src_dir
-            .join(format!("{}.sol", self.contract_name));
+    fn check_and_install_dependencies(&self) -> Result<()> {
+        let Some((package, lib_name)) = self.missing_dependency() else {
+            return Ok(());
+        };
 
-        if file_path.exists() {
-            return Err(GramrError::FileExists(file_path.display().to_string()));
+        println!("{} Installing {}...", "→".yellow(), lib_name);
+        match package {
+            Package::OpenZeppelinUpgradeable => self.project.install_openzeppelin_upgradeable()?,
+            _ => self.project.install_openzeppelin()?,
         }
-
-        let content = template.generate_contract();
-        fs::write(&file_path, content)?;
-
-        println!("{} Created contract: {}", "✓".green(), file_path.display());
+        println!("{} {} installed", "✓".green(), lib_name);
         Ok(())
     }
 
-    fn create_test_file(&self, template: &SolidityTemplate) -> Result<()> {
-        let file_path = self
-            .project
-            .test_dir
-            .join(format!("{}.t.sol", self.contract_name));
-
-        if file_path.exists() {
-            return Err(GramrError::FileExists(file_path.display().to_string()));
-        }
-
-        let content = template.generate_test();
-        fs::write(&file_path, content)?;
-
-        println!("{} Created test: {}", "✓".green(), file_path.display());
-        Ok(())
+    fn contract_file(&self, template: &SolidityTemplate) -> Result<PlannedFile> {
+        Ok(PlannedFile::new(
+            "contract",
+            self.project.src_dir.join(format!("{}.sol", self.contract_name)),
+            template.generate_contract()?,
+        ))
     }
 
-    fn create_script_file(&self, template: &SolidityTemplate) -> Result<()> {
-        let file_path = self
-            .project
-            .script_dir
-            .join(format!("{}.s.sol", self.contract_name));
-
-        if file_path.exists() {
-            return Err(GramrError::FileExists(file_path.display().to_string()));
-        }
-
-        let content = template.generate_script();
-        fs::write(&file_path, content)?;
+    fn test_file(&self, template: &SolidityTemplate) -> Result<PlannedFile> {
+        Ok(PlannedFile::new(
+            "test",
+            self.project.test_dir.join(format!("{}.t.sol", self.contract_name)),
+            template.generate_test()?,
+        ))
+    }
 
-        println!("{} Created script: {}", "✓".green(), file_path.display());
-        Ok(())
+    fn script_file(&self, template: &SolidityTemplate) -> Result<PlannedFile> {
+        Ok(PlannedFile::new(
+            "script",
+            self.project.script_dir.join(format!("{}.s.sol", self.contract_name)),
+            template.generate_script()?,
+        ))
     }
 
     fn print_success(&self) {
@@ -230,12 +264,7 @@ mod tests {
         fs::create_dir_all(project_path.join("script")).unwrap();
         fs::create_dir_all(project_path.join("lib")).unwrap();
         
-        let project = FoundryProject {
-            root: project_path.clone(),
-            src_dir: project_path.join("src"),
-            test_dir: project_path.join("test"),
-            script_dir: project_path.join("script"),
-        };
+        let project = FoundryProject::from_root(project_path).unwrap();
         (temp_dir, project)
     }
 
@@ -379,14 +408,7 @@ mod tests {
             "MIT".to_string(),
         );
         
-        let template = SolidityTemplate::new(
-            "TestContract".to_string(),
-            ContractType::Basic,
-            "0.8.30".to_string(),
-            "MIT".to_string(),
-        );
-        
-        assert!(generator.create_contract_file(&template).is_ok());
+        assert!(generator.generate().is_ok());
         
         let contract_path = project_src.join("TestContract.sol");
         assert!(contract_path.exists());
@@ -415,14 +437,7 @@ mod tests {
             "MIT".to_string(),
         );
         
-        let template = SolidityTemplate::new(
-            "TestContract".to_string(),
-            ContractType::Basic,
-            "0.8.30".to_string(),
-            "MIT".to_string(),
-        );
-        
-        let result = generator.create_contract_file(&template);
+        let result = generator.generate();
         assert!(result.is_err());
         if let Err(GramrError::FileExists(path)) = result {
             assert!(path.contains("TestContract.sol"));
@@ -446,14 +461,7 @@ mod tests {
             "MIT".to_string(),
         );
         
-        let template = SolidityTemplate::new(
-            "TestContract".to_string(),
-            ContractType::Basic,
-            "0.8.30".to_string(),
-            "MIT".to_string(),
-        );
-        
-        assert!(generator.create_test_file(&template).is_ok());
+        assert!(generator.generate().is_ok());
         
         let test_path = project_test.join("TestContract.t.sol");
         assert!(test_path.exists());
@@ -477,14 +485,7 @@ mod tests {
             "MIT".to_string(),
         );
         
-        let template = SolidityTemplate::new(
-            "TestContract".to_string(),
-            ContractType::Basic,
-            "0.8.30".to_string(),
-            "MIT".to_string(),
-        );
-        
-        assert!(generator.create_script_file(&template).is_ok());
+        assert!(generator.generate().is_ok());
         
         let script_path = project_script.join("TestContract.s.sol");
         assert!(script_path.exists());
@@ -542,6 +543,62 @@ mod tests {
         assert!(script_path.exists());
     }
 
+    #[test]
+    fn test_generate_uses_project_remappings() {
+        let (_temp_dir, project) = create_test_foundry_project();
+        fs::create_dir_all(project.root.join("lib/openzeppelin-contracts")).unwrap();
+        fs::write(
+            project.root.join("remappings.txt"),
+            "oz/=lib/openzeppelin-contracts/contracts/\nforge-std/=lib/forge-std/src/\n",
+        )
+        .unwrap();
+        let project_src = project.src_dir.clone();
+        let project_test = project.test_dir.clone();
+
+        let generator = ContractGenerator::new(
+            project,
+            "RemappedToken".to_string(),
+            ContractType::ERC20,
+            true,
+            false,
+            "0.8.30".to_string(),
+            "MIT".to_string(),
+        );
+
+        assert!(generator.generate().is_ok());
+
+        let contract = fs::read_to_string(project_src.join("RemappedToken.sol")).unwrap();
+        assert!(contract.contains("import \"oz/token/ERC20/ERC20.sol\";"));
+        assert!(!contract.contains("@openzeppelin/contracts/"));
+
+        let test = fs::read_to_string(project_test.join("RemappedToken.t.sol")).unwrap();
+        assert!(test.contains("import \"forge-std/Test.sol\";"));
+    }
+
+    #[test]
+    fn test_generate_targets_installed_openzeppelin_version() {
+        let (_temp_dir, project) = create_test_foundry_project();
+        let package_dir = project.root.join("lib/openzeppelin-contracts");
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::write(package_dir.join("package.json"), r#"{"version": "4.9.6"}"#).unwrap();
+        let project_src = project.src_dir.clone();
+
+        let generator = ContractGenerator::new(
+            project,
+            "LegacyNFT".to_string(),
+            ContractType::ERC721,
+            false,
+            false,
+            "0.8.20".to_string(),
+            "MIT".to_string(),
+        );
+
+        assert!(generator.generate().is_ok());
+
+        let contract = fs::read_to_string(project_src.join("LegacyNFT.sol")).unwrap();
+        assert!(contract.contains("constructor() ERC721(\"LegacyNFT\", \"LNF\") {}"));
+    }
+
     #[test]
     fn test_generate_invalid_name() {
         let (_temp_dir, project) = create_test_foundry_project();
@@ -562,10 +619,11 @@ mod tests {
 
     #[test]
     fn test_dependency_checking_erc20() {
-        let (_temp_dir, project) = create_test_foundry_project();
+        let (temp_dir, _) = create_test_foundry_project();
+        let forge = crate::foundry::testing::StubForge::install();
         
         let generator = ContractGenerator::new(
-            project,
+            forge.project(temp_dir.path()),
             "Token".to_string(),
             ContractType::ERC20,
             false,
@@ -1,9 +1,16 @@
+use crate::changeset::Changeset;
+use crate::defaults::Defaults;
 use crate::error::{GramrError, Result};
+use crate::foundry::{ImportPaths, Package};
 use crate::language::Language;
-use crate::project::{Project, ProjectType};
-use crate::templates::{ContractType, Template, SolidityTemplate, StylusTemplate};
+use crate::manifest::ResourceKind;
+use crate::plan::{GenerationPlan, OverwritePolicy};
+use crate::project::{relative_path, CargoProject, Project, ProjectType};
+use crate::stylus::{merge_paragraphs, remove_entrypoint};
+use crate::templates::{AccessControl, ContractType, OpenZeppelinVersion, Template, Templates, SolidityTemplate, StylusTemplate, TokenMetadata};
 use colored::*;
 use std::fs;
+use std::path::{Path, PathBuf};
 
 pub struct GenericContractGenerator {
     project: ProjectType,
@@ -14,9 +21,15 @@ pub struct GenericContractGenerator {
     with_script: bool,
     pragma: Option<String>,  // Only for Solidity
     license: Option<String>, // Only for Solidity
+    openzeppelin_version: Option<String>, // Only for Solidity
+    access_control: Option<AccessControl>, // Only for Solidity
+    overwrite: OverwritePolicy,
+    defaults: Defaults,
+    token_metadata: TokenMetadata,
 }
 
 impl GenericContractGenerator {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         project: ProjectType,
         language: Language,
@@ -36,44 +49,172 @@ impl GenericContractGenerator {
             with_script,
             pragma,
             license,
+            openzeppelin_version: None,
+            access_control: None,
+            overwrite: OverwritePolicy::default(),
+            defaults: Defaults::default(),
+            token_metadata: TokenMetadata::default(),
         }
     }
 
+    /// Set what happens when a file to generate already exists
+    pub fn with_overwrite_policy(mut self, overwrite: OverwritePolicy) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Set the defaults used for anything not given explicitly, e.g. from [`Defaults::load`]
+    pub fn with_defaults(mut self, defaults: Defaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Pin the OpenZeppelin Contracts release to install and generate code for, e.g. `4.9.6`
+    pub fn with_openzeppelin_version(mut self, version: String) -> Self {
+        self.openzeppelin_version = Some(version);
+        self
+    }
+
+    /// Generate with `access_control` instead of the configured default
+    pub fn with_access_control(mut self, access_control: AccessControl) -> Self {
+        self.access_control = Some(access_control);
+        self
+    }
+
+    /// Set the token name, symbol, decimals, initial supply and base URI
+    pub fn with_token_metadata(mut self, token_metadata: TokenMetadata) -> Self {
+        self.token_metadata = token_metadata;
+        self
+    }
+
     pub fn generate(&self) -> Result<()> {
+        let oz_version = self.validate()?;
+        // Settle conflicts and stage every file before installing anything, and
+        // snapshot the crate files the installs edit in place; any failure up to
+        // the commit drops the changeset, which removes the staged files and
+        // restores the snapshots
+        let plan = self.build_plan(oz_version)?.resolve_conflicts(self.overwrite)?;
+        let mut changeset = Changeset::stage(&plan)?;
+        if let ProjectType::Cargo(project) = &self.project {
+            for path in project.setup_files() {
+                changeset.snapshot(&path)?;
+            }
+        }
+        self.check_and_install_dependencies()?;
+        self.project.ensure_directories()?;
+        if let Some(wrapper) = plan.files.iter().find(|file| file.label == "deploy wrapper") {
+            changeset.set_executable(&wrapper.path)?;
+        }
+        changeset.commit()?;
+
+        for file in &plan.files {
+            let action = if file.edit && !file.is_new() { "Updated" } else { "Created" };
+            println!("{} {} {}: {}", "✓".green(), action, file.label, file.path.display());
+        }
+        if let ProjectType::Cargo(project) = &self.project {
+            let contract_path = self.project.src_dir().join(format!("{}.rs", crate::stylus::module_name(&self.contract_name)));
+            if let Some(entrypoint) = project.entrypoint_contracts()?.into_iter().find(|path| *path != contract_path) {
+                println!(
+                    "{} The contract in {} remains the crate's #[entrypoint]; move the attribute to {} to deploy it instead",
+                    "ℹ".blue(),
+                    entrypoint.file_name().unwrap_or_default().to_string_lossy(),
+                    self.contract_name
+                );
+            }
+        }
+
+        self.print_success();
+        Ok(())
+    }
+
+    /// The files `generate` would write and the dependencies it would install,
+    /// without touching the project
+    pub fn plan(&self) -> Result<GenerationPlan> {
+        let oz_version = self.validate()?;
+        let mut plan = self.build_plan(oz_version)?;
+        plan.dependencies = self
+            .missing_dependencies()
+            .into_iter()
+            .map(|(_, lib_name)| lib_name.to_string())
+            .collect();
+        if self.needs_motsu() {
+            plan.dependencies.push("motsu".to_string());
+        }
+        if let (ProjectType::Cargo(project), true) = (&self.project, self.with_script) {
+            plan.dependencies.extend(project.missing_deploy_dependencies()?.into_iter().map(str::to_string));
+        }
+        if let ProjectType::Cargo(project) = &self.project {
+            plan.dependencies.extend(
+                project
+                    .missing_stylus_prerequisites()?
+                    .into_iter()
+                    .map(|prerequisite| prerequisite.description().to_string()),
+            );
+        }
+        Ok(plan)
+    }
+
+    fn validate(&self) -> Result<OpenZeppelinVersion> {
         self.validate_name()?;
         self.validate_language_compatibility()?;
-        self.project.ensure_directories()?;
-        self.check_and_install_dependencies()?;
+        self.token_metadata.check(&self.contract_type)?;
+        self.defaults.check_template_pack(ResourceKind::Contract, Some(&self.contract_type))?;
+        let oz_version = self.oz_version()?;
+        self.validate_extensions(oz_version)?;
+        // Stylus tokens are Ownable whatever the default
+        if self.language == Language::Solidity {
+            self.access_control().check(&self.contract_type, &self.defaults.extensions, oz_version)?;
+        }
+        Ok(oz_version)
+    }
 
+    fn build_plan(&self, oz_version: OpenZeppelinVersion) -> Result<GenerationPlan> {
         let template: Box<dyn Template> = match self.language {
             Language::Solidity => {
-                Box::new(SolidityTemplate::new(
-                    self.contract_name.clone(),
-                    self.contract_type.clone(),
-                    self.pragma.clone().unwrap_or_else(|| "0.8.30".to_string()),
-                    self.license.clone().unwrap_or_else(|| "UNLICENSED".to_string()),
-                ))
-            }
-            Language::RustStylus => {
-                Box::new(StylusTemplate::new(
-                    self.contract_name.clone(),
-                    self.contract_type.clone(),
-                ))
+                let src_dir = self.project.src_dir();
+                Box::new(
+                    SolidityTemplate::new(
+                        self.contract_name.clone(),
+                        self.contract_type.clone(),
+                        self.pragma.clone().unwrap_or_else(|| self.defaults.pragma.clone()),
+                        self.license.clone().unwrap_or_else(|| self.defaults.license.clone()),
+                    )
+                    .with_src_paths(
+                        relative_path(&self.project.test_dir(), &src_dir),
+                        relative_path(&self.project.script_dir(), &src_dir),
+                    )
+                    .with_import_paths(self.import_paths()?)
+                    .with_oz_version(oz_version)
+                    .with_natspec(self.defaults.natspec, self.defaults.author.clone())
+                    .with_templates(Templates::load(&self.defaults.template_dirs())?)
+                    .with_extensions(self.defaults.extensions.clone())
+                    .with_token_metadata(self.token_metadata.clone())
+                    .with_access_control(self.access_control()),
+                )
             }
+            Language::RustStylus => Box::new(self.stylus_template()?),
         };
 
-        self.create_contract_file(&*template)?;
+        let mut plan = GenerationPlan::new();
+        self.plan_contract_file(&mut plan, &*template)?;
 
         if self.with_test {
-            self.create_test_file(&*template)?;
+            self.plan_test_file(&mut plan, &*template)?;
         }
 
         if self.with_script {
-            self.create_script_file(&*template)?;
+            self.plan_script_file(&mut plan, &*template)?;
         }
 
-        self.print_success();
-        Ok(())
+        Ok(plan)
+    }
+
+    fn stylus_template(&self) -> Result<StylusTemplate> {
+        Ok(StylusTemplate::new(self.contract_name.clone(), self.contract_type.clone())
+            .with_templates(Templates::load(&self.defaults.template_dirs())?)
+            .with_extensions(self.defaults.extensions.clone())
+            .with_token_metadata(self.token_metadata.clone())
+            .with_test_module(self.with_test))
     }
 
     fn validate_name(&self) -> Result<()> {
@@ -97,150 +238,294 @@ impl GenericContractGenerator {
     }
     
     fn validate_language_compatibility(&self) -> Result<()> {
-        if self.language == Language::RustStylus {
-            if self.with_test {
-                return Err(GramrError::Other(
-                    "Test generation (--with-test) is not supported for Rust/Stylus projects. Use 'cargo test' instead.".to_string()
-                ));
-            }
-            
-            if self.with_script {
-                return Err(GramrError::Other(
-                    "Script generation (--with-script) is not supported for Rust/Stylus projects. Use deployment tools like 'stylus deploy' instead.".to_string()
-                ));
-            }
+        if self.language == Language::RustStylus && self.openzeppelin_version.is_some() {
+            return Err(GramrError::Other(
+                "--oz-version only applies to Solidity contracts".to_string()
+            ));
+        }
+        if self.language == Language::RustStylus && self.access_control.is_some() {
+            return Err(GramrError::Other(
+                "--access only applies to Solidity contracts; Stylus tokens are always Ownable".to_string()
+            ));
         }
         
         Ok(())
     }
 
-    fn check_and_install_dependencies(&self) -> Result<()> {
-        match &self.contract_type {
-            ContractType::ERC20 | ContractType::ERC721 | ContractType::ERC1155 => {
-                if !self.project.has_openzeppelin() {
-                    let lib_name = match self.language {
-                        Language::Solidity => "OpenZeppelin contracts",
-                        Language::RustStylus => "OpenZeppelin Stylus",
-                    };
-                    println!("{} Installing {}...", "→".yellow(), lib_name);
-                    self.project.install_openzeppelin()?;
-                    println!("{} {} installed", "✓".green(), lib_name);
-                }
+    /// The access control to generate with: the requested one, otherwise the configured default
+    fn access_control(&self) -> AccessControl {
+        self.access_control.unwrap_or(self.defaults.access_control)
+    }
+
+    /// The OpenZeppelin release line to generate for: the pinned release when one
+    /// was requested, otherwise whatever the project already has installed
+    fn oz_version(&self) -> Result<OpenZeppelinVersion> {
+        let installed = match &self.project {
+            ProjectType::Foundry(project) => project.openzeppelin_version(),
+            ProjectType::Cargo(_) => None,
+        };
+
+        let Some(requested) = &self.openzeppelin_version else {
+            return Ok(installed
+                .as_deref()
+                .and_then(OpenZeppelinVersion::from_release)
+                .unwrap_or_default());
+        };
+
+        let version = OpenZeppelinVersion::from_release(requested).ok_or_else(|| {
+            GramrError::Other(format!(
+                "Unsupported OpenZeppelin version '{}': expected a 4.x or 5.x release",
+                requested
+            ))
+        })?;
+
+        if let Some(installed) = installed {
+            if OpenZeppelinVersion::from_release(&installed) != Some(version) {
+                println!(
+                    "{} OpenZeppelin {} is installed, but generating for {} as requested",
+                    "!".yellow(),
+                    installed,
+                    requested
+                );
+            }
+        }
+
+        Ok(version)
+    }
+
+    fn validate_extensions(&self, oz_version: OpenZeppelinVersion) -> Result<()> {
+        let ContractType::MultiInheritance { base_type, extensions } = &self.contract_type else {
+            return Ok(());
+        };
+
+        let resolved = match self.language {
+            Language::Solidity => self.defaults.extensions.resolve_all(base_type, extensions, oz_version)?,
+            Language::RustStylus => self.defaults.extensions.resolve_requested(base_type, extensions)?,
+        };
+        for change in resolved.changes {
+            println!("{} {}", "!".yellow(), change);
+        }
+
+        Ok(())
+    }
+
+    fn import_paths(&self) -> Result<ImportPaths> {
+        match &self.project {
+            ProjectType::Foundry(project) => project.import_paths(),
+            ProjectType::Cargo(_) => Ok(ImportPaths::default()),
+        }
+    }
+
+    /// The OpenZeppelin packages the contract needs but the project lacks, with their display names
+    fn missing_dependencies(&self) -> Vec<(Package, &'static str)> {
+        let base_type = match &self.contract_type {
+            ContractType::MultiInheritance { base_type, .. } => &**base_type,
+            other => other,
+        };
+
+        let mut missing: Vec<_> = match base_type {
+            ContractType::ERC20 | ContractType::ERC721 | ContractType::ERC1155
+                if !self.project.has_openzeppelin() =>
+            {
+                let lib_name = match self.language {
+                    Language::Solidity => "OpenZeppelin contracts",
+                    Language::RustStylus => "OpenZeppelin Stylus",
+                };
+                Some((Package::OpenZeppelin, lib_name))
             }
             ContractType::ERC20Upgradeable
             | ContractType::ERC721Upgradeable
-            | ContractType::ERC1155Upgradeable => {
-                if !self.project.has_openzeppelin_upgradeable() {
-                    let lib_name = match self.language {
-                        Language::Solidity => "OpenZeppelin upgradeable contracts",
-                        Language::RustStylus => "OpenZeppelin Stylus (upgradeable)",
-                    };
-                    println!("{} Installing {}...", "→".yellow(), lib_name);
-                    self.project.install_openzeppelin_upgradeable()?;
-                    println!("{} {} installed", "✓".green(), lib_name);
-                }
+            | ContractType::ERC1155Upgradeable
+                if !self.project.has_openzeppelin_upgradeable() =>
+            {
+                let lib_name = match self.language {
+                    Language::Solidity => "OpenZeppelin upgradeable contracts",
+                    // The proxy primitives ship in the same crate
+                    Language::RustStylus => "OpenZeppelin Stylus",
+                };
+                Some((Package::OpenZeppelinUpgradeable, lib_name))
             }
-            ContractType::MultiInheritance { base_type, .. } => {
-                match **base_type {
-                    ContractType::ERC20 | ContractType::ERC721 | ContractType::ERC1155 => {
-                        if !self.project.has_openzeppelin() {
-                            let lib_name = match self.language {
-                                Language::Solidity => "OpenZeppelin contracts",
-                                Language::RustStylus => "OpenZeppelin Stylus",
-                            };
-                            println!("{} Installing {}...", "→".yellow(), lib_name);
-                            self.project.install_openzeppelin()?;
-                            println!("{} {} installed", "✓".green(), lib_name);
-                        }
-                    }
-                    ContractType::ERC20Upgradeable
-                    | ContractType::ERC721Upgradeable
-                    | ContractType::ERC1155Upgradeable => {
-                        if !self.project.has_openzeppelin_upgradeable() {
-                            let lib_name = match self.language {
-                                Language::Solidity => "OpenZeppelin upgradeable contracts",
-                                Language::RustStylus => "OpenZeppelin Stylus (upgradeable)",
-                            };
-                            println!("{} Installing {}...", "→".yellow(), lib_name);
-                            self.project.install_openzeppelin_upgradeable()?;
-                            println!("{} {} installed", "✓".green(), lib_name);
-                        }
-                    }
-                    _ => {}
-                }
+            _ => None,
+        }
+        .into_iter()
+        .collect();
+
+        // Solidity tests and scripts deploy upgradeable tokens behind `ERC1967Proxy`
+        if self.language == Language::Solidity
+            && base_type.is_upgradeable()
+            && (self.with_test || self.with_script)
+            && !self.project.has_openzeppelin()
+        {
+            missing.push((Package::OpenZeppelin, "OpenZeppelin contracts"));
+        }
+        missing
+    }
+
+    fn check_and_install_dependencies(&self) -> Result<()> {
+        if let ProjectType::Cargo(project) = &self.project {
+            for prerequisite in project.repair_stylus_prerequisites()? {
+                println!("{} Added {}", "✓".green(), prerequisite.description());
+            }
+        }
+
+        for (package, lib_name) in self.missing_dependencies() {
+            println!("{} Installing {}...", "→".yellow(), lib_name);
+            self.install_openzeppelin(package)?;
+            println!("{} {} installed", "✓".green(), lib_name);
+        }
+
+        if let ProjectType::Cargo(project) = &self.project {
+            if self.needs_motsu() {
+                println!("{} Installing motsu...", "→".yellow());
+                project.install_motsu()?;
+                println!("{} motsu installed", "✓".green());
+            }
+            if self.with_script && !project.missing_deploy_dependencies()?.is_empty() {
+                project.install_deploy_dependencies()?;
+                println!("{} Added the deploy example's dev-dependencies", "✓".green());
             }
-            _ => {}
         }
 
         Ok(())
     }
 
-    fn create_contract_file(&self, template: &dyn Template) -> Result<()> {
-        let content = template.generate_contract();
-        let file_extension = match self.language {
-            Language::Solidity => "sol",
-            Language::RustStylus => "rs",
+    /// Whether the Stylus test needs `motsu` added to the crate's dev-dependencies
+    fn needs_motsu(&self) -> bool {
+        match &self.project {
+            ProjectType::Cargo(project) => self.with_test && !project.has_motsu(),
+            ProjectType::Foundry(_) => false,
+        }
+    }
+
+    /// Install through the project, pinned to the requested release when there is one
+    fn install_openzeppelin(&self, package: Package) -> Result<()> {
+        match (&self.project, &self.openzeppelin_version) {
+            (ProjectType::Foundry(project), Some(version)) => {
+                project.install_package(package, version.trim_start_matches('v'))
+            }
+            _ if package == Package::OpenZeppelinUpgradeable => self.project.install_openzeppelin_upgradeable(),
+            _ => self.project.install_openzeppelin(),
+        }
+    }
+
+    fn plan_contract_file(&self, plan: &mut GenerationPlan, template: &dyn Template) -> Result<()> {
+        let file_name = match self.language {
+            Language::Solidity => format!("{}.sol", self.contract_name),
+            Language::RustStylus => format!("{}.rs", crate::stylus::module_name(&self.contract_name)),
         };
-        let file_path = self.project.src_dir().join(format!("{}.{}", self.contract_name, file_extension));
-        
-        fs::write(&file_path, content)
-            .map_err(|e| GramrError::Other(format!("Failed to write contract file: {}", e)))?;
-        
-        println!("{} Created contract: {}", "✓".green(), file_path.display());
+        let file_path = self.project.src_dir().join(file_name);
+        let mut content = template.generate_contract()?;
+
+        let edits = match &self.project {
+            ProjectType::Cargo(project) => self.stylus_crate_edits(project, &file_path, &mut content)?,
+            ProjectType::Foundry(_) => Vec::new(),
+        };
+        plan.add_file("contract", file_path, content);
+        for (label, path, updated) in edits {
+            plan.add_edit(label, path, updated);
+        }
+
+        // One proxy crate serves every upgradeable contract; later ones reuse it
+        if let (ProjectType::Cargo(project), true) = (&self.project, self.contract_type.is_upgradeable()) {
+            let proxy = self.stylus_template()?.generate_proxy(&project.package_name()?)?;
+            for (path, content) in project.proxy_files(proxy)? {
+                plan.add_edit("proxy", path, content);
+            }
+        }
         Ok(())
     }
 
-    fn create_test_file(&self, template: &dyn Template) -> Result<()> {
-        let content = template.generate_test();
-        let (_file_name, file_path) = match self.language {
-            Language::Solidity => {
-                let name = format!("{}.t.sol", self.contract_name);
-                let path = self.project.test_dir().join(&name);
-                (name, path)
+    /// The edits that declare a Stylus contract from `lib.rs` and keep a single
+    /// `#[entrypoint]` in the crate: the first declared contract that has one keeps
+    /// it, so `content` loses it when another contract is already the entrypoint.
+    /// A contract that becomes the entrypoint gets its `print_from_args` re-exported
+    /// from `lib.rs`, where `main.rs` calls it.
+    fn stylus_crate_edits(
+        &self,
+        project: &CargoProject,
+        file_path: &Path,
+        content: &mut String,
+    ) -> Result<Vec<(&'static str, PathBuf, String)>> {
+        let mut edits: Vec<_> = project
+            .module_declarations(file_path)?
+            .into_iter()
+            .map(|(path, updated)| ("module", path, updated))
+            .collect();
+
+        let entrypoints: Vec<PathBuf> =
+            project.entrypoint_contracts()?.into_iter().filter(|path| path != file_path).collect();
+        if entrypoints.is_empty() {
+            let lib_rs = project.src_dir().join("lib.rs");
+            let source = match edits.iter().find(|(_, path, _)| *path == lib_rs) {
+                Some((_, _, declared)) => declared.clone(),
+                None => fs::read_to_string(&lib_rs)?,
+            };
+            if let Some(updated) = crate::stylus::export_abi(&source, &project.module_path(file_path)?) {
+                edits.retain(|(_, path, _)| *path != lib_rs);
+                edits.push(("module", lib_rs, updated));
             }
-            Language::RustStylus => {
-                let name = format!("{}_test.rs", self.contract_name.to_lowercase());
-                let path = self.project.test_dir().join(&name);
-                (name, path)
+        }
+        if let Some((_, others)) = entrypoints.split_first() {
+            if let Some(updated) = remove_entrypoint(content) {
+                *content = updated;
             }
+            for path in others {
+                if let Some(updated) = remove_entrypoint(&fs::read_to_string(path)?) {
+                    edits.push(("entrypoint", path.clone(), updated));
+                }
+            }
+        }
+        Ok(edits)
+    }
+
+    fn plan_test_file(&self, plan: &mut GenerationPlan, template: &dyn Template) -> Result<()> {
+        let file_path = match self.language {
+            Language::Solidity => self.project.test_dir().join(format!("{}.t.sol", self.contract_name)),
+            // The contract declares `mod tests;`, which lives in the directory named after its file
+            Language::RustStylus => self
+                .project
+                .src_dir()
+                .join(crate::stylus::module_name(&self.contract_name))
+                .join("tests.rs"),
         };
-        
-        fs::write(&file_path, content)
-            .map_err(|e| GramrError::Other(format!("Failed to write test file: {}", e)))?;
-        
-        println!("{} Created test: {}", "✓".green(), file_path.display());
+
+        plan.add_file("test", file_path, template.generate_test()?);
         Ok(())
     }
 
-    fn create_script_file(&self, template: &dyn Template) -> Result<()> {
-        let content = template.generate_script();
-        
+    fn plan_script_file(&self, plan: &mut GenerationPlan, template: &dyn Template) -> Result<()> {
         match self.language {
             Language::Solidity => {
                 let file_name = format!("Deploy{}.s.sol", self.contract_name);
-                let file_path = self.project.script_dir().join(file_name);
-                
-                fs::write(&file_path, content)
-                    .map_err(|e| GramrError::Other(format!("Failed to write script file: {}", e)))?;
-                
-                println!("{} Created script: {}", "✓".green(), file_path.display());
+                plan.add_file("script", self.project.script_dir().join(file_name), template.generate_script()?);
             }
             Language::RustStylus => {
-                // For Stylus, we create a deployment instructions file
-                let file_name = format!("{}_deploy.md", self.contract_name.to_lowercase());
-                let file_path = self.project.script_dir().join(file_name);
-                
-                // Ensure scripts directory exists
-                fs::create_dir_all(self.project.script_dir())
-                    .map_err(|e| GramrError::Other(format!("Failed to create scripts directory: {}", e)))?;
-                
-                fs::write(&file_path, content)
-                    .map_err(|e| GramrError::Other(format!("Failed to write deployment instructions: {}", e)))?;
-                
-                println!("{} Created deployment instructions: {}", "✓".green(), file_path.display());
+                let ProjectType::Cargo(project) = &self.project else {
+                    return Err(GramrError::Other("Rust/Stylus deployment needs a Cargo project".to_string()));
+                };
+                let file_name = format!("deploy_{}.rs", crate::stylus::module_name(&self.contract_name));
+                plan.add_file("deploy example", project.root().join("examples").join(file_name), template.generate_script()?);
+
+                // Shared by every contract in the crate: add what is missing, keep the rest
+                let stylus = self.stylus_template()?;
+                let env_example = project.root().join(".env.example");
+                let gitignore = project.root().join(".gitignore");
+                let merges = [
+                    ("environment", &env_example, stylus.generate_env_example()?),
+                    ("gitignore", &gitignore, ".env\n".to_string()),
+                ];
+                for (label, path, addition) in merges {
+                    let existing = fs::read_to_string(path).unwrap_or_default();
+                    if let Some(merged) = merge_paragraphs(&existing, &addition) {
+                        plan.add_edit(label, path.clone(), merged);
+                    }
+                }
+                let wrapper = project.script_dir().join("stylus.sh");
+                if !wrapper.exists() {
+                    plan.add_edit("deploy wrapper", wrapper, stylus.generate_deploy_wrapper()?);
+                }
             }
         }
-        
         Ok(())
     }
 
@@ -264,8 +549,453 @@ impl GenericContractGenerator {
                 if self.with_test {
                     println!("  3. Run {} to test", "cargo test".cyan());
                 }
-                println!("  4. Deploy with {}", "stylus deploy".cyan());
+                if self.with_script && self.contract_type.is_upgradeable() {
+                    println!(
+                        "  4. Copy .env.example to .env, deploy the implementation with {}, then the proxy with {}",
+                        "scripts/stylus.sh deploy-only".cyan(),
+                        "scripts/stylus.sh deploy".cyan()
+                    );
+                } else if self.with_script {
+                    println!("  4. Copy .env.example to .env, then deploy with {}", "scripts/stylus.sh deploy".cyan());
+                } else if self.contract_type.is_upgradeable() {
+                    println!(
+                        "  4. Deploy with {}, then deploy {} and call its init with the implementation",
+                        "cargo stylus deploy".cyan(),
+                        "proxy/".cyan()
+                    );
+                } else {
+                    println!("  4. Deploy with {}", "cargo stylus deploy".cyan());
+                }
             }
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::foundry::testing::StubForge;
+    use crate::foundry::FoundryProject;
+    use crate::project::CargoProject;
+    use crate::templates::TokenExtension;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn create_test_project() -> (TempDir, ProjectType) {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("foundry.toml"), "[profile.default]\n").unwrap();
+        let project = FoundryProject::from_root(temp_dir.path().to_path_buf()).unwrap();
+        (temp_dir, ProjectType::Foundry(project))
+    }
+
+    fn create_stubbed_project(forge: &StubForge) -> (TempDir, ProjectType) {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("foundry.toml"), "[profile.default]\n").unwrap();
+        let project = forge.project(temp_dir.path());
+        (temp_dir, ProjectType::Foundry(project))
+    }
+
+    fn generator(project: ProjectType, contract_type: ContractType) -> GenericContractGenerator {
+        GenericContractGenerator::new(
+            project,
+            Language::Solidity,
+            "Token".to_string(),
+            contract_type,
+            false,
+            false,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_pinned_openzeppelin_version_is_installed_and_targeted() {
+        let forge = StubForge::install();
+        let (temp_dir, project) = create_stubbed_project(&forge);
+
+        generator(project, ContractType::ERC721)
+            .with_openzeppelin_version("4.9.6".to_string())
+            .generate()
+            .unwrap();
+
+        assert_eq!(forge.calls(), vec!["install OpenZeppelin/openzeppelin-contracts@v4.9.6"]);
+        let contract = fs::read_to_string(temp_dir.path().join("src/Token.sol")).unwrap();
+        assert!(contract.contains("constructor() ERC721(\"Token\", \"T\") {}"));
+    }
+
+    #[test]
+    fn test_rejects_unsupported_openzeppelin_version() {
+        let (_temp_dir, project) = create_test_project();
+
+        let result = generator(project, ContractType::ERC20)
+            .with_openzeppelin_version("3.4.2".to_string())
+            .generate();
+
+        assert!(matches!(result, Err(GramrError::Other(msg)) if msg.contains("3.4.2")));
+    }
+
+    #[test]
+    fn test_rejects_v5_only_extension_for_v4() {
+        let (temp_dir, project) = create_test_project();
+        let contract_type = ContractType::MultiInheritance {
+            base_type: Box::new(ContractType::ERC20),
+            extensions: vec![TokenExtension::ERC1363],
+        };
+
+        let result = generator(project, contract_type)
+            .with_openzeppelin_version("4.9.6".to_string())
+            .generate();
+
+        assert!(matches!(result, Err(GramrError::Other(msg)) if msg.contains("ERC1363")));
+        assert!(!temp_dir.path().join("src/Token.sol").exists());
+    }
+
+    #[test]
+    fn test_rejects_access_control_nobody_could_use() {
+        let (_temp_dir, project) = create_test_project();
+        let contract_type = ContractType::MultiInheritance {
+            base_type: Box::new(ContractType::ERC20),
+            extensions: vec![TokenExtension::ERC20Pausable],
+        };
+        let result = generator(project, contract_type).with_access_control(AccessControl::None).plan();
+        assert!(matches!(result, Err(GramrError::InvalidConfig(msg)) if msg.contains("`pausable`")));
+
+        let temp_dir = TempDir::new().unwrap();
+        CargoProject::init(temp_dir.path(), "app").unwrap();
+        let result = GenericContractGenerator::new(
+            ProjectType::Cargo(CargoProject::detect_at(temp_dir.path().to_path_buf()).unwrap()),
+            Language::RustStylus,
+            "Token".to_string(),
+            ContractType::ERC20,
+            false,
+            false,
+            None,
+            None,
+        )
+        .with_access_control(AccessControl::Roles)
+        .plan();
+        assert!(matches!(result, Err(GramrError::Other(msg)) if msg.contains("--access")));
+    }
+
+    #[test]
+    fn test_existing_contract_is_not_overwritten_by_default() {
+        let (temp_dir, project) = create_test_project();
+        let existing = temp_dir.path().join("src/Token.sol");
+        fs::create_dir_all(existing.parent().unwrap()).unwrap();
+        fs::write(&existing, "// hand-written\n").unwrap();
+
+        let result = GenericContractGenerator::new(
+            project,
+            Language::Solidity,
+            "Token".to_string(),
+            ContractType::Basic,
+            true,
+            false,
+            None,
+            None,
+        )
+        .generate();
+
+        assert!(matches!(result, Err(GramrError::FileExists(msg)) if msg.contains("Token.sol")));
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "// hand-written\n");
+        assert!(!temp_dir.path().join("test/Token.t.sol").exists());
+    }
+
+    #[test]
+    fn test_force_overwrites_existing_contract() {
+        let (temp_dir, project) = create_test_project();
+        let existing = temp_dir.path().join("src/Token.sol");
+        fs::create_dir_all(existing.parent().unwrap()).unwrap();
+        fs::write(&existing, "// hand-written\n").unwrap();
+
+        generator(project, ContractType::Basic)
+            .with_overwrite_policy(OverwritePolicy::Force)
+            .generate()
+            .unwrap();
+
+        assert!(fs::read_to_string(&existing).unwrap().contains("contract Token"));
+    }
+
+    #[test]
+    fn test_failed_install_leaves_no_generated_files() {
+        let forge = StubForge::failing();
+        let (temp_dir, project) = create_stubbed_project(&forge);
+
+        let result = GenericContractGenerator::new(
+            project,
+            Language::Solidity,
+            "Token".to_string(),
+            ContractType::ERC20,
+            true,
+            true,
+            None,
+            None,
+        )
+        .generate();
+
+        assert!(matches!(result, Err(GramrError::ForgeCommandError(_))));
+        assert_eq!(forge.calls().len(), 1);
+        let entries: Vec<_> = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, vec!["foundry.toml"]);
+    }
+
+    #[test]
+    fn test_template_pack_renders_supported_types_only() {
+        let forge = StubForge::install();
+        let (temp_dir, project) = create_stubbed_project(&forge);
+        let pack_dir = temp_dir.path().join("acme");
+        fs::create_dir_all(pack_dir.join("solidity")).unwrap();
+        fs::write(
+            pack_dir.join("pack.toml"),
+            "name = \"acme\"\nresources = [\"contract\"]\ncontract_types = [\"basic\"]\n",
+        )
+        .unwrap();
+        fs::write(pack_dir.join("solidity/basic.sol"), "// acme\ncontract {{ name }} {}\n").unwrap();
+
+        let defaults = Defaults {
+            template_pack: Some(crate::pack::TemplatePack::load(&pack_dir).unwrap()),
+            ..Defaults::default()
+        };
+
+        let plan = generator(project, ContractType::Basic)
+            .with_defaults(defaults.clone())
+            .plan()
+            .unwrap();
+        assert_eq!(plan.files[0].content, "// acme\ncontract Token {}\n");
+
+        let project = ProjectType::Foundry(FoundryProject::from_root(temp_dir.path().to_path_buf()).unwrap());
+        let err = generator(project, ContractType::ERC20).with_defaults(defaults).plan().unwrap_err();
+        assert!(err.to_string().contains("does not support erc20 contracts"));
+    }
+
+    #[test]
+    fn test_plan_leaves_project_untouched() {
+        let forge = StubForge::install();
+        let (temp_dir, project) = create_stubbed_project(&forge);
+        let existing = temp_dir.path().join("src/Token.sol");
+        fs::create_dir_all(existing.parent().unwrap()).unwrap();
+        fs::write(&existing, "// hand-written\n").unwrap();
+
+        let generator = GenericContractGenerator::new(
+            project,
+            Language::Solidity,
+            "Token".to_string(),
+            ContractType::ERC20,
+            true,
+            false,
+            None,
+            None,
+        );
+        let plan = generator.plan().unwrap();
+
+        assert_eq!(plan.dependencies, vec!["OpenZeppelin contracts"]);
+        assert_eq!(plan.files.len(), 2);
+        assert_eq!(plan.files[0].label, "contract");
+        assert_eq!(plan.files[0].existing.as_deref(), Some("// hand-written\n"));
+        assert!(plan.files[0].diff().contains("-// hand-written"));
+        assert!(plan.files[1].is_new());
+        assert!(plan.files[1].path.ends_with("test/Token.t.sol"));
+
+        assert!(forge.calls().is_empty());
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "// hand-written\n");
+        assert!(!temp_dir.path().join("test").exists());
+
+        // The upgradeable token's test deploys it behind OpenZeppelin's ERC1967Proxy
+        let project = ProjectType::Foundry(FoundryProject::from_root(temp_dir.path().to_path_buf()).unwrap());
+        let plan = GenericContractGenerator::new(
+            project,
+            Language::Solidity,
+            "Token".to_string(),
+            ContractType::ERC20Upgradeable,
+            true,
+            false,
+            None,
+            None,
+        )
+        .plan()
+        .unwrap();
+        assert_eq!(plan.dependencies, vec!["OpenZeppelin upgradeable contracts", "OpenZeppelin contracts"]);
+    }
+
+    #[test]
+    fn test_stylus_generation_sets_up_a_plain_crate() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("Cargo.toml"), "[package]\nname = \"token\"\nversion = \"0.1.0\"\n").unwrap();
+        let project = ProjectType::Cargo(CargoProject::detect_at(temp_dir.path().to_path_buf()).unwrap());
+        let generator = GenericContractGenerator::new(
+            project,
+            Language::RustStylus,
+            "Token".to_string(),
+            ContractType::ERC20,
+            false,
+            false,
+            None,
+            None,
+        );
+
+        let plan = generator.plan().unwrap();
+        assert!(plan.dependencies.contains(&"stylus-sdk dependency".to_string()));
+        assert!(plan.dependencies.contains(&"src/main.rs ABI exporter".to_string()));
+
+        generator.generate().unwrap();
+
+        let ProjectType::Cargo(project) = &generator.project else { unreachable!() };
+        assert!(project.missing_stylus_prerequisites().unwrap().is_empty());
+        assert!(project.has_openzeppelin());
+        assert!(temp_dir.path().join("src/token.rs").exists());
+    }
+
+    #[test]
+    fn test_stylus_contracts_are_declared_with_a_single_entrypoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = CargoProject::init(temp_dir.path(), "app").unwrap();
+        let stylus = |name: &str| {
+            GenericContractGenerator::new(
+                ProjectType::Cargo(CargoProject::detect_at(temp_dir.path().to_path_buf()).unwrap()),
+                Language::RustStylus,
+                name.to_string(),
+                ContractType::Basic,
+                false,
+                false,
+                None,
+                None,
+            )
+        };
+
+        stylus("Vault").generate().unwrap();
+        let plan = stylus("MyToken").plan().unwrap();
+        assert!(plan.files.iter().any(|file| file.label == "module" && file.edit));
+        plan.apply().unwrap();
+
+        let lib_rs = fs::read_to_string(temp_dir.path().join("src/lib.rs")).unwrap();
+        assert!(lib_rs.ends_with("pub mod vault;\npub mod my_token;\n\n#[cfg(feature = \"export-abi\")]\npub use crate::vault::print_from_args;\n"));
+        assert!(fs::read_to_string(temp_dir.path().join("src/vault.rs")).unwrap().contains("#[entrypoint]"));
+        assert!(!fs::read_to_string(temp_dir.path().join("src/my_token.rs")).unwrap().contains("#[entrypoint]"));
+        assert_eq!(project.entrypoint_contracts().unwrap(), vec![temp_dir.path().join("src/vault.rs")]);
+    }
+
+    #[test]
+    fn test_stylus_script_is_a_deploy_example_with_shared_setup() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = CargoProject::init(temp_dir.path(), "app").unwrap();
+        let stylus = |name: &str, contract_type: ContractType| {
+            GenericContractGenerator::new(
+                ProjectType::Cargo(CargoProject::detect_at(temp_dir.path().to_path_buf()).unwrap()),
+                Language::RustStylus,
+                name.to_string(),
+                contract_type,
+                false,
+                true,
+                None,
+                None,
+            )
+        };
+
+        let plan = stylus("Token", ContractType::ERC20).plan().unwrap();
+        assert!(plan.dependencies.contains(&"alloy".to_string()));
+        assert!(plan.dependencies.contains(&"tokio".to_string()));
+        assert!(plan.files.iter().any(|file| file.label == "deploy example" && file.path.ends_with("examples/deploy_token.rs")));
+
+        stylus("Token", ContractType::ERC20).generate().unwrap();
+        stylus("Vault", ContractType::ERC1155).generate().unwrap();
+
+        let root = temp_dir.path();
+        assert!(root.join("examples/deploy_vault.rs").exists());
+        let env = fs::read_to_string(root.join(".env.example")).unwrap();
+        assert_eq!(env.matches("RPC_URL=").count(), 1);
+        assert!(env.contains("\nTOKEN_INITIAL_SUPPLY=1000000000000000000000000\n"));
+        assert!(env.contains("\nVAULT_URI="));
+        assert_eq!(fs::read_to_string(root.join(".gitignore")).unwrap(), "/target\n.env\n");
+        assert!(project.missing_deploy_dependencies().unwrap().is_empty());
+        assert!(fs::read_to_string(root.join("Cargo.toml")).unwrap().contains("tokio = { version = \"1\", features = [\"macros\", \"rt-multi-thread\"] }"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(root.join("scripts/stylus.sh")).unwrap().permissions().mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+    }
+
+    #[test]
+    #[ignore = "runs cargo check on generated crates, which fetches stylus-sdk and openzeppelin-stylus"]
+    fn test_stylus_tokens_compile() {
+        use TokenExtension::*;
+        let multi = |base_type: ContractType, extensions: Vec<TokenExtension>| ContractType::MultiInheritance {
+            base_type: Box::new(base_type),
+            extensions,
+        };
+        let contract_types = [
+            ContractType::Basic,
+            ContractType::ERC20,
+            multi(ContractType::ERC20, vec![ERC20Burnable, ERC20Capped(Vec::new()), ERC20Pausable, ERC20Permit]),
+            ContractType::ERC721,
+            multi(ContractType::ERC721, vec![ERC721Burnable, ERC721Enumerable, ERC721Pausable, ERC721URIStorage]),
+            ContractType::ERC1155,
+            multi(ContractType::ERC1155, vec![ERC1155Burnable, ERC1155Supply, ERC1155Pausable, ERC1155URIStorage]),
+            ContractType::ERC20Upgradeable,
+            multi(ContractType::ERC721Upgradeable, vec![ERC721Enumerable, ERC721Pausable]),
+            ContractType::ERC1155Upgradeable,
+        ];
+        // One target directory, so the dependencies build once
+        let target_dir = TempDir::new().unwrap();
+
+        for contract_type in contract_types {
+            let temp_dir = TempDir::new().unwrap();
+            CargoProject::init(temp_dir.path(), "app").unwrap();
+            GenericContractGenerator::new(
+                ProjectType::Cargo(CargoProject::detect_at(temp_dir.path().to_path_buf()).unwrap()),
+                Language::RustStylus,
+                "MyToken".to_string(),
+                contract_type.clone(),
+                true,
+                false,
+                None,
+                None,
+            )
+            .generate()
+            .unwrap();
+
+            let status = std::process::Command::new("cargo")
+                .args(["check", "--all-targets"])
+                .env("CARGO_TARGET_DIR", target_dir.path())
+                .current_dir(temp_dir.path())
+                .status()
+                .unwrap();
+            assert!(status.success(), "{:?} does not compile", contract_type);
+        }
+    }
+
+    #[test]
+    fn test_stylus_upgradeable_contracts_share_a_proxy_crate() {
+        let temp_dir = TempDir::new().unwrap();
+        CargoProject::init(temp_dir.path(), "app").unwrap();
+        let stylus = |name: &str, contract_type: ContractType| {
+            GenericContractGenerator::new(
+                ProjectType::Cargo(CargoProject::detect_at(temp_dir.path().to_path_buf()).unwrap()),
+                Language::RustStylus,
+                name.to_string(),
+                contract_type,
+                false,
+                false,
+                None,
+                None,
+            )
+        };
+
+        stylus("Token", ContractType::ERC20Upgradeable).generate().unwrap();
+
+        let root = temp_dir.path();
+        let token = fs::read_to_string(root.join("src/token.rs")).unwrap();
+        assert!(token.contains("    uups: UUPSUpgradeable,\n"));
+        assert!(token.contains("#[implements(IErc20<Error = Vec<u8>>, IErc20Metadata, IOwnable, IUUPSUpgradeable, IErc1822Proxiable, IErc165)]"));
+        assert!(fs::read_to_string(root.join("Cargo.toml")).unwrap().contains("openzeppelin-stylus"));
+        assert!(fs::read_to_string(root.join("proxy/Cargo.toml")).unwrap().contains("name = \"app-proxy\""));
+        assert!(fs::read_to_string(root.join("proxy/src/lib.rs")).unwrap().contains("    erc1967: Erc1967Proxy,\n"));
+        assert!(root.join("proxy/src/main.rs").exists());
+
+        let plan = stylus("Collection", ContractType::ERC721Upgradeable).plan().unwrap();
+        assert!(!plan.files.iter().any(|file| file.label == "proxy"));
+    }
+}
@@ -1,12 +1,17 @@
-use crate::{Result, GramrError, ProjectType, Language, templates::SolidityTemplate, project::Project};
+use crate::{changeset::Changeset, defaults::Defaults, Result, GramrError, GenerationPlan, OverwritePolicy, ProjectType, Language, templates::{SolidityTemplate, StylusTemplate, Template, Templates}, project::Project};
+use colored::*;
+use std::path::PathBuf;
 
-/// Interface generator for creating empty interfaces
+/// Interface generator for creating interfaces, empty or from an existing ABI
 pub struct InterfaceGenerator {
     project: ProjectType,
     language: Language,
     name: String,
     pragma: Option<String>,
     license: Option<String>,
+    overwrite: OverwritePolicy,
+    defaults: Defaults,
+    source: Option<PathBuf>,
 }
 
 impl InterfaceGenerator {
@@ -24,40 +29,90 @@ impl InterfaceGenerator {
             name,
             pragma,
             license,
+            overwrite: OverwritePolicy::default(),
+            defaults: Defaults::default(),
+            source: None,
         }
     }
 
+    /// Set what happens when the interface file already exists
+    pub fn with_overwrite_policy(mut self, overwrite: OverwritePolicy) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Set the defaults used for anything not given explicitly, e.g. from [`Defaults::load`]
+    pub fn with_defaults(mut self, defaults: Defaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Declare the functions of the Solidity interface (`.sol`) or ABI (`.json`) at `path`
+    pub fn with_source(mut self, path: PathBuf) -> Self {
+        self.source = Some(path);
+        self
+    }
+
     /// Generate the interface
     pub fn generate(&self) -> Result<()> {
+        let plan = self.plan()?.resolve_conflicts(self.overwrite)?;
+        if !plan.files.iter().any(|file| file.label == "interface") {
+            return Ok(());
+        }
+        // The repair edits the crate files in place, so they are snapshotted to
+        // be restored along with the staged files should anything fail
+        let mut changeset = Changeset::stage(&plan)?;
+        if let ProjectType::Cargo(project) = &self.project {
+            for path in project.setup_files() {
+                changeset.snapshot(&path)?;
+            }
+            for prerequisite in project.repair_stylus_prerequisites()? {
+                println!("{} Added {}", "✓".green(), prerequisite.description());
+            }
+        }
+        changeset.commit()?;
+
+        println!("✅ Interface I{} created successfully!", self.name);
+        Ok(())
+    }
+
+    /// The interface file `generate` would write, without writing it
+    pub fn plan(&self) -> Result<GenerationPlan> {
+        self.defaults.check_template_pack(crate::manifest::ResourceKind::Interface, None)?;
+        let functions = match &self.source {
+            Some(path) => crate::abi::interface_functions(path, &self.name)?,
+            None => Vec::new(),
+        };
+
         match self.language {
-            Language::Solidity => self.generate_solidity_interface(),
-            Language::RustStylus => self.generate_rust_interface(),
+            Language::Solidity => self.plan_solidity_interface(functions),
+            Language::RustStylus => self.plan_rust_interface(functions),
         }
     }
 
-    fn generate_solidity_interface(&self) -> Result<()> {
-        let pragma = self.pragma.as_deref().unwrap_or("0.8.30");
-        let license = self.license.as_deref().unwrap_or("UNLICENSED");
+    fn plan_solidity_interface(&self, functions: Vec<String>) -> Result<GenerationPlan> {
+        let pragma = self.pragma.as_deref().unwrap_or(&self.defaults.pragma);
+        let license = self.license.as_deref().unwrap_or(&self.defaults.license);
 
         let template = SolidityTemplate::new(
             self.name.clone(),
             crate::templates::ContractType::Interface,
             pragma.to_string(),
             license.to_string(),
-        );
+        )
+        .with_natspec(self.defaults.natspec, self.defaults.author.clone())
+        .with_templates(Templates::load(&self.defaults.template_dirs())?)
+        .with_interface_functions(functions);
 
-        let content = template.generate_interface();
+        let content = template.generate_interface()?;
 
         match &self.project {
             ProjectType::Foundry(foundry) => {
-                let interfaces_dir = foundry.src_dir().join("interfaces");
-                std::fs::create_dir_all(&interfaces_dir)?;
-                
-                let file_path = interfaces_dir.join(format!("I{}.sol", self.name));
-                std::fs::write(file_path, content)?;
-                
-                println!("✅ Interface I{} created successfully!", self.name);
-                Ok(())
+                let file_path = foundry.src_dir().join(&self.defaults.paths.interfaces).join(format!("I{}.sol", self.name));
+
+                let mut plan = GenerationPlan::new();
+                plan.add_file("interface", file_path, content);
+                Ok(plan)
             }
             ProjectType::Cargo(_) => Err(GramrError::Other(
                 "Interface generation for Rust/Stylus projects is not yet supported".to_string()
@@ -65,10 +120,105 @@ impl InterfaceGenerator {
         }
     }
 
-    fn generate_rust_interface(&self) -> Result<()> {
-        // For now, return an error since Rust/Stylus interfaces might work differently
-        Err(GramrError::Other(
-            "Interface generation for Rust/Stylus projects is not yet supported. Use traits instead.".to_string()
-        ))
+    /// A `sol_interface!` module under the interfaces directory, declared from `lib.rs` down
+    fn plan_rust_interface(&self, functions: Vec<String>) -> Result<GenerationPlan> {
+        let ProjectType::Cargo(project) = &self.project else {
+            return Err(GramrError::Other(
+                "Rust/Stylus interfaces can only be generated in a Cargo project".to_string()
+            ));
+        };
+
+        let content = StylusTemplate::new(self.name.clone(), crate::templates::ContractType::Interface)
+            .with_templates(Templates::load(&self.defaults.template_dirs())?)
+            .with_interface_functions(functions)
+            .generate_interface()?;
+        let file_path = project
+            .src_dir()
+            .join(&self.defaults.paths.interfaces)
+            .join(format!("{}.rs", crate::stylus::module_name(&self.name)));
+
+        let mut plan = GenerationPlan::new();
+        plan.add_file("interface", file_path.clone(), content);
+        for (path, content) in project.module_declarations(&file_path)? {
+            plan.add_edit("module", path, content);
+        }
+        plan.dependencies = project
+            .missing_stylus_prerequisites()?
+            .into_iter()
+            .map(|prerequisite| prerequisite.description().to_string())
+            .collect();
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::CargoProject;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn stylus_crate() -> (TempDir, ProjectType) {
+        let temp_dir = TempDir::new().unwrap();
+        let project = CargoProject::init(&temp_dir.path().join("app"), "app").unwrap();
+        (temp_dir, ProjectType::Cargo(project))
+    }
+
+    fn generator(project: ProjectType, name: &str) -> InterfaceGenerator {
+        InterfaceGenerator::new(project, Language::RustStylus, name.to_string(), None, None)
+    }
+
+    #[test]
+    fn test_stylus_interface_is_a_registered_sol_interface_module() {
+        let (temp_dir, project) = stylus_crate();
+        let src = temp_dir.path().join("app/src");
+
+        generator(project, "Counter").generate().unwrap();
+
+        let interface = fs::read_to_string(src.join("interfaces/counter.rs")).unwrap();
+        assert!(interface.contains("sol_interface! {\n    interface ICounter {\n        // Add your function signatures here"));
+        assert!(fs::read_to_string(src.join("lib.rs")).unwrap().ends_with("\npub mod interfaces;\n"));
+        assert_eq!(fs::read_to_string(src.join("interfaces/mod.rs")).unwrap(), "pub mod counter;\n");
+
+        let project = ProjectType::Cargo(CargoProject::detect_at(temp_dir.path().join("app")).unwrap());
+        generator(project, "Vault").generate().unwrap();
+        assert_eq!(fs::read_to_string(src.join("interfaces/mod.rs")).unwrap(), "pub mod counter;\npub mod vault;\n");
+        assert_eq!(fs::read_to_string(src.join("lib.rs")).unwrap().matches("pub mod interfaces;").count(), 1);
+    }
+
+    #[test]
+    fn test_stylus_interface_from_abi() {
+        let (temp_dir, project) = stylus_crate();
+        let abi = temp_dir.path().join("Token.json");
+        fs::write(
+            &abi,
+            r#"[{"type": "function", "name": "balanceOf", "stateMutability": "view",
+                 "inputs": [{"name": "owner", "type": "address"}], "outputs": [{"name": "", "type": "uint256"}]}]"#,
+        )
+        .unwrap();
+
+        let plan = generator(project, "Token").with_source(abi).plan().unwrap();
+
+        assert_eq!(plan.files[0].label, "interface");
+        assert!(plan.files[0].content.contains(
+            "    interface IToken {\n        function balanceOf(address owner) external view returns (uint256);\n    }"
+        ));
+        assert!(plan.files[1..].iter().all(|file| file.edit));
+    }
+
+    #[test]
+    fn test_solidity_interface_from_solidity_source() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("foundry.toml"), "[profile.default]\n").unwrap();
+        let source = temp_dir.path().join("IPool.sol");
+        fs::write(&source, "interface IPool {\n    function deposit(uint256 amount) external;\n}\n").unwrap();
+        let project = crate::foundry::FoundryProject::from_root(temp_dir.path().to_path_buf()).unwrap();
+
+        let plan = InterfaceGenerator::new(ProjectType::Foundry(project), Language::Solidity, "Pool".to_string(), None, None)
+            .with_source(source)
+            .plan()
+            .unwrap();
+
+        assert!(plan.files[0].content.contains("interface IPool {\n    function deposit(uint256 amount) external;\n}"));
     }
 }
\ No newline at end of file
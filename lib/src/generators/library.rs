@@ -1,9 +1,13 @@
+use crate::defaults::Defaults;
 use crate::error::{GramrError, Result};
 use crate::language::Language;
+use crate::manifest::ResourceKind;
+use crate::plan::{GenerationPlan, OverwritePolicy};
 use crate::project::{Project, ProjectType};
-use crate::templates::{Template, SolidityTemplate, StylusTemplate, ContractType};
+use crate::templates::{Template, Templates, SolidityTemplate, StylusTemplate, ContractType};
 use colored::*;
 use std::fs;
+use std::path::PathBuf;
 
 pub struct LibraryGenerator {
     project: ProjectType,
@@ -11,6 +15,8 @@ pub struct LibraryGenerator {
     library_name: String,
     pragma: Option<String>,  // Only for Solidity
     license: Option<String>, // Only for Solidity
+    overwrite: OverwritePolicy,
+    defaults: Defaults,
 }
 
 impl LibraryGenerator {
@@ -27,36 +33,82 @@ impl LibraryGenerator {
             library_name,
             pragma,
             license,
+            overwrite: OverwritePolicy::default(),
+            defaults: Defaults::default(),
         }
     }
 
+    /// Set what happens when the library file already exists
+    pub fn with_overwrite_policy(mut self, overwrite: OverwritePolicy) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Set the defaults used for anything not given explicitly, e.g. from [`Defaults::load`]
+    pub fn with_defaults(mut self, defaults: Defaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
     pub fn generate(&self) -> Result<()> {
-        self.validate_name()?;
+        let plan = self.plan()?.resolve_conflicts(self.overwrite)?;
+        if plan.files.is_empty() {
+            return Ok(());
+        }
         self.project.ensure_directories()?;
         
         // Ensure libraries directory exists
         self.ensure_libraries_directory()?;
 
+        plan.apply()
+            .map_err(|e| GramrError::Other(format!("Failed to write library file: {}", e)))?;
+
+        println!("{} Created library: {}", "✓".green(), self.display_path());
+        for file in plan.files.iter().filter(|file| file.edit) {
+            let action = if file.is_new() { "Created" } else { "Updated" };
+            println!("{} {} {}: {}", "✓".green(), action, file.label, file.path.display());
+        }
+        self.print_success();
+        Ok(())
+    }
+
+    /// The library file `generate` would write, without writing it
+    pub fn plan(&self) -> Result<GenerationPlan> {
+        self.validate_name()?;
+        self.defaults.check_template_pack(ResourceKind::Library, None)?;
+
         let template: Box<dyn Template> = match self.language {
             Language::Solidity => {
-                Box::new(SolidityTemplate::new(
-                    self.library_name.clone(),
-                    ContractType::Basic, // Libraries don't use contract types
-                    self.pragma.clone().unwrap_or_else(|| "0.8.30".to_string()),
-                    self.license.clone().unwrap_or_else(|| "UNLICENSED".to_string()),
-                ))
+                Box::new(
+                    SolidityTemplate::new(
+                        self.library_name.clone(),
+                        ContractType::Basic, // Libraries don't use contract types
+                        self.pragma.clone().unwrap_or_else(|| self.defaults.pragma.clone()),
+                        self.license.clone().unwrap_or_else(|| self.defaults.license.clone()),
+                    )
+                    .with_natspec(self.defaults.natspec, self.defaults.author.clone())
+                    .with_templates(Templates::load(&self.defaults.template_dirs())?),
+                )
             }
             Language::RustStylus => {
-                Box::new(StylusTemplate::new(
-                    self.library_name.clone(),
-                    ContractType::Basic, // Libraries don't use contract types
-                ))
+                Box::new(
+                    StylusTemplate::new(
+                        self.library_name.clone(),
+                        ContractType::Basic, // Libraries don't use contract types
+                    )
+                    .with_templates(Templates::load(&self.defaults.template_dirs())?),
+                )
             }
         };
 
-        self.create_library_file(&*template)?;
-        self.print_success();
-        Ok(())
+        let mut plan = GenerationPlan::new();
+        plan.add_file("library", self.file_path(), template.generate_library()?);
+        if let ProjectType::Cargo(project) = &self.project {
+            for (path, content) in project.module_declarations(&self.file_path())? {
+                plan.add_edit("module", path, content);
+            }
+        }
+        Ok(plan)
     }
 
     fn validate_name(&self) -> Result<()> {
@@ -80,10 +132,7 @@ impl LibraryGenerator {
     }
 
     fn ensure_libraries_directory(&self) -> Result<()> {
-        let libraries_dir = match self.language {
-            Language::Solidity => self.project.src_dir().join("libraries"),
-            Language::RustStylus => self.project.src_dir().join("libraries"),
-        };
+        let libraries_dir = self.project.src_dir().join(&self.defaults.paths.libraries);
 
         fs::create_dir_all(&libraries_dir)
             .map_err(|e| GramrError::Other(format!("Failed to create libraries directory: {}", e)))?;
@@ -91,28 +140,19 @@ impl LibraryGenerator {
         Ok(())
     }
 
-    fn create_library_file(&self, template: &dyn Template) -> Result<()> {
-        let content = template.generate_library();
-        let (file_path, relative_path) = match self.language {
-            Language::Solidity => {
-                let file_name = format!("{}.sol", self.library_name);
-                let path = self.project.src_dir().join("libraries").join(&file_name);
-                let relative = format!("src/libraries/{}", file_name);
-                (path, relative)
-            }
-            Language::RustStylus => {
-                let file_name = format!("{}.rs", self.library_name.to_lowercase());
-                let path = self.project.src_dir().join("libraries").join(&file_name);
-                let relative = format!("src/libraries/{}", file_name);
-                (path, relative)
-            }
+    fn file_path(&self) -> PathBuf {
+        let file_name = match self.language {
+            Language::Solidity => format!("{}.sol", self.library_name),
+            Language::RustStylus => format!("{}.rs", crate::stylus::module_name(&self.library_name)),
         };
-        
-        fs::write(&file_path, content)
-            .map_err(|e| GramrError::Other(format!("Failed to write library file: {}", e)))?;
-        
-        println!("{} Created library: {}", "✓".green(), relative_path);
-        Ok(())
+        self.project.src_dir().join(&self.defaults.paths.libraries).join(file_name)
+    }
+
+    fn display_path(&self) -> String {
+        match self.language {
+            Language::Solidity => self.file_path().display().to_string(),
+            Language::RustStylus => format!("src/{}/{}.rs", self.defaults.paths.libraries, crate::stylus::module_name(&self.library_name)),
+        }
     }
 
     fn print_success(&self) {
@@ -123,15 +163,40 @@ impl LibraryGenerator {
         
         match self.language {
             Language::Solidity => {
-                println!("  3. Import in contracts with: {}", format!("import \"./libraries/{}.sol\";", self.library_name).cyan());
+                println!("  3. Import in contracts with: {}", format!("import \"./{}/{}.sol\";", self.defaults.paths.libraries, self.library_name).cyan());
                 println!("  4. Use library functions with: {}", format!("using {} for <Type>;", self.library_name).cyan());
                 println!("  5. Run {} to compile", "forge build".cyan());
             }
             Language::RustStylus => {
-                println!("  3. Add to lib.rs: {}", format!("pub mod libraries;").cyan());
-                println!("  4. Import in contracts with: {}", format!("use crate::libraries::{};", self.library_name.to_lowercase()).cyan());
-                println!("  5. Run {} to build", "cargo build --release".cyan());
+                println!("  3. Import in contracts with: {}", format!("use crate::{}::{};", self.defaults.paths.libraries, crate::stylus::module_name(&self.library_name)).cyan());
+                println!("  4. Run {} to build", "cargo build --release".cyan());
             }
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::CargoProject;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_stylus_library_is_declared_from_lib_rs() {
+        let temp_dir = TempDir::new().unwrap();
+        CargoProject::init(temp_dir.path(), "app").unwrap();
+        let generator = || {
+            let project = CargoProject::detect_at(temp_dir.path().to_path_buf()).unwrap();
+            LibraryGenerator::new(ProjectType::Cargo(project), Language::RustStylus, "MathUtils".to_string(), None, None)
+        };
+
+        generator().generate().unwrap();
+        // A second run with the existing library kept changes nothing
+        let plan = generator().plan().unwrap();
+        assert_eq!(plan.files.len(), 1);
+
+        let src = temp_dir.path().join("src");
+        assert!(fs::read_to_string(src.join("lib.rs")).unwrap().ends_with("extern crate alloc;\n\npub mod libraries;\n"));
+        assert_eq!(fs::read_to_string(src.join("libraries/mod.rs")).unwrap(), "pub mod math_utils;\n");
+        assert!(fs::read_to_string(src.join("libraries/math_utils.rs")).unwrap().contains("pub mod math_utils {"));
+    }
+}
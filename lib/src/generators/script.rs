@@ -1,13 +1,15 @@
 use crate::error::{GramrError, Result};
 use crate::foundry::FoundryProject;
+use crate::plan::{GenerationPlan, OverwritePolicy, PlannedFile};
+use crate::project::relative_path;
 use colored::*;
-use std::fs;
 
 pub struct ScriptGenerator {
     project: FoundryProject,
     script_name: String,
     pragma: String,
     license: String,
+    overwrite: OverwritePolicy,
 }
 
 impl ScriptGenerator {
@@ -22,17 +24,41 @@ impl ScriptGenerator {
             script_name,
             pragma,
             license,
+            overwrite: OverwritePolicy::default(),
         }
     }
 
+    /// Set what happens when the script file already exists
+    pub fn with_overwrite_policy(mut self, overwrite: OverwritePolicy) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
     pub fn generate(&self) -> Result<()> {
-        self.validate_name()?;
+        let plan = self.plan()?.resolve_conflicts(self.overwrite)?;
+        if plan.files.is_empty() {
+            return Ok(());
+        }
+
         self.project.ensure_directories()?;
-        self.create_script_file()?;
+        plan.apply()?;
+
+        for file in &plan.files {
+            println!("{} Created {}: {}", "✓".green(), file.label, file.path.display());
+        }
         self.print_success();
         Ok(())
     }
 
+    /// The script file `generate` would write, without writing it
+    pub fn plan(&self) -> Result<GenerationPlan> {
+        self.validate_name()?;
+
+        let mut plan = GenerationPlan::new();
+        plan.files.push(self.script_file()?);
+        Ok(plan)
+    }
+
     fn validate_name(&self) -> Result<()> {
         if self.script_name.is_empty() {
             return Err(GramrError::InvalidContractName(
@@ -59,21 +85,17 @@ impl ScriptGenerator {
         Ok(())
     }
 
-    fn create_script_file(&self) -> Result<()> {
+    fn script_file(&self) -> Result<PlannedFile> {
         let file_path = self
             .project
             .script_dir
             .join(format!("{}.s.sol", self.script_name));
 
-        if file_path.exists() {
-            return Err(GramrError::FileExists(file_path.display().to_string()));
-        }
-
-        let content = self.generate_script_content();
-        fs::write(&file_path, content)?;
-
-        println!("{} Created script: {}", "✓".green(), file_path.display());
-        Ok(())
+        let content = self
+            .project
+            .import_paths()?
+            .rewrite(&self.generate_script_content());
+        Ok(PlannedFile::new("script", file_path, content))
     }
 
     fn generate_script_content(&self) -> String {
@@ -106,7 +128,12 @@ contract {} is Script {{
         println!("  2. Set the PRIVATE_KEY environment variable");
         println!(
             "  3. Run {} to execute",
-            format!("forge script script/{}.s.sol", self.script_name).cyan()
+            format!(
+                "forge script {}/{}.s.sol",
+                relative_path(&self.project.root, &self.project.script_dir),
+                self.script_name
+            )
+            .cyan()
         );
     }
 }
@@ -1,13 +1,14 @@
 use crate::error::{GramrError, Result};
 use crate::foundry::FoundryProject;
+use crate::plan::{GenerationPlan, OverwritePolicy, PlannedFile};
 use colored::*;
-use std::fs;
 
 pub struct TestGenerator {
     project: FoundryProject,
     test_name: String,
     pragma: String,
     license: String,
+    overwrite: OverwritePolicy,
 }
 
 impl TestGenerator {
@@ -22,17 +23,41 @@ impl TestGenerator {
             test_name,
             pragma,
             license,
+            overwrite: OverwritePolicy::default(),
         }
     }
 
+    /// Set what happens when the test file already exists
+    pub fn with_overwrite_policy(mut self, overwrite: OverwritePolicy) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
     pub fn generate(&self) -> Result<()> {
-        self.validate_name()?;
+        let plan = self.plan()?.resolve_conflicts(self.overwrite)?;
+        if plan.files.is_empty() {
+            return Ok(());
+        }
+
         self.project.ensure_directories()?;
-        self.create_test_file()?;
+        plan.apply()?;
+
+        for file in &plan.files {
+            println!("{} Created {}: {}", "✓".green(), file.label, file.path.display());
+        }
         self.print_success();
         Ok(())
     }
 
+    /// The test file `generate` would write, without writing it
+    pub fn plan(&self) -> Result<GenerationPlan> {
+        self.validate_name()?;
+
+        let mut plan = GenerationPlan::new();
+        plan.files.push(self.test_file()?);
+        Ok(plan)
+    }
+
     fn validate_name(&self) -> Result<()> {
         if self.test_name.is_empty() {
             return Err(GramrError::InvalidContractName(
@@ -59,21 +84,17 @@ impl TestGenerator {
         Ok(())
     }
 
-    fn create_test_file(&self) -> Result<()> {
+    fn test_file(&self) -> Result<PlannedFile> {
         let file_path = self
             .project
             .test_dir
             .join(format!("{}.t.sol", self.test_name));
 
-        if file_path.exists() {
-            return Err(GramrError::FileExists(file_path.display().to_string()));
-        }
-
-        let content = self.generate_test_content();
-        fs::write(&file_path, content)?;
-
-        println!("{} Created test: {}", "✓".green(), file_path.display());
-        Ok(())
+        let content = self
+            .project
+            .import_paths()?
+            .rewrite(&self.generate_test_content());
+        Ok(PlannedFile::new("test", file_path, content))
     }
 
     fn generate_test_content(&self) -> String {
@@ -3,20 +3,36 @@
 //! This library provides the core functionality for generating Solidity and Rust/Stylus contracts,
 //! tests, and deployment scripts for Foundry and Cargo projects.
 
+pub mod abi;
+pub mod changeset;
+pub mod defaults;
 pub mod error;
+pub mod extensions;
 pub mod foundry;
 pub mod generators;
 pub mod templates;
 pub mod language;
+pub mod manifest;
+pub mod pack;
+pub mod plan;
 pub mod project;
+pub mod prompt;
+pub mod stylus;
 
 // Re-export commonly used types
+pub use changeset::Changeset;
+pub use defaults::Defaults;
 pub use error::{GramrError, Result};
+pub use extensions::{ExtensionParameter, ExtensionRegistry, ExtensionSpec};
 pub use foundry::FoundryProject;
 pub use generators::{ContractGenerator, ScriptGenerator, TestGenerator, GenericContractGenerator, LibraryGenerator, InterfaceGenerator, AbstractContractGenerator, ConfigGenerator};
-pub use templates::{ContractType, TokenExtension, SolidityTemplate, StylusTemplate};
+pub use templates::{AccessControl, ContractType, NatSpecStyle, OpenZeppelinVersion, TokenExtension, TokenMetadata, SolidityTemplate, StylusTemplate, Templates};
 pub use language::Language;
+pub use manifest::{Manifest, Resource, ResourceKind};
+pub use pack::TemplatePack;
+pub use plan::{GenerationPlan, OverwritePolicy, PlannedFile};
 pub use project::{Project, ProjectType, CargoProject};
+pub use stylus::{Prerequisite, StylusCrate};
 
 // Version info
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -27,16 +43,25 @@ pub struct ContractBuilder {
     contract_type: ContractType,
     pragma: String,
     license: String,
+    metadata: TokenMetadata,
+    defaults: Defaults,
 }
 
 impl ContractBuilder {
-    /// Create a new contract builder
+    /// Create a new contract builder, starting from the user and project defaults
     pub fn new(name: impl Into<String>) -> Self {
+        Self::new_with_defaults(name, Defaults::load_or_default())
+    }
+
+    /// Create a new contract builder with explicit defaults instead of the configured ones
+    pub fn new_with_defaults(name: impl Into<String>, defaults: Defaults) -> Self {
         Self {
             name: name.into(),
             contract_type: ContractType::Basic,
-            pragma: "0.8.30".to_string(),
-            license: "UNLICENSED".to_string(),
+            pragma: defaults.pragma.clone(),
+            license: defaults.license.clone(),
+            metadata: TokenMetadata::default(),
+            defaults,
         }
     }
 
@@ -58,14 +83,60 @@ impl ContractBuilder {
         self
     }
 
+    /// Set the token name, when it differs from the contract name
+    pub fn token_name(mut self, name: impl Into<String>) -> Self {
+        self.metadata.name = Some(name.into());
+        self
+    }
+
+    /// Set the token symbol
+    pub fn symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.metadata.symbol = Some(symbol.into());
+        self
+    }
+
+    /// Set the ERC20 decimals
+    pub fn decimals(mut self, decimals: u8) -> Self {
+        self.metadata.decimals = Some(decimals);
+        self
+    }
+
+    /// Set the ERC20 supply generated tests and scripts mint, in whole tokens
+    pub fn initial_supply(mut self, supply: impl Into<String>) -> Self {
+        self.metadata.initial_supply = Some(supply.into());
+        self
+    }
+
+    /// Set the ERC721 base URI or ERC1155 URI
+    pub fn base_uri(mut self, uri: impl Into<String>) -> Self {
+        self.metadata.base_uri = Some(uri.into());
+        self
+    }
+
+    /// Set who may call the privileged functions (mint, pause, upgrade) of a token
+    pub fn access_control(mut self, access_control: AccessControl) -> Self {
+        self.defaults.access_control = access_control;
+        self
+    }
+
     /// Generate the contract source code
-    pub fn build(self) -> String {
+    pub fn build(self) -> Result<String> {
+        self.metadata.check(&self.contract_type)?;
+        self.defaults
+            .access_control
+            .check(&self.contract_type, &self.defaults.extensions, OpenZeppelinVersion::default())?;
+        let templates = Templates::load(&self.defaults.template_dirs())?;
         let template = SolidityTemplate::new(
             self.name,
             self.contract_type,
             self.pragma,
             self.license,
-        );
+        )
+        .with_natspec(self.defaults.natspec, self.defaults.author)
+        .with_templates(templates)
+        .with_extensions(self.defaults.extensions)
+        .with_token_metadata(self.metadata)
+        .with_access_control(self.defaults.access_control);
         template.generate_contract()
     }
 
@@ -79,50 +150,16 @@ impl ContractBuilder {
             false,
             self.pragma,
             self.license,
-        );
+        )
+        .with_token_metadata(self.metadata)
+        .with_access_control(self.defaults.access_control);
         generator.generate()
     }
 }
 
-/// Helper function to parse extensions from strings
+/// Parse extension names such as `burnable` against the built-in extensions
 pub fn parse_extensions(extensions: &[String]) -> Result<Vec<TokenExtension>> {
-    use templates::TokenExtension;
-    
-    let mut result = Vec::new();
-    
-    for ext in extensions {
-        let extension = match ext.to_lowercase().as_str() {
-            // ERC20 Extensions
-            "permit" => TokenExtension::ERC20Permit,
-            "burnable" => TokenExtension::ERC20Burnable,
-            "capped" => TokenExtension::ERC20Capped,
-            "pausable" => TokenExtension::ERC20Pausable,
-            "votes" => TokenExtension::ERC20Votes,
-            "wrapper" => TokenExtension::ERC20Wrapper,
-            "flashmint" => TokenExtension::ERC20FlashMint,
-            "temporaryapproval" => TokenExtension::ERC20TemporaryApproval,
-            "bridgeable" => TokenExtension::ERC20Bridgeable,
-            "erc1363" => TokenExtension::ERC1363,
-            "erc4626" => TokenExtension::ERC4626,
-            
-            // ERC721 Extensions  
-            "consecutive" => TokenExtension::ERC721Consecutive,
-            "uristorage" => TokenExtension::ERC721URIStorage,
-            "royalty" => TokenExtension::ERC721Royalty,
-            "enumerable" => TokenExtension::ERC721Enumerable,
-            
-            // ERC1155 Extensions
-            "supply" => TokenExtension::ERC1155Supply,
-            
-            _ => return Err(GramrError::Other(
-                format!("Unknown extension: {}. Available extensions: permit, burnable, capped, pausable, votes, wrapper, flashmint, temporaryapproval, bridgeable, erc1363, erc4626, consecutive, uristorage, royalty, wrapper, enumerable, supply", ext)
-            )),
-        };
-        
-        result.push(extension);
-    }
-    
-    Ok(result)
+    ExtensionRegistry::builtin().parse(extensions)
 }
 
 #[cfg(test)]
@@ -140,7 +177,7 @@ mod tests {
 
         #[test]
         fn test_new_contract_builder() {
-            let builder = ContractBuilder::new("TestContract");
+            let builder = ContractBuilder::new_with_defaults("TestContract", Defaults::default());
             assert_eq!(builder.name, "TestContract");
             assert!(matches!(builder.contract_type, ContractType::Basic));
             assert_eq!(builder.pragma, "0.8.30");
@@ -149,7 +186,7 @@ mod tests {
 
         #[test]
         fn test_contract_builder_chain() {
-            let builder = ContractBuilder::new("MyToken")
+            let builder = ContractBuilder::new_with_defaults("MyToken", Defaults::default())
                 .contract_type(ContractType::ERC20)
                 .pragma("0.8.25")
                 .license("MIT");
@@ -162,22 +199,22 @@ mod tests {
 
         #[test]
         fn test_contract_builder_with_different_types() {
-            let erc20_builder = ContractBuilder::new("Token").contract_type(ContractType::ERC20);
+            let erc20_builder = ContractBuilder::new_with_defaults("Token", Defaults::default()).contract_type(ContractType::ERC20);
             assert!(matches!(erc20_builder.contract_type, ContractType::ERC20));
 
-            let erc721_builder = ContractBuilder::new("NFT").contract_type(ContractType::ERC721);
+            let erc721_builder = ContractBuilder::new_with_defaults("NFT", Defaults::default()).contract_type(ContractType::ERC721);
             assert!(matches!(erc721_builder.contract_type, ContractType::ERC721));
 
-            let erc1155_builder = ContractBuilder::new("Multi").contract_type(ContractType::ERC1155);
+            let erc1155_builder = ContractBuilder::new_with_defaults("Multi", Defaults::default()).contract_type(ContractType::ERC1155);
             assert!(matches!(erc1155_builder.contract_type, ContractType::ERC1155));
         }
 
         #[test]
         fn test_build_basic_contract() {
-            let contract = ContractBuilder::new("BasicContract")
+            let contract = ContractBuilder::new_with_defaults("BasicContract", Defaults::default())
                 .pragma("0.8.20")
                 .license("MIT")
-                .build();
+                .build().unwrap();
             
             assert!(contract.contains("BasicContract"));
             assert!(contract.contains("0.8.20"));
@@ -187,9 +224,9 @@ mod tests {
 
         #[test]
         fn test_build_erc20_contract() {
-            let contract = ContractBuilder::new("MyToken")
+            let contract = ContractBuilder::new_with_defaults("MyToken", Defaults::default())
                 .contract_type(ContractType::ERC20)
-                .build();
+                .build().unwrap();
             
             assert!(contract.contains("MyToken"));
             assert!(contract.contains("ERC20"));
@@ -197,9 +234,44 @@ mod tests {
         }
 
         #[test]
-        fn test_build_with_empty_name() {
-            let contract = ContractBuilder::new("")
+        fn test_build_with_token_metadata() {
+            let contract = ContractBuilder::new_with_defaults("MyToken", Defaults::default())
+                .contract_type(ContractType::ERC20)
+                .token_name("My Stable Token")
+                .symbol("MST")
+                .decimals(6)
+                .initial_supply("1e9")
+                .build().unwrap();
+
+            assert!(contract.contains("ERC20(\"My Stable Token\", \"MST\")"));
+            assert!(contract.contains("return 6;"));
+
+            let result = ContractBuilder::new_with_defaults("MyNFT", Defaults::default())
+                .contract_type(ContractType::ERC721)
+                .decimals(6)
+                .build();
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn test_build_with_access_control() {
+            let contract = ContractBuilder::new_with_defaults("MyNFT", Defaults::default())
+                .contract_type(ContractType::ERC721)
+                .access_control(AccessControl::Roles)
+                .build().unwrap();
+            assert!(contract.contains("onlyRole(MINTER_ROLE)"));
+
+            let result = ContractBuilder::new_with_defaults("MyNFT", Defaults::default())
+                .contract_type(ContractType::ERC721Upgradeable)
+                .access_control(AccessControl::None)
                 .build();
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn test_build_with_empty_name() {
+            let contract = ContractBuilder::new_with_defaults("", Defaults::default())
+                .build().unwrap();
             
             // Should still generate valid solidity
             assert!(contract.contains("pragma solidity"));
@@ -207,8 +279,8 @@ mod tests {
 
         #[test]
         fn test_build_with_special_characters_in_name() {
-            let contract = ContractBuilder::new("My_Token_123")
-                .build();
+            let contract = ContractBuilder::new_with_defaults("My_Token_123", Defaults::default())
+                .build().unwrap();
             
             assert!(contract.contains("My_Token_123"));
         }
@@ -257,7 +329,7 @@ mod tests {
             assert_eq!(extensions.len(), 3);
             assert!(matches!(extensions[0], TokenExtension::ERC721Enumerable));
             assert!(matches!(extensions[1], TokenExtension::ERC721URIStorage));
-            assert!(matches!(extensions[2], TokenExtension::ERC721Royalty));
+            assert!(matches!(extensions[2], TokenExtension::ERC721Royalty(_)));
         }
 
         #[test]
@@ -343,6 +415,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_module_exports() {
         // Test that key types are properly exported
         let _error: GramrError = GramrError::Other("test".to_string());
@@ -356,7 +429,7 @@ mod tests {
 
     #[test]
     fn test_contract_builder_string_conversions() {
-        let builder = ContractBuilder::new("Test".to_string())
+        let builder = ContractBuilder::new_with_defaults("Test".to_string(), Defaults::default())
             .pragma("0.8.19".to_string())
             .license("Apache-2.0".to_string());
         
@@ -367,7 +440,7 @@ mod tests {
 
     #[test]
     fn test_contract_builder_str_conversions() {
-        let builder = ContractBuilder::new("Test")
+        let builder = ContractBuilder::new_with_defaults("Test", Defaults::default())
             .pragma("0.8.19")
             .license("Apache-2.0");
         
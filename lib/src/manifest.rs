@@ -0,0 +1,538 @@
+//! Project manifest (`gramr.toml`) declaring the resources a project should contain.
+//!
+//! ```toml
+//! [project]
+//! language = "solidity"
+//! pragma = "0.8.30"
+//! license = "MIT"
+//!
+//! [[resources]]
+//! kind = "contract"
+//! name = "MyToken"
+//! type = "erc20"
+//! extensions = ["burnable", "permit"]
+//! with_test = true
+//!
+//! [[resources]]
+//! kind = "library"
+//! name = "MathUtils"
+//! ```
+//!
+//! Applying a manifest generates whatever is missing and leaves existing files
+//! alone, so it can be run repeatedly.
+
+use crate::defaults::Defaults;
+use crate::error::{GramrError, Result};
+use crate::foundry::FoundryProject;
+use crate::generators::{
+    AbstractContractGenerator, GenericContractGenerator, InterfaceGenerator, LibraryGenerator, ScriptGenerator,
+    TestGenerator,
+};
+use crate::language::Language;
+use crate::plan::{GenerationPlan, OverwritePolicy};
+use crate::project::ProjectType;
+use crate::templates::ContractType;
+use std::fs;
+use std::path::Path;
+use toml::Table;
+
+/// File name `gramr apply` looks for by default
+pub const MANIFEST_FILE: &str = "gramr.toml";
+
+const PROJECT_KEYS: &[&str] = &["language", "pragma", "license", "oz_version"];
+const RESOURCE_KEYS: &[&str] = &["kind", "name", "pragma", "license"];
+const CONTRACT_KEYS: &[&str] = &["type", "upgradeable", "extensions", "with_test", "with_script"];
+
+/// What a manifest entry generates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Contract,
+    Library,
+    Interface,
+    Abstract,
+    Script,
+    Test,
+}
+
+impl ResourceKind {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "contract" => Some(Self::Contract),
+            "library" => Some(Self::Library),
+            "interface" => Some(Self::Interface),
+            "abstract" => Some(Self::Abstract),
+            "script" => Some(Self::Script),
+            "test" => Some(Self::Test),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Contract => "contract",
+            Self::Library => "library",
+            Self::Interface => "interface",
+            Self::Abstract => "abstract",
+            Self::Script => "script",
+            Self::Test => "test",
+        }
+    }
+}
+
+/// A single `[[resources]]` entry, with project defaults already applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resource {
+    pub kind: ResourceKind,
+    pub name: String,
+    /// Always `ContractType::Basic` for anything other than a contract
+    pub contract_type: ContractType,
+    pub with_test: bool,
+    pub with_script: bool,
+    pub pragma: String,
+    pub license: String,
+}
+
+/// The parsed contents of a `gramr.toml` file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub language: Language,
+    /// OpenZeppelin Contracts release to install and target, e.g. `5.4.0`
+    pub openzeppelin_version: Option<String>,
+    pub resources: Vec<Resource>,
+    /// User and project defaults, with `pragma` and `license` from `[project]` applied
+    pub defaults: Defaults,
+}
+
+impl Manifest {
+    /// Read and parse the manifest at `path`, on top of the user and project defaults
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(GramrError::InvalidConfig(format!("No manifest found at {}", path.display())));
+        }
+
+        let content = fs::read_to_string(path)?;
+        Self::parse_with_defaults(&content, Defaults::load()?)
+    }
+
+    /// Parse the contents of a `gramr.toml` file on top of the built-in defaults
+    pub fn parse(content: &str) -> Result<Self> {
+        Self::parse_with_defaults(content, Defaults::default())
+    }
+
+    /// Parse the contents of a `gramr.toml` file; `[project]` settings take
+    /// precedence over `defaults`, and each resource's settings over both
+    pub fn parse_with_defaults(content: &str, mut defaults: Defaults) -> Result<Self> {
+        let table: Table = content.parse().map_err(|e| invalid(format!("{}", e)))?;
+        check_keys(&table, &["project", "resources"], "the top level")?;
+
+        let project = match table.get("project") {
+            Some(value) => value.as_table().ok_or_else(|| invalid_type("project", "a table"))?.clone(),
+            None => Table::new(),
+        };
+        check_keys(&project, PROJECT_KEYS, "[project]")?;
+
+        let language = match string_key(&project, "language")?.as_deref() {
+            None | Some("solidity") => Language::Solidity,
+            Some("rust-stylus") => Language::RustStylus,
+            Some(other) => {
+                return Err(invalid(format!(
+                    "unknown language `{}` (expected `solidity` or `rust-stylus`)",
+                    other
+                )))
+            }
+        };
+        if let Some(pragma) = string_key(&project, "pragma")? {
+            defaults.pragma = pragma;
+        }
+        if let Some(license) = string_key(&project, "license")? {
+            defaults.license = license;
+        }
+
+        let entries = match table.get("resources") {
+            Some(value) => value
+                .as_array()
+                .ok_or_else(|| invalid_type("resources", "an array of tables"))?
+                .clone(),
+            None => Vec::new(),
+        };
+
+        let mut resources: Vec<Resource> = Vec::new();
+        for entry in &entries {
+            let entry = entry
+                .as_table()
+                .ok_or_else(|| invalid_type("resources", "an array of tables"))?;
+            let resource = parse_resource(entry, &language, &defaults)?;
+
+            if resources.iter().any(|r| r.kind == resource.kind && r.name == resource.name) {
+                return Err(invalid(format!(
+                    "{} `{}` is listed more than once",
+                    resource.kind.as_str(),
+                    resource.name
+                )));
+            }
+            resources.push(resource);
+        }
+
+        Ok(Self {
+            language,
+            openzeppelin_version: string_key(&project, "oz_version")?,
+            resources,
+            defaults,
+        })
+    }
+
+    /// The files `resource` would write, without touching the project
+    pub fn plan(&self, resource: &Resource, project: ProjectType) -> Result<GenerationPlan> {
+        self.generator(resource, project)?.plan()
+    }
+
+    /// Generate the files of `resource` that do not exist yet, keeping the rest
+    pub fn generate(&self, resource: &Resource, project: ProjectType) -> Result<()> {
+        self.generator(resource, project)?.generate()
+    }
+
+    fn generator(&self, resource: &Resource, project: ProjectType) -> Result<ResourceGenerator> {
+        let overwrite = OverwritePolicy::SkipExisting;
+        let language = self.language.clone();
+        let name = resource.name.clone();
+        let pragma = Some(resource.pragma.clone());
+        let license = Some(resource.license.clone());
+
+        let generator = match resource.kind {
+            ResourceKind::Contract => {
+                let mut generator = GenericContractGenerator::new(
+                    project,
+                    language,
+                    name,
+                    resource.contract_type.clone(),
+                    resource.with_test,
+                    resource.with_script,
+                    pragma,
+                    license,
+                )
+                .with_overwrite_policy(overwrite)
+                .with_defaults(self.defaults.clone());
+                if let Some(version) = &self.openzeppelin_version {
+                    generator = generator.with_openzeppelin_version(version.clone());
+                }
+                ResourceGenerator::Contract(generator)
+            }
+            ResourceKind::Library => ResourceGenerator::Library(
+                LibraryGenerator::new(project, language, name, pragma, license)
+                    .with_overwrite_policy(overwrite)
+                    .with_defaults(self.defaults.clone()),
+            ),
+            ResourceKind::Interface => ResourceGenerator::Interface(
+                InterfaceGenerator::new(project, language, name, pragma, license)
+                    .with_overwrite_policy(overwrite)
+                    .with_defaults(self.defaults.clone()),
+            ),
+            ResourceKind::Abstract => ResourceGenerator::Abstract(
+                AbstractContractGenerator::new(project, language, name, pragma, license)
+                    .with_overwrite_policy(overwrite)
+                    .with_defaults(self.defaults.clone()),
+            ),
+            ResourceKind::Script => ResourceGenerator::Script(
+                ScriptGenerator::new(
+                    foundry_project(project, resource.kind)?,
+                    name,
+                    resource.pragma.clone(),
+                    resource.license.clone(),
+                )
+                .with_overwrite_policy(overwrite),
+            ),
+            ResourceKind::Test => ResourceGenerator::Test(
+                TestGenerator::new(
+                    foundry_project(project, resource.kind)?,
+                    name,
+                    resource.pragma.clone(),
+                    resource.license.clone(),
+                )
+                .with_overwrite_policy(overwrite),
+            ),
+        };
+
+        Ok(generator)
+    }
+}
+
+/// The existing generator behind each resource kind
+enum ResourceGenerator {
+    Contract(GenericContractGenerator),
+    Library(LibraryGenerator),
+    Interface(InterfaceGenerator),
+    Abstract(AbstractContractGenerator),
+    Script(ScriptGenerator),
+    Test(TestGenerator),
+}
+
+impl ResourceGenerator {
+    fn plan(&self) -> Result<GenerationPlan> {
+        match self {
+            Self::Contract(generator) => generator.plan(),
+            Self::Library(generator) => generator.plan(),
+            Self::Interface(generator) => generator.plan(),
+            Self::Abstract(generator) => generator.plan(),
+            Self::Script(generator) => generator.plan(),
+            Self::Test(generator) => generator.plan(),
+        }
+    }
+
+    fn generate(&self) -> Result<()> {
+        match self {
+            Self::Contract(generator) => generator.generate(),
+            Self::Library(generator) => generator.generate(),
+            Self::Interface(generator) => generator.generate(),
+            Self::Abstract(generator) => generator.generate(),
+            Self::Script(generator) => generator.generate(),
+            Self::Test(generator) => generator.generate(),
+        }
+    }
+}
+
+fn foundry_project(project: ProjectType, kind: ResourceKind) -> Result<FoundryProject> {
+    match project {
+        ProjectType::Foundry(project) => Ok(project),
+        _ => Err(GramrError::Other(format!(
+            "{} generation is only supported for Foundry projects",
+            kind.as_str()
+        ))),
+    }
+}
+
+fn parse_resource(entry: &Table, language: &Language, defaults: &Defaults) -> Result<Resource> {
+    let name = string_key(entry, "name")?.ok_or_else(|| invalid("every resource needs a `name`".to_string()))?;
+    let kind = match string_key(entry, "kind")? {
+        None => ResourceKind::Contract,
+        Some(kind) => ResourceKind::parse(&kind).ok_or_else(|| {
+            invalid(format!(
+                "`{}` has unknown kind `{}` (expected contract, library, interface, abstract, script or test)",
+                name, kind
+            ))
+        })?,
+    };
+
+    if kind == ResourceKind::Contract {
+        check_keys(entry, &[RESOURCE_KEYS, CONTRACT_KEYS].concat(), &format!("`{}`", name))?;
+    } else {
+        check_keys(entry, RESOURCE_KEYS, &format!("{} `{}`", kind.as_str(), name))?;
+    }
+
+    if matches!(kind, ResourceKind::Script | ResourceKind::Test) && *language == Language::RustStylus {
+        return Err(invalid(format!(
+            "{} `{}`: {} generation is not supported for Rust/Stylus projects",
+            kind.as_str(),
+            name,
+            kind.as_str()
+        )));
+    }
+
+    Ok(Resource {
+        kind,
+        contract_type: contract_type(entry, &name, defaults)?,
+        with_test: bool_key(entry, "with_test")?.unwrap_or(false),
+        with_script: bool_key(entry, "with_script")?.unwrap_or(false),
+        pragma: string_key(entry, "pragma")?.unwrap_or_else(|| defaults.pragma.clone()),
+        license: string_key(entry, "license")?.unwrap_or_else(|| defaults.license.clone()),
+        name,
+    })
+}
+
+/// Build the `ContractType` from `type`, `upgradeable` and `extensions`
+fn contract_type(entry: &Table, name: &str, defaults: &Defaults) -> Result<ContractType> {
+    let upgradeable = bool_key(entry, "upgradeable")?.unwrap_or(false);
+    let extensions = string_array_key(entry, "extensions")?.unwrap_or_default();
+
+    let base_type = match (string_key(entry, "type")?.as_deref().unwrap_or("basic"), upgradeable) {
+        ("basic", false) => ContractType::Basic,
+        ("erc20", false) => ContractType::ERC20,
+        ("erc20", true) => ContractType::ERC20Upgradeable,
+        ("erc721", false) => ContractType::ERC721,
+        ("erc721", true) => ContractType::ERC721Upgradeable,
+        ("erc1155", false) => ContractType::ERC1155,
+        ("erc1155", true) => ContractType::ERC1155Upgradeable,
+        ("basic", true) => {
+            return Err(invalid(format!("`{}`: `upgradeable` needs a token `type`", name)));
+        }
+        (other, _) => {
+            return Err(invalid(format!(
+                "`{}` has unknown type `{}` (expected basic, erc20, erc721 or erc1155)",
+                name, other
+            )));
+        }
+    };
+
+    if extensions.is_empty() {
+        return Ok(base_type);
+    }
+    if base_type == ContractType::Basic {
+        return Err(invalid(format!("`{}`: `extensions` need a token `type`", name)));
+    }
+
+    Ok(ContractType::MultiInheritance {
+        base_type: Box::new(base_type),
+        extensions: defaults.extensions.parse(&extensions)?,
+    })
+}
+
+fn check_keys(table: &Table, allowed: &[&str], context: &str) -> Result<()> {
+    match table.keys().find(|key| !allowed.contains(&key.as_str())) {
+        Some(key) => Err(invalid(format!("unknown key `{}` in {}", key, context))),
+        None => Ok(()),
+    }
+}
+
+fn string_key(table: &Table, key: &str) -> Result<Option<String>> {
+    match table.get(key) {
+        Some(value) => value
+            .as_str()
+            .map(|s| Some(s.to_string()))
+            .ok_or_else(|| invalid_type(key, "a string")),
+        None => Ok(None),
+    }
+}
+
+fn bool_key(table: &Table, key: &str) -> Result<Option<bool>> {
+    match table.get(key) {
+        Some(value) => value.as_bool().map(Some).ok_or_else(|| invalid_type(key, "true or false")),
+        None => Ok(None),
+    }
+}
+
+fn string_array_key(table: &Table, key: &str) -> Result<Option<Vec<String>>> {
+    let Some(value) = table.get(key) else {
+        return Ok(None);
+    };
+
+    value
+        .as_array()
+        .and_then(|values| {
+            values
+                .iter()
+                .map(|v| v.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+        })
+        .map(Some)
+        .ok_or_else(|| invalid_type(key, "an array of strings"))
+}
+
+fn invalid(message: String) -> GramrError {
+    GramrError::InvalidConfig(format!("{}: {}", MANIFEST_FILE, message))
+}
+
+fn invalid_type(key: &str, expected: &str) -> GramrError {
+    invalid(format!("`{}` must be {}", key, expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::foundry::testing::StubForge;
+    use crate::templates::TokenExtension;
+    use tempfile::TempDir;
+
+    const MANIFEST: &str = r#"
+[project]
+pragma = "0.8.26"
+license = "MIT"
+
+[[resources]]
+name = "Token"
+type = "erc20"
+extensions = ["burnable", "permit"]
+with_test = true
+
+[[resources]]
+kind = "library"
+name = "MathUtils"
+license = "Apache-2.0"
+"#;
+
+    #[test]
+    fn test_parse_applies_project_defaults() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+
+        assert_eq!(manifest.language, Language::Solidity);
+        assert_eq!(manifest.resources.len(), 2);
+
+        let token = &manifest.resources[0];
+        assert_eq!(token.kind, ResourceKind::Contract);
+        assert_eq!(
+            token.contract_type,
+            ContractType::MultiInheritance {
+                base_type: Box::new(ContractType::ERC20),
+                extensions: vec![TokenExtension::ERC20Burnable, TokenExtension::ERC20Permit],
+            }
+        );
+        assert!(token.with_test);
+        assert!(!token.with_script);
+        assert_eq!(token.pragma, "0.8.26");
+        assert_eq!(token.license, "MIT");
+
+        let library = &manifest.resources[1];
+        assert_eq!(library.kind, ResourceKind::Library);
+        assert_eq!(library.contract_type, ContractType::Basic);
+        assert_eq!(library.license, "Apache-2.0");
+    }
+
+    #[test]
+    fn test_project_table_overrides_configured_defaults() {
+        let defaults = Defaults {
+            pragma: "0.8.24".to_string(),
+            license: "GPL-3.0".to_string(),
+            author: Some("Acme Labs".to_string()),
+            ..Defaults::default()
+        };
+        let manifest = Manifest::parse_with_defaults(
+            "[project]\nlicense = \"MIT\"\n\n[[resources]]\nname = \"Token\"\n",
+            defaults,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.resources[0].pragma, "0.8.24");
+        assert_eq!(manifest.resources[0].license, "MIT");
+        assert_eq!(manifest.defaults.author.as_deref(), Some("Acme Labs"));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_resources() {
+        let cases = [
+            ("[[resources]]\nname = \"A\"\nkind = \"widget\"\n", "unknown kind"),
+            ("[[resources]]\nname = \"A\"\ntype = \"erc4337\"\n", "unknown type"),
+            ("[[resources]]\nname = \"A\"\nupgradeable = true\n", "needs a token"),
+            ("[[resources]]\nkind = \"library\"\nname = \"A\"\nwith_test = true\n", "unknown key `with_test`"),
+            ("[[resources]]\nname = \"A\"\n\n[[resources]]\nname = \"A\"\n", "more than once"),
+            ("[project]\nlanguage = \"rust-stylus\"\n\n[[resources]]\nkind = \"script\"\nname = \"A\"\n", "not supported"),
+            ("[[resources]]\ntype = \"erc20\"\n", "needs a `name`"),
+        ];
+
+        for (content, expected) in cases {
+            let err = Manifest::parse(content).unwrap_err().to_string();
+            assert!(err.contains(expected), "{:?} gave {:?}", content, err);
+        }
+    }
+
+    #[test]
+    fn test_generate_only_writes_missing_files() {
+        let forge = StubForge::install();
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("foundry.toml"), "[profile.default]\n").unwrap();
+        let project = || ProjectType::Foundry(forge.project(temp_dir.path()));
+
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+        let token = &manifest.resources[0];
+        fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        fs::write(temp_dir.path().join("src/Token.sol"), "// hand-written").unwrap();
+
+        let plan = manifest.plan(token, project()).unwrap();
+        assert_eq!(plan.files.iter().filter(|file| file.is_new()).count(), 1);
+
+        manifest.generate(token, project()).unwrap();
+        assert_eq!(fs::read_to_string(temp_dir.path().join("src/Token.sol")).unwrap(), "// hand-written");
+        assert!(temp_dir.path().join("test/Token.t.sol").exists());
+        assert_eq!(forge.calls().len(), 1);
+
+        let plan = manifest.plan(token, project()).unwrap();
+        assert!(plan.files.iter().all(|file| !file.is_new()));
+    }
+}
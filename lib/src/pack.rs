@@ -0,0 +1,400 @@
+//! Template packs: named sets of template overrides installed into a local cache.
+//!
+//! A pack is a directory (or git repository) laid out like `.gramr/templates/`,
+//! with a `pack.toml` at its root declaring what it covers:
+//!
+//! ```toml
+//! name = "acme"
+//! description = "Acme house style"
+//! resources = ["contract", "library", "interface"]
+//! contract_types = ["basic", "erc20", "erc721"]
+//! ```
+//!
+//! `gramr template add` copies a pack into the cache, and `--template-pack acme`
+//! (or `template_pack = "acme"` in `.gramr.toml`) renders from it. Templates the
+//! pack does not provide fall back to the user overrides and then the built-ins.
+
+use crate::error::{GramrError, Result};
+use crate::manifest::ResourceKind;
+use crate::templates::{ContractType, Templates};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use toml::Table;
+
+/// Manifest at the root of every pack
+pub const PACK_MANIFEST: &str = "pack.toml";
+
+const KEYS: &[&str] = &["name", "description", "resources", "contract_types"];
+const CONTRACT_TYPES: &[&str] = &["basic", "erc20", "erc721", "erc1155"];
+/// Directories of a pack that hold templates; anything else (README, LICENSE) is ignored
+const TEMPLATE_DIRS: &[&str] = &["solidity", "stylus"];
+
+/// An installed (or about to be installed) template pack
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplatePack {
+    pub name: String,
+    pub description: Option<String>,
+    /// Kinds of resource the pack's templates are written for
+    pub resources: Vec<ResourceKind>,
+    /// Contract `type`s (as in `gramr.toml`) the pack supports; upgradeable and
+    /// extended tokens count as their base type
+    pub contract_types: Vec<String>,
+    /// Directory holding `pack.toml` and the templates
+    pub path: PathBuf,
+}
+
+impl TemplatePack {
+    /// Read and check the pack in `dir`, including that every template in it compiles
+    pub fn load(dir: &Path) -> Result<Self> {
+        let manifest_path = dir.join(PACK_MANIFEST);
+        if !manifest_path.is_file() {
+            return Err(GramrError::InvalidConfig(format!(
+                "{} is not a template pack: {} not found",
+                dir.display(),
+                PACK_MANIFEST
+            )));
+        }
+
+        let source = manifest_path.display().to_string();
+        let invalid = |message: String| GramrError::InvalidConfig(format!("{}: {}", source, message));
+
+        let table: Table = fs::read_to_string(&manifest_path)?
+            .parse()
+            .map_err(|e| invalid(format!("{}", e)))?;
+        if let Some(key) = table.keys().find(|key| !KEYS.contains(&key.as_str())) {
+            return Err(invalid(format!("unknown key `{}`", key)));
+        }
+
+        let name = match table.get("name").map(|name| name.as_str()) {
+            Some(Some(name)) if is_valid_name(name) => name.to_string(),
+            Some(Some(name)) => {
+                return Err(invalid(format!(
+                    "invalid pack name `{}` (use letters, digits, `-` and `_`)",
+                    name
+                )))
+            }
+            Some(None) => return Err(invalid("`name` must be a string".to_string())),
+            None => return Err(invalid("missing `name`".to_string())),
+        };
+        let description = match table.get("description") {
+            Some(description) => Some(
+                description
+                    .as_str()
+                    .ok_or_else(|| invalid("`description` must be a string".to_string()))?
+                    .to_string(),
+            ),
+            None => None,
+        };
+
+        let resources = string_array(&table, "resources").map_err(invalid)?;
+        if resources.is_empty() {
+            return Err(invalid("`resources` must list at least one resource kind".to_string()));
+        }
+        let resources = resources
+            .iter()
+            .map(|kind| ResourceKind::parse(kind).ok_or_else(|| invalid(format!("unknown resource kind `{}`", kind))))
+            .collect::<Result<Vec<_>>>()?;
+
+        let contract_types = string_array(&table, "contract_types").map_err(invalid)?;
+        if let Some(unknown) = contract_types.iter().find(|t| !CONTRACT_TYPES.contains(&t.as_str())) {
+            return Err(invalid(format!(
+                "unknown contract type `{}` (expected basic, erc20, erc721 or erc1155)",
+                unknown
+            )));
+        }
+        if resources.contains(&ResourceKind::Contract) && contract_types.is_empty() {
+            return Err(invalid("`contract_types` is required for packs that cover contracts".to_string()));
+        }
+
+        check_templates(dir)?;
+        Templates::load(&[dir.to_path_buf()])?;
+
+        Ok(Self {
+            name,
+            description,
+            resources,
+            contract_types,
+            path: dir.to_path_buf(),
+        })
+    }
+
+    /// Install the pack at `source`, a local directory or a git URL, into `cache_dir`,
+    /// replacing any installed pack of the same name
+    pub fn install(source: &str, cache_dir: &Path) -> Result<Self> {
+        fs::create_dir_all(cache_dir)?;
+        let staging = cache_dir.join(format!(".incoming-{}", std::process::id()));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+
+        let result = fetch(source, &staging).and_then(|_| Self::load(&staging)).and_then(|pack| {
+            let target = cache_dir.join(&pack.name);
+            if target.exists() {
+                fs::remove_dir_all(&target)?;
+            }
+            fs::rename(&staging, &target)?;
+            Ok(Self { path: target, ..pack })
+        });
+
+        if staging.exists() {
+            let _ = fs::remove_dir_all(&staging);
+        }
+        result
+    }
+
+    /// The installed pack called `name`
+    pub fn find(name: &str, cache_dir: &Path) -> Result<Self> {
+        let dir = cache_dir.join(name);
+        if !is_valid_name(name) || !dir.join(PACK_MANIFEST).is_file() {
+            return Err(GramrError::Other(format!(
+                "Template pack `{}` is not installed. Install it with `gramr template add <git-url|path>`",
+                name
+            )));
+        }
+        Self::load(&dir)
+    }
+
+    /// Every pack installed in `cache_dir`, by name
+    pub fn list(cache_dir: &Path) -> Result<Vec<Self>> {
+        if !cache_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut packs = Vec::new();
+        for entry in fs::read_dir(cache_dir)? {
+            let path = entry?.path();
+            if path.join(PACK_MANIFEST).is_file() {
+                packs.push(Self::load(&path)?);
+            }
+        }
+        packs.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(packs)
+    }
+
+    /// `packs` in `$XDG_CACHE_HOME/gramr`, or `~/.cache/gramr` when it is unset.
+    pub fn cache_dir() -> Option<PathBuf> {
+        let cache_dir = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+
+        Some(cache_dir.join("gramr").join("packs"))
+    }
+
+    /// Error unless the pack covers `kind`, and for contracts, the base type of `contract_type`
+    pub fn check_supports(&self, kind: ResourceKind, contract_type: Option<&ContractType>) -> Result<()> {
+        if !self.resources.contains(&kind) {
+            return Err(GramrError::Other(format!(
+                "Template pack `{}` does not support {} resources",
+                self.name,
+                kind.as_str()
+            )));
+        }
+
+        if let Some(contract_type) = contract_type.filter(|_| kind == ResourceKind::Contract) {
+            let type_name = contract_type.type_name();
+            if !self.contract_types.iter().any(|t| t == type_name) {
+                return Err(GramrError::Other(format!(
+                    "Template pack `{}` does not support {} contracts (supported: {})",
+                    self.name,
+                    type_name,
+                    self.contract_types.join(", ")
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn string_array(table: &Table, key: &str) -> std::result::Result<Vec<String>, String> {
+    let Some(value) = table.get(key) else {
+        return Ok(Vec::new());
+    };
+    value
+        .as_array()
+        .and_then(|items| items.iter().map(|item| item.as_str().map(str::to_string)).collect())
+        .ok_or_else(|| format!("`{}` must be an array of strings", key))
+}
+
+/// Every file under the template directories must replace a known template
+fn check_templates(dir: &Path) -> Result<()> {
+    for template_dir in TEMPLATE_DIRS {
+        let template_dir = dir.join(template_dir);
+        if !template_dir.is_dir() {
+            continue;
+        }
+
+        for entry in fs::read_dir(&template_dir)? {
+            let path = entry?.path();
+            let name = path
+                .strip_prefix(dir)
+                .map(|name| name.to_string_lossy().replace('\\', "/"))
+                .unwrap_or_default();
+            if !Templates::names().any(|known| known == name) {
+                return Err(GramrError::InvalidConfig(format!(
+                    "{}: unknown template `{}`",
+                    dir.display(),
+                    name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Copy a local pack, or clone a git one, into `dest`
+fn fetch(source: &str, dest: &Path) -> Result<()> {
+    let local = Path::new(source);
+    if local.is_dir() {
+        return copy_dir(local, dest);
+    }
+
+    let output = Command::new("git")
+        .args(["clone", "--quiet", "--depth", "1", source])
+        .arg(dest)
+        .output()
+        .map_err(|e| GramrError::Other(format!("Failed to run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(GramrError::Other(format!(
+            "Failed to clone template pack from {}: {}",
+            source,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    fs::remove_dir_all(dest.join(".git"))?;
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_name() == ".git" {
+            continue;
+        }
+
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_pack(dir: &Path, manifest: &str) {
+        fs::create_dir_all(dir.join("solidity")).unwrap();
+        fs::write(dir.join(PACK_MANIFEST), manifest).unwrap();
+        fs::write(dir.join("solidity/erc20.sol"), "// acme {{ name }}").unwrap();
+        fs::write(dir.join("README.md"), "Acme house style").unwrap();
+    }
+
+    const ACME: &str = "name = \"acme\"\nresources = [\"contract\"]\ncontract_types = [\"erc20\"]\n";
+
+    #[test]
+    fn test_install_from_local_path() {
+        let source = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        write_pack(source.path(), ACME);
+
+        let pack = TemplatePack::install(&source.path().display().to_string(), cache.path()).unwrap();
+
+        assert_eq!(pack.name, "acme");
+        assert_eq!(pack.path, cache.path().join("acme"));
+        assert!(pack.path.join("solidity/erc20.sol").is_file());
+        assert_eq!(TemplatePack::find("acme", cache.path()).unwrap(), pack);
+        assert_eq!(TemplatePack::list(cache.path()).unwrap(), vec![pack.clone()]);
+
+        let templates = Templates::load(std::slice::from_ref(&pack.path)).unwrap();
+        let rendered = templates
+            .render("solidity/erc20.sol", minijinja::context! { name => "Token" })
+            .unwrap();
+        assert_eq!(rendered, "// acme Token");
+    }
+
+    #[test]
+    fn test_install_from_file_git_repository() {
+        let source = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        write_pack(source.path(), ACME);
+
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=gramr", "-c", "user.email=gramr@example.com"])
+                .args(args)
+                .current_dir(source.path())
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        git(&["init", "--quiet"]);
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", "Add pack"]);
+
+        let url = format!("file://{}", source.path().display());
+        let pack = TemplatePack::install(&url, cache.path()).unwrap();
+
+        assert_eq!(pack.name, "acme");
+        assert!(pack.path.join("solidity/erc20.sol").is_file());
+        assert!(!pack.path.join(".git").exists());
+    }
+
+    #[test]
+    fn test_rejects_invalid_packs() {
+        let cases = [
+            ("resources = [\"contract\"]", "missing `name`"),
+            ("name = \"acme\"\nresources = [\"widget\"]", "unknown resource kind `widget`"),
+            ("name = \"acme\"\nresources = [\"contract\"]", "`contract_types` is required"),
+            ("name = \"a/b\"\nresources = [\"library\"]", "invalid pack name"),
+        ];
+
+        for (manifest, expected) in cases {
+            let source = TempDir::new().unwrap();
+            let cache = TempDir::new().unwrap();
+            write_pack(source.path(), manifest);
+
+            let err = TemplatePack::install(&source.path().display().to_string(), cache.path())
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains(expected), "{:?} gave {:?}", manifest, err);
+            assert_eq!(fs::read_dir(cache.path()).unwrap().count(), 0);
+        }
+
+        let source = TempDir::new().unwrap();
+        write_pack(source.path(), ACME);
+        fs::write(source.path().join("solidity/erc20.sl"), "").unwrap();
+        let err = TemplatePack::load(source.path()).unwrap_err().to_string();
+        assert!(err.contains("unknown template `solidity/erc20.sl`"), "{}", err);
+    }
+
+    #[test]
+    fn test_check_supports() {
+        let source = TempDir::new().unwrap();
+        write_pack(source.path(), ACME);
+        let pack = TemplatePack::load(source.path()).unwrap();
+
+        let extended = ContractType::MultiInheritance {
+            base_type: Box::new(ContractType::ERC20Upgradeable),
+            extensions: vec![],
+        };
+        assert!(pack.check_supports(ResourceKind::Contract, Some(&extended)).is_ok());
+
+        let err = pack.check_supports(ResourceKind::Contract, Some(&ContractType::ERC721)).unwrap_err();
+        assert!(err.to_string().contains("does not support erc721 contracts"));
+        assert!(pack.check_supports(ResourceKind::Library, None).is_err());
+    }
+}
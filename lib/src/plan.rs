@@ -0,0 +1,337 @@
+//! Planned changes, so generated output can be previewed before it is written.
+
+use crate::changeset::Changeset;
+use crate::error::{GramrError, Result};
+use crate::prompt;
+use colored::*;
+use similar::TextDiff;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal};
+use std::path::{Path, PathBuf};
+
+/// A file a generator intends to write
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedFile {
+    /// What the file is, e.g. `contract` or `test`
+    pub label: &'static str,
+    pub path: PathBuf,
+    pub content: String,
+    /// Current content of the file, if it already exists
+    pub existing: Option<String>,
+    /// Updates the file in place, e.g. to declare a module, so an existing
+    /// file is expected rather than a conflict
+    pub edit: bool,
+}
+
+impl PlannedFile {
+    /// Plan writing `content` to `path`, recording whatever is there now
+    pub fn new(label: &'static str, path: PathBuf, content: String) -> Self {
+        let existing = fs::read_to_string(&path).ok();
+        Self {
+            label,
+            path,
+            content,
+            existing,
+            edit: false,
+        }
+    }
+
+    /// Plan updating `path` to `content`, which may or may not exist yet
+    pub fn edit(label: &'static str, path: PathBuf, content: String) -> Self {
+        Self {
+            edit: true,
+            ..Self::new(label, path, content)
+        }
+    }
+
+    pub fn is_new(&self) -> bool {
+        self.existing.is_none()
+    }
+
+    pub fn is_unchanged(&self) -> bool {
+        self.existing.as_deref() == Some(self.content.as_str())
+    }
+
+    /// Path relative to the working directory when the file is inside it
+    pub fn display_path(&self) -> String {
+        std::env::current_dir()
+            .ok()
+            .and_then(|cwd| self.path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+            .unwrap_or_else(|| self.path.clone())
+            .display()
+            .to_string()
+    }
+
+    /// Unified diff from the current file (or nothing) to the planned content
+    pub fn diff(&self) -> String {
+        let path = self.display_path();
+        let (old_prefix, new_prefix) = if Path::new(&path).is_absolute() { ("", "") } else { ("a/", "b/") };
+        let old = self.existing.as_deref().unwrap_or("");
+        let old_header = if self.is_new() { "/dev/null".to_string() } else { format!("{}{}", old_prefix, path) };
+
+        TextDiff::from_lines(old, self.content.as_str())
+            .unified_diff()
+            .context_radius(3)
+            .header(&old_header, &format!("{}{}", new_prefix, path))
+            .to_string()
+    }
+}
+
+/// What generators do when a file they would write already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Refuse to generate anything
+    #[default]
+    Error,
+    /// Replace existing files
+    Force,
+    /// Ask before replacing each existing file; declining cancels generation
+    Prompt,
+    /// Keep existing files and write only the new ones
+    SkipExisting,
+}
+
+/// Everything a generator would do, without having done any of it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GenerationPlan {
+    pub files: Vec<PlannedFile>,
+    /// Dependencies that would be installed first, e.g. `OpenZeppelin contracts`
+    pub dependencies: Vec<String>,
+}
+
+impl GenerationPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_file(&mut self, label: &'static str, path: PathBuf, content: String) {
+        self.files.push(PlannedFile::new(label, path, content));
+    }
+
+    pub fn add_edit(&mut self, label: &'static str, path: PathBuf, content: String) {
+        self.files.push(PlannedFile::edit(label, path, content));
+    }
+
+    /// Planned files that would replace an existing file
+    pub fn existing_files(&self) -> impl Iterator<Item = &PlannedFile> {
+        self.files.iter().filter(|file| !file.is_new() && !file.edit)
+    }
+
+    /// Settle every file that already exists according to `policy`, before
+    /// anything is written, so a refusal leaves the project untouched.
+    ///
+    /// [`OverwritePolicy::Prompt`] asks on the terminal.
+    pub fn resolve_conflicts(self, policy: OverwritePolicy) -> Result<Self> {
+        let stdin = io::stdin();
+        let interactive = stdin.is_terminal();
+        self.resolve_conflicts_from(policy, &mut stdin.lock(), interactive)
+    }
+
+    /// Like [`GenerationPlan::resolve_conflicts`], reading prompt answers from `input`
+    pub fn resolve_conflicts_from(
+        mut self,
+        policy: OverwritePolicy,
+        input: &mut impl BufRead,
+        interactive: bool,
+    ) -> Result<Self> {
+        let existing: Vec<String> = self.existing_files().map(PlannedFile::display_path).collect();
+        if existing.is_empty() {
+            return Ok(self);
+        }
+
+        match policy {
+            OverwritePolicy::Error => {
+                return Err(GramrError::FileExists(format!(
+                    "{} (use --force to overwrite or --skip-existing to keep existing files)",
+                    existing.join(", ")
+                )));
+            }
+            OverwritePolicy::Force => {}
+            OverwritePolicy::Prompt => {
+                for path in existing {
+                    if !prompt::confirm(input, interactive, &format!("Overwrite {}?", path), false) {
+                        return Err(GramrError::FileExists(format!("{} (overwrite declined)", path)));
+                    }
+                }
+            }
+            OverwritePolicy::SkipExisting => {
+                for file in self.existing_files() {
+                    println!("{} Skipped existing {}: {}", "!".yellow(), file.label, file.display_path());
+                }
+                self.files.retain(|file| file.is_new() || file.edit);
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Resolve conflicts with `policy`, then write what is left.
+    ///
+    /// Returns the files that were written.
+    pub fn apply_with(self, policy: OverwritePolicy) -> Result<Self> {
+        let plan = self.resolve_conflicts(policy)?;
+        plan.apply()?;
+        Ok(plan)
+    }
+
+    /// Write every planned file as one [`Changeset`], creating parent directories as needed
+    pub fn apply(&self) -> Result<()> {
+        Changeset::stage(self)?.commit()
+    }
+
+    /// Print what `apply` would do, with a diff for every file
+    pub fn print_dry_run(&self) {
+        println!("{} Dry run: no files will be written\n", "→".yellow());
+
+        for dependency in &self.dependencies {
+            println!("{} Would install {}", "→".yellow(), dependency);
+        }
+
+        for file in &self.files {
+            let action = if file.is_new() {
+                "create".green()
+            } else if file.is_unchanged() {
+                "unchanged".dimmed()
+            } else if file.edit {
+                "update".cyan()
+            } else {
+                "overwrite".yellow()
+            };
+            println!("{} {}: {}", action, file.label, file.display_path());
+        }
+
+        for file in self.files.iter().filter(|file| !file.is_unchanged()) {
+            println!();
+            for line in file.diff().lines() {
+                if line.starts_with("+++") || line.starts_with("---") {
+                    println!("{}", line.bold());
+                } else if line.starts_with('+') {
+                    println!("{}", line.green());
+                } else if line.starts_with('-') {
+                    println!("{}", line.red());
+                } else if line.starts_with("@@") {
+                    println!("{}", line.cyan());
+                } else {
+                    println!("{}", line);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_new_file_diff_adds_every_line() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Token.sol");
+        let file = PlannedFile::new("contract", path, "line one\nline two\n".to_string());
+
+        assert!(file.is_new());
+        let diff = file.diff();
+        assert!(diff.contains("--- /dev/null"));
+        assert!(diff.contains("+line one\n+line two\n"));
+    }
+
+    #[test]
+    fn test_existing_file_diff() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("Token.sol");
+        fs::write(&path, "keep\nold\n").unwrap();
+
+        let file = PlannedFile::new("contract", path.clone(), "keep\nnew\n".to_string());
+        assert!(!file.is_new());
+        assert!(!file.is_unchanged());
+
+        let diff = file.diff();
+        assert!(diff.contains(&format!("--- {}", path.display())));
+        assert!(diff.contains("-old\n+new\n"));
+        assert!(diff.contains(" keep\n"));
+
+        let same = PlannedFile::new("contract", path, "keep\nold\n".to_string());
+        assert!(same.is_unchanged());
+        assert!(!same.diff().contains("@@"));
+    }
+
+    #[test]
+    fn test_apply_creates_parent_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut plan = GenerationPlan::new();
+        plan.add_file("interface", temp_dir.path().join("src/interfaces/IToken.sol"), "interface IToken {}".to_string());
+
+        assert_eq!(plan.existing_files().count(), 0);
+        plan.apply().unwrap();
+
+        let written = fs::read_to_string(temp_dir.path().join("src/interfaces/IToken.sol")).unwrap();
+        assert_eq!(written, "interface IToken {}");
+    }
+
+    fn plan_with_existing_file(temp_dir: &TempDir) -> GenerationPlan {
+        fs::write(temp_dir.path().join("Token.sol"), "// hand-written").unwrap();
+
+        let mut plan = GenerationPlan::new();
+        plan.add_file("contract", temp_dir.path().join("Token.sol"), "contract Token {}".to_string());
+        plan.add_file("test", temp_dir.path().join("Token.t.sol"), "contract TokenTest {}".to_string());
+        plan
+    }
+
+    #[test]
+    fn test_error_policy_writes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = plan_with_existing_file(&temp_dir).apply_with(OverwritePolicy::Error);
+
+        assert!(matches!(result, Err(GramrError::FileExists(msg)) if msg.contains("Token.sol")));
+        assert_eq!(fs::read_to_string(temp_dir.path().join("Token.sol")).unwrap(), "// hand-written");
+        assert!(!temp_dir.path().join("Token.t.sol").exists());
+    }
+
+    #[test]
+    fn test_prompt_policy_follows_the_answer() {
+        let temp_dir = TempDir::new().unwrap();
+        let resolve = |input: &str, interactive: bool| {
+            plan_with_existing_file(&temp_dir).resolve_conflicts_from(
+                OverwritePolicy::Prompt,
+                &mut input.as_bytes(),
+                interactive,
+            )
+        };
+
+        assert_eq!(resolve("y\n", true).unwrap().files.len(), 2);
+        assert!(matches!(resolve("n\n", true), Err(GramrError::FileExists(msg)) if msg.contains("overwrite declined")));
+        assert!(matches!(resolve("\n", true), Err(GramrError::FileExists(_))));
+        assert!(matches!(resolve("y\n", false), Err(GramrError::FileExists(_))));
+        assert!(!temp_dir.path().join("Token.t.sol").exists());
+    }
+
+    #[test]
+    fn test_force_and_skip_existing_policies() {
+        let temp_dir = TempDir::new().unwrap();
+        let written = plan_with_existing_file(&temp_dir).apply_with(OverwritePolicy::SkipExisting).unwrap();
+
+        assert_eq!(written.files.len(), 1);
+        assert_eq!(fs::read_to_string(temp_dir.path().join("Token.sol")).unwrap(), "// hand-written");
+        assert_eq!(fs::read_to_string(temp_dir.path().join("Token.t.sol")).unwrap(), "contract TokenTest {}");
+
+        let written = plan_with_existing_file(&temp_dir).apply_with(OverwritePolicy::Force).unwrap();
+        assert_eq!(written.files.len(), 2);
+        assert_eq!(fs::read_to_string(temp_dir.path().join("Token.sol")).unwrap(), "contract Token {}");
+    }
+
+    #[test]
+    fn test_edits_to_existing_files_are_not_conflicts() {
+        let temp_dir = TempDir::new().unwrap();
+        let lib_rs = temp_dir.path().join("lib.rs");
+        fs::write(&lib_rs, "mod a;\n").unwrap();
+
+        let mut plan = GenerationPlan::new();
+        plan.add_edit("module", lib_rs.clone(), "mod a;\npub mod b;\n".to_string());
+        assert_eq!(plan.existing_files().count(), 0);
+
+        let written = plan.apply_with(OverwritePolicy::SkipExisting).unwrap();
+        assert_eq!(written.files.len(), 1);
+        assert_eq!(fs::read_to_string(lib_rs).unwrap(), "mod a;\npub mod b;\n");
+    }
+}
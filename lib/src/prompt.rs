@@ -0,0 +1,61 @@
+use std::io::{self, BufRead, Write};
+
+/// Ask a yes/no question, reading the answer from `input`.
+///
+/// Returns `default` without prompting when `interactive` is false, so
+/// generation keeps working in scripts and CI; callers pass whether stdin is
+/// a terminal. An empty, unreadable or unrecognized answer also gives `default`.
+pub fn confirm(input: &mut impl BufRead, interactive: bool, question: &str, default: bool) -> bool {
+    if !interactive {
+        return default;
+    }
+
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    print!("{} {} ", question, hint);
+    if io::stdout().flush().is_err() {
+        return default;
+    }
+
+    let mut answer = String::new();
+    if input.read_line(&mut answer).is_err() {
+        return default;
+    }
+
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirm_answers() {
+        let cases = [
+            ("y\n", false, true),
+            ("Yes\n", false, true),
+            ("n\n", true, false),
+            ("NO\n", true, false),
+            ("\n", true, true),
+            ("\n", false, false),
+            ("maybe\n", false, false),
+            ("", true, true),
+        ];
+
+        for (answer, default, expected) in cases {
+            assert_eq!(confirm(&mut answer.as_bytes(), true, "Overwrite?", default), expected, "{:?}", answer);
+        }
+    }
+
+    #[test]
+    fn test_confirm_does_not_read_when_not_interactive() {
+        let mut input = "y\n".as_bytes();
+
+        assert!(!confirm(&mut input, false, "Overwrite?", false));
+        assert!(confirm(&mut input, false, "Overwrite?", true));
+        assert_eq!(input, b"y\n");
+    }
+}
@@ -0,0 +1,1001 @@
+//! What a Cargo crate needs before `cargo stylus` can build it, checked and set up
+//! with plain file edits so it works without network access
+
+use crate::error::{GramrError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table, Value};
+
+/// stylus-sdk release OpenZeppelin Stylus 0.3 builds against
+pub const STYLUS_SDK_VERSION: &str = "=0.9.0";
+
+/// alloy-primitives release stylus-sdk pins; `#[storage]` expands to paths in it
+pub const ALLOY_PRIMITIVES_VERSION: &str = "=0.8.20";
+
+/// ruint releases stylus-sdk 0.9 builds with; from 1.17 `to_le_bytes` rejects its
+/// 32-byte encoding of narrow integers at compile time
+pub const RUINT_VERSION: &str = ">=1.12, <1.17";
+
+/// OpenZeppelin Stylus release gramr pins when it adds the contracts
+pub const OPENZEPPELIN_STYLUS_VERSION: &str = "=0.3.0";
+
+/// motsu release the generated unit tests are written for
+pub const MOTSU_VERSION: &str = "0.10";
+
+/// Dev-dependencies of the generated unit tests: name, version and features.
+/// motsu turns on alloy-primitives' `arbitrary` derives, which only build with
+/// arbitrary's `derive` feature
+pub const MOTSU_DEPENDENCIES: &[(&str, &str, &[&str])] =
+    &[("motsu", MOTSU_VERSION, &[]), ("arbitrary", "1", &["derive"])];
+
+/// Dev-dependencies of the generated deploy example: name, version and features
+pub const DEPLOY_DEPENDENCIES: &[(&str, &str, &[&str])] = &[
+    ("alloy", "1", &[]),
+    ("brotli", "8", &[]),
+    ("dotenvy", "0.15", &[]),
+    ("eyre", "0.6", &[]),
+    ("tokio", "1", &["macros", "rt-multi-thread"]),
+];
+
+/// Toolchain pinned in `rust-toolchain.toml`
+pub const RUST_TOOLCHAIN: &str = "1.88.0";
+
+/// Directory of the ERC-1967 proxy crate upgradeable contracts are deployed behind
+pub const PROXY_DIR: &str = "proxy";
+
+/// `src/main.rs` as `cargo new` writes it, safe to replace with the ABI exporter
+const CARGO_NEW_MAIN: &str = "fn main() {\n    println!(\"Hello, world!\");\n}\n";
+
+/// Something a Stylus crate needs that a plain Cargo crate may lack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prerequisite {
+    /// `stylus-sdk` under `[dependencies]`
+    StylusSdk,
+    /// `alloy-primitives` and a compatible `ruint` under `[dependencies]`; the storage
+    /// macros refer to the former
+    AlloyPrimitives,
+    /// A `rust-version` matching the pinned toolchain with the MSRV-aware resolver,
+    /// so dependency resolution picks releases that toolchain can build
+    RustVersion,
+    /// An `export-abi` feature that enables `stylus-sdk/export-abi`
+    ExportAbiFeature,
+    /// `crate-type = ["lib", "cdylib"]` under `[lib]`
+    CrateType,
+    /// `rust-toolchain.toml` pinning the toolchain and the wasm target
+    Toolchain,
+    /// `src/lib.rs`, the crate root contracts are registered in
+    LibRoot,
+    /// `src/main.rs` printing the ABI when built with `export-abi`
+    AbiExporter,
+}
+
+impl Prerequisite {
+    pub const ALL: [Prerequisite; 8] = [
+        Prerequisite::StylusSdk,
+        Prerequisite::AlloyPrimitives,
+        Prerequisite::RustVersion,
+        Prerequisite::ExportAbiFeature,
+        Prerequisite::CrateType,
+        Prerequisite::Toolchain,
+        Prerequisite::LibRoot,
+        Prerequisite::AbiExporter,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Prerequisite::StylusSdk => "stylus-sdk dependency",
+            Prerequisite::AlloyPrimitives => "alloy-primitives and ruint dependencies",
+            Prerequisite::RustVersion => "rust-version and resolver",
+            Prerequisite::ExportAbiFeature => "export-abi feature",
+            Prerequisite::CrateType => "lib and cdylib crate types",
+            Prerequisite::Toolchain => "rust-toolchain.toml",
+            Prerequisite::LibRoot => "src/lib.rs",
+            Prerequisite::AbiExporter => "src/main.rs ABI exporter",
+        }
+    }
+}
+
+/// The Cargo.toml, toolchain file and crate roots of the crate at `root`
+pub struct StylusCrate {
+    root: PathBuf,
+}
+
+impl StylusCrate {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Create a Stylus-ready crate named `name` at `root`, which must not hold a
+    /// Cargo.toml yet; files already there are left alone
+    pub fn init(root: &Path, name: &str) -> Result<Self> {
+        validate_crate_name(name)?;
+        let manifest = root.join("Cargo.toml");
+        if manifest.exists() {
+            return Err(GramrError::FileExists(format!(
+                "{} (run gramr from that crate instead; missing Stylus setup is added when contracts are generated)",
+                manifest.display()
+            )));
+        }
+
+        fs::create_dir_all(root.join("src"))?;
+        let stylus_crate = Self::new(root);
+        let files = [
+            (manifest, cargo_toml(name)),
+            (root.join("rust-toolchain.toml"), rust_toolchain()),
+            (root.join(".gitignore"), "/target\n.env\n".to_string()),
+            (stylus_crate.lib_rs_path(), lib_rs(name)),
+            (stylus_crate.main_rs_path(), main_rs(name)),
+        ];
+        for (path, content) in files {
+            if !path.exists() {
+                fs::write(&path, content)?;
+            }
+        }
+
+        Ok(stylus_crate)
+    }
+
+    /// The files `repair` and the dependency installs may rewrite
+    pub fn setup_files(&self) -> Vec<PathBuf> {
+        vec![
+            self.cargo_toml_path(),
+            self.root.join("rust-toolchain.toml"),
+            self.lib_rs_path(),
+            self.main_rs_path(),
+        ]
+    }
+
+    fn cargo_toml_path(&self) -> PathBuf {
+        self.root.join("Cargo.toml")
+    }
+
+    fn lib_rs_path(&self) -> PathBuf {
+        self.root.join("src").join("lib.rs")
+    }
+
+    fn main_rs_path(&self) -> PathBuf {
+        self.root.join("src").join("main.rs")
+    }
+
+    fn read_manifest(&self) -> Result<DocumentMut> {
+        let content = fs::read_to_string(self.cargo_toml_path())?;
+        content
+            .parse::<DocumentMut>()
+            .map_err(|e| GramrError::InvalidConfig(format!("Failed to parse Cargo.toml: {}", e)))
+    }
+
+    fn write_manifest(&self, manifest: &DocumentMut) -> Result<()> {
+        fs::write(self.cargo_toml_path(), manifest.to_string())?;
+        Ok(())
+    }
+
+    /// The `[package]` name, which the ABI exporter calls into
+    pub fn package_name(&self) -> Result<String> {
+        self.read_manifest()?
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(Item::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| {
+                GramrError::ProjectNotFound(
+                    "Cargo.toml has no [package] name. Please run from the Stylus crate's directory, not a workspace root."
+                        .to_string(),
+                )
+            })
+    }
+
+    /// The prerequisites the crate lacks, in the order `repair` sets them up
+    pub fn missing(&self) -> Result<Vec<Prerequisite>> {
+        let manifest = self.read_manifest()?;
+        Ok(Prerequisite::ALL
+            .into_iter()
+            .filter(|prerequisite| !self.is_met(*prerequisite, &manifest))
+            .collect())
+    }
+
+    fn is_met(&self, prerequisite: Prerequisite, manifest: &DocumentMut) -> bool {
+        match prerequisite {
+            Prerequisite::StylusSdk => manifest
+                .get("dependencies")
+                .and_then(|dependencies| dependencies.get("stylus-sdk"))
+                .is_some(),
+            Prerequisite::AlloyPrimitives => manifest
+                .get("dependencies")
+                .is_some_and(|dependencies| {
+                    dependencies.get("alloy-primitives").is_some() && dependencies.get("ruint").is_some()
+                }),
+            Prerequisite::RustVersion => manifest
+                .get("package")
+                .and_then(|package| package.get("rust-version"))
+                .is_some(),
+            Prerequisite::ExportAbiFeature => string_array(manifest, "features", "export-abi")
+                .is_some_and(|entries| entries.contains(&"stylus-sdk/export-abi".to_string())),
+            Prerequisite::CrateType => string_array(manifest, "lib", "crate-type").is_some_and(|types| {
+                types.contains(&"lib".to_string()) && types.contains(&"cdylib".to_string())
+            }),
+            Prerequisite::Toolchain => {
+                self.root.join("rust-toolchain.toml").exists() || self.root.join("rust-toolchain").exists()
+            }
+            Prerequisite::LibRoot => self.lib_rs_path().exists(),
+            Prerequisite::AbiExporter => fs::read_to_string(self.main_rs_path())
+                .is_ok_and(|content| content.contains("export-abi")),
+        }
+    }
+
+    /// Set up whatever the crate lacks and return what was added. A `src/main.rs`
+    /// other than the `cargo new` placeholder is never overwritten.
+    pub fn repair(&self) -> Result<Vec<Prerequisite>> {
+        let missing = self.missing()?;
+        if missing.is_empty() {
+            return Ok(missing);
+        }
+
+        let name = self.package_name()?;
+        let main_path = self.main_rs_path();
+        if missing.contains(&Prerequisite::AbiExporter)
+            && fs::read_to_string(&main_path).is_ok_and(|content| content != CARGO_NEW_MAIN)
+        {
+            return Err(GramrError::Other(format!(
+                "{} does not export the ABI; replace it with a main that calls `{}::print_from_args()` under the export-abi feature",
+                main_path.display(),
+                name.replace('-', "_")
+            )));
+        }
+
+        let mut manifest = self.read_manifest()?;
+        for prerequisite in &missing {
+            match prerequisite {
+                Prerequisite::StylusSdk => {
+                    set_dependency(&mut manifest, "dependencies", "stylus-sdk", STYLUS_SDK_VERSION)?
+                }
+                Prerequisite::AlloyPrimitives => {
+                    for (name, version) in [("alloy-primitives", ALLOY_PRIMITIVES_VERSION), ("ruint", RUINT_VERSION)] {
+                        if manifest.get("dependencies").and_then(|dependencies| dependencies.get(name)).is_none() {
+                            set_dependency(&mut manifest, "dependencies", name, version)?;
+                        }
+                    }
+                }
+                Prerequisite::RustVersion => {
+                    let package = table_mut(&mut manifest, "package")?;
+                    package["rust-version"] = toml_edit::value(RUST_TOOLCHAIN);
+                    if package.get("resolver").is_none() {
+                        package["resolver"] = toml_edit::value("3");
+                    }
+                }
+                Prerequisite::ExportAbiFeature => {
+                    push_string(&mut manifest, "features", "export-abi", "stylus-sdk/export-abi")?
+                }
+                Prerequisite::CrateType => {
+                    push_string(&mut manifest, "lib", "crate-type", "lib")?;
+                    push_string(&mut manifest, "lib", "crate-type", "cdylib")?;
+                }
+                Prerequisite::Toolchain => fs::write(self.root.join("rust-toolchain.toml"), rust_toolchain())?,
+                Prerequisite::LibRoot => {
+                    fs::create_dir_all(self.root.join("src"))?;
+                    fs::write(self.lib_rs_path(), lib_rs(&name))?;
+                }
+                Prerequisite::AbiExporter => fs::write(&main_path, main_rs(&name))?,
+            }
+        }
+        self.write_manifest(&manifest)?;
+
+        Ok(missing)
+    }
+
+    /// The edits that declare the module in `file`, a `.rs` file under `src/`, all
+    /// the way from `lib.rs` down, as the new content of each file that needs one.
+    /// A missing `lib.rs` starts from the crate root `repair` would write.
+    pub fn module_declarations(&self, file: &Path) -> Result<Vec<(PathBuf, String)>> {
+        let src_dir = self.root.join("src");
+        let modules = self.modules(file)?;
+
+        let mut edits = Vec::new();
+        let mut parent_dir = src_dir.clone();
+        for (depth, module) in modules.iter().enumerate() {
+            let parent = if depth == 0 {
+                self.lib_rs_path()
+            } else if parent_dir.with_extension("rs").exists() {
+                parent_dir.with_extension("rs")
+            } else {
+                parent_dir.join("mod.rs")
+            };
+            let source = match fs::read_to_string(&parent) {
+                Ok(source) => source,
+                Err(_) if depth == 0 => lib_rs(&self.package_name()?),
+                Err(_) => String::new(),
+            };
+            if let Some(updated) = declare_module(&source, module) {
+                edits.push((parent, updated));
+            }
+            parent_dir = parent_dir.join(module);
+        }
+        Ok(edits)
+    }
+
+    /// The path code refers to the module in `file` by, e.g. `crate::interfaces::vault`
+    pub fn module_path(&self, file: &Path) -> Result<String> {
+        Ok(format!("crate::{}", self.modules(file)?.join("::")))
+    }
+
+    /// The modules from the crate root down to `file`, a `.rs` file under `src/`
+    fn modules(&self, file: &Path) -> Result<Vec<String>> {
+        let src_dir = self.root.join("src");
+        let relative = file.strip_prefix(&src_dir).map_err(|_| {
+            GramrError::Other(format!("{} is outside {}", file.display(), src_dir.display()))
+        })?;
+        Ok(relative
+            .with_extension("")
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect())
+    }
+
+    /// The contract files declared from `lib.rs` whose storage struct is marked
+    /// `#[entrypoint]`, in declaration order. A crate deploys exactly one.
+    pub fn entrypoint_contracts(&self) -> Result<Vec<PathBuf>> {
+        let lib_rs = fs::read_to_string(self.lib_rs_path()).unwrap_or_default();
+        Ok(lib_rs
+            .lines()
+            .filter_map(declared_module)
+            .map(|module| self.root.join("src").join(format!("{}.rs", module)))
+            .filter(|path| fs::read_to_string(path).is_ok_and(|source| has_entrypoint(&source)))
+            .collect())
+    }
+
+    /// The files of the proxy crate in [`PROXY_DIR`] that don't exist yet, with
+    /// `lib_rs` as its crate root. The proxy is its own crate because a crate
+    /// deploys a single `#[entrypoint]`; every upgradeable contract here shares it.
+    pub fn proxy_files(&self, lib_rs: String) -> Result<Vec<(PathBuf, String)>> {
+        let name = format!("{}-proxy", self.package_name()?);
+        let mut manifest = cargo_toml(&name)
+            .parse::<DocumentMut>()
+            .map_err(|e| GramrError::Other(format!("Failed to build the proxy's Cargo.toml: {}", e)))?;
+        set_openzeppelin(&mut manifest)?;
+
+        let proxy = Self::new(self.root.join(PROXY_DIR));
+        Ok([
+            (proxy.cargo_toml_path(), manifest.to_string()),
+            (proxy.root.join("rust-toolchain.toml"), rust_toolchain()),
+            (proxy.root.join(".gitignore"), "/target\n".to_string()),
+            (proxy.lib_rs_path(), lib_rs),
+            (proxy.main_rs_path(), main_rs(&name)),
+        ]
+        .into_iter()
+        .filter(|(path, _)| !path.exists())
+        .collect())
+    }
+
+    /// Add `openzeppelin-stylus`, enabling its ABI export along with the crate's:
+    /// off wasm it only builds with `export-abi` or motsu's test VM
+    pub fn add_openzeppelin(&self) -> Result<()> {
+        let mut manifest = self.read_manifest()?;
+        set_openzeppelin(&mut manifest)?;
+        self.write_manifest(&manifest)
+    }
+
+    /// Add `name = "version"` under `[table]` unless the crate already has it
+    pub fn add_dependency(&self, table: &str, name: &str, version: &str) -> Result<()> {
+        let mut manifest = self.read_manifest()?;
+        if manifest.get(table).and_then(|dependencies| dependencies.get(name)).is_none() {
+            set_dependency(&mut manifest, table, name, version)?;
+            self.write_manifest(&manifest)?;
+        }
+        Ok(())
+    }
+
+    /// Whether `[table]` lists `name`
+    pub fn has_dependency(&self, table: &str, name: &str) -> Result<bool> {
+        Ok(self.read_manifest()?.get(table).and_then(|dependencies| dependencies.get(name)).is_some())
+    }
+
+    /// The [`DEPLOY_DEPENDENCIES`] missing from `[dev-dependencies]`
+    pub fn missing_deploy_dependencies(&self) -> Result<Vec<&'static str>> {
+        self.missing_dev_dependencies(DEPLOY_DEPENDENCIES)
+    }
+
+    /// Add the missing [`DEPLOY_DEPENDENCIES`] to `[dev-dependencies]`
+    pub fn add_deploy_dependencies(&self) -> Result<()> {
+        self.add_dev_dependencies(DEPLOY_DEPENDENCIES)
+    }
+
+    /// Add the missing [`MOTSU_DEPENDENCIES`] to `[dev-dependencies]`
+    pub fn add_motsu_dependencies(&self) -> Result<()> {
+        self.add_dev_dependencies(MOTSU_DEPENDENCIES)
+    }
+
+    fn missing_dev_dependencies(&self, dependencies: &[(&'static str, &str, &[&str])]) -> Result<Vec<&'static str>> {
+        let manifest = self.read_manifest()?;
+        let dev_dependencies = manifest.get("dev-dependencies");
+        Ok(dependencies
+            .iter()
+            .map(|(name, _, _)| *name)
+            .filter(|name| dev_dependencies.and_then(|table| table.get(name)).is_none())
+            .collect())
+    }
+
+    fn add_dev_dependencies(&self, dependencies: &[(&'static str, &str, &[&str])]) -> Result<()> {
+        let missing = self.missing_dev_dependencies(dependencies)?;
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let mut manifest = self.read_manifest()?;
+        for (name, version, features) in dependencies.iter().filter(|(name, _, _)| missing.contains(name)) {
+            if features.is_empty() {
+                set_dependency(&mut manifest, "dev-dependencies", name, version)?;
+            } else {
+                let mut dependency = InlineTable::new();
+                dependency.insert("version", (*version).into());
+                dependency.insert("features", Value::Array(features.iter().copied().collect()));
+                table_mut(&mut manifest, "dev-dependencies")?[*name] = toml_edit::value(dependency);
+            }
+        }
+        self.write_manifest(&manifest)
+    }
+}
+
+/// The snake_case module name for a CamelCase item name, e.g. `IERC20Permit` to `ierc20_permit`
+pub fn module_name(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut module = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if previous.is_lowercase() || previous.is_ascii_digit() || (previous.is_uppercase() && next_is_lower) {
+                module.push('_');
+            }
+        }
+        module.extend(c.to_lowercase());
+    }
+    module
+}
+
+/// `source` with `pub mod module;` added after its last top-level module
+/// declaration, or `None` when it already declares `module`
+pub fn declare_module(source: &str, module: &str) -> Option<String> {
+    let lines: Vec<&str> = source.lines().collect();
+    if lines.iter().any(|line| declared_module(line) == Some(module)) {
+        return None;
+    }
+
+    let declaration = format!("pub mod {};", module);
+    let updated = match lines.iter().rposition(|line| declared_module(line).is_some()) {
+        Some(index) => {
+            let mut lines = lines;
+            lines.insert(index + 1, &declaration);
+            lines.join("\n") + "\n"
+        }
+        None if source.trim().is_empty() => declaration + "\n",
+        None => format!("{}\n\n{}\n", source.trim_end(), declaration),
+    };
+    Some(updated)
+}
+
+/// `source`, the crate root, re-exporting the `print_from_args` that `#[entrypoint]`
+/// generates in `module` for `main.rs` to export the ABI with, or `None` when it
+/// already exports one
+pub fn export_abi(source: &str, module: &str) -> Option<String> {
+    if source.contains("print_from_args") {
+        return None;
+    }
+    Some(format!(
+        "{}
+
+#[cfg(feature = \"export-abi\")]\npub use {}::print_from_args;\n",
+        source.trim_end(),
+        module
+    ))
+}
+
+/// The module a top-level `mod name;` or `mod name {` line declares
+fn declared_module(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix("pub(crate) ").or_else(|| line.strip_prefix("pub ")).unwrap_or(line);
+    let rest = rest.strip_prefix("mod ")?.trim_start();
+    let end = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+    let after = rest[end..].trim_start();
+    (end > 0 && (after.starts_with(';') || after.starts_with('{'))).then(|| &rest[..end])
+}
+
+/// `existing` with the paragraphs of `addition` it lacks appended, or `None` when it
+/// has them all. Paragraphs are separated by blank lines, and one is there when each
+/// of its entries is, comparing `KEY=value` lines by key and other lines whole;
+/// comments don't count. Keeps `.env.example` and `.gitignore` additions idempotent.
+pub fn merge_paragraphs(existing: &str, addition: &str) -> Option<String> {
+    let key = |line: &str| line.split('=').next().unwrap_or(line).trim().to_string();
+    let entries = |text: &str| -> Vec<String> {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(key)
+            .collect()
+    };
+    let present = entries(existing);
+
+    let missing: Vec<&str> = addition
+        .split("\n\n")
+        .map(|paragraph| paragraph.trim_matches('\n'))
+        .filter(|paragraph| entries(paragraph).iter().any(|entry| !present.contains(entry)))
+        .collect();
+    if missing.is_empty() {
+        return None;
+    }
+
+    let mut merged = existing.trim_end().to_string();
+    for paragraph in missing {
+        if !merged.is_empty() {
+            merged.push_str("\n\n");
+        }
+        merged.push_str(paragraph);
+    }
+    merged.push('\n');
+    Some(merged)
+}
+
+/// Whether the Stylus contract in `source` is marked `#[entrypoint]`
+pub fn has_entrypoint(source: &str) -> bool {
+    source.lines().any(|line| line.trim() == "#[entrypoint]")
+}
+
+/// `source` without its `#[entrypoint]` attribute, or `None` when it has none
+pub fn remove_entrypoint(source: &str) -> Option<String> {
+    if !has_entrypoint(source) {
+        return None;
+    }
+    let mut updated: String = source
+        .lines()
+        .filter(|line| line.trim() != "#[entrypoint]")
+        .collect::<Vec<_>>()
+        .join("\n");
+    if source.ends_with('\n') {
+        updated.push('\n');
+    }
+    Some(updated)
+}
+
+/// `source`, the file of Stylus contract `contract`, built on the abstract contract
+/// `base` from `module` (e.g. `crate::abstracts::pausable_base`): the struct embeds
+/// it with `#[borrow]`, the `#[public]` impl inherits it and `I<base>` is implemented.
+/// `None` when the contract already implements `I<base>`.
+pub fn inherit_abstract(source: &str, contract: &str, base: &str, module: &str) -> Result<Option<String>> {
+    let trait_name = format!("I{}", base);
+    if source.contains(&format!("impl {} for {}", trait_name, contract)) {
+        return Ok(None);
+    }
+    let missing = |what: &str| GramrError::Other(format!("Could not find {} of {} to add {} to", what, contract, base));
+    let mut lines: Vec<String> = source.lines().map(str::to_string).collect();
+    let field = module_name(base);
+
+    // Storage: embed the base at the end of the struct
+    let struct_start = lines
+        .iter()
+        .position(|line| {
+            let line = line.strip_prefix("pub ").unwrap_or(line);
+            line.strip_prefix("struct ").is_some_and(|rest| rest.trim_end() == format!("{} {{", contract))
+        })
+        .ok_or_else(|| missing("the storage struct"))?;
+    let struct_end = struct_start
+        + lines[struct_start..].iter().position(|line| line == "}").ok_or_else(|| missing("the end of the storage struct"))?;
+    lines.splice(struct_end..struct_end, [
+        "    #[borrow]".to_string(),
+        format!("    {}: {},", field, base),
+    ]);
+
+    // Router: add the base to the `#[public]` impl's `#[inherit]`
+    let impl_line = lines
+        .iter()
+        .position(|line| line.trim_end() == format!("impl {} {{", contract))
+        .ok_or_else(|| missing("the #[public] impl"))?;
+    let attributes = (0..impl_line).rev().take_while(|&i| lines[i].starts_with("#[")).collect::<Vec<_>>();
+    if !attributes.iter().any(|&i| lines[i] == "#[public]") {
+        return Err(missing("the #[public] impl"));
+    }
+    match attributes.iter().find(|&&i| lines[i].starts_with("#[inherit(")) {
+        Some(&i) => {
+            let inherited = lines[i].trim_end().trim_end_matches(")]").to_string();
+            lines[i] = format!("{}, {})]", inherited, base);
+        }
+        None => lines.insert(impl_line, format!("#[inherit({})]", base)),
+    }
+
+    // Imports: after the last top-level `use`, which may span several lines
+    let last_use = lines.iter().rposition(|line| line.starts_with("use ")).ok_or_else(|| missing("the imports"))?;
+    let use_end = last_use + lines[last_use..].iter().position(|line| line.trim_end().ends_with(';')).unwrap_or(0);
+    lines.insert(use_end + 1, format!("use {}::{{{}, {}}};", module, base, trait_name));
+
+    let mut updated = lines.join("\n");
+    updated.push_str(&format!(
+        "\n\nimpl {trait_name} for {contract} {{\n    fn {field}(&self) -> &{base} {{\n        &self.{field}\n    }}\n\n    fn {field}_mut(&mut self) -> &mut {base} {{\n        &mut self.{field}\n    }}\n}}\n"
+    ));
+    Ok(Some(updated))
+}
+
+/// Cargo package names: ASCII letters, digits, `-` and `_`, not starting with a digit
+fn validate_crate_name(name: &str) -> Result<()> {
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(GramrError::InvalidConfig(format!(
+            "'{}' is not a valid crate name; use letters, digits, '-' and '_', starting with a letter",
+            name
+        )))
+    }
+}
+
+/// The `[table]` section, created when missing; an inline `table = { ... }` is
+/// rejected rather than rewritten
+fn table_mut<'a>(manifest: &'a mut DocumentMut, table: &str) -> Result<&'a mut Table> {
+    manifest
+        .entry(table)
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_mut()
+        .ok_or_else(|| {
+            GramrError::InvalidConfig(format!(
+                "Cargo.toml's {} is not a [{}] table; write it as one so gramr can edit it",
+                table, table
+            ))
+        })
+}
+
+fn set_dependency(manifest: &mut DocumentMut, table: &str, name: &str, version: &str) -> Result<()> {
+    table_mut(manifest, table)?[name] = toml_edit::value(version);
+    Ok(())
+}
+
+fn set_openzeppelin(manifest: &mut DocumentMut) -> Result<()> {
+    if manifest.get("dependencies").and_then(|dependencies| dependencies.get("openzeppelin-stylus")).is_none() {
+        set_dependency(manifest, "dependencies", "openzeppelin-stylus", OPENZEPPELIN_STYLUS_VERSION)?;
+    }
+    push_string(manifest, "features", "export-abi", "openzeppelin-stylus/export-abi")
+}
+
+fn string_array(manifest: &DocumentMut, table: &str, key: &str) -> Option<Vec<String>> {
+    let array = manifest.get(table)?.get(key)?.as_array()?;
+    Some(array.iter().filter_map(|entry| entry.as_str().map(str::to_string)).collect())
+}
+
+/// Append `entry` to the string array `[table] key`, creating either as needed
+fn push_string(manifest: &mut DocumentMut, table: &str, key: &str, entry: &str) -> Result<()> {
+    let table = table_mut(manifest, table)?;
+    if !table.get(key).is_some_and(|item| item.is_array()) {
+        table[key] = toml_edit::value(Array::new());
+    }
+    let array = table[key].as_array_mut().expect("checked above");
+    if !array.iter().any(|existing| existing.as_str() == Some(entry)) {
+        array.push(entry);
+    }
+    Ok(())
+}
+
+fn cargo_toml(name: &str) -> String {
+    format!(
+        r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+rust-version = "{RUST_TOOLCHAIN}"
+resolver = "3"
+
+[dependencies]
+stylus-sdk = "{STYLUS_SDK_VERSION}"
+alloy-primitives = "{ALLOY_PRIMITIVES_VERSION}"
+ruint = "{RUINT_VERSION}"
+
+[features]
+export-abi = ["stylus-sdk/export-abi"]
+
+[lib]
+crate-type = ["lib", "cdylib"]
+
+[profile.release]
+codegen-units = 1
+strip = true
+lto = true
+panic = "abort"
+opt-level = "s"
+"#
+    )
+}
+
+fn rust_toolchain() -> String {
+    format!(
+        "[toolchain]\nchannel = \"{}\"\ntargets = [\"wasm32-unknown-unknown\"]\n",
+        RUST_TOOLCHAIN
+    )
+}
+
+fn lib_rs(name: &str) -> String {
+    format!(
+        r#"//! {name}: Arbitrum Stylus contracts
+#![cfg_attr(not(any(test, feature = "export-abi")), no_main)]
+
+extern crate alloc;
+"#
+    )
+}
+
+fn main_rs(name: &str) -> String {
+    format!(
+        r#"#![cfg_attr(not(any(test, feature = "export-abi")), no_main)]
+
+#[cfg(not(any(test, feature = "export-abi")))]
+#[no_mangle]
+pub extern "C" fn main() {{}}
+
+#[cfg(feature = "export-abi")]
+fn main() {{
+    {}::print_from_args();
+}}
+"#,
+        name.replace('-', "_")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn plain_crate() -> (TempDir, StylusCrate) {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"my-token\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nserde = \"1\" # keep me\n",
+        )
+        .unwrap();
+        fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        fs::write(temp_dir.path().join("src/main.rs"), CARGO_NEW_MAIN).unwrap();
+        let stylus_crate = StylusCrate::new(temp_dir.path());
+        (temp_dir, stylus_crate)
+    }
+
+    #[test]
+    fn test_init_creates_a_complete_crate() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("my-token");
+
+        let stylus_crate = StylusCrate::init(&root, "my-token").unwrap();
+
+        assert!(stylus_crate.missing().unwrap().is_empty());
+        assert_eq!(stylus_crate.package_name().unwrap(), "my-token");
+        let main_rs = fs::read_to_string(root.join("src/main.rs")).unwrap();
+        assert!(main_rs.contains("my_token::print_from_args();"));
+        assert!(fs::read_to_string(root.join("rust-toolchain.toml"))
+            .unwrap()
+            .contains("wasm32-unknown-unknown"));
+    }
+
+    #[test]
+    fn test_init_refuses_an_existing_crate_and_bad_names() {
+        let (temp_dir, _) = plain_crate();
+
+        assert!(matches!(
+            StylusCrate::init(temp_dir.path(), "my-token"),
+            Err(GramrError::FileExists(_))
+        ));
+        assert!(matches!(
+            StylusCrate::init(&temp_dir.path().join("x"), "1token"),
+            Err(GramrError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_repair_sets_up_a_plain_crate() {
+        let (temp_dir, stylus_crate) = plain_crate();
+        assert_eq!(stylus_crate.missing().unwrap(), Prerequisite::ALL.to_vec());
+
+        assert_eq!(stylus_crate.repair().unwrap(), Prerequisite::ALL.to_vec());
+
+        assert!(stylus_crate.missing().unwrap().is_empty());
+        let manifest = fs::read_to_string(temp_dir.path().join("Cargo.toml")).unwrap();
+        assert!(manifest.contains("serde = \"1\" # keep me"));
+        assert!(manifest.contains(&format!("stylus-sdk = \"{}\"", STYLUS_SDK_VERSION)));
+        assert!(manifest.contains(&format!("alloy-primitives = \"{}\"", ALLOY_PRIMITIVES_VERSION)));
+        assert!(manifest.contains(&format!("ruint = \"{}\"", RUINT_VERSION)));
+        assert!(manifest.contains(&format!("rust-version = \"{}\"\nresolver = \"3\"", RUST_TOOLCHAIN)));
+        assert!(manifest.contains("export-abi = [\"stylus-sdk/export-abi\"]"));
+        assert!(manifest.contains("crate-type = [\"lib\", \"cdylib\"]"));
+        assert!(temp_dir.path().join("src/lib.rs").exists());
+        assert!(stylus_crate.repair().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_repair_extends_existing_entries() {
+        let (temp_dir, stylus_crate) = plain_crate();
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"t\"\n\n[dependencies]\nstylus-sdk = \"0.9.0\"\n\n[features]\nexport-abi = [\"other/export-abi\"]\n\n[lib]\ncrate-type = [\"cdylib\"]\n",
+        )
+        .unwrap();
+
+        stylus_crate.repair().unwrap();
+
+        let manifest = fs::read_to_string(temp_dir.path().join("Cargo.toml")).unwrap();
+        assert!(manifest.contains("stylus-sdk = \"0.9.0\""));
+        assert!(manifest.contains("export-abi = [\"other/export-abi\", \"stylus-sdk/export-abi\"]"));
+        assert!(manifest.contains("crate-type = [\"cdylib\", \"lib\"]"));
+    }
+
+    #[test]
+    fn test_inline_sections_are_rejected_by_name() {
+        let (temp_dir, stylus_crate) = plain_crate();
+        let inline = "dependencies = { serde = \"1\" }\ndev-dependencies = { tokio = \"1\" }\n\n[package]\nname = \"t\"\n";
+        fs::write(temp_dir.path().join("Cargo.toml"), inline).unwrap();
+
+        match stylus_crate.repair() {
+            Err(GramrError::InvalidConfig(message)) => assert!(message.contains("[dependencies]")),
+            other => panic!("expected an InvalidConfig error, got {:?}", other),
+        }
+        match stylus_crate.add_deploy_dependencies() {
+            Err(GramrError::InvalidConfig(message)) => assert!(message.contains("[dev-dependencies]")),
+            other => panic!("expected an InvalidConfig error, got {:?}", other),
+        }
+        assert_eq!(fs::read_to_string(temp_dir.path().join("Cargo.toml")).unwrap(), inline);
+    }
+
+    #[test]
+    fn test_repair_keeps_a_custom_main() {
+        let (temp_dir, stylus_crate) = plain_crate();
+        fs::write(temp_dir.path().join("src/main.rs"), "fn main() { run(); }\n").unwrap();
+
+        assert!(stylus_crate.repair().is_err());
+        assert!(!temp_dir.path().join("rust-toolchain.toml").exists());
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("src/main.rs")).unwrap(),
+            "fn main() { run(); }\n"
+        );
+    }
+
+    #[test]
+    fn test_module_name() {
+        assert_eq!(module_name("Counter"), "counter");
+        assert_eq!(module_name("MyToken"), "my_token");
+        assert_eq!(module_name("IERC20Permit"), "ierc20_permit");
+        assert_eq!(module_name("ERC721Receiver"), "erc721_receiver");
+    }
+
+    #[test]
+    fn test_export_abi_reexports_the_entrypoint_once() {
+        let exported = export_abi("pub mod token;\n", "crate::token").unwrap();
+        assert_eq!(exported, "pub mod token;\n\n#[cfg(feature = \"export-abi\")]\npub use crate::token::print_from_args;\n");
+        assert_eq!(export_abi(&exported, "crate::vault"), None);
+    }
+
+    #[test]
+    fn test_declare_module_is_idempotent() {
+        let root = "//! Crate\n#![no_main]\n\nextern crate alloc;\n";
+        let declared = declare_module(root, "interfaces").unwrap();
+        assert_eq!(declared, "//! Crate\n#![no_main]\n\nextern crate alloc;\n\npub mod interfaces;\n");
+        assert_eq!(declare_module(&declared, "interfaces"), None);
+
+        let with_modules = "mod token;\npub mod interfaces {}\n\nfn helper() {}\n";
+        assert_eq!(
+            declare_module(with_modules, "counter").unwrap(),
+            "mod token;\npub mod interfaces {}\npub mod counter;\n\nfn helper() {}\n"
+        );
+        assert_eq!(declare_module(with_modules, "token"), None);
+        assert_eq!(declare_module("", "counter").unwrap(), "pub mod counter;\n");
+    }
+
+    #[test]
+    fn test_module_declarations_walk_from_lib_rs() {
+        let (temp_dir, stylus_crate) = plain_crate();
+        let src = temp_dir.path().join("src");
+
+        let edits = stylus_crate.module_declarations(&src.join("interfaces/erc20.rs")).unwrap();
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[0].0, src.join("lib.rs"));
+        assert!(edits[0].1.starts_with(&lib_rs("my-token")));
+        assert!(edits[0].1.ends_with("extern crate alloc;\n\npub mod interfaces;\n"));
+
+        fs::write(src.join("lib.rs"), "pub mod interfaces;\n").unwrap();
+        let edits = stylus_crate.module_declarations(&src.join("interfaces/erc20.rs")).unwrap();
+        assert_eq!(edits, vec![(src.join("interfaces/mod.rs"), "pub mod erc20;\n".to_string())]);
+        assert_eq!(stylus_crate.module_path(&src.join("interfaces/erc20.rs")).unwrap(), "crate::interfaces::erc20");
+    }
+
+    #[test]
+    fn test_contract_modules_and_entrypoints() {
+        let (temp_dir, stylus) = plain_crate();
+        let src = temp_dir.path().join("src");
+        let contract = "#[entrypoint]\n#[storage]\npub struct Token {}\n";
+        fs::write(src.join("token.rs"), contract).unwrap();
+        fs::write(src.join("vault.rs"), contract.replace("Token", "Vault")).unwrap();
+        fs::write(src.join("lib.rs"), "pub mod interfaces;\n").unwrap();
+
+        let edits = stylus.module_declarations(&src.join("token.rs")).unwrap();
+        assert_eq!(edits[0].1, "pub mod interfaces;\npub mod token;\n");
+        assert!(stylus.entrypoint_contracts().unwrap().is_empty());
+
+        fs::write(src.join("lib.rs"), "pub mod vault;\npub mod token;\n").unwrap();
+        assert_eq!(stylus.entrypoint_contracts().unwrap(), vec![src.join("vault.rs"), src.join("token.rs")]);
+        assert_eq!(remove_entrypoint(contract).unwrap(), "#[storage]\npub struct Token {}\n");
+        assert_eq!(remove_entrypoint("#[storage]\npub struct Token {}\n"), None);
+    }
+
+    #[test]
+    fn test_merge_paragraphs_appends_what_is_missing() {
+        let env = "# Node\nRPC_URL=http://localhost:8547\nPRIVATE_KEY=0x01\n";
+        let addition = "# Node\nRPC_URL=http://localhost:8547\nPRIVATE_KEY=0x02\n\n# Token\nTOKEN_INITIAL_SUPPLY=1\n";
+
+        let merged = merge_paragraphs(env, addition).unwrap();
+        assert_eq!(merged, "# Node\nRPC_URL=http://localhost:8547\nPRIVATE_KEY=0x01\n\n# Token\nTOKEN_INITIAL_SUPPLY=1\n");
+        assert_eq!(merge_paragraphs(&merged, addition), None);
+        assert_eq!(merge_paragraphs("/target\n", ".env\n").unwrap(), "/target\n\n.env\n");
+        assert_eq!(merge_paragraphs("", ".env\n").unwrap(), ".env\n");
+    }
+
+    #[test]
+    fn test_deploy_dependencies_keep_existing_entries() {
+        let (temp_dir, stylus) = plain_crate();
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dev-dependencies]\ntokio = \"1.40\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(stylus.missing_deploy_dependencies().unwrap(), vec!["alloy", "brotli", "dotenvy", "eyre"]);
+        stylus.add_deploy_dependencies().unwrap();
+
+        let manifest = fs::read_to_string(temp_dir.path().join("Cargo.toml")).unwrap();
+        assert!(manifest.contains("tokio = \"1.40\"\nalloy = \"1\"\n"));
+        assert!(stylus.missing_deploy_dependencies().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_inherit_abstract_extends_existing_inherit() {
+        let source = "use stylus_sdk::{\n    msg,\n    prelude::*,\n};\nuse alloc::string::String;\n\n#[entrypoint]\n#[storage]\nstruct Token {\n    #[borrow]\n    erc20: Erc20,\n}\n\n#[public]\n#[inherit(Erc20)]\nimpl Token {\n    pub fn mint(&mut self) {}\n}\n";
+
+        let updated = inherit_abstract(source, "Token", "FeeBase", "crate::abstracts::fee_base").unwrap().unwrap();
+
+        assert!(updated.contains("use alloc::string::String;\nuse crate::abstracts::fee_base::{FeeBase, IFeeBase};\n"));
+        assert!(updated.contains("    erc20: Erc20,\n    #[borrow]\n    fee_base: FeeBase,\n}"));
+        assert!(updated.contains("#[public]\n#[inherit(Erc20, FeeBase)]\nimpl Token {"));
+        assert!(updated.ends_with("impl IFeeBase for Token {\n    fn fee_base(&self) -> &FeeBase {\n        &self.fee_base\n    }\n\n    fn fee_base_mut(&mut self) -> &mut FeeBase {\n        &mut self.fee_base\n    }\n}\n"));
+        assert_eq!(inherit_abstract(&updated, "Token", "FeeBase", "crate::abstracts::fee_base").unwrap(), None);
+    }
+
+    #[test]
+    fn test_inherit_abstract_adds_inherit_and_rejects_unknown_contracts() {
+        let source = "use stylus_sdk::prelude::*;\n\n#[entrypoint]\n#[storage]\npub struct Counter {\n    count: StorageU256,\n}\n\n#[public]\nimpl Counter {\n}\n";
+
+        let updated = inherit_abstract(source, "Counter", "Base", "crate::base").unwrap().unwrap();
+
+        assert!(updated.contains("#[public]\n#[inherit(Base)]\nimpl Counter {"));
+        assert!(matches!(inherit_abstract(source, "Other", "Base", "crate::base"), Err(GramrError::Other(_))));
+    }
+
+    #[test]
+    fn test_proxy_files_make_a_stylus_crate_of_their_own() {
+        let (temp_dir, stylus_crate) = plain_crate();
+
+        let files = stylus_crate.proxy_files("// proxy\n".to_string()).unwrap();
+        for (path, content) in &files {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        let proxy = StylusCrate::new(temp_dir.path().join(PROXY_DIR));
+        assert!(proxy.missing().unwrap().is_empty());
+        assert_eq!(proxy.package_name().unwrap(), "my-token-proxy");
+        let manifest = fs::read_to_string(proxy.cargo_toml_path()).unwrap();
+        assert!(manifest.contains("openzeppelin-stylus = \"=0.3.0\""));
+        assert!(manifest.contains("export-abi = [\"stylus-sdk/export-abi\", \"openzeppelin-stylus/export-abi\"]"));
+        assert_eq!(fs::read_to_string(proxy.lib_rs_path()).unwrap(), "// proxy\n");
+        assert!(stylus_crate.proxy_files(String::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_add_dependency_leaves_existing_versions() {
+        let (temp_dir, stylus_crate) = plain_crate();
+
+        stylus_crate.add_dependency("dependencies", "serde", "2").unwrap();
+        stylus_crate.add_dependency("dev-dependencies", "motsu", MOTSU_VERSION).unwrap();
+
+        let manifest = fs::read_to_string(temp_dir.path().join("Cargo.toml")).unwrap();
+        assert!(manifest.contains("serde = \"1\""));
+        assert!(manifest.contains("[dev-dependencies]\nmotsu = \"0.10\""));
+    }
+}
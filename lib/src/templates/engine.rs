@@ -0,0 +1,168 @@
+//! Named templates that generated code is rendered from.
+//!
+//! Every template ships built in, and any one of them can be replaced by a file
+//! with the same name in a template directory, e.g.
+//! `.gramr/templates/solidity/erc20.sol`. Templates use
+//! [MiniJinja](https://docs.rs/minijinja) syntax; the variables each one receives
+//! are listed in the book.
+
+use crate::error::{GramrError, Result};
+use minijinja::{AutoEscape, Environment, UndefinedBehavior, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+/// Project template directory, relative to the project root
+pub const PROJECT_TEMPLATES_DIR: &str = ".gramr/templates";
+
+macro_rules! builtin {
+    ($($name:literal),* $(,)?) => {
+        &[$(($name, include_str!(concat!("../../templates/", $name)))),*]
+    };
+}
+
+const BUILTIN_TEMPLATES: &[(&str, &str)] = builtin![
+    "solidity/header.sol",
+    "solidity/basic.sol",
+    "solidity/erc20.sol",
+    "solidity/erc721.sol",
+    "solidity/erc1155.sol",
+    "solidity/erc20_upgradeable.sol",
+    "solidity/erc721_upgradeable.sol",
+    "solidity/erc1155_upgradeable.sol",
+    "solidity/token.sol",
+    "solidity/library.sol",
+    "solidity/interface.sol",
+    "solidity/abstract.sol",
+    "solidity/test.sol",
+    "solidity/script.sol",
+    "stylus/basic.rs",
+    "stylus/erc20.rs",
+    "stylus/erc721.rs",
+    "stylus/erc1155.rs",
+    "stylus/library.rs",
+    "stylus/interface.rs",
+    "stylus/abstract.rs",
+    "stylus/test.rs",
+    "stylus/script.rs",
+    "stylus/env.example",
+    "stylus/deploy.sh",
+    "stylus/proxy.rs",
+];
+
+/// The set of templates to render from: the built-ins, with any overrides applied
+#[derive(Clone)]
+pub struct Templates {
+    env: Arc<Environment<'static>>,
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl Templates {
+    /// The built-in templates only
+    pub fn builtin() -> Self {
+        static BUILTIN: OnceLock<Templates> = OnceLock::new();
+        BUILTIN
+            .get_or_init(|| Self::load(&[]).expect("built-in templates are valid"))
+            .clone()
+    }
+
+    /// The built-in templates, each replaced by the file of the same name in the
+    /// first of `dirs` that has one
+    pub fn load(dirs: &[PathBuf]) -> Result<Self> {
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+        env.set_auto_escape_callback(|_| AutoEscape::None);
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+
+        for (name, builtin) in BUILTIN_TEMPLATES {
+            let path = dirs.iter().map(|dir| dir.join(name)).find(|path| path.is_file());
+            match path {
+                Some(path) => {
+                    let source = fs::read_to_string(&path)?;
+                    env.add_template_owned(*name, source).map_err(|e| {
+                        GramrError::TemplateError(format!("{}: {}", path.display(), e))
+                    })?;
+                }
+                None => env
+                    .add_template(name, builtin)
+                    .map_err(|e| GramrError::TemplateError(format!("{}: {}", name, e)))?,
+            }
+        }
+
+        Ok(Self { env: Arc::new(env) })
+    }
+
+    /// Names of every template that can be overridden
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        BUILTIN_TEMPLATES.iter().map(|(name, _)| *name)
+    }
+
+    /// Render the template `name` with `context`
+    pub fn render(&self, name: &str, context: Value) -> Result<String> {
+        self.env
+            .get_template(name)
+            .and_then(|template| template.render(context))
+            .map_err(|e| GramrError::TemplateError(e.to_string()))
+    }
+
+    /// Render a one-off template `source`, such as an extension snippet
+    pub fn render_str(&self, source: &str, context: Value) -> Result<String> {
+        self.env
+            .render_str(source, context)
+            .map_err(|e| GramrError::TemplateError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minijinja::context;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_override_replaces_single_template() {
+        let project = TempDir::new().unwrap();
+        let user = TempDir::new().unwrap();
+        for dir in [project.path(), user.path()] {
+            fs::create_dir_all(dir.join("solidity")).unwrap();
+        }
+        fs::write(project.path().join("solidity/basic.sol"), "// project {{ name }}").unwrap();
+        fs::write(user.path().join("solidity/basic.sol"), "// user {{ name }}").unwrap();
+        fs::write(user.path().join("solidity/header.sol"), "// user header").unwrap();
+
+        let templates = Templates::load(&[project.path().to_path_buf(), user.path().to_path_buf()]).unwrap();
+
+        assert_eq!(templates.render("solidity/basic.sol", context! { name => "Token" }).unwrap(), "// project Token");
+        let interface = templates
+            .render("solidity/interface.sol", context! { name => "Token", natspec => "" })
+            .unwrap();
+        assert!(interface.starts_with("// user header\n\ninterface IToken {"));
+    }
+
+    #[test]
+    fn test_reports_broken_override() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("solidity")).unwrap();
+        fs::write(dir.path().join("solidity/erc20.sol"), "contract {{ name }").unwrap();
+
+        let err = Templates::load(&[dir.path().to_path_buf()]).err().unwrap().to_string();
+        assert!(err.contains("solidity/erc20.sol"), "{}", err);
+
+        fs::write(dir.path().join("solidity/erc20.sol"), "contract {{ nmae }} {}").unwrap();
+        let templates = Templates::load(&[dir.path().to_path_buf()]).unwrap();
+        assert!(templates.render("solidity/erc20.sol", context! { name => "Token" }).is_err());
+    }
+
+    #[test]
+    fn test_builtin_templates_compile() {
+        let templates = Templates::builtin();
+        for name in Templates::names() {
+            assert!(templates.env.get_template(name).is_ok(), "{}", name);
+        }
+    }
+}
@@ -1,9 +1,15 @@
+pub mod engine;
 pub mod solidity;
 pub mod stylus;
 
+pub use engine::Templates;
 pub use solidity::SolidityTemplate;
 pub use stylus::StylusTemplate;
 
+use crate::error::{GramrError, Result};
+use crate::extensions::{is_number_literal, ExtensionRegistry};
+use std::{fmt, mem};
+
 // Move these to a common location since they're shared
 #[derive(Clone, Debug, PartialEq)]
 pub enum ContractType {
@@ -22,20 +28,47 @@ pub enum ContractType {
     },
 }
 
+impl ContractType {
+    /// The `gramr.toml` `type` this contract type is built on; upgradeable and
+    /// extended tokens count as their base type
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ContractType::Basic => "basic",
+            ContractType::ERC20 | ContractType::ERC20Upgradeable => "erc20",
+            ContractType::ERC721 | ContractType::ERC721Upgradeable => "erc721",
+            ContractType::ERC1155 | ContractType::ERC1155Upgradeable => "erc1155",
+            ContractType::Interface => "interface",
+            ContractType::Abstract => "abstract",
+            ContractType::MultiInheritance { base_type, .. } => base_type.type_name(),
+        }
+    }
+
+    /// Whether this is a UUPS upgradeable token, with or without extensions
+    pub fn is_upgradeable(&self) -> bool {
+        match self {
+            ContractType::ERC20Upgradeable | ContractType::ERC721Upgradeable | ContractType::ERC1155Upgradeable => true,
+            ContractType::MultiInheritance { base_type, .. } => base_type.is_upgradeable(),
+            _ => false,
+        }
+    }
+}
+
+/// Variants for extensions with parameters carry the values given for them,
+/// empty when the defaults apply
 #[derive(Clone, Debug, PartialEq)]
 pub enum TokenExtension {
     // ERC20 Extensions
     ERC20Permit,
     ERC20Burnable,
-    ERC20Capped,
+    ERC20Capped(Vec<String>),
     ERC20Pausable,
     ERC20Votes,
-    ERC20Wrapper,
-    ERC20FlashMint,
+    ERC20Wrapper(Vec<String>),
+    ERC20FlashMint(Vec<String>),
     ERC20TemporaryApproval,
     ERC20Bridgeable,
     ERC1363,
-    ERC4626,
+    ERC4626(Vec<String>),
     
     // ERC721 Extensions
     ERC721Pausable,
@@ -43,8 +76,8 @@ pub enum TokenExtension {
     ERC721Consecutive,
     ERC721URIStorage,
     ERC721Votes,
-    ERC721Royalty,
-    ERC721Wrapper,
+    ERC721Royalty(Vec<String>),
+    ERC721Wrapper(Vec<String>),
     ERC721Enumerable,
     
     // ERC1155 Extensions
@@ -52,13 +85,421 @@ pub enum TokenExtension {
     ERC1155Burnable,
     ERC1155Supply,
     ERC1155URIStorage,
+
+    /// An extension registered from TOML, by its id
+    Custom(String, Vec<String>),
+}
+
+const BUILTIN_EXTENSIONS: &[(&str, TokenExtension)] = &[
+    ("ERC20Permit", TokenExtension::ERC20Permit),
+    ("ERC20Burnable", TokenExtension::ERC20Burnable),
+    ("ERC20Capped", TokenExtension::ERC20Capped(Vec::new())),
+    ("ERC20Pausable", TokenExtension::ERC20Pausable),
+    ("ERC20Votes", TokenExtension::ERC20Votes),
+    ("ERC20Wrapper", TokenExtension::ERC20Wrapper(Vec::new())),
+    ("ERC20FlashMint", TokenExtension::ERC20FlashMint(Vec::new())),
+    ("ERC20TemporaryApproval", TokenExtension::ERC20TemporaryApproval),
+    ("ERC20Bridgeable", TokenExtension::ERC20Bridgeable),
+    ("ERC1363", TokenExtension::ERC1363),
+    ("ERC4626", TokenExtension::ERC4626(Vec::new())),
+    ("ERC721Pausable", TokenExtension::ERC721Pausable),
+    ("ERC721Burnable", TokenExtension::ERC721Burnable),
+    ("ERC721Consecutive", TokenExtension::ERC721Consecutive),
+    ("ERC721URIStorage", TokenExtension::ERC721URIStorage),
+    ("ERC721Votes", TokenExtension::ERC721Votes),
+    ("ERC721Royalty", TokenExtension::ERC721Royalty(Vec::new())),
+    ("ERC721Wrapper", TokenExtension::ERC721Wrapper(Vec::new())),
+    ("ERC721Enumerable", TokenExtension::ERC721Enumerable),
+    ("ERC1155Pausable", TokenExtension::ERC1155Pausable),
+    ("ERC1155Burnable", TokenExtension::ERC1155Burnable),
+    ("ERC1155Supply", TokenExtension::ERC1155Supply),
+    ("ERC1155URIStorage", TokenExtension::ERC1155URIStorage),
+];
+
+impl TokenExtension {
+    /// Registry id, which for the built-in extensions is the OpenZeppelin contract name
+    pub fn id(&self) -> &str {
+        match self {
+            TokenExtension::Custom(id, _) => id,
+            extension => BUILTIN_EXTENSIONS
+                .iter()
+                .find(|(_, builtin)| mem::discriminant(builtin) == mem::discriminant(extension))
+                .map(|(id, _)| *id)
+                .expect("every built-in extension has an id"),
+        }
+    }
+
+    /// The extension with registry id `id`; ids without a variant become `Custom`
+    pub fn from_id(id: &str) -> Self {
+        BUILTIN_EXTENSIONS
+            .iter()
+            .find(|(builtin, _)| *builtin == id)
+            .map(|(_, extension)| extension.clone())
+            .unwrap_or_else(|| TokenExtension::Custom(id.to_string(), Vec::new()))
+    }
+
+    /// Values given for the extension's parameters, e.g. the cap of
+    /// `capped=1000000e18`; empty when the parameter defaults apply
+    pub fn args(&self) -> &[String] {
+        match self {
+            TokenExtension::ERC20Capped(args)
+            | TokenExtension::ERC20Wrapper(args)
+            | TokenExtension::ERC20FlashMint(args)
+            | TokenExtension::ERC4626(args)
+            | TokenExtension::ERC721Royalty(args)
+            | TokenExtension::ERC721Wrapper(args)
+            | TokenExtension::Custom(_, args) => args,
+            _ => &[],
+        }
+    }
+
+    /// The same extension carrying `args`; extensions without parameters ignore them
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        match &mut self {
+            TokenExtension::ERC20Capped(current)
+            | TokenExtension::ERC20Wrapper(current)
+            | TokenExtension::ERC20FlashMint(current)
+            | TokenExtension::ERC4626(current)
+            | TokenExtension::ERC721Royalty(current)
+            | TokenExtension::ERC721Wrapper(current)
+            | TokenExtension::Custom(_, current) => *current = args,
+            _ => {}
+        }
+        self
+    }
+}
+
+impl fmt::Display for TokenExtension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+/// Major OpenZeppelin Contracts release that generated Solidity targets
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OpenZeppelinVersion {
+    /// 4.x, with `_beforeTokenTransfer` hooks and an argument-less `Ownable`
+    V4,
+    /// 5.x, with `_update` hooks and `Ownable(initialOwner)`
+    #[default]
+    V5,
+}
+
+impl OpenZeppelinVersion {
+    /// Major version of a release such as `4.9.6` or `v5.1.0`
+    pub fn from_release(release: &str) -> Option<Self> {
+        let major = release.trim().trim_start_matches('v').split('.').next()?;
+        match major {
+            "4" => Some(OpenZeppelinVersion::V4),
+            "5" => Some(OpenZeppelinVersion::V5),
+            _ => None,
+        }
+    }
+
+    pub fn major(self) -> u8 {
+        match self {
+            OpenZeppelinVersion::V4 => 4,
+            OpenZeppelinVersion::V5 => 5,
+        }
+    }
+
+    /// Whether the built-in extension exists in this release line
+    pub fn supports(self, extension: &TokenExtension) -> bool {
+        ExtensionRegistry::builtin().get(extension, self).is_some()
+    }
+}
+
+/// How much NatSpec goes in the header above a generated contract, library or interface
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NatSpecStyle {
+    /// `@title`, `@author`, `@notice` and `@dev`
+    #[default]
+    Full,
+    /// `@title` and `@author` only
+    Minimal,
+    /// No header
+    None,
+}
+
+impl NatSpecStyle {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "full" => Some(NatSpecStyle::Full),
+            "minimal" => Some(NatSpecStyle::Minimal),
+            "none" => Some(NatSpecStyle::None),
+            _ => None,
+        }
+    }
+}
+
+/// Who may call the privileged functions (mint, pause, upgrade) of a generated token
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AccessControl {
+    /// A single owner through `Ownable` and `onlyOwner`
+    #[default]
+    Ownable,
+    /// `Ownable2Step`, where a new owner has to accept the transfer
+    Ownable2Step,
+    /// Role-based through `AccessControl`, with a role such as `MINTER_ROLE` per privilege
+    Roles,
+    /// Delegated to an OpenZeppelin `AccessManager` through `AccessManaged` and `restricted`
+    Managed,
+    /// No access control; anyone may mint, and nothing may be paused or upgraded
+    None,
+}
+
+impl AccessControl {
+    pub const ALL: [AccessControl; 5] = [
+        AccessControl::Ownable,
+        AccessControl::Ownable2Step,
+        AccessControl::Roles,
+        AccessControl::Managed,
+        AccessControl::None,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|access| access.name() == name)
+    }
+
+    /// Name accepted by `--access` and `access_control` in config files
+    pub fn name(self) -> &'static str {
+        match self {
+            AccessControl::Ownable => "ownable",
+            AccessControl::Ownable2Step => "ownable2step",
+            AccessControl::Roles => "access-control",
+            AccessControl::Managed => "access-managed",
+            AccessControl::None => "none",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            AccessControl::Ownable => "Single owner (Ownable)",
+            AccessControl::Ownable2Step => "Single owner, transferred in two steps (Ownable2Step)",
+            AccessControl::Roles => "Roles such as MINTER_ROLE and PAUSER_ROLE (AccessControl)",
+            AccessControl::Managed => "Permissions managed by an AccessManager (AccessManaged)",
+            AccessControl::None => "None; anyone may mint",
+        }
+    }
+
+    /// OpenZeppelin contract the generated token inherits, without the `Upgradeable` suffix
+    pub fn contract(self) -> Option<&'static str> {
+        match self {
+            AccessControl::Ownable => Some("Ownable"),
+            AccessControl::Ownable2Step => Some("Ownable2Step"),
+            AccessControl::Roles => Some("AccessControl"),
+            AccessControl::Managed => Some("AccessManaged"),
+            AccessControl::None => None,
+        }
+    }
+
+    /// Whether `contract_type` can be generated with this access control:
+    /// without any, nobody could authorize upgrades or call the functions an
+    /// extension restricts to a role
+    pub fn check(
+        self,
+        contract_type: &ContractType,
+        extensions: &ExtensionRegistry,
+        oz_version: OpenZeppelinVersion,
+    ) -> Result<()> {
+        if self != AccessControl::None {
+            return Ok(());
+        }
+        if contract_type.is_upgradeable() {
+            return Err(GramrError::InvalidConfig(
+                "Upgradeable contracts need access control to authorize upgrades; choose ownable, ownable2step, access-control or access-managed".to_string(),
+            ));
+        }
+        if let ContractType::MultiInheritance { base_type, extensions: requested } = contract_type {
+            for extension in extensions.resolve_all(base_type, requested, oz_version)?.extensions {
+                let spec = extensions.require(&extension, oz_version)?;
+                if let Some(role) = &spec.role {
+                    return Err(GramrError::InvalidConfig(format!(
+                        "`{}` restricts its functions to the {} role, which needs access control; choose ownable, ownable2step, access-control or access-managed, or drop the extension",
+                        spec.name.as_deref().unwrap_or(&spec.id),
+                        role
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for AccessControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// ERC20 supply minted in generated tests and scripts when none is given, in whole tokens
+pub const DEFAULT_INITIAL_SUPPLY: &str = "1000000";
+
+/// Name, symbol, decimals, supply and URI of a generated token. Anything unset
+/// is derived from the contract name or left at the OpenZeppelin default.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TokenMetadata {
+    /// Token name, e.g. `My Token`; the contract name when unset
+    pub name: Option<String>,
+    /// Ticker symbol; up to three capitals of the contract name when unset
+    pub symbol: Option<String>,
+    /// ERC20 decimals; 18 when unset
+    pub decimals: Option<u8>,
+    /// ERC20 supply generated tests and scripts mint to the deployer, in whole tokens
+    pub initial_supply: Option<String>,
+    /// ERC721 base URI or ERC1155 URI
+    pub base_uri: Option<String>,
+}
+
+impl TokenMetadata {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn token_name(&self, contract_name: &str) -> String {
+        self.name.clone().unwrap_or_else(|| contract_name.to_string())
+    }
+
+    /// The configured symbol, or the capitals of `contract_name` (its first
+    /// letters when it has none, so `token` becomes `TOK`)
+    pub fn symbol(&self, contract_name: &str) -> String {
+        if let Some(symbol) = &self.symbol {
+            return symbol.clone();
+        }
+
+        let capitals: String = contract_name.chars().filter(|c| c.is_uppercase()).take(3).collect();
+        if capitals.is_empty() {
+            contract_name
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .take(3)
+                .collect::<String>()
+                .to_uppercase()
+        } else {
+            capitals.to_uppercase()
+        }
+    }
+
+    pub fn decimals(&self) -> u8 {
+        self.decimals.unwrap_or(18)
+    }
+
+    /// Initial supply in base units as a Solidity expression, e.g. `1000000 * 10 ** 18`
+    pub fn initial_supply_expression(&self) -> String {
+        format!(
+            "{} * 10 ** {}",
+            self.initial_supply.as_deref().unwrap_or(DEFAULT_INITIAL_SUPPLY),
+            self.decimals()
+        )
+    }
+
+    /// Initial supply in base units as a plain integer, for tools that take no expressions
+    pub fn initial_supply_base_units(&self) -> String {
+        let supply = self.initial_supply.as_deref().unwrap_or(DEFAULT_INITIAL_SUPPLY);
+        integer_literal(supply, usize::from(self.decimals())).unwrap_or_default()
+    }
+
+    /// Error when `cap`, in base units, is below the initial supply, which the
+    /// generated test and script mint at deployment. `cap` is a number literal
+    /// or an `N * 10 ** D` expression; anything else is not checked.
+    pub fn check_cap(&self, cap: &str) -> Result<()> {
+        let cap_units = match cap.split_once("* 10 **") {
+            Some((mantissa, exponent)) => exponent
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .and_then(|exponent| integer_literal(mantissa.trim(), exponent)),
+            None => integer_literal(cap, 0),
+        };
+        let supply = self.initial_supply_base_units();
+        match cap_units {
+            Some(cap_units) if (cap_units.len(), &cap_units) < (supply.len(), &supply) => Err(GramrError::Other(format!(
+                "Cap `{}` is below the initial supply of {} tokens, so deployment would revert; raise the cap or lower --initial-supply",
+                cap,
+                self.initial_supply.as_deref().unwrap_or(DEFAULT_INITIAL_SUPPLY)
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Error unless every value is well-formed and applies to `contract_type`
+    pub fn check(&self, contract_type: &ContractType) -> Result<()> {
+        let invalid = |message: &str| Err(GramrError::Other(message.to_string()));
+        let base_type = contract_type.type_name();
+        if !matches!(base_type, "erc20" | "erc721" | "erc1155") {
+            return if self.is_empty() {
+                Ok(())
+            } else {
+                invalid("Token name, symbol, decimals, initial supply and base URI need an ERC20, ERC721 or ERC1155 token")
+            };
+        }
+
+        if base_type == "erc1155" && (self.name.is_some() || self.symbol.is_some()) {
+            return invalid("ERC1155 tokens have no name or symbol");
+        }
+        if base_type != "erc20" && (self.decimals.is_some() || self.initial_supply.is_some()) {
+            return invalid("Decimals and initial supply only apply to ERC20 tokens");
+        }
+        if base_type == "erc20" && self.base_uri.is_some() {
+            return invalid("A base URI only applies to ERC721 and ERC1155 tokens");
+        }
+
+        let is_string_literal = |value: &str| !value.contains(['"', '\\', '\n', '\r']);
+        if self.name.as_deref().is_some_and(|name| name.is_empty() || !is_string_literal(name)) {
+            return invalid("Token name must be non-empty and cannot contain quotes, backslashes or line breaks");
+        }
+        if self
+            .symbol
+            .as_deref()
+            .is_some_and(|symbol| symbol.is_empty() || !is_string_literal(symbol) || symbol.contains(char::is_whitespace))
+        {
+            return invalid("Symbol must be non-empty and cannot contain whitespace, quotes or backslashes");
+        }
+        if self.base_uri.as_deref().is_some_and(|uri| !is_string_literal(uri)) {
+            return invalid("Base URI cannot contain quotes, backslashes or line breaks");
+        }
+        if let Some(supply) = &self.initial_supply {
+            if !is_number_literal(supply) {
+                return Err(GramrError::Other(format!(
+                    "Invalid initial supply `{}`: expected a whole number of tokens, e.g. 1000000 or 21e6",
+                    supply
+                )));
+            }
+        }
+        if let ContractType::MultiInheritance { extensions, .. } = contract_type {
+            for extension in extensions {
+                if let TokenExtension::ERC20Capped(args) = extension {
+                    if let Some(cap) = args.first() {
+                        self.check_cap(cap)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A number literal such as `21_000e6`, times `10 ** extra_zeros`, as plain
+/// digits without leading zeros; `None` unless `value` is a number literal
+fn integer_literal(value: &str, extra_zeros: usize) -> Option<String> {
+    if !is_number_literal(value) {
+        return None;
+    }
+    let value = value.replace('_', "");
+    let (mantissa, exponent) = value.split_once(['e', 'E']).unwrap_or((&value, "0"));
+    let mantissa = mantissa.trim_start_matches('0');
+    if mantissa.is_empty() {
+        return Some("0".to_string());
+    }
+    let zeros = exponent.parse::<usize>().ok()? + extra_zeros;
+    Some(format!("{}{}", mantissa, "0".repeat(zeros)))
 }
 
 pub trait Template {
-    fn generate_contract(&self) -> String;
-    fn generate_test(&self) -> String;
-    fn generate_script(&self) -> String;
-    fn generate_library(&self) -> String;
-    fn generate_interface(&self) -> String;
-    fn generate_abstract_contract(&self) -> String;
+    fn generate_contract(&self) -> Result<String>;
+    fn generate_test(&self) -> Result<String>;
+    fn generate_script(&self) -> Result<String>;
+    fn generate_library(&self) -> Result<String>;
+    fn generate_interface(&self) -> Result<String>;
+    fn generate_abstract_contract(&self) -> Result<String>;
 }
\ No newline at end of file
@@ -1,11 +1,26 @@
 
-use super::{ContractType, TokenExtension, Template};
+use super::{AccessControl, ContractType, NatSpecStyle, OpenZeppelinVersion, TokenExtension, TokenMetadata, Template, Templates};
+use crate::error::{GramrError, Result};
+use crate::extensions::ExtensionRegistry;
+use crate::foundry::ImportPaths;
+use minijinja::{context, Value};
 
 pub struct SolidityTemplate {
     contract_name: String,
     contract_type: ContractType,
     pragma: String,
     license: String,
+    test_src_path: String,
+    script_src_path: String,
+    import_paths: ImportPaths,
+    oz_version: OpenZeppelinVersion,
+    natspec: NatSpecStyle,
+    author: Option<String>,
+    templates: Templates,
+    extensions: ExtensionRegistry,
+    metadata: TokenMetadata,
+    access_control: AccessControl,
+    functions: Vec<String>,
 }
 
 impl SolidityTemplate {
@@ -15,715 +30,1018 @@ impl SolidityTemplate {
             contract_type,
             pragma,
             license,
+            test_src_path: "../src".to_string(),
+            script_src_path: "../src".to_string(),
+            import_paths: ImportPaths::default(),
+            oz_version: OpenZeppelinVersion::default(),
+            natspec: NatSpecStyle::default(),
+            author: None,
+            templates: Templates::builtin(),
+            extensions: ExtensionRegistry::builtin(),
+            metadata: TokenMetadata::default(),
+            access_control: AccessControl::default(),
+            functions: Vec::new(),
         }
     }
 
-    pub fn generate_contract(&self) -> String {
-        match &self.contract_type {
-            ContractType::Basic => self.generate_basic_contract(),
-            ContractType::ERC20 => self.generate_erc20_contract(),
-            ContractType::ERC721 => self.generate_erc721_contract(),
-            ContractType::ERC1155 => self.generate_erc1155_contract(),
-            ContractType::ERC20Upgradeable => self.generate_erc20_upgradeable_contract(),
-            ContractType::ERC721Upgradeable => self.generate_erc721_upgradeable_contract(),
-            ContractType::ERC1155Upgradeable => self.generate_erc1155_upgradeable_contract(),
-            ContractType::MultiInheritance { base_type, extensions } => {
-                self.generate_multi_inheritance_contract(base_type, extensions)
-            }
-            ContractType::Interface => self.generate_interface(),
-            ContractType::Abstract => self.generate_abstract_contract(),
-        }
+    /// Set the paths from the test and script directories to the source directory,
+    /// used when generated tests and scripts import the contract
+    pub fn with_src_paths(mut self, from_test: String, from_script: String) -> Self {
+        self.test_src_path = from_test;
+        self.script_src_path = from_script;
+        self
     }
 
-    pub fn generate_test(&self) -> String {
-        let constructor_args = match &self.contract_type {
-            ContractType::ERC20 | ContractType::ERC20Upgradeable => "(1000000 * 10 ** 18)",
-            ContractType::MultiInheritance { base_type, .. } => {
-                match **base_type {
-                    ContractType::ERC20 | ContractType::ERC20Upgradeable => "(1000000 * 10 ** 18)",
-                    _ => "()",
-                }
-            }
-            _ => "()",
-        };
+    /// Set the dependency import prefixes, as resolved from the project's remappings
+    pub fn with_import_paths(mut self, import_paths: ImportPaths) -> Self {
+        self.import_paths = import_paths;
+        self
+    }
 
-        format!(
-r#"// SPDX-License-Identifier: {}
-pragma solidity ^{};
-
-import "forge-std/Test.sol";
-import "../src/{}.sol";
-
-contract {}Test is Test {{
-    {} public instance;
-
-    function setUp() public {{
-        instance = new {}{};
-    }}
-
-    function test_Deployment() public view {{
-        assertNotEq(address(instance), address(0));
-    }}
-}}"#,
-            self.license,
-            self.pragma,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name,
-            constructor_args
-        )
+    /// Set the OpenZeppelin Contracts release line the generated code compiles against
+    pub fn with_oz_version(mut self, oz_version: OpenZeppelinVersion) -> Self {
+        self.oz_version = oz_version;
+        self
     }
 
-    pub fn generate_script(&self) -> String {
-        let constructor_args = match &self.contract_type {
-            ContractType::ERC20 | ContractType::ERC20Upgradeable => "(1000000 * 10 ** 18)",
-            ContractType::MultiInheritance { base_type, .. } => {
-                match **base_type {
-                    ContractType::ERC20 | ContractType::ERC20Upgradeable => "(1000000 * 10 ** 18)",
-                    _ => "()",
-                }
-            }
-            _ => "()",
-        };
+    /// Set the NatSpec header style and the `@author` it names
+    pub fn with_natspec(mut self, natspec: NatSpecStyle, author: Option<String>) -> Self {
+        self.natspec = natspec;
+        self.author = author;
+        self
+    }
 
-        format!(
-r#"// SPDX-License-Identifier: {}
-pragma solidity ^{};
+    /// Set the templates to render from, e.g. with a project's overrides applied
+    pub fn with_templates(mut self, templates: Templates) -> Self {
+        self.templates = templates;
+        self
+    }
 
-import "forge-std/Script.sol";
-import "../src/{}.sol";
+    /// Set the extensions token contracts are assembled from, e.g. with a project's own registered
+    pub fn with_extensions(mut self, extensions: ExtensionRegistry) -> Self {
+        self.extensions = extensions;
+        self
+    }
 
-contract Deploy{} is Script {{
-    function run() external returns ({}) {{
-        uint256 deployerPrivateKey = vm.envUint("PRIVATE_KEY");
-        
-        vm.startBroadcast(deployerPrivateKey);
-        
-        {} instance = new {}{};
-        
-        vm.stopBroadcast();
-        
-        return instance;
-    }}
-}}"#,
-            self.license,
-            self.pragma,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name,
-            constructor_args
-        )
+    /// Set the token name, symbol, decimals, initial supply and base URI
+    pub fn with_token_metadata(mut self, metadata: TokenMetadata) -> Self {
+        self.metadata = metadata;
+        self
     }
 
-    fn generate_basic_contract(&self) -> String {
-        format!(
-r#"// SPDX-License-Identifier: {}
-pragma solidity ^{};
-
-contract {} {{
-    constructor() {{}}
-}}"#,
-            self.license,
-            self.pragma,
-            self.contract_name
-        )
+    /// Set who may call the privileged functions of generated tokens
+    pub fn with_access_control(mut self, access_control: AccessControl) -> Self {
+        self.access_control = access_control;
+        self
     }
 
-    fn generate_erc20_contract(&self) -> String {
-        format!(
-r#"// SPDX-License-Identifier: {}
-pragma solidity ^{};
-
-import "@openzeppelin/contracts/token/ERC20/ERC20.sol";
-
-contract {} is ERC20 {{
-    constructor(uint256 initialSupply) ERC20("{}", "{}") {{
-        _mint(msg.sender, initialSupply);
-    }}
-}}"#,
-            self.license,
-            self.pragma,
-            self.contract_name,
-            self.contract_name,
-            self.get_symbol()
-        )
+    /// Set the function declarations a generated interface lists, e.g. from [`crate::abi::interface_functions`]
+    pub fn with_interface_functions(mut self, functions: Vec<String>) -> Self {
+        self.functions = functions;
+        self
     }
 
-    fn generate_erc721_contract(&self) -> String {
-        format!(
-r#"// SPDX-License-Identifier: {}
-pragma solidity ^{};
-
-import "@openzeppelin/contracts/token/ERC721/ERC721.sol";
-import "@openzeppelin/contracts/access/Ownable.sol";
-
-contract {} is ERC721, Ownable {{
-    uint256 private _tokenIdCounter;
-
-    constructor() ERC721("{}", "{}") Ownable(msg.sender) {{}}
-
-    function mint(address to) public onlyOwner {{
-        uint256 tokenId = _tokenIdCounter++;
-        _safeMint(to, tokenId);
-    }}
-}}"#,
-            self.license,
-            self.pragma,
-            self.contract_name,
-            self.contract_name,
-            self.get_symbol()
-        )
+    pub fn generate_contract(&self) -> Result<String> {
+        let contract = match &self.contract_type {
+            ContractType::Basic => self.render_contract("solidity/basic.sol")?,
+            ContractType::ERC20 => self.render_contract("solidity/erc20.sol")?,
+            ContractType::ERC721 => self.render_contract("solidity/erc721.sol")?,
+            ContractType::ERC1155 => self.render_contract("solidity/erc1155.sol")?,
+            ContractType::ERC20Upgradeable => self.render_contract("solidity/erc20_upgradeable.sol")?,
+            ContractType::ERC721Upgradeable => self.render_contract("solidity/erc721_upgradeable.sol")?,
+            ContractType::ERC1155Upgradeable => self.render_contract("solidity/erc1155_upgradeable.sol")?,
+            ContractType::MultiInheritance { base_type, extensions } => {
+                self.generate_multi_inheritance_contract(base_type, extensions)?
+            }
+            ContractType::Interface => self.generate_interface()?,
+            ContractType::Abstract => self.generate_abstract_contract()?,
+        };
+        Ok(self.import_paths.rewrite(&contract))
     }
 
-    fn generate_erc20_upgradeable_contract(&self) -> String {
-        format!(
-r#"// SPDX-License-Identifier: {}
-pragma solidity ^{};
-
-import "@openzeppelin/contracts-upgradeable/token/ERC20/ERC20Upgradeable.sol";
-import "@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol";
-import "@openzeppelin/contracts-upgradeable/access/OwnableUpgradeable.sol";
-import "@openzeppelin/contracts-upgradeable/proxy/utils/UUPSUpgradeable.sol";
-
-contract {} is Initializable, ERC20Upgradeable, OwnableUpgradeable, UUPSUpgradeable {{
-    /// @custom:oz-upgrades-unsafe-allow constructor
-    constructor() {{
-        _disableInitializers();
-    }}
-
-    function initialize(uint256 initialSupply) public initializer {{
-        __ERC20_init("{}", "{}");
-        __Ownable_init(msg.sender);
-        __UUPSUpgradeable_init();
-        
-        _mint(msg.sender, initialSupply);
-    }}
-
-    function _authorizeUpgrade(address newImplementation) internal override onlyOwner {{}}
-}}"#,
-            self.license,
-            self.pragma,
-            self.contract_name,
-            self.contract_name,
-            self.get_symbol()
-        )
+    pub fn generate_test(&self) -> Result<String> {
+        let test = self.render(
+            "solidity/test.sol",
+            context! { src_path => &self.test_src_path, constructor_args => self.constructor_args()? },
+        )?;
+        Ok(self.import_paths.rewrite(&test))
     }
 
-    fn generate_erc721_upgradeable_contract(&self) -> String {
-        format!(
-r#"// SPDX-License-Identifier: {}
-pragma solidity ^{};
-
-import "@openzeppelin/contracts-upgradeable/token/ERC721/ERC721Upgradeable.sol";
-import "@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol";
-import "@openzeppelin/contracts-upgradeable/access/OwnableUpgradeable.sol";
-import "@openzeppelin/contracts-upgradeable/proxy/utils/UUPSUpgradeable.sol";
-
-contract {} is Initializable, ERC721Upgradeable, OwnableUpgradeable, UUPSUpgradeable {{
-    uint256 private _tokenIdCounter;
-
-    /// @custom:oz-upgrades-unsafe-allow constructor
-    constructor() {{
-        _disableInitializers();
-    }}
-
-    function initialize() public initializer {{
-        __ERC721_init("{}", "{}");
-        __Ownable_init(msg.sender);
-        __UUPSUpgradeable_init();
-    }}
-
-    function mint(address to) public onlyOwner {{
-        uint256 tokenId = _tokenIdCounter++;
-        _safeMint(to, tokenId);
-    }}
-
-    function _authorizeUpgrade(address newImplementation) internal override onlyOwner {{}}
-}}"#,
-            self.license,
-            self.pragma,
-            self.contract_name,
-            self.contract_name,
-            self.get_symbol()
-        )
+    pub fn generate_script(&self) -> Result<String> {
+        let script = self.render(
+            "solidity/script.sol",
+            context! { src_path => &self.script_src_path, constructor_args => self.constructor_args()? },
+        )?;
+        Ok(self.import_paths.rewrite(&script))
     }
 
-    fn generate_erc1155_contract(&self) -> String {
-        format!(
-r#"// SPDX-License-Identifier: {}
-pragma solidity ^{};
-
-import "@openzeppelin/contracts/token/ERC1155/ERC1155.sol";
-import "@openzeppelin/contracts/access/Ownable.sol";
-
-contract {} is ERC1155, Ownable {{
-    constructor() ERC1155("https://api.example.com/tokens/{{id}}.json") Ownable(msg.sender) {{}}
-
-    function mint(address to, uint256 id, uint256 amount, bytes memory data) public onlyOwner {{
-        _mint(to, id, amount, data);
-    }}
-
-    function mintBatch(address to, uint256[] memory ids, uint256[] memory amounts, bytes memory data) public onlyOwner {{
-        _mintBatch(to, ids, amounts, data);
-    }}
-}}"#,
-            self.license,
-            self.pragma,
-            self.contract_name
-        )
+    /// Arguments the generated test and script deploy the contract with, or
+    /// initialize its proxy with when it is upgradeable: the initial supply, the
+    /// `AccessManager` they deploy for `access-managed` contracts, then each
+    /// extension parameter's value or default
+    fn constructor_args(&self) -> Result<String> {
+        let mut args = Vec::new();
+        if self.contract_type.type_name() == "erc20" {
+            args.push(self.metadata.initial_supply_expression());
+        }
+        if self.access_components()?.parameter.is_some() {
+            args.push("address(manager)".to_string());
+        }
+        if let ContractType::MultiInheritance { base_type, extensions } = &self.contract_type {
+            for extension in self.extensions.resolve_all(base_type, extensions, self.oz_version)?.extensions {
+                let spec = self.extensions.require(&extension, self.oz_version)?;
+                for (index, parameter) in spec.parameters.iter().enumerate() {
+                    let value = match extension.args().get(index) {
+                        Some(value) => value.clone(),
+                        // A default cap below the initial supply would make the deployment revert
+                        None if matches!(extension, TokenExtension::ERC20Capped(_))
+                            && self.metadata.check_cap(&parameter.default).is_err() =>
+                        {
+                            self.metadata.initial_supply_expression()
+                        }
+                        None => parameter.default.clone(),
+                    };
+                    args.push(value);
+                }
+            }
+        }
+        Ok(format!("({})", args.join(", ")))
     }
 
-    fn generate_erc1155_upgradeable_contract(&self) -> String {
-        format!(
-r#"// SPDX-License-Identifier: {}
-pragma solidity ^{};
-
-import "@openzeppelin/contracts-upgradeable/token/ERC1155/ERC1155Upgradeable.sol";
-import "@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol";
-import "@openzeppelin/contracts-upgradeable/access/OwnableUpgradeable.sol";
-import "@openzeppelin/contracts-upgradeable/proxy/utils/UUPSUpgradeable.sol";
-
-contract {} is Initializable, ERC1155Upgradeable, OwnableUpgradeable, UUPSUpgradeable {{
-    /// @custom:oz-upgrades-unsafe-allow constructor
-    constructor() {{
-        _disableInitializers();
-    }}
-
-    function initialize() public initializer {{
-        __ERC1155_init("https://api.example.com/tokens/{{id}}.json");
-        __Ownable_init(msg.sender);
-        __UUPSUpgradeable_init();
-    }}
-
-    function mint(address to, uint256 id, uint256 amount, bytes memory data) public onlyOwner {{
-        _mint(to, id, amount, data);
-    }}
-
-    function mintBatch(address to, uint256[] memory ids, uint256[] memory amounts, bytes memory data) public onlyOwner {{
-        _mintBatch(to, ids, amounts, data);
-    }}
-
-    function _authorizeUpgrade(address newImplementation) internal override onlyOwner {{}}
-}}"#,
-            self.license,
-            self.pragma,
-            self.contract_name
-        )
+    /// Render one of the single-contract templates
+    fn render_contract(&self, name: &str) -> Result<String> {
+        self.render(name, context! { natspec => self.author_header() })
     }
 
-    fn generate_multi_inheritance_contract(&self, base_type: &ContractType, extensions: &[TokenExtension]) -> String {
-        let (imports, inheritance_chain, _initializers, additional_functions) = self.build_inheritance_components(base_type, extensions);
-        
-        let constructor_params = match base_type {
-            ContractType::ERC20 | ContractType::ERC20Upgradeable => "uint256 initialSupply",
-            _ => "",
+    /// Render `name` with `extra` on top of the variables every template gets
+    fn render(&self, name: &str, extra: Value) -> Result<String> {
+        let access = self.access_components()?;
+        let upgradeable = self.contract_type.is_upgradeable();
+        let (constructor, initializer) = if upgradeable {
+            (String::new(), access.initializers.join("\n        "))
+        } else {
+            (access.initializers.iter().map(|call| format!(" {}", call)).collect(), String::new())
         };
-
-        let has_wrapper = extensions.iter().any(|ext| matches!(ext, TokenExtension::ERC721Wrapper | TokenExtension::ERC20Wrapper));
-        
-        let constructor_call = match base_type {
-            ContractType::ERC20 => format!("ERC20(\"{}\", \"{}\")", self.contract_name, self.get_symbol()),
-            ContractType::ERC721 => {
-                let base_call = format!("ERC721(\"{}\", \"{}\")", self.contract_name, self.get_symbol());
-                if has_wrapper {
-                    format!("{} ERC721Wrapper(IERC721(UNDERLYING_TOKEN))", base_call)
-                } else {
-                    base_call
+        self.templates.render(
+            name,
+            context! {
+                ..extra,
+                ..context! {
+                    name => &self.contract_name,
+                    token_name => self.metadata.token_name(&self.contract_name),
+                    symbol => self.get_symbol(),
+                    decimals => self.metadata.decimals(),
+                    base_uri => &self.metadata.base_uri,
+                    pragma => &self.pragma,
+                    license => &self.license,
+                    oz_version => self.oz_version.major(),
+                    upgradeable,
+                    access => context! {
+                        model => access.model(),
+                        contract => access.contract.map(|contract| oz_contract_name(contract, upgradeable)).unwrap_or_default(),
+                        import => access.import_path.map(|path| oz_import(path, upgradeable)).unwrap_or_default(),
+                        constructor => &constructor,
+                        initializer => &initializer,
+                        parameter => access.parameter.clone().unwrap_or_default(),
+                        roles => access.declarations(),
+                        grants => access.grants(),
+                        only_minter => access.modifier(Some("minter")).unwrap_or_default(),
+                        only_upgrader => access.modifier(Some("upgrader")).unwrap_or_default(),
+                    },
+                    // Names templates used before access control was configurable
+                    ownable_constructor => &constructor,
+                    ownable_initializer => &initializer,
                 }
             },
-            ContractType::ERC1155 => "ERC1155(\"https://api.example.com/tokens/{id}.json\")".to_string(),
-            _ => "".to_string(),
-        };
+        )
+    }
 
-        let constructor_body = match base_type {
-            ContractType::ERC20 => "        _mint(msg.sender, initialSupply);",
-            _ => "",
-        };
+    fn generate_multi_inheritance_contract(&self, base_type: &ContractType, extensions: &[TokenExtension]) -> Result<String> {
+        let components = self.build_inheritance_components(base_type, extensions)?;
 
-        format!(
-r#"// SPDX-License-Identifier: {}
-pragma solidity ^{};
-
-{}
-
-contract {} is {} {{
-{}
-
-    constructor({}) {} Ownable(msg.sender) {{
-{}
-    }}
-
-{}
-}}"#,
-            self.license,
-            self.pragma,
-            imports,
-            self.contract_name,
-            inheritance_chain,
-            self.generate_state_variables(extensions),
-            constructor_params,
-            constructor_call,
-            constructor_body,
-            additional_functions
+        let mut constructor_params = Vec::new();
+        let mut constructor_body = components.constructor_body.clone();
+        if matches!(base_type, ContractType::ERC20 | ContractType::ERC20Upgradeable) {
+            constructor_params.push("uint256 initialSupply".to_string());
+            constructor_body.push("        _mint(msg.sender, initialSupply);".to_string());
+        }
+        constructor_params.extend(components.parameters.iter().cloned());
+        let constructor_params = constructor_params.join(", ");
+        let constructor_body = constructor_body.join("\n");
+
+        let mut sections = Vec::new();
+
+        if !components.state_variables.is_empty() {
+            sections.push(components.state_variables.join("\n"));
+        }
+
+        if components.upgradeable {
+            sections.push("    /// @custom:oz-upgrades-unsafe-allow constructor\n    constructor() {\n        _disableInitializers();\n    }".to_string());
+
+            let mut initialize = format!(
+                "    function initialize({}) public initializer {{\n        {}\n",
+                constructor_params,
+                components.initializers.join("\n        ")
+            );
+            if !constructor_body.is_empty() {
+                initialize.push_str(&format!("\n{}\n", constructor_body));
+            }
+            initialize.push_str("    }");
+            sections.push(initialize);
+        } else if constructor_body.is_empty() {
+            sections.push(format!(
+                "    constructor({}) {} {{}}",
+                constructor_params,
+                components.initializers.join(" ")
+            ));
+        } else {
+            sections.push(format!(
+                "    constructor({}) {} {{\n{}\n    }}",
+                constructor_params,
+                components.initializers.join(" "),
+                constructor_body
+            ));
+        }
+
+        sections.extend(components.functions);
+
+        // Without a second base declaring `decimals()` there is no required override to carry it
+        let decimals_overridden = components.overrides.iter().any(|function| function.starts_with("    function decimals()"));
+        if let (ContractType::ERC20 | ContractType::ERC20Upgradeable, false) = (base_type, decimals_overridden) {
+            if self.metadata.decimals() != 18 {
+                sections.push(format!(
+                    "    function decimals() public view virtual override returns (uint8) {{\n        return {};\n    }}",
+                    self.metadata.decimals()
+                ));
+            }
+        }
+
+        if let (ContractType::ERC721 | ContractType::ERC721Upgradeable, Some(base_uri)) = (base_type, &self.metadata.base_uri) {
+            sections.push(format!(
+                "    function _baseURI() internal pure override returns (string memory) {{\n        return \"{}\";\n    }}",
+                base_uri
+            ));
+        }
+
+        if components.upgradeable {
+            sections.push(format!(
+                "    function _authorizeUpgrade(address newImplementation) internal override {} {{}}",
+                components.upgrade_modifier
+            ));
+        }
+
+        if !components.overrides.is_empty() {
+            sections.push("    // The following functions are overrides required by Solidity.".to_string());
+            sections.extend(components.overrides);
+        }
+
+        self.render(
+            "solidity/token.sol",
+            context! {
+                natspec => self.author_header(),
+                imports => components.imports,
+                bases => components.inheritance,
+                sections,
+            },
         )
     }
 
-    fn build_inheritance_components(&self, base_type: &ContractType, extensions: &[TokenExtension]) -> (String, String, String, String) {
-        let mut imports = Vec::new();
-        let mut inheritance = Vec::new();
-        let mut initializers = Vec::new();
-        let mut functions = Vec::new();
+    fn build_inheritance_components(&self, base_type: &ContractType, extensions: &[TokenExtension]) -> Result<InheritanceComponents> {
+        // Ahead of rendering snippets that restrict functions with `access_modifier`
+        self.access_control.check(&self.contract_type, &self.extensions, self.oz_version)?;
+        let mut components = InheritanceComponents {
+            upgradeable: matches!(
+                base_type,
+                ContractType::ERC20Upgradeable | ContractType::ERC721Upgradeable | ContractType::ERC1155Upgradeable
+            ),
+            ..Default::default()
+        };
+
+        if components.upgradeable {
+            components.imports.push("import \"@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol\";".to_string());
+            components.inheritance.push("Initializable".to_string());
+        }
 
         // Base imports and inheritance
         match base_type {
-            ContractType::ERC20 => {
-                imports.push("import \"@openzeppelin/contracts/token/ERC20/ERC20.sol\";".to_string());
-                inheritance.push("ERC20".to_string());
-            },
-            ContractType::ERC721 => {
-                imports.push("import \"@openzeppelin/contracts/token/ERC721/ERC721.sol\";".to_string());
-                inheritance.push("ERC721".to_string());
-            },
-            ContractType::ERC1155 => {
-                imports.push("import \"@openzeppelin/contracts/token/ERC1155/ERC1155.sol\";".to_string());
-                inheritance.push("ERC1155".to_string());
-            },
+            ContractType::ERC20 | ContractType::ERC20Upgradeable => components.push_contract(
+                "ERC20",
+                "token/ERC20/ERC20.sol",
+                Some(format!("\"{}\", \"{}\"", self.metadata.token_name(&self.contract_name), self.get_symbol())),
+                builtin_virtual_functions("ERC20", self.oz_version),
+            ),
+            ContractType::ERC721 | ContractType::ERC721Upgradeable => components.push_contract(
+                "ERC721",
+                "token/ERC721/ERC721.sol",
+                Some(format!("\"{}\", \"{}\"", self.metadata.token_name(&self.contract_name), self.get_symbol())),
+                builtin_virtual_functions("ERC721", self.oz_version),
+            ),
+            ContractType::ERC1155 | ContractType::ERC1155Upgradeable => components.push_contract(
+                "ERC1155",
+                "token/ERC1155/ERC1155.sol",
+                Some(format!(
+                    "\"{}\"",
+                    self.metadata.base_uri.as_deref().unwrap_or("https://api.example.com/tokens/{id}.json")
+                )),
+                builtin_virtual_functions("ERC1155", self.oz_version),
+            ),
             _ => {}
         }
 
-        // Always add Ownable as it's needed for most extensions
-        imports.push("import \"@openzeppelin/contracts/access/Ownable.sol\";".to_string());
-        inheritance.push("Ownable".to_string());
+        // Convert cross-compatible extensions based on base type, dropping duplicates
+        // and pulling in what they build on, e.g. the EIP712 domain votes need
+        let resolved_extensions = self
+            .extensions
+            .resolve_all(base_type, extensions, self.oz_version)?
+            .extensions;
+
+        let state_variables = self.generate_state_variables(&resolved_extensions, components.upgradeable)?;
+        if !state_variables.is_empty() {
+            components.state_variables.push(state_variables);
+        }
 
         // Add extension-specific imports and inheritance
-        for extension in extensions {
-            // Convert cross-compatible extensions based on base type
-            let converted_extension = self.convert_extension_for_base_type(base_type, extension);
-            let (import, inherit, init, func) = self.get_extension_components(&converted_extension);
-            if !import.is_empty() { imports.push(import); }
-            if !inherit.is_empty() { inheritance.push(inherit); }
-            if !init.is_empty() { initializers.push(init); }
-            if !func.is_empty() { functions.push(func); }
+        for extension in &resolved_extensions {
+            let extension_components = self.get_extension_components(extension, components.upgradeable)?;
+            components.push_contract(
+                &extension_components.contract,
+                &extension_components.import_path,
+                extension_components.constructor_args,
+                extension_components.virtual_functions,
+            );
+            components.parameters.extend(extension_components.parameters);
+            components.constructor_body.extend(extension_components.constructor_body);
+            components.functions.extend(extension_components.functions);
         }
 
-        (
-            imports.join("\n"),
-            inheritance.join(", "),
-            initializers.join("\n        "),
-            functions.join("\n\n")
-        )
-    }
+        // Access control goes last so every extension function can use its modifiers
+        let access = self.access_components()?;
+        if let (Some(contract), Some(import_path)) = (access.contract, access.import_path) {
+            components.imports.push(oz_import(import_path, components.upgradeable));
+            components.inheritance.push(oz_contract_name(contract, components.upgradeable));
+            components.virtual_functions.extend(builtin_virtual_functions(contract, self.oz_version));
+            components.initializers.extend(access.initializers.iter().cloned());
+            components.parameters.splice(0..0, access.parameter.clone());
+            let declarations = access.declarations();
+            if !declarations.is_empty() {
+                components.state_variables.insert(0, format!("    {}", declarations.join("\n    ")));
+            }
+            components.constructor_body.splice(0..0, access.grants().iter().map(|grant| format!("        {}", grant)));
+            components.upgrade_modifier = access.modifier(Some("upgrader")).unwrap_or_default();
+        }
 
-    fn convert_extension_for_base_type(&self, base_type: &ContractType, extension: &TokenExtension) -> TokenExtension {
-        match (base_type, extension) {
-            // Convert ERC20Burnable to appropriate token type
-            (ContractType::ERC721 | ContractType::ERC721Upgradeable, TokenExtension::ERC20Burnable) => TokenExtension::ERC721Burnable,
-            (ContractType::ERC1155 | ContractType::ERC1155Upgradeable, TokenExtension::ERC20Burnable) => TokenExtension::ERC1155Burnable,
-            
-            // Convert ERC20Pausable to appropriate token type
-            (ContractType::ERC721 | ContractType::ERC721Upgradeable, TokenExtension::ERC20Pausable) => TokenExtension::ERC721Pausable,
-            (ContractType::ERC1155 | ContractType::ERC1155Upgradeable, TokenExtension::ERC20Pausable) => TokenExtension::ERC1155Pausable,
-            
-            // Convert wrapper extensions
-            (ContractType::ERC721 | ContractType::ERC721Upgradeable, TokenExtension::ERC20Wrapper) => TokenExtension::ERC721Wrapper,
-            
-            // Convert uristorage extensions
-            (ContractType::ERC1155 | ContractType::ERC1155Upgradeable, TokenExtension::ERC721URIStorage) => TokenExtension::ERC1155URIStorage,
-            
-            // By default, keep the extension as-is
-            _ => extension.clone(),
+        // Same counter-backed mint as the plain ERC721 template, open to anyone without access control
+        if matches!(base_type, ContractType::ERC721 | ContractType::ERC721Upgradeable) {
+            let modifier = access.modifier(Some("minter")).map(|modifier| format!(" {}", modifier)).unwrap_or_default();
+            components.state_variables.push("    uint256 private _tokenIdCounter;".to_string());
+            components.functions.insert(
+                0,
+                format!(
+                    "    function safeMint(address to) public{} {{\n        uint256 tokenId = _tokenIdCounter++;\n        _safeMint(to, tokenId);\n    }}",
+                    modifier
+                ),
+            );
         }
+
+        if components.upgradeable {
+            components.imports.push("import \"@openzeppelin/contracts-upgradeable/proxy/utils/UUPSUpgradeable.sol\";".to_string());
+            components.inheritance.push("UUPSUpgradeable".to_string());
+            components.initializers.push("__UUPSUpgradeable_init();".to_string());
+        }
+
+        components.overrides = Self::resolve_overrides(
+            &components.virtual_functions,
+            components.upgradeable,
+            Some(self.metadata.decimals()).filter(|decimals| *decimals != 18),
+        );
+        Ok(components)
     }
 
-    fn get_extension_components(&self, extension: &TokenExtension) -> (String, String, String, String) {
-        match extension {
-            TokenExtension::ERC20Burnable => (
-                "import \"@openzeppelin/contracts/token/ERC20/extensions/ERC20Burnable.sol\";".to_string(),
-                "ERC20Burnable".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC20Pausable => (
-                "import \"@openzeppelin/contracts/token/ERC20/extensions/ERC20Pausable.sol\";".to_string(),
-                "ERC20Pausable".to_string(),
-                "".to_string(),
-                "    function pause() public onlyOwner {\n        _pause();\n    }\n\n    function unpause() public onlyOwner {\n        _unpause();\n    }".to_string()
-            ),
-            TokenExtension::ERC20Votes => (
-                "import \"@openzeppelin/contracts/token/ERC20/extensions/ERC20Votes.sol\";\nimport \"@openzeppelin/contracts/utils/cryptography/EIP712.sol\";".to_string(),
-                "ERC20Votes, EIP712".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC20Permit => (
-                "import \"@openzeppelin/contracts/token/ERC20/extensions/ERC20Permit.sol\";".to_string(),
-                "ERC20Permit".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC721Burnable => (
-                "import \"@openzeppelin/contracts/token/ERC721/extensions/ERC721Burnable.sol\";".to_string(),
-                "ERC721Burnable".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC721Pausable => (
-                "import \"@openzeppelin/contracts/token/ERC721/extensions/ERC721Pausable.sol\";".to_string(),
-                "ERC721Pausable".to_string(),
-                "".to_string(),
-                "    function pause() public onlyOwner {\n        _pause();\n    }\n\n    function unpause() public onlyOwner {\n        _unpause();\n    }".to_string()
-            ),
-            TokenExtension::ERC721Enumerable => (
-                "import \"@openzeppelin/contracts/token/ERC721/extensions/ERC721Enumerable.sol\";".to_string(),
-                "ERC721Enumerable".to_string(),
-                "".to_string(),
-                "    function supportsInterface(bytes4 interfaceId) public view virtual override(ERC721, ERC721Enumerable) returns (bool) {\n        return super.supportsInterface(interfaceId);\n    }\n\n    function _update(address to, uint256 tokenId, address auth) internal virtual override(ERC721, ERC721Enumerable) returns (address) {\n        return super._update(to, tokenId, auth);\n    }\n\n    function _increaseBalance(address account, uint128 value) internal virtual override(ERC721, ERC721Enumerable) {\n        super._increaseBalance(account, value);\n    }".to_string()
-            ),
-            TokenExtension::ERC1155Burnable => (
-                "import \"@openzeppelin/contracts/token/ERC1155/extensions/ERC1155Burnable.sol\";".to_string(),
-                "ERC1155Burnable".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC1155Pausable => (
-                "import \"@openzeppelin/contracts/token/ERC1155/extensions/ERC1155Pausable.sol\";".to_string(),
-                "ERC1155Pausable".to_string(),
-                "".to_string(),
-                "    function pause() public onlyOwner {\n        _pause();\n    }\n\n    function unpause() public onlyOwner {\n        _unpause();\n    }".to_string()
-            ),
-            // Additional ERC20 Extensions
-            TokenExtension::ERC20Capped => (
-                "import \"@openzeppelin/contracts/token/ERC20/extensions/ERC20Capped.sol\";".to_string(),
-                "ERC20Capped".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC20Wrapper => (
-                "import \"@openzeppelin/contracts/token/ERC20/extensions/ERC20Wrapper.sol\";".to_string(),
-                "ERC20Wrapper".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC20FlashMint => (
-                "import \"@openzeppelin/contracts/token/ERC20/extensions/ERC20FlashMint.sol\";".to_string(),
-                "ERC20FlashMint".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC20TemporaryApproval => (
-                "import \"@openzeppelin/contracts/token/ERC20/extensions/draft-ERC20TemporaryApproval.sol\";".to_string(),
-                "ERC20TemporaryApproval".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC20Bridgeable => (
-                "import \"@openzeppelin/contracts/token/ERC20/extensions/draft-ERC20Bridgeable.sol\";".to_string(),
-                "ERC20Bridgeable".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC1363 => (
-                "import \"@openzeppelin/contracts/token/ERC20/extensions/ERC1363.sol\";".to_string(),
-                "ERC1363".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC4626 => (
-                "import \"@openzeppelin/contracts/token/ERC20/extensions/ERC4626.sol\";".to_string(),
-                "ERC4626".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            // Additional ERC721 Extensions
-            TokenExtension::ERC721Consecutive => (
-                "import \"@openzeppelin/contracts/token/ERC721/extensions/ERC721Consecutive.sol\";".to_string(),
-                "ERC721Consecutive".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC721URIStorage => (
-                "import \"@openzeppelin/contracts/token/ERC721/extensions/ERC721URIStorage.sol\";".to_string(),
-                "ERC721URIStorage".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC721Votes => (
-                "import \"@openzeppelin/contracts/token/ERC721/extensions/ERC721Votes.sol\";\nimport \"@openzeppelin/contracts/utils/cryptography/EIP712.sol\";".to_string(),
-                "ERC721Votes, EIP712".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC721Royalty => (
-                "import \"@openzeppelin/contracts/token/ERC721/extensions/ERC721Royalty.sol\";".to_string(),
-                "ERC721Royalty".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC721Wrapper => (
-                "import \"@openzeppelin/contracts/token/ERC721/extensions/ERC721Wrapper.sol\";\nimport \"@openzeppelin/contracts/token/ERC721/IERC721.sol\";".to_string(),
-                "ERC721Wrapper".to_string(),
-                "".to_string(),
-                "    // ERC721Wrapper requires an underlying token address\n    // Replace with actual token address when deploying\n    address constant UNDERLYING_TOKEN = address(0x0);".to_string()
-            ),
-            // Additional ERC1155 Extensions
-            TokenExtension::ERC1155Supply => (
-                "import \"@openzeppelin/contracts/token/ERC1155/extensions/ERC1155Supply.sol\";".to_string(),
-                "ERC1155Supply".to_string(),
-                "".to_string(),
-                "".to_string()
-            ),
-            TokenExtension::ERC1155URIStorage => (
-                "import \"@openzeppelin/contracts/token/ERC1155/extensions/ERC1155URIStorage.sol\";".to_string(),
-                "ERC1155URIStorage".to_string(),
-                "".to_string(),
-                "    function uri(uint256 tokenId) public view virtual override(ERC1155, ERC1155URIStorage) returns (string memory) {\n        return super.uri(tokenId);\n    }".to_string()
-            )
+    /// Finds every function that more than one base of the combined contract
+    /// declares, and renders the `override(A, B, ...)` implementation Solidity
+    /// requires for it. Non-default `decimals` are returned by the `decimals()`
+    /// override.
+    fn resolve_overrides(
+        virtual_functions: &[(OverrideFunction, String)],
+        upgradeable: bool,
+        decimals: Option<u8>,
+    ) -> Vec<String> {
+        let mut declarations: Vec<(OverrideFunction, Vec<&str>)> = Vec::new();
+
+        for (function, declared_in) in virtual_functions {
+            match declarations.iter_mut().find(|(existing, _)| existing == function) {
+                Some((_, declared_by)) => {
+                    if !declared_by.contains(&declared_in.as_str()) {
+                        declared_by.push(declared_in);
+                    }
+                }
+                None => declarations.push((*function, vec![declared_in])),
+            }
         }
+
+        declarations
+            .into_iter()
+            .filter(|(_, declared_by)| declared_by.len() > 1)
+            .map(|(function, declared_by)| {
+                let bases = declared_by
+                    .iter()
+                    .map(|name| oz_contract_name(name, upgradeable))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                match (function, decimals) {
+                    (OverrideFunction::Decimals, Some(decimals)) => format!(
+                        "    function decimals() public view virtual override({}) returns (uint8) {{\n        return {};\n    }}",
+                        bases, decimals
+                    ),
+                    _ => function.render(&bases),
+                }
+            })
+            .collect()
     }
 
-    fn generate_state_variables(&self, extensions: &[TokenExtension]) -> String {
-        let mut state_vars = Vec::new();
-        
+    /// What `extension` adds to the contract, with its snippets rendered
+    fn get_extension_components(&self, extension: &TokenExtension, upgradeable: bool) -> Result<ExtensionComponents> {
+        let spec = self.extensions.require(extension, self.oz_version)?;
+        let context = context! {
+            name => &self.contract_name,
+            token_name => self.metadata.token_name(&self.contract_name),
+            symbol => self.get_symbol(),
+            upgradeable,
+            oz_version => self.oz_version.major(),
+            // v4 upgradeable extensions take the `*Upgradeable` interfaces
+            interface_suffix => if upgradeable && self.oz_version == OpenZeppelinVersion::V4 { "Upgradeable" } else { "" },
+            // Undefined without access control, so a snippet needing it fails to render
+            access_modifier => access_modifier(self.access_control, spec.role.as_deref()).map_or(Value::UNDEFINED, Value::from),
+        };
+        let render = |snippet: &String| self.templates.render_str(snippet, context.clone());
+
+        let base_type = self.contract_type.type_name();
+        let virtual_functions = spec
+            .overrides
+            .iter()
+            .filter_map(|name| {
+                // `Nonces.nonces` names a function one of the extension's own bases declares
+                let (declared_in, function) = name.split_once('.').unwrap_or((&spec.contract, name));
+                OverrideFunction::from_name(function, base_type, self.oz_version)
+                    .map(|function| (function, declared_in.to_string()))
+            })
+            .collect();
+
+        Ok(ExtensionComponents {
+            contract: spec.contract.clone(),
+            import_path: spec.import.clone(),
+            constructor_args: spec.constructor_args.as_ref().map(render).transpose()?,
+            parameters: spec
+                .parameters
+                .iter()
+                .map(|parameter| format!("{} {}", parameter.solidity_type, parameter.name))
+                .collect(),
+            constructor_body: spec.constructor_body.iter().map(render).collect::<Result<_>>()?,
+            state_variables: spec.state_variables.iter().map(render).collect::<Result<_>>()?,
+            functions: spec.functions.iter().map(render).collect::<Result<_>>()?,
+            virtual_functions,
+        })
+    }
+
+    /// State variables of every extension, each declared once
+    fn generate_state_variables(&self, extensions: &[TokenExtension], upgradeable: bool) -> Result<String> {
+        let mut state_variables: Vec<String> = Vec::new();
         for extension in extensions {
-            match extension {
-                TokenExtension::ERC721Enumerable | TokenExtension::ERC721Burnable => {
-                    if !state_vars.contains(&"    uint256 private _tokenIdCounter;") {
-                        state_vars.push("    uint256 private _tokenIdCounter;");
+            for variable in self.get_extension_components(extension, upgradeable)?.state_variables {
+                if !state_variables.contains(&variable) {
+                    state_variables.push(variable);
+                }
+            }
+        }
+        Ok(state_variables.join("\n"))
+    }
+
+    /// The access control of the contract being generated, with the roles its
+    /// privileged functions need. Contracts without privileged functions, such
+    /// as a plain ERC20, get none.
+    fn access_components(&self) -> Result<AccessComponents> {
+        let mut roles: Vec<String> = Vec::new();
+        match &self.contract_type {
+            ContractType::ERC721 | ContractType::ERC1155 | ContractType::ERC721Upgradeable | ContractType::ERC1155Upgradeable => {
+                roles.push("minter".to_string())
+            }
+            ContractType::ERC20Upgradeable => {}
+            ContractType::MultiInheritance { base_type, extensions } => {
+                if matches!(**base_type, ContractType::ERC721 | ContractType::ERC721Upgradeable) {
+                    roles.push("minter".to_string());
+                }
+                for extension in self.extensions.resolve_all(base_type, extensions, self.oz_version)?.extensions {
+                    if let Some(role) = &self.extensions.require(&extension, self.oz_version)?.role {
+                        if !roles.contains(role) {
+                            roles.push(role.clone());
+                        }
                     }
-                },
-                _ => {}
+                }
             }
+            _ => return Ok(AccessComponents::none()),
         }
-        
-        state_vars.join("\n")
+        let upgradeable = self.contract_type.is_upgradeable();
+        if upgradeable {
+            roles.push("upgrader".to_string());
+        }
+
+        self.access_control.check(&self.contract_type, &self.extensions, self.oz_version)?;
+        if self.access_control == AccessControl::Managed && self.oz_version == OpenZeppelinVersion::V4 {
+            return Err(GramrError::Other(
+                "access-managed needs OpenZeppelin Contracts 5.x, which introduced AccessManager; use a 5.x release".to_string(),
+            ));
+        }
+
+        let v4 = self.oz_version == OpenZeppelinVersion::V4;
+        let (import_path, initializers): (&'static str, &[&str]) = match (self.access_control, upgradeable) {
+            (AccessControl::None, _) => return Ok(AccessComponents::none()),
+            // v4 takes the deployer as owner implicitly
+            (AccessControl::Ownable, false) if v4 => ("access/Ownable.sol", &[]),
+            (AccessControl::Ownable, false) => ("access/Ownable.sol", &["Ownable(msg.sender)"]),
+            (AccessControl::Ownable, true) if v4 => ("access/Ownable.sol", &["__Ownable_init();"]),
+            (AccessControl::Ownable, true) => ("access/Ownable.sol", &["__Ownable_init(msg.sender);"]),
+            (AccessControl::Ownable2Step, false) if v4 => ("access/Ownable2Step.sol", &[]),
+            (AccessControl::Ownable2Step, false) => ("access/Ownable2Step.sol", &["Ownable(msg.sender)"]),
+            (AccessControl::Ownable2Step, true) if v4 => ("access/Ownable2Step.sol", &["__Ownable2Step_init();"]),
+            (AccessControl::Ownable2Step, true) => {
+                ("access/Ownable2Step.sol", &["__Ownable_init(msg.sender);", "__Ownable2Step_init();"])
+            }
+            (AccessControl::Roles, false) => ("access/AccessControl.sol", &[]),
+            (AccessControl::Roles, true) => ("access/AccessControl.sol", &["__AccessControl_init();"]),
+            (AccessControl::Managed, false) => ("access/manager/AccessManaged.sol", &["AccessManaged(initialAuthority)"]),
+            (AccessControl::Managed, true) => {
+                ("access/manager/AccessManaged.sol", &["__AccessManaged_init(initialAuthority);"])
+            }
+        };
+
+        Ok(AccessComponents {
+            access_control: self.access_control,
+            contract: self.access_control.contract(),
+            import_path: Some(import_path),
+            initializers: initializers.iter().map(|call| call.to_string()).collect(),
+            parameter: (self.access_control == AccessControl::Managed).then(|| "address initialAuthority".to_string()),
+            roles,
+        })
     }
 
     fn get_symbol(&self) -> String {
-        self.contract_name
-            .chars()
-            .filter(|c| c.is_uppercase())
-            .take(3)
-            .collect::<String>()
-            .to_uppercase()
+        self.metadata.symbol(&self.contract_name)
     }
     
-    pub fn generate_library(&self) -> String {
-        format!(
-r#"// SPDX-License-Identifier: {}
-pragma solidity ^{};
-
-/// @title {}
-/// @notice A library contract for reusable utility functions
-/// @dev Add your custom functions here
-library {} {{
-    /// @notice Example function - replace with your own
-    /// @param value The input value
-    /// @return The processed result
-    function exampleFunction(uint256 value) internal pure returns (uint256) {{
-        return value * 2;
-    }}
-    
-    /// @notice Example struct for data organization
-    struct Data {{
-        uint256 id;
-        address owner;
-        bool isActive;
-    }}
-    
-    /// @notice Example function that works with structs
-    /// @param data The input data struct
-    /// @return Whether the data is valid
-    function validateData(Data memory data) internal pure returns (bool) {{
-        return data.owner != address(0) && data.isActive;
-    }}
-}}
-"#,
-            self.license,
-            self.pragma,
-            self.contract_name,
-            self.contract_name
-        )
+    pub fn generate_library(&self) -> Result<String> {
+        let natspec = self.natspec_header(
+            &self.contract_name,
+            "A library contract for reusable utility functions",
+            "Add your custom functions here",
+        );
+        self.render("solidity/library.sol", context! { natspec })
     }
 
-    pub fn generate_interface(&self) -> String {
-        format!(
-r#"// SPDX-License-Identifier: {}
-pragma solidity ^{};
-
-/// @title I{}
-/// @notice Interface for {}
-/// @dev Define your function signatures here
-interface I{} {{
-    // Add your function signatures here
-    // Example:
-    // function exampleFunction(uint256 value) external returns (bool);
-}}
-"#,
-            self.license,
-            self.pragma,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name
-        )
+    pub fn generate_interface(&self) -> Result<String> {
+        let natspec = self.natspec_header(
+            &format!("I{}", self.contract_name),
+            &format!("Interface for {}", self.contract_name),
+            "Define your function signatures here",
+        );
+        self.render("solidity/interface.sol", context! { natspec, functions => &self.functions })
     }
 
-    pub fn generate_abstract_contract(&self) -> String {
-        format!(
-r#"// SPDX-License-Identifier: {}
-pragma solidity ^{};
-
-/// @title {}
-/// @notice Abstract contract for {}
-/// @dev Implement the required functions in the inheriting contract
-abstract contract {} {{
-    // Add your abstract functions and state variables here
-    // Example:
-    // function abstractFunction() public virtual returns (uint256);
-    
-    // You can also implement concrete functions
-    function concreteFunction() public pure returns (string memory) {{
-        return "This is a concrete function";
-    }}
-}}
-"#,
-            self.license,
-            self.pragma,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name
-        )
+    pub fn generate_abstract_contract(&self) -> Result<String> {
+        let natspec = self.natspec_header(
+            &self.contract_name,
+            &format!("Abstract contract for {}", self.contract_name),
+            "Implement the required functions in the inheriting contract",
+        );
+        self.render("solidity/abstract.sol", context! { natspec })
+    }
+
+    /// NatSpec block placed directly above a declaration, in the configured style
+    fn natspec_header(&self, title: &str, notice: &str, dev: &str) -> String {
+        if self.natspec == NatSpecStyle::None {
+            return String::new();
+        }
+
+        let mut header = format!("/// @title {}\n", title);
+        if let Some(author) = &self.author {
+            header.push_str(&format!("/// @author {}\n", author));
+        }
+        if self.natspec == NatSpecStyle::Full {
+            header.push_str(&format!("/// @notice {}\n/// @dev {}\n", notice, dev));
+        }
+        header
+    }
+
+    /// Token and basic contract templates carry no NatSpec; give them a
+    /// `@title`/`@author` header when an author is configured
+    fn author_header(&self) -> String {
+        match &self.author {
+            Some(author) if self.natspec != NatSpecStyle::None => {
+                format!("/// @title {}\n/// @author {}\n", self.contract_name, author)
+            }
+            _ => String::new(),
+        }
     }
 }
 
 impl Template for SolidityTemplate {
-    fn generate_contract(&self) -> String {
+    fn generate_contract(&self) -> Result<String> {
         self.generate_contract()
     }
     
-    fn generate_test(&self) -> String {
+    fn generate_test(&self) -> Result<String> {
         self.generate_test()
     }
     
-    fn generate_script(&self) -> String {
+    fn generate_script(&self) -> Result<String> {
         self.generate_script()
     }
     
-    fn generate_library(&self) -> String {
+    fn generate_library(&self) -> Result<String> {
         self.generate_library()
     }
     
-    fn generate_interface(&self) -> String {
+    fn generate_interface(&self) -> Result<String> {
         self.generate_interface()
     }
     
-    fn generate_abstract_contract(&self) -> String {
+    fn generate_abstract_contract(&self) -> Result<String> {
         self.generate_abstract_contract()
     }
 }
 
+/// Everything a combined token contract is assembled from.
+#[derive(Default)]
+struct InheritanceComponents {
+    upgradeable: bool,
+    imports: Vec<String>,
+    inheritance: Vec<String>,
+    /// Base constructor calls, or `__X_init` calls for upgradeable contracts
+    initializers: Vec<String>,
+    /// Extension parameters, declared after the base ones
+    parameters: Vec<String>,
+    /// Extension statements, run before the initial mint
+    constructor_body: Vec<String>,
+    state_variables: Vec<String>,
+    functions: Vec<String>,
+    overrides: Vec<String>,
+    /// Overridable functions of every base, in inheritance order, with the
+    /// contract declaring each (without the `Upgradeable` suffix)
+    virtual_functions: Vec<(OverrideFunction, String)>,
+    /// Modifier restricting `_authorizeUpgrade`
+    upgrade_modifier: String,
+}
+
+impl InheritanceComponents {
+    /// Adds an OpenZeppelin contract to the inheritance list, picking the
+    /// upgradeable flavour of its import, name and initializer when needed.
+    fn push_contract(
+        &mut self,
+        contract: &str,
+        import_path: &str,
+        constructor_args: Option<String>,
+        virtual_functions: Vec<(OverrideFunction, String)>,
+    ) {
+        self.imports.push(oz_import(import_path, self.upgradeable));
+
+        if self.upgradeable {
+            self.initializers.push(format!(
+                "__{}_init({});",
+                contract,
+                constructor_args.unwrap_or_default()
+            ));
+        } else if let Some(args) = constructor_args {
+            self.initializers.push(format!("{}({})", contract, args));
+        }
+
+        self.inheritance.push(oz_contract_name(contract, self.upgradeable));
+        self.virtual_functions.extend(virtual_functions);
+    }
+}
+
+/// How the chosen access control appears in a token contract.
+struct AccessComponents {
+    access_control: AccessControl,
+    /// Contract to inherit, without the `Upgradeable` suffix; `None` for no access control
+    contract: Option<&'static str>,
+    /// Path relative to the OpenZeppelin contracts root
+    import_path: Option<&'static str>,
+    /// Base constructor calls, or `__X_init` calls for upgradeable contracts
+    initializers: Vec<String>,
+    /// `address initialAuthority` for `AccessManaged`
+    parameter: Option<String>,
+    /// Roles the contract's privileged functions are restricted to, e.g. `minter`
+    roles: Vec<String>,
+}
+
+impl AccessComponents {
+    fn none() -> Self {
+        Self {
+            access_control: AccessControl::None,
+            contract: None,
+            import_path: None,
+            initializers: Vec::new(),
+            parameter: None,
+            roles: Vec::new(),
+        }
+    }
+
+    /// The `--access` name of the model in use, `none` without access control
+    fn model(&self) -> &'static str {
+        match self.contract {
+            Some(_) => self.access_control.name(),
+            None => AccessControl::None.name(),
+        }
+    }
+
+    fn modifier(&self, role: Option<&str>) -> Option<String> {
+        self.contract.and_then(|_| access_modifier(self.access_control, role))
+    }
+
+    /// Role constants `AccessControl` contracts declare
+    fn declarations(&self) -> Vec<String> {
+        if self.access_control != AccessControl::Roles {
+            return Vec::new();
+        }
+        self.roles
+            .iter()
+            .map(|role| {
+                let constant = role_constant(role);
+                format!("bytes32 public constant {} = keccak256(\"{}\");", constant, constant)
+            })
+            .collect()
+    }
+
+    /// Statements giving the deployer the admin role and every other role
+    fn grants(&self) -> Vec<String> {
+        if self.access_control != AccessControl::Roles {
+            return Vec::new();
+        }
+        std::iter::once("DEFAULT_ADMIN_ROLE".to_string())
+            .chain(self.roles.iter().map(|role| role_constant(role)))
+            .map(|constant| format!("_grantRole({}, msg.sender);", constant))
+            .collect()
+    }
+}
+
+/// Modifier restricting a function to `role`, or to the owner or admin when
+/// it has none; `None` without access control
+fn access_modifier(access_control: AccessControl, role: Option<&str>) -> Option<String> {
+    match access_control {
+        AccessControl::Ownable | AccessControl::Ownable2Step => Some("onlyOwner".to_string()),
+        AccessControl::Roles => Some(format!(
+            "onlyRole({})",
+            role.map(role_constant).unwrap_or_else(|| "DEFAULT_ADMIN_ROLE".to_string())
+        )),
+        AccessControl::Managed => Some("restricted".to_string()),
+        AccessControl::None => None,
+    }
+}
+
+/// `MINTER_ROLE` for `minter`
+fn role_constant(role: &str) -> String {
+    format!("{}_ROLE", role.to_uppercase())
+}
+
+/// The pieces an extension contributes to a combined contract.
+struct ExtensionComponents {
+    contract: String,
+    /// Path relative to the OpenZeppelin contracts root
+    import_path: String,
+    constructor_args: Option<String>,
+    /// Declarations such as `uint256 cap_`
+    parameters: Vec<String>,
+    constructor_body: Vec<String>,
+    state_variables: Vec<String>,
+    functions: Vec<String>,
+    /// Overridable functions the extension declares, with the contract declaring each
+    virtual_functions: Vec<(OverrideFunction, String)>,
+}
+
+/// Functions that several OpenZeppelin bases declare `virtual`. When a
+/// combined contract inherits more than one declaration, Solidity requires an
+/// explicit `override(A, B, ...)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum OverrideFunction {
+    Erc20Update,
+    Erc721Update,
+    Erc1155Update,
+    IncreaseBalance,
+    OwnerOf,
+    Nonces,
+    Decimals,
+    Allowance,
+    Approve,
+    TokenUri,
+    Uri,
+    SupportsInterface,
+    // v4 hooks, replaced by `_update` in v5
+    Erc20BeforeTokenTransfer,
+    Erc20AfterTokenTransfer,
+    Erc20Mint,
+    Erc20Burn,
+    Erc721BeforeTokenTransfer,
+    Erc721AfterTokenTransfer,
+    Erc721Mint,
+    Erc721Burn,
+    Erc1155BeforeTokenTransfer,
+}
+
+impl OverrideFunction {
+    fn render(self, bases: &str) -> String {
+        match self {
+            OverrideFunction::Erc20Update => format!(
+                "    function _update(address from, address to, uint256 value) internal virtual override({}) {{\n        super._update(from, to, value);\n    }}",
+                bases
+            ),
+            OverrideFunction::Erc721Update => format!(
+                "    function _update(address to, uint256 tokenId, address auth) internal virtual override({}) returns (address) {{\n        return super._update(to, tokenId, auth);\n    }}",
+                bases
+            ),
+            OverrideFunction::Erc1155Update => format!(
+                "    function _update(address from, address to, uint256[] memory ids, uint256[] memory values) internal virtual override({}) {{\n        super._update(from, to, ids, values);\n    }}",
+                bases
+            ),
+            OverrideFunction::IncreaseBalance => format!(
+                "    function _increaseBalance(address account, uint128 value) internal virtual override({}) {{\n        super._increaseBalance(account, value);\n    }}",
+                bases
+            ),
+            OverrideFunction::OwnerOf => format!(
+                "    function _ownerOf(uint256 tokenId) internal view virtual override({}) returns (address) {{\n        return super._ownerOf(tokenId);\n    }}",
+                bases
+            ),
+            OverrideFunction::Nonces => format!(
+                "    function nonces(address owner) public view virtual override({}) returns (uint256) {{\n        return super.nonces(owner);\n    }}",
+                bases
+            ),
+            OverrideFunction::Decimals => format!(
+                "    function decimals() public view virtual override({}) returns (uint8) {{\n        return super.decimals();\n    }}",
+                bases
+            ),
+            OverrideFunction::Allowance => format!(
+                "    function allowance(address owner, address spender) public view virtual override({}) returns (uint256) {{\n        return super.allowance(owner, spender);\n    }}",
+                bases
+            ),
+            OverrideFunction::Approve => format!(
+                "    function _approve(address owner, address spender, uint256 value, bool emitEvent) internal virtual override({}) {{\n        super._approve(owner, spender, value, emitEvent);\n    }}",
+                bases
+            ),
+            OverrideFunction::TokenUri => format!(
+                "    function tokenURI(uint256 tokenId) public view virtual override({}) returns (string memory) {{\n        return super.tokenURI(tokenId);\n    }}",
+                bases
+            ),
+            OverrideFunction::Uri => format!(
+                "    function uri(uint256 tokenId) public view virtual override({}) returns (string memory) {{\n        return super.uri(tokenId);\n    }}",
+                bases
+            ),
+            OverrideFunction::SupportsInterface => format!(
+                "    function supportsInterface(bytes4 interfaceId) public view virtual override({}) returns (bool) {{\n        return super.supportsInterface(interfaceId);\n    }}",
+                bases
+            ),
+            OverrideFunction::Erc20BeforeTokenTransfer => format!(
+                "    function _beforeTokenTransfer(address from, address to, uint256 amount) internal virtual override({}) {{\n        super._beforeTokenTransfer(from, to, amount);\n    }}",
+                bases
+            ),
+            OverrideFunction::Erc20AfterTokenTransfer => format!(
+                "    function _afterTokenTransfer(address from, address to, uint256 amount) internal virtual override({}) {{\n        super._afterTokenTransfer(from, to, amount);\n    }}",
+                bases
+            ),
+            OverrideFunction::Erc20Mint => format!(
+                "    function _mint(address to, uint256 amount) internal virtual override({}) {{\n        super._mint(to, amount);\n    }}",
+                bases
+            ),
+            OverrideFunction::Erc20Burn => format!(
+                "    function _burn(address account, uint256 amount) internal virtual override({}) {{\n        super._burn(account, amount);\n    }}",
+                bases
+            ),
+            OverrideFunction::Erc721BeforeTokenTransfer => format!(
+                "    function _beforeTokenTransfer(address from, address to, uint256 firstTokenId, uint256 batchSize) internal virtual override({}) {{\n        super._beforeTokenTransfer(from, to, firstTokenId, batchSize);\n    }}",
+                bases
+            ),
+            OverrideFunction::Erc721AfterTokenTransfer => format!(
+                "    function _afterTokenTransfer(address from, address to, uint256 firstTokenId, uint256 batchSize) internal virtual override({}) {{\n        super._afterTokenTransfer(from, to, firstTokenId, batchSize);\n    }}",
+                bases
+            ),
+            OverrideFunction::Erc721Mint => format!(
+                "    function _mint(address to, uint256 tokenId) internal virtual override({}) {{\n        super._mint(to, tokenId);\n    }}",
+                bases
+            ),
+            OverrideFunction::Erc721Burn => format!(
+                "    function _burn(uint256 tokenId) internal virtual override({}) {{\n        super._burn(tokenId);\n    }}",
+                bases
+            ),
+            OverrideFunction::Erc1155BeforeTokenTransfer => format!(
+                "    function _beforeTokenTransfer(address operator, address from, address to, uint256[] memory ids, uint256[] memory amounts, bytes memory data) internal virtual override({}) {{\n        super._beforeTokenTransfer(operator, from, to, ids, amounts, data);\n    }}",
+                bases
+            ),
+        }
+    }
+}
+
+/// Function names an extension's `overrides` may list
+pub(crate) const OVERRIDABLE_FUNCTIONS: &[&str] = &[
+    "_update",
+    "_increaseBalance",
+    "_ownerOf",
+    "nonces",
+    "decimals",
+    "allowance",
+    "_approve",
+    "tokenURI",
+    "uri",
+    "supportsInterface",
+    "_beforeTokenTransfer",
+    "_afterTokenTransfer",
+    "_mint",
+    "_burn",
+];
+
+impl OverrideFunction {
+    /// The function `name` refers to on a `base_type` (`erc20`, `erc721` or
+    /// `erc1155`) token; `None` when it is not overridable in `oz_version`, such
+    /// as `_update` in 4.x or the transfer hooks in 5.x
+    fn from_name(name: &str, base_type: &str, oz_version: OpenZeppelinVersion) -> Option<Self> {
+        use OverrideFunction::*;
+
+        let v4 = oz_version == OpenZeppelinVersion::V4;
+        let function = match (name, base_type) {
+            ("_update", "erc20") if !v4 => Erc20Update,
+            ("_update", "erc721") if !v4 => Erc721Update,
+            ("_update", "erc1155") if !v4 => Erc1155Update,
+            ("_increaseBalance", _) if !v4 => IncreaseBalance,
+            ("nonces", _) if !v4 => Nonces,
+            ("allowance", _) if !v4 => Allowance,
+            ("_approve", _) if !v4 => Approve,
+            ("_ownerOf", _) => OwnerOf,
+            ("decimals", _) => Decimals,
+            ("tokenURI", _) => TokenUri,
+            ("uri", _) => Uri,
+            ("supportsInterface", _) => SupportsInterface,
+            ("_beforeTokenTransfer", "erc20") if v4 => Erc20BeforeTokenTransfer,
+            ("_beforeTokenTransfer", "erc721") if v4 => Erc721BeforeTokenTransfer,
+            ("_beforeTokenTransfer", "erc1155") if v4 => Erc1155BeforeTokenTransfer,
+            ("_afterTokenTransfer", "erc20") if v4 => Erc20AfterTokenTransfer,
+            ("_afterTokenTransfer", "erc721") if v4 => Erc721AfterTokenTransfer,
+            ("_mint", "erc20") if v4 => Erc20Mint,
+            ("_mint", "erc721") if v4 => Erc721Mint,
+            ("_burn", "erc20") if v4 => Erc20Burn,
+            ("_burn", "erc721") if v4 => Erc721Burn,
+            _ => return None,
+        };
+        Some(function)
+    }
+}
+
+/// Overridable functions of the contracts that are not registry extensions:
+/// the token bases and `AccessControl`. Extensions list theirs under `overrides`.
+fn builtin_virtual_functions(contract: &str, oz_version: OpenZeppelinVersion) -> Vec<(OverrideFunction, String)> {
+    use OverrideFunction::*;
+
+    let functions: &[OverrideFunction] = match (contract, oz_version) {
+        ("ERC20", OpenZeppelinVersion::V5) => &[Erc20Update, Decimals, Allowance, Approve],
+        ("ERC20", OpenZeppelinVersion::V4) => {
+            &[Erc20BeforeTokenTransfer, Erc20AfterTokenTransfer, Erc20Mint, Erc20Burn, Decimals]
+        }
+        ("ERC721", OpenZeppelinVersion::V5) => &[Erc721Update, IncreaseBalance, OwnerOf, TokenUri, SupportsInterface],
+        ("ERC721", OpenZeppelinVersion::V4) => &[
+            Erc721BeforeTokenTransfer,
+            Erc721AfterTokenTransfer,
+            Erc721Mint,
+            Erc721Burn,
+            OwnerOf,
+            TokenUri,
+            SupportsInterface,
+        ],
+        ("ERC1155", OpenZeppelinVersion::V5) => &[Erc1155Update, Uri, SupportsInterface],
+        ("ERC1155", OpenZeppelinVersion::V4) => &[Erc1155BeforeTokenTransfer, Uri, SupportsInterface],
+        ("AccessControl", _) => &[SupportsInterface],
+        _ => &[],
+    };
+    functions.iter().map(|function| (*function, contract.to_string())).collect()
+}
+
+/// Import of an OpenZeppelin contract, from the upgradeable package when needed;
+/// `@`-prefixed paths are used as is
+fn oz_import(import_path: &str, upgradeable: bool) -> String {
+    if import_path.starts_with('@') {
+        format!("import \"{}\";", import_path)
+    } else if upgradeable {
+        format!(
+            "import \"@openzeppelin/contracts-upgradeable/{}\";",
+            import_path.replace(".sol", "Upgradeable.sol")
+        )
+    } else {
+        format!("import \"@openzeppelin/contracts/{}\";", import_path)
+    }
+}
+
+fn oz_contract_name(contract: &str, upgradeable: bool) -> String {
+    if upgradeable {
+        format!("{}Upgradeable", contract)
+    } else {
+        contract.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -755,7 +1073,7 @@ mod tests {
     #[test]
     fn test_generate_basic_contract() {
         let template = create_test_template(ContractType::Basic);
-        let contract = template.generate_contract();
+        let contract = template.generate_contract().unwrap();
         
         assert!(contract.contains("// SPDX-License-Identifier: MIT"));
         assert!(contract.contains("pragma solidity ^0.8.30"));
@@ -767,7 +1085,7 @@ mod tests {
     #[test]
     fn test_generate_erc20_contract() {
         let template = create_test_template(ContractType::ERC20);
-        let contract = template.generate_contract();
+        let contract = template.generate_contract().unwrap();
         
         assert!(contract.contains("// SPDX-License-Identifier: MIT"));
         assert!(contract.contains("pragma solidity ^0.8.30"));
@@ -780,18 +1098,18 @@ mod tests {
     #[test]
     fn test_generate_erc721_contract() {
         let template = create_test_template(ContractType::ERC721);
-        let contract = template.generate_contract();
+        let contract = template.generate_contract().unwrap();
         
         assert!(contract.contains("import \"@openzeppelin/contracts/token/ERC721/ERC721.sol\""));
         assert!(contract.contains("contract TestContract is ERC721"));
-        assert!(contract.contains("constructor() ERC721(\"TestContract\", \"TST\")"));
+        assert!(contract.contains("constructor() ERC721(\"TestContract\", \"TC\")"));
         assert!(contract.contains("function safeMint"));
     }
 
     #[test]
     fn test_generate_erc1155_contract() {
         let template = create_test_template(ContractType::ERC1155);
-        let contract = template.generate_contract();
+        let contract = template.generate_contract().unwrap();
         
         assert!(contract.contains("import \"@openzeppelin/contracts/token/ERC1155/ERC1155.sol\""));
         assert!(contract.contains("contract TestContract is ERC1155"));
@@ -802,7 +1120,7 @@ mod tests {
     #[test]
     fn test_generate_interface() {
         let template = create_test_template(ContractType::Interface);
-        let contract = template.generate_contract();
+        let contract = template.generate_contract().unwrap();
         
         assert!(contract.contains("interface ITestContract"));
         assert!(contract.contains("/// @title ITestContract"));
@@ -813,7 +1131,7 @@ mod tests {
     #[test]
     fn test_generate_abstract_contract() {
         let template = create_test_template(ContractType::Abstract);
-        let contract = template.generate_contract();
+        let contract = template.generate_contract().unwrap();
         
         assert!(contract.contains("abstract contract TestContract"));
         assert!(contract.contains("/// @title TestContract"));
@@ -824,7 +1142,7 @@ mod tests {
     #[test]
     fn test_generate_library() {
         let template = create_test_template(ContractType::Basic);
-        let library = template.generate_library();
+        let library = template.generate_library().unwrap();
         
         assert!(library.contains("library TestContract"));
         assert!(library.contains("function exampleFunction"));
@@ -835,7 +1153,7 @@ mod tests {
     #[test]
     fn test_generate_test_basic() {
         let template = create_test_template(ContractType::Basic);
-        let test = template.generate_test();
+        let test = template.generate_test().unwrap();
         
         assert!(test.contains("import \"forge-std/Test.sol\""));
         assert!(test.contains("import \"../src/TestContract.sol\""));
@@ -848,15 +1166,45 @@ mod tests {
     #[test]
     fn test_generate_test_erc20() {
         let template = create_test_template(ContractType::ERC20);
-        let test = template.generate_test();
+        let test = template.generate_test().unwrap();
         
         assert!(test.contains("instance = new TestContract(1000000 * 10 ** 18)"));
     }
 
+    #[test]
+    fn test_generate_test_and_script_with_custom_src_paths() {
+        let template = create_test_template(ContractType::Basic)
+            .with_src_paths("../../contracts".to_string(), "../contracts".to_string());
+
+        assert!(template.generate_test().unwrap().contains("import \"../../contracts/TestContract.sol\""));
+        assert!(template.generate_script().unwrap().contains("import \"../contracts/TestContract.sol\""));
+    }
+
+    #[test]
+    fn test_import_paths_rewrite_generated_imports() {
+        let import_paths = ImportPaths {
+            openzeppelin: "oz/".to_string(),
+            openzeppelin_upgradeable: "@openzeppelin-contracts-upgradeable-5.1.0/".to_string(),
+            forge_std: "forge-std-1.9.4/src/".to_string(),
+        };
+
+        let template = create_test_template(ContractType::ERC721).with_import_paths(import_paths.clone());
+        let contract = template.generate_contract().unwrap();
+        assert!(contract.contains("import \"oz/token/ERC721/ERC721.sol\";"));
+        assert!(contract.contains("import \"oz/access/Ownable.sol\";"));
+        assert!(template.generate_test().unwrap().contains("import \"forge-std-1.9.4/src/Test.sol\";"));
+        assert!(template.generate_script().unwrap().contains("import \"forge-std-1.9.4/src/Script.sol\";"));
+
+        let upgradeable = create_test_template(ContractType::ERC20Upgradeable).with_import_paths(import_paths);
+        assert!(upgradeable
+            .generate_contract().unwrap()
+            .contains("import \"@openzeppelin-contracts-upgradeable-5.1.0/token/ERC20/ERC20Upgradeable.sol\";"));
+    }
+
     #[test]
     fn test_generate_script_basic() {
         let template = create_test_template(ContractType::Basic);
-        let script = template.generate_script();
+        let script = template.generate_script().unwrap();
         
         assert!(script.contains("import \"forge-std/Script.sol\""));
         assert!(script.contains("import \"../src/TestContract.sol\""));
@@ -871,7 +1219,7 @@ mod tests {
     #[test]
     fn test_generate_script_erc20() {
         let template = create_test_template(ContractType::ERC20);
-        let script = template.generate_script();
+        let script = template.generate_script().unwrap();
         
         assert!(script.contains("TestContract instance = new TestContract(1000000 * 10 ** 18)"));
     }
@@ -888,7 +1236,7 @@ mod tests {
             "MIT".to_string(),
         );
         
-        let contract = template.generate_contract();
+        let contract = template.generate_contract().unwrap();
         
         assert!(contract.contains("import \"@openzeppelin/contracts/token/ERC20/ERC20.sol\""));
         assert!(contract.contains("import \"@openzeppelin/contracts/token/ERC20/extensions/ERC20Burnable.sol\""));
@@ -910,12 +1258,13 @@ mod tests {
             "MIT".to_string(),
         );
         
-        let contract = template.generate_contract();
+        let contract = template.generate_contract().unwrap();
         
         assert!(contract.contains("import \"@openzeppelin/contracts/token/ERC721/extensions/ERC721Enumerable.sol\""));
         assert!(contract.contains("import \"@openzeppelin/contracts/token/ERC721/extensions/ERC721Burnable.sol\""));
         assert!(contract.contains("contract NFTCollection is ERC721, ERC721Enumerable, ERC721Burnable"));
         assert!(contract.contains("uint256 private _tokenIdCounter"));
+        assert!(contract.contains("function safeMint(address to) public onlyOwner {\n        uint256 tokenId = _tokenIdCounter++;"));
         assert!(contract.contains("function supportsInterface"));
         assert!(contract.contains("function _update"));
         assert!(contract.contains("function _increaseBalance"));
@@ -933,7 +1282,7 @@ mod tests {
             "MIT".to_string(),
         );
         
-        let contract = template.generate_contract();
+        let contract = template.generate_contract().unwrap();
         
         // Should convert ERC20 extensions to ERC721 equivalents
         assert!(contract.contains("ERC721Burnable"));
@@ -968,51 +1317,347 @@ mod tests {
         assert_eq!(symbol, "T");
     }
 
+    #[test]
+    fn test_get_symbol_without_capitals() {
+        let template = SolidityTemplate::new(
+            "token".to_string(),
+            ContractType::ERC20,
+            "0.8.30".to_string(),
+            "MIT".to_string(),
+        );
+
+        assert_eq!(template.get_symbol(), "TOK");
+    }
+
+    #[test]
+    fn test_token_metadata() {
+        let metadata = TokenMetadata {
+            name: Some("Test Dollar".to_string()),
+            symbol: Some("TUSD".to_string()),
+            decimals: Some(6),
+            initial_supply: Some("5_000".to_string()),
+            base_uri: None,
+        };
+        let erc20 = create_test_template(ContractType::ERC20).with_token_metadata(metadata.clone());
+
+        let contract = erc20.generate_contract().unwrap();
+        assert!(contract.contains("ERC20(\"Test Dollar\", \"TUSD\")"));
+        assert!(contract.contains("    function decimals() public view virtual override returns (uint8) {\n        return 6;\n    }\n}"));
+        assert!(erc20.generate_test().unwrap().contains("new TestContract(5_000 * 10 ** 6);"));
+        assert!(erc20.generate_script().unwrap().contains("new TestContract(5_000 * 10 ** 6);"));
+
+        // Configured decimals win over the ones wrappers take from the underlying token
+        let wrapper = create_multi_template(ContractType::ERC20, vec![TokenExtension::ERC20Wrapper(Vec::new()), TokenExtension::ERC20Permit])
+            .with_token_metadata(metadata);
+        let contract = wrapper.generate_contract().unwrap();
+        assert!(contract.contains("ERC20Permit(\"Test Dollar\")"));
+        assert!(contract.contains("function decimals() public view virtual override(ERC20, ERC20Wrapper) returns (uint8) {\n        return 6;"));
+        assert!(!contract.contains("return super.decimals();"));
+        let permit = create_multi_template(ContractType::ERC20, vec![TokenExtension::ERC20Permit])
+            .with_token_metadata(TokenMetadata { decimals: Some(8), ..Default::default() })
+            .generate_contract()
+            .unwrap();
+        assert!(permit.contains("function decimals() public view virtual override returns (uint8) {\n        return 8;"));
+        assert!(!permit.contains("overrides required by Solidity"));
+
+        let base_uri = TokenMetadata {
+            base_uri: Some("ipfs://bafy/".to_string()),
+            ..Default::default()
+        };
+        let erc721 = create_test_template(ContractType::ERC721Upgradeable)
+            .with_token_metadata(base_uri.clone())
+            .generate_contract()
+            .unwrap();
+        assert!(erc721.contains("function _baseURI() internal pure override returns (string memory) {\n        return \"ipfs://bafy/\";"));
+        let erc1155 = create_multi_template(ContractType::ERC1155, vec![TokenExtension::ERC1155Burnable])
+            .with_token_metadata(base_uri)
+            .generate_contract()
+            .unwrap();
+        assert!(erc1155.contains("ERC1155(\"ipfs://bafy/\")"));
+
+        // Defaults leave the generated code as it was
+        let contract = create_test_template(ContractType::ERC20).generate_contract().unwrap();
+        assert!(contract.contains("_mint(msg.sender, initialSupply);\n    }\n}"));
+    }
+
+    #[test]
+    fn test_token_metadata_check() {
+        let decimals = TokenMetadata { decimals: Some(6), ..Default::default() };
+        assert!(decimals.check(&ContractType::ERC20Upgradeable).is_ok());
+        assert!(decimals.check(&ContractType::ERC721).unwrap_err().to_string().contains("only apply to ERC20"));
+        assert!(decimals.check(&ContractType::Basic).is_err());
+
+        let symbol = TokenMetadata { symbol: Some("A B".to_string()), ..Default::default() };
+        assert!(symbol.check(&ContractType::ERC20).is_err());
+        let named_1155 = TokenMetadata { name: Some("Items".to_string()), ..Default::default() };
+        assert!(named_1155.check(&ContractType::ERC1155).unwrap_err().to_string().contains("no name or symbol"));
+        let supply = TokenMetadata { initial_supply: Some("lots".to_string()), ..Default::default() };
+        assert!(supply.check(&ContractType::ERC20).is_err());
+
+        let supply = TokenMetadata { initial_supply: Some("21e6".to_string()), decimals: Some(8), ..Default::default() };
+        assert_eq!(supply.initial_supply_base_units(), format!("21{}", "0".repeat(14)));
+        assert!(supply.check_cap("21e14").is_ok());
+        assert!(supply.check_cap("21 * 10 ** 14").is_ok());
+        let err = supply.check_cap("2_099_999e8").unwrap_err().to_string();
+        assert!(err.contains("Cap `2_099_999e8` is below the initial supply of 21e6 tokens"), "{}", err);
+        let capped = ContractType::MultiInheritance {
+            base_type: Box::new(ContractType::ERC20),
+            extensions: vec![TokenExtension::ERC20Capped(vec!["1000e18".to_string()])],
+        };
+        assert!(TokenMetadata::default().check(&capped).unwrap_err().to_string().contains("below the initial supply"));
+        assert_eq!(TokenMetadata::default().initial_supply_base_units(), format!("1{}", "0".repeat(24)));
+    }
+
     #[test]
     fn test_generate_state_variables() {
         let template = create_test_template(ContractType::Basic);
         
-        let state_vars = template.generate_state_variables(&[TokenExtension::ERC721Enumerable]);
-        assert!(state_vars.contains("uint256 private _tokenIdCounter"));
+        let state_vars = template.generate_state_variables(&[TokenExtension::ERC20FlashMint(Vec::new())], false).unwrap();
+        assert!(state_vars.contains("uint256 private _flashLoanFee"));
         
-        let empty_state_vars = template.generate_state_variables(&[TokenExtension::ERC20Pausable]);
+        let empty_state_vars = template.generate_state_variables(&[TokenExtension::ERC20Pausable], false).unwrap();
         assert!(empty_state_vars.is_empty());
     }
 
     #[test]
     fn test_extension_components_erc20_burnable() {
         let template = create_test_template(ContractType::Basic);
-        let (import, inherit, init, func) = template.get_extension_components(&TokenExtension::ERC20Burnable);
+        let components = template.get_extension_components(&TokenExtension::ERC20Burnable, false).unwrap();
         
-        assert!(import.contains("ERC20Burnable.sol"));
-        assert_eq!(inherit, "ERC20Burnable");
-        assert!(init.is_empty());
-        assert!(func.is_empty());
+        assert!(components.import_path.contains("ERC20Burnable.sol"));
+        assert_eq!(components.contract, "ERC20Burnable");
+        assert!(components.constructor_args.is_none());
+        assert!(components.functions.is_empty());
     }
 
     #[test]
     fn test_extension_components_pausable() {
         let template = create_test_template(ContractType::Basic);
-        let (import, inherit, init, func) = template.get_extension_components(&TokenExtension::ERC20Pausable);
+        let components = template.get_extension_components(&TokenExtension::ERC20Pausable, false).unwrap();
+        let functions = components.functions.join("\n");
         
-        assert!(import.contains("ERC20Pausable.sol"));
-        assert_eq!(inherit, "ERC20Pausable");
-        assert!(init.is_empty());
-        assert!(func.contains("function pause()"));
-        assert!(func.contains("function unpause()"));
+        assert!(components.import_path.contains("ERC20Pausable.sol"));
+        assert_eq!(components.contract, "ERC20Pausable");
+        assert!(components.constructor_args.is_none());
+        assert!(functions.contains("function pause()"));
+        assert!(functions.contains("function unpause()"));
     }
 
     #[test]
     fn test_extension_components_enumerable() {
-        let template = create_test_template(ContractType::Basic);
-        let (import, inherit, init, func) = template.get_extension_components(&TokenExtension::ERC721Enumerable);
+        let template = create_test_template(ContractType::ERC721);
+        let components = template.get_extension_components(&TokenExtension::ERC721Enumerable, false).unwrap();
         
-        assert!(import.contains("ERC721Enumerable.sol"));
-        assert_eq!(inherit, "ERC721Enumerable");
-        assert!(init.is_empty());
-        assert!(func.contains("function supportsInterface"));
-        assert!(func.contains("function _update"));
-        assert!(func.contains("function _increaseBalance"));
+        assert!(components.import_path.contains("ERC721Enumerable.sol"));
+        assert_eq!(components.contract, "ERC721Enumerable");
+        assert!(components.constructor_args.is_none());
+        // Overrides are resolved for the whole contract, not per extension
+        assert!(components.functions.is_empty());
+        let declared = |function| (function, "ERC721Enumerable".to_string());
+        assert_eq!(
+            components.virtual_functions,
+            [
+                declared(OverrideFunction::Erc721Update),
+                declared(OverrideFunction::IncreaseBalance),
+                declared(OverrideFunction::SupportsInterface),
+            ]
+        );
+
+        let v4 = template.with_oz_version(OpenZeppelinVersion::V4);
+        let components = v4.get_extension_components(&TokenExtension::ERC721Enumerable, false).unwrap();
+        assert_eq!(
+            components.virtual_functions,
+            [declared(OverrideFunction::Erc721BeforeTokenTransfer), declared(OverrideFunction::SupportsInterface)]
+        );
+    }
+
+    fn create_multi_template(base_type: ContractType, extensions: Vec<TokenExtension>) -> SolidityTemplate {
+        SolidityTemplate::new(
+            "MultiToken".to_string(),
+            ContractType::MultiInheritance {
+                base_type: Box::new(base_type),
+                extensions,
+            },
+            "0.8.30".to_string(),
+            "MIT".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_custom_extension_overrides() {
+        let mut registry = ExtensionRegistry::builtin();
+        registry
+            .register(
+                "[[extension]]\nid = \"ERC20Blocklist\"\nname = \"blocklist\"\nbase_types = [\"erc20\"]\nimport = \"@acme/ERC20Blocklist.sol\"\noverrides = [\"_update\", \"_beforeTokenTransfer\"]\n",
+                "acme.toml",
+            )
+            .unwrap();
+        let extensions = vec![TokenExtension::ERC20Pausable, TokenExtension::Custom("ERC20Blocklist".to_string(), Vec::new())];
+
+        let v5 = create_multi_template(ContractType::ERC20, extensions.clone())
+            .with_extensions(registry.clone())
+            .generate_contract().unwrap();
+        assert!(v5.contains("function _update(address from, address to, uint256 value) internal virtual override(ERC20, ERC20Pausable, ERC20Blocklist)"));
+        assert!(!v5.contains("_beforeTokenTransfer"));
+
+        let v4 = create_multi_template(ContractType::ERC20, extensions)
+            .with_extensions(registry)
+            .with_oz_version(OpenZeppelinVersion::V4)
+            .generate_contract().unwrap();
+        assert!(v4.contains("override(ERC20, ERC20Pausable, ERC20Blocklist) {\n        super._beforeTokenTransfer(from, to, amount);"));
+    }
+
+    #[test]
+    fn test_erc20_pausable_votes_permit_overrides() {
+        let contract = create_multi_template(
+            ContractType::ERC20,
+            vec![TokenExtension::ERC20Pausable, TokenExtension::ERC20Permit, TokenExtension::ERC20Votes],
+        )
+        .generate_contract().unwrap();
+
+        assert!(contract.contains("contract MultiToken is ERC20, ERC20Pausable, ERC20Permit, ERC20Votes, Ownable"));
+        assert!(contract.contains("ERC20Permit(\"MultiToken\")"));
+        assert!(contract.contains("function _update(address from, address to, uint256 value) internal virtual override(ERC20, ERC20Pausable, ERC20Votes)"));
+        assert!(contract.contains("function nonces(address owner) public view virtual override(ERC20Permit, Nonces) returns (uint256)"));
+        // Permit already provides the EIP712 domain
+        assert!(!contract.contains("EIP712("));
+        assert_eq!(contract.matches("function _update(").count(), 1);
+    }
+
+    #[test]
+    fn test_erc20_votes_without_permit_adds_eip712() {
+        let contract = create_multi_template(ContractType::ERC20, vec![TokenExtension::ERC20Votes]).generate_contract().unwrap();
+
+        assert!(contract.contains("import \"@openzeppelin/contracts/utils/cryptography/EIP712.sol\""));
+        assert!(contract.contains("is ERC20, EIP712, ERC20Votes, Ownable"));
+        assert!(contract.contains("EIP712(\"MultiToken\", \"1\")"));
+        assert!(contract.contains("override(ERC20, ERC20Votes)"));
+        assert!(!contract.contains("function nonces"));
+    }
+
+    #[test]
+    fn test_erc721_enumerable_uristorage_overrides() {
+        let contract = create_multi_template(
+            ContractType::ERC721,
+            vec![TokenExtension::ERC721Enumerable, TokenExtension::ERC721URIStorage],
+        )
+        .generate_contract().unwrap();
+
+        assert!(contract.contains("override(ERC721, ERC721Enumerable) returns (address)"));
+        assert!(contract.contains("function _increaseBalance(address account, uint128 value) internal virtual override(ERC721, ERC721Enumerable)"));
+        assert!(contract.contains("function tokenURI(uint256 tokenId) public view virtual override(ERC721, ERC721URIStorage)"));
+        assert!(contract.contains("function supportsInterface(bytes4 interfaceId) public view virtual override(ERC721, ERC721Enumerable, ERC721URIStorage)"));
+        assert_eq!(contract.matches("function supportsInterface(").count(), 1);
+    }
+
+    #[test]
+    fn test_single_declaration_needs_no_override() {
+        let contract = create_multi_template(ContractType::ERC20, vec![TokenExtension::ERC20Burnable]).generate_contract().unwrap();
+
+        assert!(!contract.contains("override("));
+        assert!(!contract.contains("overrides required by Solidity"));
+    }
+
+    #[test]
+    fn test_erc1155_pausable_supply_overrides() {
+        let contract = create_multi_template(
+            ContractType::ERC1155,
+            vec![TokenExtension::ERC1155Pausable, TokenExtension::ERC1155Supply],
+        )
+        .generate_contract().unwrap();
+
+        assert!(contract.contains("uint256[] memory values) internal virtual override(ERC1155, ERC1155Pausable, ERC1155Supply)"));
+    }
+
+    #[test]
+    fn test_erc20_extension_overrides_and_constructor_args() {
+        let contract = create_multi_template(
+            ContractType::ERC20,
+            vec![
+                TokenExtension::ERC20Capped(Vec::new()),
+                TokenExtension::ERC20TemporaryApproval,
+                TokenExtension::ERC20Bridgeable,
+                TokenExtension::ERC1363,
+            ],
+        )
+        .generate_contract().unwrap();
+
+        assert!(contract.contains("constructor(uint256 initialSupply, uint256 cap_) ERC20(\"MultiToken\", \"MT\") ERC20Capped(cap_)"));
+        assert!(contract.contains("override(ERC20, ERC20Capped)"));
+        assert!(contract.contains("function allowance(address owner, address spender) public view virtual override(ERC20, ERC20TemporaryApproval)"));
+        assert!(contract.contains("function _approve(address owner, address spender, uint256 value, bool emitEvent) internal virtual override(ERC20, ERC20TemporaryApproval)"));
+        assert!(contract.contains("override(ERC20Bridgeable, ERC1363) returns (bool)"));
+        assert!(contract.contains("function _checkTokenBridge(address caller)"));
+    }
+
+    #[test]
+    fn test_default_cap_covers_the_initial_supply() {
+        let capped = |initial_supply: &str| {
+            create_multi_template(ContractType::ERC20, vec![TokenExtension::ERC20Capped(Vec::new())])
+                .with_token_metadata(TokenMetadata { initial_supply: Some(initial_supply.to_string()), ..Default::default() })
+                .generate_test().unwrap()
+        };
+
+        assert!(capped("1000000").contains("new MultiToken(1000000 * 10 ** 18, 1000000000 * 10 ** 18);"));
+        assert!(capped("21e9").contains("new MultiToken(21e9 * 10 ** 18, 21e9 * 10 ** 18);"));
+    }
+
+    #[test]
+    fn test_extension_parameters_become_constructor_parameters() {
+        let template = create_multi_template(
+            ContractType::ERC721,
+            vec![
+                TokenExtension::ERC721Royalty(vec!["0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string(), "250".to_string()]),
+                TokenExtension::ERC721Wrapper(Vec::new()),
+            ],
+        );
+
+        let contract = template.generate_contract().unwrap();
+        assert!(contract.contains("constructor(address royaltyReceiver, uint96 royaltyFee, address underlyingToken)"));
+        assert!(contract.contains("ERC721Wrapper(IERC721(underlyingToken))"));
+        assert!(contract.contains("        _setDefaultRoyalty(royaltyReceiver, royaltyFee);\n    }"));
+        assert!(!contract.contains("UNDERLYING_TOKEN"));
+
+        // Given values first, defaults for the rest
+        let deploy = "new MultiToken(0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed, 250, address(0));";
+        assert!(template.generate_test().unwrap().contains(deploy));
+        assert!(template.generate_script().unwrap().contains(deploy));
+
+        let flash_mint = create_multi_template(ContractType::ERC20, vec![TokenExtension::ERC20FlashMint(Vec::new())]);
+        let contract = flash_mint.generate_contract().unwrap();
+        assert!(contract.contains("        _flashLoanFee = flashLoanFee;\n        _mint(msg.sender, initialSupply);"));
+        assert!(flash_mint.generate_test().unwrap().contains("new MultiToken(1000000 * 10 ** 18, 0);"));
+    }
+
+    #[test]
+    fn test_duplicate_extensions_inherited_once() {
+        let contract = create_multi_template(
+            ContractType::ERC20,
+            vec![TokenExtension::ERC20Burnable, TokenExtension::ERC20Burnable],
+        )
+        .generate_contract().unwrap();
+
+        assert!(contract.contains("is ERC20, ERC20Burnable, Ownable {"));
+        assert_eq!(contract.matches("ERC20Burnable.sol").count(), 1);
+    }
+
+    #[test]
+    fn test_upgradeable_multi_inheritance_overrides() {
+        let contract = create_multi_template(
+            ContractType::ERC20Upgradeable,
+            vec![TokenExtension::ERC20Pausable, TokenExtension::ERC20Permit, TokenExtension::ERC20Votes],
+        )
+        .generate_contract().unwrap();
+
+        assert!(contract.contains("import \"@openzeppelin/contracts-upgradeable/token/ERC20/extensions/ERC20PausableUpgradeable.sol\""));
+        assert!(contract.contains("is Initializable, ERC20Upgradeable, ERC20PausableUpgradeable, ERC20PermitUpgradeable, ERC20VotesUpgradeable, OwnableUpgradeable, UUPSUpgradeable"));
+        assert!(contract.contains("__ERC20_init(\"MultiToken\", \"MT\");"));
+        assert!(contract.contains("__ERC20Permit_init(\"MultiToken\");"));
+        assert!(contract.contains("__Ownable_init(msg.sender);"));
+        assert!(contract.contains("function initialize(uint256 initialSupply) public initializer"));
+        assert!(contract.contains("override(ERC20Upgradeable, ERC20PausableUpgradeable, ERC20VotesUpgradeable)"));
+        assert!(contract.contains("override(ERC20PermitUpgradeable, NoncesUpgradeable)"));
+        assert!(contract.contains("function _authorizeUpgrade"));
     }
 
     #[test]
@@ -1027,23 +1672,269 @@ mod tests {
             "MIT".to_string(),
         );
         
-        let contract = template.generate_contract();
+        let contract = template.generate_contract().unwrap();
         
         assert!(contract.contains("ERC1155URIStorage"));
         assert!(contract.contains("function uri(uint256 tokenId)"));
     }
 
+    #[test]
+    fn test_v4_single_templates_use_implicit_owner() {
+        let erc721 = create_test_template(ContractType::ERC721)
+            .with_oz_version(OpenZeppelinVersion::V4)
+            .generate_contract().unwrap();
+        assert!(erc721.contains("constructor() ERC721(\"TestContract\", \"TC\") {}"));
+        assert!(!erc721.contains("Ownable(msg.sender)"));
+
+        let erc1155 = create_test_template(ContractType::ERC1155)
+            .with_oz_version(OpenZeppelinVersion::V4)
+            .generate_contract().unwrap();
+        assert!(!erc1155.contains("Ownable(msg.sender)"));
+
+        let upgradeable = create_test_template(ContractType::ERC20Upgradeable)
+            .with_oz_version(OpenZeppelinVersion::V4)
+            .generate_contract().unwrap();
+        assert!(upgradeable.contains("__Ownable_init();"));
+
+        let v5 = create_test_template(ContractType::ERC721).generate_contract().unwrap();
+        assert!(v5.contains("ERC721(\"TestContract\", \"TC\") Ownable(msg.sender) {}"));
+    }
+
+    #[test]
+    fn test_v4_erc20_overrides_use_transfer_hooks() {
+        let contract = create_multi_template(
+            ContractType::ERC20,
+            vec![TokenExtension::ERC20Pausable, TokenExtension::ERC20Votes, TokenExtension::ERC20Capped(Vec::new())],
+        )
+        .with_oz_version(OpenZeppelinVersion::V4)
+        .generate_contract().unwrap();
+
+        // v4 ERC20Votes builds on ERC20Permit, which brings its own EIP712 domain
+        assert!(contract.contains("is ERC20, ERC20Pausable, ERC20Permit, ERC20Votes, ERC20Capped, Ownable {"));
+        assert!(!contract.contains("EIP712("));
+        assert!(contract.contains("function _beforeTokenTransfer(address from, address to, uint256 amount) internal virtual override(ERC20, ERC20Pausable)"));
+        assert!(contract.contains("function _afterTokenTransfer(address from, address to, uint256 amount) internal virtual override(ERC20, ERC20Votes)"));
+        assert!(contract.contains("function _mint(address to, uint256 amount) internal virtual override(ERC20, ERC20Votes, ERC20Capped)"));
+        assert!(contract.contains("function _burn(address account, uint256 amount) internal virtual override(ERC20, ERC20Votes)"));
+        assert!(!contract.contains("function _update("));
+        assert!(!contract.contains("function nonces("));
+        assert!(!contract.contains("Ownable(msg.sender)"));
+    }
+
+    #[test]
+    fn test_v4_permit_listed_after_votes_comes_first() {
+        let contract = create_multi_template(
+            ContractType::ERC20,
+            vec![TokenExtension::ERC20Pausable, TokenExtension::ERC20Votes, TokenExtension::ERC20Permit],
+        )
+        .with_oz_version(OpenZeppelinVersion::V4)
+        .generate_contract().unwrap();
+
+        assert!(contract.contains("is ERC20, ERC20Pausable, ERC20Permit, ERC20Votes, Ownable {"));
+        assert_eq!(contract.matches("ERC20Permit(\"MultiToken\")").count(), 1);
+    }
+
+    #[test]
+    fn test_v4_erc721_and_erc1155_overrides() {
+        let erc721 = create_multi_template(
+            ContractType::ERC721,
+            vec![TokenExtension::ERC721Enumerable, TokenExtension::ERC721URIStorage],
+        )
+        .with_oz_version(OpenZeppelinVersion::V4)
+        .generate_contract().unwrap();
+
+        assert!(erc721.contains("function _beforeTokenTransfer(address from, address to, uint256 firstTokenId, uint256 batchSize) internal virtual override(ERC721, ERC721Enumerable)"));
+        assert!(erc721.contains("function _burn(uint256 tokenId) internal virtual override(ERC721, ERC721URIStorage)"));
+        assert!(erc721.contains("override(ERC721, ERC721Enumerable, ERC721URIStorage) returns (bool)"));
+        assert!(!erc721.contains("_increaseBalance"));
+
+        let erc1155 = create_multi_template(
+            ContractType::ERC1155,
+            vec![TokenExtension::ERC1155Pausable, TokenExtension::ERC1155Supply],
+        )
+        .with_oz_version(OpenZeppelinVersion::V4)
+        .generate_contract().unwrap();
+
+        assert!(erc1155.contains("uint256[] memory amounts, bytes memory data) internal virtual override(ERC1155, ERC1155Pausable, ERC1155Supply)"));
+    }
+
+    #[test]
+    fn test_v4_upgradeable_multi_inheritance() {
+        let contract = create_multi_template(ContractType::ERC20Upgradeable, vec![TokenExtension::ERC4626(Vec::new())])
+            .with_oz_version(OpenZeppelinVersion::V4)
+            .generate_contract().unwrap();
+
+        assert!(contract.contains("function initialize(uint256 initialSupply, address asset_) public initializer"));
+        assert!(contract.contains("__ERC4626_init(IERC20Upgradeable(asset_));"));
+        assert!(contract.contains("__Ownable_init();"));
+        assert!(contract.contains("override(ERC20Upgradeable, ERC4626Upgradeable) returns (uint8)"));
+    }
+
+    #[test]
+    fn test_access_control_models() {
+        let contract = |access_control| {
+            create_test_template(ContractType::ERC721)
+                .with_access_control(access_control)
+                .generate_contract().unwrap()
+        };
+
+        let two_step = contract(AccessControl::Ownable2Step);
+        assert!(two_step.contains("import \"@openzeppelin/contracts/access/Ownable2Step.sol\";"));
+        assert!(two_step.contains("is ERC721, Ownable2Step {"));
+        assert!(two_step.contains("ERC721(\"TestContract\", \"TC\") Ownable(msg.sender) {}"));
+        assert!(two_step.contains("function safeMint(address to) public onlyOwner"));
+
+        let roles = contract(AccessControl::Roles);
+        assert!(roles.contains("import \"@openzeppelin/contracts/access/AccessControl.sol\";"));
+        assert!(roles.contains("is ERC721, AccessControl {"));
+        assert!(roles.contains("bytes32 public constant MINTER_ROLE = keccak256(\"MINTER_ROLE\");"));
+        assert!(roles.contains("_grantRole(DEFAULT_ADMIN_ROLE, msg.sender);"));
+        assert!(roles.contains("_grantRole(MINTER_ROLE, msg.sender);"));
+        assert!(roles.contains("function safeMint(address to) public onlyRole(MINTER_ROLE)"));
+        assert!(roles.contains("override(ERC721, AccessControl)"));
+        assert!(!roles.contains("Ownable"));
+
+        let managed = contract(AccessControl::Managed);
+        assert!(managed.contains("import \"@openzeppelin/contracts/access/manager/AccessManaged.sol\";"));
+        assert!(managed.contains("constructor(address initialAuthority) ERC721(\"TestContract\", \"TC\") AccessManaged(initialAuthority) {}"));
+        assert!(managed.contains("function safeMint(address to) public restricted"));
+
+        let none = contract(AccessControl::None);
+        assert!(none.contains("contract TestContract is ERC721 {"));
+        assert!(none.contains("constructor() ERC721(\"TestContract\", \"TC\") {}"));
+        assert!(!none.contains("import \"@openzeppelin/contracts/access"));
+        assert!(none.contains("uint256 private _tokenIdCounter;"));
+        assert!(none.contains("function safeMint(address to) public {"));
+
+        let none = create_test_template(ContractType::ERC1155)
+            .with_access_control(AccessControl::None)
+            .generate_contract().unwrap();
+        assert!(none.contains("function mint(address to, uint256 id, uint256 amount, bytes memory data) public {"));
+    }
+
+    #[test]
+    fn test_access_control_upgradeable_roles() {
+        let contract = create_test_template(ContractType::ERC1155Upgradeable)
+            .with_access_control(AccessControl::Roles)
+            .generate_contract().unwrap();
+
+        assert!(contract.contains("import \"@openzeppelin/contracts-upgradeable/access/AccessControlUpgradeable.sol\";"));
+        assert!(contract.contains("ERC1155Upgradeable, AccessControlUpgradeable, UUPSUpgradeable"));
+        assert!(contract.contains("bytes32 public constant UPGRADER_ROLE = keccak256(\"UPGRADER_ROLE\");"));
+        assert!(contract.contains("__AccessControl_init();"));
+        assert!(contract.contains("_grantRole(UPGRADER_ROLE, msg.sender);"));
+        assert!(contract.contains("function _authorizeUpgrade(address newImplementation) internal override onlyRole(UPGRADER_ROLE) {}"));
+        assert!(contract.contains("override(ERC1155Upgradeable, AccessControlUpgradeable)"));
+    }
+
+    #[test]
+    fn test_upgradeable_test_and_script_deploy_behind_proxy() {
+        let template = create_test_template(ContractType::ERC20Upgradeable);
+        let deploy = "TestContract implementation = new TestContract();";
+        let proxy = "new ERC1967Proxy(address(implementation), abi.encodeCall(TestContract.initialize, (1000000 * 10 ** 18)))";
+
+        let test = template.generate_test().unwrap();
+        assert!(test.contains("import \"@openzeppelin/contracts/proxy/ERC1967/ERC1967Proxy.sol\";"));
+        assert!(test.contains(deploy));
+        assert!(test.contains(&format!("instance = TestContract(address({}));", proxy)));
+        assert!(test.contains("assertEq(instance.owner(), address(this));"));
+        assert!(!test.contains("new TestContract(1000000"));
+
+        let script = template.generate_script().unwrap();
+        assert!(script.contains(deploy));
+        assert!(script.contains(proxy));
+
+        let managed = create_multi_template(ContractType::ERC721Upgradeable, vec![TokenExtension::ERC721Royalty(Vec::new())])
+            .with_access_control(AccessControl::Managed)
+            .generate_test().unwrap();
+        assert!(managed.contains("manager = new AccessManager(address(this));"));
+        assert!(managed.contains("abi.encodeCall(MultiToken.initialize, (address(manager), msg.sender, 500))"));
+        assert!(managed.contains("assertEq(instance.authority(), address(manager));"));
+    }
+
+    #[test]
+    fn test_access_managed_test_and_script_deploy_manager() {
+        let template = create_test_template(ContractType::ERC721).with_access_control(AccessControl::Managed);
+
+        let test = template.generate_test().unwrap();
+        assert!(test.contains("manager = new AccessManager(address(this));"));
+        assert!(test.contains("instance = new TestContract(address(manager));"));
+        assert!(test.contains("assertEq(instance.authority(), address(manager));"));
+
+        let script = template.generate_script().unwrap();
+        assert!(script.contains("AccessManager manager = new AccessManager(vm.addr(deployerPrivateKey));"));
+        assert!(script.contains("new TestContract(address(manager))"));
+
+        let roles_test = create_test_template(ContractType::ERC721)
+            .with_access_control(AccessControl::Roles)
+            .generate_test().unwrap();
+        assert!(roles_test.contains("assertTrue(instance.hasRole(instance.DEFAULT_ADMIN_ROLE(), address(this)));"));
+        assert!(!roles_test.contains("AccessManager"));
+    }
+
+    #[test]
+    fn test_access_control_multi_inheritance_roles() {
+        let contract = create_multi_template(ContractType::ERC721, vec![TokenExtension::ERC721Pausable])
+            .with_access_control(AccessControl::Roles)
+            .generate_contract().unwrap();
+
+        assert!(contract.contains("is ERC721, ERC721Pausable, AccessControl {"));
+        assert!(contract.contains("bytes32 public constant PAUSER_ROLE = keccak256(\"PAUSER_ROLE\");"));
+        assert!(contract.contains("_grantRole(PAUSER_ROLE, msg.sender);"));
+        assert!(contract.contains("function pause() public onlyRole(PAUSER_ROLE)"));
+        assert!(contract.contains("bytes32 public constant MINTER_ROLE = keccak256(\"MINTER_ROLE\");"));
+        assert!(contract.contains("function safeMint(address to) public onlyRole(MINTER_ROLE)"));
+        assert!(contract.contains("override(ERC721, AccessControl)"));
+
+        let err = create_multi_template(ContractType::ERC721, vec![TokenExtension::ERC721Pausable])
+            .with_access_control(AccessControl::None)
+            .generate_contract().unwrap_err();
+        assert!(err.to_string().contains("`pausable` restricts its functions to the pauser role"), "{}", err);
+
+        let none = create_multi_template(ContractType::ERC721, vec![TokenExtension::ERC721Burnable])
+            .with_access_control(AccessControl::None)
+            .generate_contract().unwrap();
+        assert!(none.contains("is ERC721, ERC721Burnable {"));
+        assert!(none.contains("uint256 private _tokenIdCounter;"));
+        assert!(none.contains("function safeMint(address to) public {"));
+    }
+
+    #[test]
+    fn test_access_control_rejected_combinations() {
+        let err = create_test_template(ContractType::ERC721Upgradeable)
+            .with_access_control(AccessControl::None)
+            .generate_contract().unwrap_err();
+        assert!(err.to_string().contains("Upgradeable contracts need access control"));
+
+        let err = create_test_template(ContractType::ERC721)
+            .with_oz_version(OpenZeppelinVersion::V4)
+            .with_access_control(AccessControl::Managed)
+            .generate_contract().unwrap_err();
+        assert!(err.to_string().contains("access-managed needs OpenZeppelin Contracts 5.x"));
+    }
+
+    #[test]
+    fn test_openzeppelin_version_from_release() {
+        assert_eq!(OpenZeppelinVersion::from_release("4.9.6"), Some(OpenZeppelinVersion::V4));
+        assert_eq!(OpenZeppelinVersion::from_release("v5.1.0"), Some(OpenZeppelinVersion::V5));
+        assert_eq!(OpenZeppelinVersion::from_release("5"), Some(OpenZeppelinVersion::V5));
+        assert_eq!(OpenZeppelinVersion::from_release("3.4.2"), None);
+        assert!(!OpenZeppelinVersion::V4.supports(&TokenExtension::ERC1363));
+        assert!(OpenZeppelinVersion::V4.supports(&TokenExtension::ERC20Votes));
+        assert!(OpenZeppelinVersion::V5.supports(&TokenExtension::ERC20Bridgeable));
+    }
+
     #[test]
     fn test_template_trait_implementation() {
         let template = create_test_template(ContractType::Basic);
         
         // Test that Template trait methods work
-        let contract = Template::generate_contract(&template);
-        let test = Template::generate_test(&template);
-        let script = Template::generate_script(&template);
-        let library = Template::generate_library(&template);
-        let interface = Template::generate_interface(&template);
-        let abstract_contract = Template::generate_abstract_contract(&template);
+        let contract = Template::generate_contract(&template).unwrap();
+        let test = Template::generate_test(&template).unwrap();
+        let script = Template::generate_script(&template).unwrap();
+        let library = Template::generate_library(&template).unwrap();
+        let interface = Template::generate_interface(&template).unwrap();
+        let abstract_contract = Template::generate_abstract_contract(&template).unwrap();
         
         assert!(contract.contains("contract TestContract"));
         assert!(test.contains("TestContractTest"));
@@ -1056,12 +1947,14 @@ mod tests {
     #[test]
     fn test_upgradeable_contracts() {
         let template = create_test_template(ContractType::ERC20Upgradeable);
-        let contract = template.generate_contract();
+        let contract = template.generate_contract().unwrap();
         
         assert!(contract.contains("import \"@openzeppelin/contracts-upgradeable"));
         assert!(contract.contains("Initializable"));
         assert!(contract.contains("function initialize"));
-        assert!(!contract.contains("constructor"));
+        // The only constructor locks the implementation; setup happens in initialize
+        assert!(contract.contains("constructor() {\n        _disableInitializers();\n    }"));
+        assert!(!contract.contains("constructor(uint256"));
     }
 
     #[test]
@@ -1069,15 +1962,15 @@ mod tests {
         let extensions = vec![
             TokenExtension::ERC20Permit,
             TokenExtension::ERC20Burnable,
-            TokenExtension::ERC20Capped,
+            TokenExtension::ERC20Capped(Vec::new()),
             TokenExtension::ERC20Pausable,
             TokenExtension::ERC20Votes,
-            TokenExtension::ERC20Wrapper,
-            TokenExtension::ERC20FlashMint,
+            TokenExtension::ERC20Wrapper(Vec::new()),
+            TokenExtension::ERC20FlashMint(Vec::new()),
             TokenExtension::ERC20TemporaryApproval,
             TokenExtension::ERC20Bridgeable,
             TokenExtension::ERC1363,
-            TokenExtension::ERC4626,
+            TokenExtension::ERC4626(Vec::new()),
         ];
         
         for extension in extensions {
@@ -1091,7 +1984,7 @@ mod tests {
                 "MIT".to_string(),
             );
             
-            let contract = template.generate_contract();
+            let contract = template.generate_contract().unwrap();
             assert!(contract.contains("TestToken"));
             assert!(contract.contains("import"));
         }
@@ -1106,7 +1999,7 @@ mod tests {
             "MIT".to_string(),
         );
         
-        let contract = template.generate_contract();
+        let contract = template.generate_contract().unwrap();
         assert!(contract.contains("contract My_Token_123"));
     }
 
@@ -1119,7 +2012,7 @@ mod tests {
             "MIT".to_string(),
         );
         
-        let contract = template.generate_contract();
+        let contract = template.generate_contract().unwrap();
         assert!(contract.contains("pragma solidity ^0.8.19"));
     }
 
@@ -1135,8 +2028,29 @@ mod tests {
                 license.to_string(),
             );
             
-            let contract = template.generate_contract();
+            let contract = template.generate_contract().unwrap();
             assert!(contract.contains(&format!("// SPDX-License-Identifier: {}", license)));
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_natspec_style_and_author() {
+        let template = |natspec| {
+            create_test_template(ContractType::ERC20).with_natspec(natspec, Some("Acme Labs".to_string()))
+        };
+
+        let library = template(NatSpecStyle::Full).generate_library().unwrap();
+        assert!(library.contains("/// @title TestContract\n/// @author Acme Labs\n/// @notice A library contract"));
+
+        let interface = template(NatSpecStyle::Minimal).generate_interface().unwrap();
+        assert!(interface.contains("/// @title ITestContract\n/// @author Acme Labs\ninterface ITestContract"));
+        assert!(!interface.contains("@notice"));
+
+        let contract = template(NatSpecStyle::Minimal).generate_contract().unwrap();
+        assert!(contract.contains("/// @title TestContract\n/// @author Acme Labs\ncontract TestContract is ERC20"));
+
+        let abstract_contract = template(NatSpecStyle::None).generate_abstract_contract().unwrap();
+        assert!(!abstract_contract.contains("///"));
+        assert!(!template(NatSpecStyle::None).generate_contract().unwrap().contains("@author"));
+    }
+}
@@ -1,8 +1,33 @@
-use super::{ContractType, TokenExtension, Template};
+use super::{ContractType, Template, Templates, TokenMetadata};
+use crate::error::{GramrError, Result};
+use crate::extensions::ExtensionRegistry;
+use minijinja::{context, Value};
+
+/// Registry ids of the extensions OpenZeppelin Stylus provides, with the name
+/// the token templates test for in `extensions`
+const STYLUS_EXTENSIONS: &[(&str, &str)] = &[
+    ("ERC20Burnable", "burnable"),
+    ("ERC20Pausable", "pausable"),
+    ("ERC20Capped", "capped"),
+    ("ERC20Permit", "permit"),
+    ("ERC721Burnable", "burnable"),
+    ("ERC721Pausable", "pausable"),
+    ("ERC721Enumerable", "enumerable"),
+    ("ERC721URIStorage", "uristorage"),
+    ("ERC1155Burnable", "burnable"),
+    ("ERC1155Pausable", "pausable"),
+    ("ERC1155Supply", "supply"),
+    ("ERC1155URIStorage", "uristorage"),
+];
 
 pub struct StylusTemplate {
     contract_name: String,
     contract_type: ContractType,
+    templates: Templates,
+    metadata: TokenMetadata,
+    extensions: ExtensionRegistry,
+    with_test: bool,
+    functions: Vec<String>,
 }
 
 impl StylusTemplate {
@@ -10,502 +35,381 @@ impl StylusTemplate {
         Self {
             contract_name,
             contract_type,
+            templates: Templates::builtin(),
+            metadata: TokenMetadata::default(),
+            extensions: ExtensionRegistry::builtin(),
+            with_test: false,
+            functions: Vec::new(),
         }
     }
-    
-    fn generate_basic_contract(&self) -> String {
-        format!(
-r#"#![cfg_attr(not(feature = "export-abi"), no_std)]
-
-extern crate alloc;
-
-use stylus_sdk::{{prelude::*, msg}};
-use alloc::string::String;
-use alloc::vec::Vec;
-
-/// Entrypoint for the {} contract
-#[entrypoint]
-#[storage]
-pub struct {} {{
-    owner: StorageAddress,
-}}
-
-#[public]
-impl {} {{
-    /// Constructor
-    pub fn init(&mut self) -> Result<(), Vec<u8>> {{
-        self.owner.set(msg::sender());
-        Ok(())
-    }}
-    
-    /// Get the owner
-    pub fn owner(&self) -> Address {{
-        self.owner.get()
-    }}
-}}
-"#,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name
-        )
+
+    /// Whether OpenZeppelin Stylus has an equivalent of the extension with registry id `id`
+    pub fn supports_extension(id: &str) -> bool {
+        STYLUS_EXTENSIONS.iter().any(|(stylus_id, _)| *stylus_id == id)
     }
-    
-    fn generate_erc20_contract(&self) -> String {
-        let symbol = self.get_symbol();
-        format!(
-r#"#![cfg_attr(not(feature = "export-abi"), no_std)]
-
-extern crate alloc;
-
-use openzeppelin_stylus::{{
-    token::erc20::{{
-        Erc20, IErc20, IErc20Metadata,
-        extensions::{{Burnable, IBurnable}},
-    }},
-}};
-use stylus_sdk::{{
-    msg,
-    prelude::*,
-}};
-use alloc::string::String;
-
-/// {} ERC20 Token
-#[entrypoint]
-#[storage]
-struct {} {{
-    #[borrow]
-    erc20: Erc20,
-}}
-
-#[public]
-#[inherit(Erc20)]
-impl {} {{
-    /// Initialize the contract with an initial supply
-    pub fn init(&mut self, initial_supply: U256) -> Result<(), Vec<u8>> {{
-        self.erc20._metadata.name.set(String::from("{}"));
-        self.erc20._metadata.symbol.set(String::from("{}"));
-        
-        // Mint initial supply to deployer
-        let deployer = msg::sender();
-        self.erc20._mint(deployer, initial_supply)?;
-        
-        Ok(())
-    }}
-    
-    /// Mint new tokens
-    pub fn mint(&mut self, to: Address, amount: U256) -> Result<(), Vec<u8>> {{
-        // Add access control as needed
-        self.erc20._mint(to, amount)?;
-        Ok(())
-    }}
-}}
-"#,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name,
-            symbol
-        )
+
+    /// Set the registry extension names are resolved against
+    pub fn with_extensions(mut self, extensions: ExtensionRegistry) -> Self {
+        self.extensions = extensions;
+        self
     }
-    
-    fn generate_erc721_contract(&self) -> String {
-        let symbol = self.get_symbol();
-        format!(
-r#"#![cfg_attr(not(feature = "export-abi"), no_std)]
-
-extern crate alloc;
-
-use openzeppelin_stylus::{{
-    token::erc721::{{
-        Erc721, IErc721, IErc721Metadata,
-        extensions::{{enumerable::{{Erc721Enumerable, IErc721Enumerable}}}},
-    }},
-}};
-use stylus_sdk::{{
-    msg,
-    prelude::*,
-}};
-use alloc::string::String;
-
-/// {} NFT Collection
-#[entrypoint]
-#[storage]
-struct {} {{
-    #[borrow]
-    erc721: Erc721,
-    #[borrow]
-    enumerable: Erc721Enumerable,
-    next_token_id: StorageU256,
-}}
-
-#[public]
-#[inherit(Erc721, Erc721Enumerable)]
-impl {} {{
-    /// Initialize the NFT collection
-    pub fn init(&mut self) -> Result<(), Vec<u8>> {{
-        self.erc721._metadata.name.set(String::from("{}"));
-        self.erc721._metadata.symbol.set(String::from("{}"));
-        self.next_token_id.set(U256::from(1));
-        Ok(())
-    }}
-    
-    /// Mint a new NFT
-    pub fn mint(&mut self, to: Address) -> Result<U256, Vec<u8>> {{
-        let token_id = self.next_token_id.get();
-        self.erc721._mint(to, token_id)?;
-        self.next_token_id.set(token_id + U256::from(1));
-        Ok(token_id)
-    }}
-    
-    /// Set token URI
-    pub fn set_token_uri(&mut self, token_id: U256, uri: String) -> Result<(), Vec<u8>> {{
-        // Implement URI storage as needed
-        Ok(())
-    }}
-}}
-"#,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name,
-            symbol
-        )
+
+    /// Set the templates to render from, e.g. with a project's overrides applied
+    pub fn with_templates(mut self, templates: Templates) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Set the token name, symbol, decimals, initial supply and base URI
+    pub fn with_token_metadata(mut self, metadata: TokenMetadata) -> Self {
+        self.metadata = metadata;
+        self
     }
     
-    fn generate_erc1155_contract(&self) -> String {
-        format!(
-r#"#![cfg_attr(not(feature = "export-abi"), no_std)]
-
-extern crate alloc;
-
-use openzeppelin_stylus::{{
-    token::erc1155::{{
-        Erc1155, IErc1155, IErc1155MetadataUri,
-        extensions::{{supply::{{Erc1155Supply, IErc1155Supply}}}},
-    }},
-}};
-use stylus_sdk::{{
-    msg,
-    prelude::*,
-}};
-use alloc::string::String;
-use alloc::vec::Vec;
-
-/// {} Multi-Token Contract
-#[entrypoint]
-#[storage]
-struct {} {{
-    #[borrow]
-    erc1155: Erc1155,
-    #[borrow]
-    supply: Erc1155Supply,
-}}
-
-#[public]
-#[inherit(Erc1155, Erc1155Supply)]
-impl {} {{
-    /// Initialize the multi-token contract
-    pub fn init(&mut self, uri: String) -> Result<(), Vec<u8>> {{
-        self.erc1155._uri.set(uri);
-        Ok(())
-    }}
-    
-    /// Mint tokens
-    pub fn mint(
-        &mut self,
-        to: Address,
-        id: U256,
-        amount: U256,
-        data: Vec<u8>,
-    ) -> Result<(), Vec<u8>> {{
-        self.erc1155._mint(to, id, amount, &data)?;
-        Ok(())
-    }}
-    
-    /// Mint batch of tokens
-    pub fn mint_batch(
-        &mut self,
-        to: Address,
-        ids: Vec<U256>,
-        amounts: Vec<U256>,
-        data: Vec<u8>,
-    ) -> Result<(), Vec<u8>> {{
-        self.erc1155._mint_batch(to, ids, amounts, &data)?;
-        Ok(())
-    }}
-}}
-"#,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name
+    /// Declare the `tests` module [`Template::generate_test`] renders in the contract
+    pub fn with_test_module(mut self, with_test: bool) -> Self {
+        self.with_test = with_test;
+        self
+    }
+
+    /// Set the function declarations the `sol_interface!` block lists, e.g. from [`crate::abi::interface_functions`]
+    pub fn with_interface_functions(mut self, functions: Vec<String>) -> Self {
+        self.functions = functions;
+        self
+    }
+
+    /// The `.env.example` the deploy example reads: the RPC endpoint, the deployer
+    /// key and the contract's `init` arguments
+    pub fn generate_env_example(&self) -> Result<String> {
+        self.render("stylus/env.example", context! { snake_name => crate::stylus::module_name(&self.contract_name) })
+    }
+
+    /// `scripts/stylus.sh`, which runs `cargo stylus check` and the deploy example
+    pub fn generate_deploy_wrapper(&self) -> Result<String> {
+        self.render("stylus/deploy.sh", context! {})
+    }
+
+    /// The crate root of the ERC-1967 proxy upgradeable contracts in the crate
+    /// `package` are deployed behind
+    pub fn generate_proxy(&self, package: &str) -> Result<String> {
+        self.render("stylus/proxy.rs", context! { package })
+    }
+
+    /// Render `name` with `extra` on top of the variables every template gets
+    fn render(&self, name: &str, extra: Value) -> Result<String> {
+        self.templates.render(
+            name,
+            context! {
+                ..extra,
+                ..context! {
+                    name => &self.contract_name,
+                    token_name => self.metadata.token_name(&self.contract_name),
+                    symbol => self.metadata.symbol(&self.contract_name),
+                    decimals => self.metadata.decimals(),
+                    initial_supply => self.metadata.initial_supply_base_units(),
+                    base_uri => &self.metadata.base_uri,
+                    token_type => self.contract_type.type_name(),
+                    upgradeable => self.contract_type.is_upgradeable(),
+                    extensions => self.extension_names()?,
+                    with_test => self.with_test,
+                }
+            },
         )
     }
-    
-    fn generate_erc20_upgradeable_contract(&self) -> String {
-        // Upgradeable contracts are not yet supported in OpenZeppelin Stylus
-        // This should not be called due to validation in determine_contract_type
-        panic!("Upgradeable contracts are not yet supported for Rust/Stylus")
+
+    fn contract_template(&self, contract_type: &ContractType) -> Result<&'static str> {
+        match contract_type {
+            ContractType::Basic => Ok("stylus/basic.rs"),
+            // Upgradeable tokens are the same templates with `upgradeable` set
+            ContractType::ERC20 | ContractType::ERC20Upgradeable => Ok("stylus/erc20.rs"),
+            ContractType::ERC721 | ContractType::ERC721Upgradeable => Ok("stylus/erc721.rs"),
+            ContractType::ERC1155 | ContractType::ERC1155Upgradeable => Ok("stylus/erc1155.rs"),
+            ContractType::Interface | ContractType::Abstract => Err(GramrError::Other(
+                "Rust/Stylus interfaces and abstract contracts are generated with `gramr new interface` and `gramr new abstract`".to_string(),
+            )),
+            // Extensions are composed by the base token's template
+            ContractType::MultiInheritance { base_type, .. } => match **base_type {
+                ContractType::ERC20 | ContractType::ERC20Upgradeable => Ok("stylus/erc20.rs"),
+                ContractType::ERC721 | ContractType::ERC721Upgradeable => Ok("stylus/erc721.rs"),
+                ContractType::ERC1155 | ContractType::ERC1155Upgradeable => Ok("stylus/erc1155.rs"),
+                _ => Ok("stylus/basic.rs"),
+            },
+        }
     }
     
-    fn generate_erc721_upgradeable_contract(&self) -> String {
-        // Upgradeable contracts are not yet supported in OpenZeppelin Stylus
-        panic!("Upgradeable contracts are not yet supported for Rust/Stylus")
+    /// Template names of the token's extensions, e.g. `burnable`; errors for
+    /// extensions OpenZeppelin Stylus doesn't provide
+    fn extension_names(&self) -> Result<Vec<&'static str>> {
+        let ContractType::MultiInheritance { base_type, extensions } = &self.contract_type else {
+            return Ok(Vec::new());
+        };
+
+        let resolved = self.extensions.resolve_requested(base_type, extensions)?;
+        resolved
+            .extensions
+            .iter()
+            .map(|extension| {
+                STYLUS_EXTENSIONS
+                    .iter()
+                    .find(|(id, _)| *id == extension.id())
+                    .map(|(_, name)| *name)
+                    .ok_or_else(|| {
+                        let supported: Vec<&str> = STYLUS_EXTENSIONS
+                            .iter()
+                            .filter(|(id, _)| {
+                                self.extensions
+                                    .specs()
+                                    .iter()
+                                    .any(|spec| spec.id == *id && spec.supports_base(base_type))
+                            })
+                            .map(|(_, name)| *name)
+                            .collect();
+                        GramrError::Other(format!(
+                            "{} is not available for Rust/Stylus; OpenZeppelin Stylus {} tokens support {}",
+                            extension,
+                            base_type.type_name().to_uppercase(),
+                            supported.join(", ")
+                        ))
+                    })
+            })
+            .collect()
     }
-    
-    fn generate_erc1155_upgradeable_contract(&self) -> String {
-        // Upgradeable contracts are not yet supported in OpenZeppelin Stylus
-        panic!("Upgradeable contracts are not yet supported for Rust/Stylus")
+}
+
+impl Template for StylusTemplate {
+    fn generate_contract(&self) -> Result<String> {
+        let name = self.contract_template(&self.contract_type)?;
+        self.render(name, context! {})
     }
     
-    fn generate_multi_inheritance_contract(&self, base_type: &ContractType, extensions: &[TokenExtension]) -> String {
-        // TODO: Implement multi-inheritance for Stylus
-        // This will be more complex as Rust doesn't have traditional inheritance
-        match base_type {
-            ContractType::ERC20 | ContractType::ERC20Upgradeable => self.generate_erc20_with_extensions(extensions),
-            ContractType::ERC721 | ContractType::ERC721Upgradeable => self.generate_erc721_with_extensions(extensions),
-            ContractType::ERC1155 | ContractType::ERC1155Upgradeable => self.generate_erc1155_with_extensions(extensions),
-            _ => self.generate_basic_contract(),
-        }
+    fn generate_test(&self) -> Result<String> {
+        self.render("stylus/test.rs", context! {})
     }
     
-    fn generate_erc20_with_extensions(&self, _extensions: &[TokenExtension]) -> String {
-        // Generate ERC20 with selected extensions
-        // For now, return basic ERC20
-        self.generate_erc20_contract()
+    fn generate_script(&self) -> Result<String> {
+        // A cargo example that deploys, activates and initializes the contract
+        self.render("stylus/script.rs", context! { snake_name => crate::stylus::module_name(&self.contract_name) })
     }
     
-    fn generate_erc721_with_extensions(&self, _extensions: &[TokenExtension]) -> String {
-        // Generate ERC721 with selected extensions  
-        // For now, return basic ERC721
-        self.generate_erc721_contract()
+    fn generate_library(&self) -> Result<String> {
+        self.render("stylus/library.rs", context! { snake_name => crate::stylus::module_name(&self.contract_name) })
     }
     
-    fn generate_erc1155_with_extensions(&self, _extensions: &[TokenExtension]) -> String {
-        // Generate ERC1155 with selected extensions
-        // For now, return basic ERC1155
-        self.generate_erc1155_contract()
+    fn generate_interface(&self) -> Result<String> {
+        self.render("stylus/interface.rs", context! { functions => &self.functions })
     }
     
-    fn get_symbol(&self) -> String {
-        self.contract_name
-            .chars()
-            .filter(|c| c.is_uppercase())
-            .take(3)
-            .collect::<String>()
-            .to_uppercase()
+    fn generate_abstract_contract(&self) -> Result<String> {
+        self.render(
+            "stylus/abstract.rs",
+            context! { snake_name => crate::stylus::module_name(&self.contract_name) },
+        )
     }
 }
 
-impl Template for StylusTemplate {
-    fn generate_contract(&self) -> String {
-        match &self.contract_type {
-            ContractType::Basic => self.generate_basic_contract(),
-            ContractType::ERC20 => self.generate_erc20_contract(),
-            ContractType::ERC721 => self.generate_erc721_contract(),
-            ContractType::ERC1155 => self.generate_erc1155_contract(),
-            ContractType::ERC20Upgradeable => self.generate_erc20_upgradeable_contract(),
-            ContractType::ERC721Upgradeable => self.generate_erc721_upgradeable_contract(),
-            ContractType::ERC1155Upgradeable => self.generate_erc1155_upgradeable_contract(),
-            ContractType::Interface | ContractType::Abstract => {
-                panic!("Interface and Abstract contracts are not supported for Rust/Stylus")
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::TokenExtension;
+
+    fn create_multi_template(base_type: ContractType, extensions: Vec<TokenExtension>) -> StylusTemplate {
+        StylusTemplate::new(
+            "MultiToken".to_string(),
+            ContractType::MultiInheritance {
+                base_type: Box::new(base_type),
+                extensions,
             },
-            ContractType::MultiInheritance { base_type, extensions } => {
-                self.generate_multi_inheritance_contract(base_type, extensions)
-            }
-        }
+        )
     }
-    
-    fn generate_test(&self) -> String {
-        format!(
-r#"#[cfg(test)]
-mod tests {{
-    use super::*;
-    use stylus_sdk::{{Address, U256}};
-    
+
     #[test]
-    fn test_deployment() {{
-        // Test contract deployment
-        let contract = {}::default();
-        // Add test assertions here
-    }}
-    
+    fn test_plain_tokens_have_no_extensions() {
+        let erc20 = StylusTemplate::new("MyToken".to_string(), ContractType::ERC20).generate_contract().unwrap();
+        assert!(erc20.contains("#[implements(IErc20<Error = Vec<u8>>, IErc20Metadata, IOwnable, IErc165)]"));
+        assert!(erc20.contains("pub fn init(&mut self, initial_supply: U256) -> Result<(), Vec<u8>>"));
+        assert!(!erc20.contains("pausable"));
+
+        let erc721 = StylusTemplate::new("MyNFT".to_string(), ContractType::ERC721).generate_contract().unwrap();
+        assert!(erc721.contains("#[implements(IErc721<Error = Vec<u8>>, IErc721Metadata<Error = Vec<u8>>, IOwnable, IErc165)]"));
+        assert!(!erc721.contains("Erc721Enumerable"));
+    }
+
     #[test]
-    fn test_basic_functionality() {{
-        // Add functionality tests here
-    }}
-}}
-"#,
-            self.contract_name
+    fn test_erc20_extensions() {
+        let contract = create_multi_template(
+            ContractType::ERC20,
+            vec![TokenExtension::ERC20Burnable, TokenExtension::ERC20Capped(Vec::new()), TokenExtension::ERC20Pausable, TokenExtension::ERC20Permit],
         )
+        .generate_contract().unwrap();
+
+        assert!(contract.contains("            IErc20Burnable,\n"));
+        assert!(contract.contains("pausable::{IPausable, Pausable},"));
+        assert!(contract.contains("    capped: Capped,"));
+        assert!(contract.contains("    permit: Erc20Permit<Eip712>,\n    nonces: Nonces,"));
+        assert!(contract.contains("#[implements(IErc20<Error = Vec<u8>>, IErc20Metadata, IErc20Burnable<Error = Vec<u8>>, ICapped, IPausable, IErc20Permit<Error = Vec<u8>>, INonces, IOwnable, IErc165)]"));
+        assert!(contract.contains("const NAME: &'static str = \"MultiToken\";"));
+        assert!(contract.contains("pub fn init(&mut self, initial_supply: U256, cap: U256)"));
+        assert!(contract.contains("self.capped.constructor(cap)?;"));
+        assert!(contract.contains("capped::Error::ExceededCap"));
+        assert!(contract.contains("fn burn_from(&mut self, account: Address, value: U256) -> Result<(), Self::Error>"));
+        assert!(contract.contains("pub fn pause(&mut self)"));
+        assert!(contract.contains("fn transfer(&mut self, to: Address, value: U256) -> Result<bool, Self::Error> {\n        self.pausable.when_not_paused()?;"));
+        assert!(contract.contains("&mut self.erc20, &mut self.nonces)?)"));
     }
-    
-    fn generate_script(&self) -> String {
-        // Stylus doesn't have scripts like Foundry
-        // Return deployment instructions instead
-        format!(
-r#"// {} Deployment Instructions
-//
-// 1. Build the contract:
-//    cargo build --release --target wasm32-unknown-unknown \
-//      -Z build-std=std,panic_abort \
-//      -Z build-std-features=panic_immediate_abort
-//
-// 2. Deploy using Stylus CLI:
-//    stylus deploy --private-key $PRIVATE_KEY
-//
-// 3. Verify the contract:
-//    stylus verify --address <CONTRACT_ADDRESS>
-"#,
-            self.contract_name
+
+    #[test]
+    fn test_erc721_enumerable_burnable_uristorage() {
+        let contract = create_multi_template(
+            ContractType::ERC721,
+            vec![TokenExtension::ERC721Enumerable, TokenExtension::ERC721Burnable, TokenExtension::ERC721URIStorage],
         )
-    }
-    
-    fn generate_library(&self) -> String {
-        let snake_name = self.contract_name.chars()
-            .enumerate()
-            .map(|(i, c)| {
-                if c.is_uppercase() && i > 0 {
-                    format!("_{}", c.to_lowercase())
-                } else {
-                    c.to_lowercase().to_string()
-                }
-            })
-            .collect::<String>();
-            
-        format!(
-r#"//! {} Library
-//! 
-//! A reusable library for Stylus contracts providing utility functions.
+        .generate_contract().unwrap();
 
-#![cfg_attr(not(feature = "export-abi"), no_std)]
+        assert!(contract.contains("IErc721Burnable<Error = Vec<u8>>, IErc721Enumerable<Error = Vec<u8>>, IOwnable, IErc165)]"));
+        assert!(contract.contains("    uri_storage: Erc721UriStorage,"));
+        assert!(contract.contains("self.enumerable._add_token_to_all_tokens_enumeration(token_id);"));
+        assert!(contract.contains("self.enumerable._remove_token_from_all_tokens_enumeration(token_id);"));
+        assert!(contract.contains("fn transfer_from(&mut self, from: Address, to: Address, token_id: U256)"));
+        assert!(contract.contains("        self.ownable.only_owner()?;\n        self.erc721.owner_of(token_id)?;\n        self.uri_storage._set_token_uri(token_id, uri);"));
+        assert!(contract.contains("self.uri_storage.token_uri(token_id, &self.erc721, &self.metadata)"));
+        assert!(!contract.contains("when_not_paused"));
+    }
 
-extern crate alloc;
+    #[test]
+    fn test_erc1155_supply_wraps_token() {
+        let contract = create_multi_template(
+            ContractType::ERC1155,
+            vec![TokenExtension::ERC1155Supply, TokenExtension::ERC1155Pausable, TokenExtension::ERC1155Burnable],
+        )
+        .generate_contract().unwrap();
 
-use stylus_sdk::prelude::*;
-use alloc::{{string::String, vec::Vec}};
+        assert!(contract.contains("IErc1155Burnable<Error = Vec<u8>>, IErc1155Supply, IPausable, IOwnable, IErc165)]"));
+        assert!(!contract.contains("    erc1155: Erc1155,"));
+        assert!(contract.contains("self.metadata_uri.constructor(uri);"));
+        assert!(contract.contains("self.supply._mint(to, id, amount, &data)?;"));
+        assert!(contract.contains("        self.only_approved(account)?;\n        Ok(self.supply._burn_batch(account, token_ids, values)?)"));
+        assert!(contract.contains("fn safe_batch_transfer_from("));
+    }
 
-/// Example trait for common functionality
-pub trait {}Operations {{
-    /// Example function - replace with your own
-    fn example_operation(&self, value: U256) -> U256;
-    
-    /// Validation function
-    fn is_valid(&self) -> bool;
-}}
-
-/// Example data structure for library use
-#[derive(Default)]
-pub struct {}Data {{
-    pub id: U256,
-    pub owner: Address,
-    pub is_active: bool,
-}}
-
-impl {}Data {{
-    /// Create new data instance
-    pub fn new(id: U256, owner: Address) -> Self {{
-        Self {{
-            id,
-            owner,
-            is_active: true,
-        }}
-    }}
-    
-    /// Validate data integrity
-    pub fn validate(&self) -> bool {{
-        !self.owner.is_zero() && self.is_active
-    }}
-    
-    /// Example transformation
-    pub fn transform(&mut self, multiplier: U256) {{
-        self.id = self.id * multiplier;
-    }}
-}}
-
-/// Utility functions module
-pub mod {} {{
-    use super::*;
-    
-    /// Example pure function
-    pub fn double_value(value: U256) -> U256 {{
-        value * U256::from(2)
-    }}
-    
-    /// Example validation function
-    pub fn is_non_zero_address(addr: Address) -> bool {{
-        !addr.is_zero()
-    }}
-    
-    /// Example calculation function
-    pub fn calculate_percentage(value: U256, percentage: u8) -> U256 {{
-        if percentage > 100 {{
-            return U256::ZERO;
-        }}
-        value * U256::from(percentage) / U256::from(100)
-    }}
-}}
-
-/// Error types for the library
-#[derive(Debug)]
-pub enum {}Error {{
-    InvalidInput,
-    ZeroAddress,
-    InactiveData,
-}}
-
-impl {}Error {{
-    pub fn as_bytes(&self) -> Vec<u8> {{
-        match self {{
-            Self::InvalidInput => b"Invalid input".to_vec(),
-            Self::ZeroAddress => b"Zero address".to_vec(),
-            Self::InactiveData => b"Inactive data".to_vec(),
-        }}
-    }}
-}}
-"#,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name,
-            self.contract_name,
-            snake_name,
-            self.contract_name,
-            self.contract_name
+    #[test]
+    fn test_pausing_is_owner_only() {
+        let contract = create_multi_template(
+            ContractType::ERC20,
+            vec![TokenExtension::ERC20Pausable, TokenExtension::ERC20Capped(Vec::new())],
         )
+        .generate_contract().unwrap();
+        assert!(contract.contains("#[implements(IErc20<Error = Vec<u8>>, IErc20Metadata, ICapped, IPausable, IOwnable, IErc165)]"));
+        assert!(contract.contains("    pub fn pause(&mut self) -> Result<(), Vec<u8>> {\n        self.ownable.only_owner()?;"));
+        assert!(contract.contains("    pub fn unpause(&mut self) -> Result<(), Vec<u8>> {\n        self.ownable.only_owner()?;"));
+        assert!(contract.contains("        self.initialized.set(true);\n        self.ownable.constructor(msg::sender())?;"));
+        assert!(contract.contains("let supply = self.erc20.total_supply().checked_add(amount);"));
+
+        let plain = StylusTemplate::new("MyToken".to_string(), ContractType::ERC721).generate_contract().unwrap();
+        assert!(plain.contains("        if self.initialized.get() {\n            return Err(b\"already initialized\".to_vec());\n        }"));
+        assert!(plain.contains("    pub fn mint(&mut self, to: Address) -> Result<U256, Vec<u8>> {\n        self.ownable.only_owner()?;"));
+        assert!(!plain.contains("pausable"));
     }
-    
-    fn generate_interface(&self) -> String {
-        // Interfaces are not typically used in Rust/Stylus - use traits instead
-        format!(
-r#"// Interfaces are not directly supported in Rust/Stylus
-// Use traits instead for similar functionality
-// 
-// Example trait definition:
-//
-// pub trait {} {{
-//     fn example_function(&self) -> bool;
-// }}
-"#,
-            self.contract_name
-        )
+
+    #[test]
+    fn test_motsu_tests_per_token_type() {
+        let erc20 = StylusTemplate::new("MyToken".to_string(), ContractType::ERC20);
+        let test = erc20.generate_test().unwrap();
+        assert!(test.contains("use motsu::prelude::*;"));
+        assert!(test.contains("const INITIAL_SUPPLY: U256 = uint!(1000000000000000000000000_U256);"));
+        assert!(test.contains("#[motsu::test]\nfn deploys_with_initial_supply(contract: Contract<MyToken>, alice: Address)"));
+        assert!(test.contains("fn mints("));
+        assert!(test.contains("contract.sender(alice).transfer(bob, amount).motsu_unwrap();"));
+
+        let pausable = create_multi_template(ContractType::ERC721, vec![TokenExtension::ERC721Pausable]).generate_test().unwrap();
+        assert!(pausable.contains("contract.sender(alice).transfer_from(alice, bob, token_id).motsu_unwrap();"));
+        assert!(pausable.contains("fn pausing_blocks_minting("));
+        assert!(pausable.contains("fn only_the_owner_pauses("));
+        assert!(pausable.contains("    assert_eq!(contract.sender(alice).owner(), alice);"));
+
+        let erc1155 = StylusTemplate::new("Items".to_string(), ContractType::ERC1155).generate_test().unwrap();
+        assert!(erc1155.contains("        .safe_transfer_from(alice, bob, TOKEN_ID, amount, Vec::new().into())"));
+        assert!(erc1155.contains("fn only_the_owner_mints("));
+        let uris = create_multi_template(ContractType::ERC1155, vec![TokenExtension::ERC1155URIStorage]).generate_test().unwrap();
+        assert!(uris.contains("fn only_the_owner_sets_token_uris("));
+
+        let basic = StylusTemplate::new("Counter".to_string(), ContractType::Basic).generate_test().unwrap();
+        assert!(basic.contains("assert_eq!(contract.sender(alice).owner(), alice);"));
+        assert!(!basic.contains("fn transfers("));
     }
-    
-    fn generate_abstract_contract(&self) -> String {
-        // Abstract contracts don't exist in Rust - use traits instead
-        format!(
-r#"// Abstract contracts are not supported in Rust/Stylus
-// Use traits for similar patterns
-//
-// Example trait with default implementations:
-//
-// pub trait {} {{
-//     fn abstract_function(&self) -> U256;
-//     
-//     fn concrete_function(&self) -> String {{
-//         "This is a concrete function".to_string()
-//     }}
-// }}
-"#,
-            self.contract_name
-        )
+
+    #[test]
+    fn test_deploy_example_initializes_from_the_environment() {
+        let capped = create_multi_template(ContractType::ERC20, vec![TokenExtension::ERC20Capped(Vec::new())]);
+        let example = capped.generate_script().unwrap();
+        assert!(example.contains("    interface IMultiToken {\n        function init(uint256 initial_supply, uint256 cap) external;\n    }"));
+        assert!(example.contains("let cap: U256 = env(\"MULTI_TOKEN_CAP\")?.parse()?;"));
+        assert!(example.contains("let init = contract.init(initial_supply, cap);"));
+        let env = capped.generate_env_example().unwrap();
+        assert!(env.contains("RPC_URL=http://localhost:8547\n"));
+        assert!(env.ends_with("\n\n# MultiToken init arguments, in base units\nMULTI_TOKEN_INITIAL_SUPPLY=1000000000000000000000000\nMULTI_TOKEN_CAP=115792089237316195423570985008687907853269984665640564039457584007913129639935\n"));
+
+        let items = StylusTemplate::new("Items".to_string(), ContractType::ERC1155);
+        assert!(items.generate_script().unwrap().contains("let init = contract.init(env(\"ITEMS_URI\")?);"));
+        assert!(items.generate_env_example().unwrap().contains("ITEMS_URI="));
+
+        let counter = StylusTemplate::new("Counter".to_string(), ContractType::Basic);
+        assert!(counter.generate_script().unwrap().contains("let init = contract.init();"));
+        assert!(!counter.generate_env_example().unwrap().contains("COUNTER_"));
+    }
+
+    #[test]
+    fn test_upgradeable_tokens_are_uups_implementations() {
+        let erc20 = StylusTemplate::new("MyToken".to_string(), ContractType::ERC20Upgradeable).generate_contract().unwrap();
+        assert!(erc20.contains("    #[constructor]\n    pub fn constructor(&mut self) {\n        self.uups.constructor();\n    }\n\n    /// Initialize"));
+        assert!(erc20.contains("        if self.initialized.get() {\n            return Err(b\"already initialized\".to_vec());\n        }"));
+        assert!(erc20.contains("        self.ownable.only_owner()?;\n        self.uups.upgrade_to_and_call(new_implementation, data)"));
+        assert!(erc20.contains("    pub fn mint(&mut self, to: Address, amount: U256) -> Result<(), Vec<u8>> {\n        self.ownable.only_owner()?;"));
+        assert!(!StylusTemplate::new("MyToken".to_string(), ContractType::ERC20).generate_contract().unwrap().contains("uups"));
+
+        let items = create_multi_template(ContractType::ERC1155Upgradeable, vec![TokenExtension::ERC1155Supply]);
+        let contract = items.generate_contract().unwrap();
+        assert!(contract.contains("#[implements(IErc1155<Error = Vec<u8>>, IErc1155MetadataUri, IErc1155Supply, IOwnable, IUUPSUpgradeable, IErc1822Proxiable, IErc165)]"));
+        assert!(contract.contains("pub fn init(&mut self, uri: String) -> Result<(), Vec<u8>> {"));
+        let test = items.with_test_module(true).generate_test().unwrap();
+        assert!(test.contains("assert!(contract.sender(bob).init(String::from(\"https://example.com/{id}.json\")).is_err());"));
+
+        let nft = StylusTemplate::new("Art".to_string(), ContractType::ERC721Upgradeable);
+        assert!(nft.generate_test().unwrap().contains("    assert!(contract.sender(bob).init().is_err());"));
+        let example = nft.generate_script().unwrap();
+        assert!(example.contains("    let data = IArt::initCall {}.abi_encode();\n"));
+        assert!(example.contains("constructor.extend((implementation, Bytes::from(data)).abi_encode_params());"));
+        assert!(example.contains("let deployer = IStylusDeployer::new(STYLUS_DEPLOYER, &provider);"));
+        assert!(!example.contains("function init(address implementation, bytes data)"));
+        assert!(example.contains("env(\"ART_IMPLEMENTATION\")"));
+        assert!(nft.generate_env_example().unwrap().ends_with("\nART_IMPLEMENTATION=\n"));
+        let proxy = nft.generate_proxy("art").unwrap();
+        assert!(proxy.contains("//! ERC-1967 proxy for the upgradeable contracts of art\n"));
+        assert!(proxy.contains("    #[constructor]\n    pub fn constructor(&mut self, implementation: Address, data: Bytes)"));
+        assert!(!proxy.contains("pub fn init("));
+    }
+
+    #[test]
+    fn test_contract_declares_test_module() {
+        let template = StylusTemplate::new("MyToken".to_string(), ContractType::ERC20);
+        assert!(!template.generate_contract().unwrap().contains("mod tests;"));
+
+        let contract = template.with_test_module(true).generate_contract().unwrap();
+        assert!(contract.ends_with("}\n\n#[cfg(test)]\nmod tests;\n"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_shared_names_resolve_and_unsupported_extensions_error() {
+        // `burnable` is parsed as ERC20Burnable and resolved for the base type
+        let contract = create_multi_template(ContractType::ERC721, vec![TokenExtension::ERC20Burnable])
+            .generate_contract().unwrap();
+        assert!(contract.contains("            IErc721Burnable,\n"));
+
+        let err = create_multi_template(ContractType::ERC20, vec![TokenExtension::ERC20Votes])
+            .generate_contract().unwrap_err().to_string();
+        assert!(err.contains("ERC20Votes is not available for Rust/Stylus"), "{}", err);
+        assert!(err.contains("support burnable, pausable, capped, permit"), "{}", err);
+
+        assert!(StylusTemplate::supports_extension("ERC721URIStorage"));
+        assert!(!StylusTemplate::supports_extension("ERC721Royalty"));
+    }
+}
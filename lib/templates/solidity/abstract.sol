@@ -0,0 +1,12 @@
+{% include "solidity/header.sol" %}
+
+{{ natspec }}abstract contract {{ name }} {
+    // Add your abstract functions and state variables here
+    // Example:
+    // function abstractFunction() public virtual returns (uint256);
+    
+    // You can also implement concrete functions
+    function concreteFunction() public pure returns (string memory) {
+        return "This is a concrete function";
+    }
+}
@@ -0,0 +1,5 @@
+{% include "solidity/header.sol" %}
+
+{{ natspec }}contract {{ name }} {
+    constructor() {}
+}
\ No newline at end of file
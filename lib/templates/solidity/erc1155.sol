@@ -0,0 +1,35 @@
+{% include "solidity/header.sol" %}
+
+import "@openzeppelin/contracts/token/ERC1155/ERC1155.sol";
+{%- if access.import %}
+{{ access.import }}
+{%- endif %}
+
+{{ natspec }}contract {{ name }} is ERC1155{% if access.contract %}, {{ access.contract }}{% endif %} {
+{%- for role in access.roles %}
+    {{ role }}
+{%- endfor %}
+{%- if access.roles %}
+{% endif %}
+    constructor({{ access.parameter }}) ERC1155("{{ base_uri or "https://api.example.com/tokens/{id}.json" }}"){{ access.constructor }} {
+{%- for grant in access.grants %}
+        {{ grant }}
+{%- endfor %}
+{%- if access.grants %}
+    {% endif %}}
+
+
+    function mint(address to, uint256 id, uint256 amount, bytes memory data) public{% if access.only_minter %} {{ access.only_minter }}{% endif %} {
+        _mint(to, id, amount, data);
+    }
+
+    function mintBatch(address to, uint256[] memory ids, uint256[] memory amounts, bytes memory data) public{% if access.only_minter %} {{ access.only_minter }}{% endif %} {
+        _mintBatch(to, ids, amounts, data);
+    }
+{%- if access.model == "access-control" %}
+
+    function supportsInterface(bytes4 interfaceId) public view override(ERC1155, AccessControl) returns (bool) {
+        return super.supportsInterface(interfaceId);
+    }
+{%- endif %}
+}
\ No newline at end of file
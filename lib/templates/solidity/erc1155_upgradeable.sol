@@ -0,0 +1,43 @@
+{% include "solidity/header.sol" %}
+
+import "@openzeppelin/contracts-upgradeable/token/ERC1155/ERC1155Upgradeable.sol";
+import "@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol";
+{{ access.import }}
+import "@openzeppelin/contracts-upgradeable/proxy/utils/UUPSUpgradeable.sol";
+
+{{ natspec }}contract {{ name }} is Initializable, ERC1155Upgradeable, {{ access.contract }}, UUPSUpgradeable {
+{%- for role in access.roles %}
+    {{ role }}
+{%- endfor %}
+{%- if access.roles %}
+{% endif %}
+    /// @custom:oz-upgrades-unsafe-allow constructor
+    constructor() {
+        _disableInitializers();
+    }
+
+    function initialize({{ access.parameter }}) public initializer {
+        __ERC1155_init("{{ base_uri or "https://api.example.com/tokens/{id}.json" }}");
+        {{ access.initializer }}
+        __UUPSUpgradeable_init();
+{%- for grant in access.grants %}
+        {{ grant }}
+{%- endfor %}
+    }
+
+    function mint(address to, uint256 id, uint256 amount, bytes memory data) public {{ access.only_minter }} {
+        _mint(to, id, amount, data);
+    }
+
+    function mintBatch(address to, uint256[] memory ids, uint256[] memory amounts, bytes memory data) public {{ access.only_minter }} {
+        _mintBatch(to, ids, amounts, data);
+    }
+{%- if access.model == "access-control" %}
+
+    function supportsInterface(bytes4 interfaceId) public view override(ERC1155Upgradeable, AccessControlUpgradeable) returns (bool) {
+        return super.supportsInterface(interfaceId);
+    }
+{%- endif %}
+
+    function _authorizeUpgrade(address newImplementation) internal override {{ access.only_upgrader }} {}
+}
\ No newline at end of file
@@ -0,0 +1,15 @@
+{% include "solidity/header.sol" %}
+
+import "@openzeppelin/contracts/token/ERC20/ERC20.sol";
+
+{{ natspec }}contract {{ name }} is ERC20 {
+    constructor(uint256 initialSupply) ERC20("{{ token_name }}", "{{ symbol }}") {
+        _mint(msg.sender, initialSupply);
+    }
+{%- if decimals != 18 %}
+
+    function decimals() public view virtual override returns (uint8) {
+        return {{ decimals }};
+    }
+{%- endif %}
+}
\ No newline at end of file
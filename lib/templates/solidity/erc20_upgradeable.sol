@@ -0,0 +1,37 @@
+{% include "solidity/header.sol" %}
+
+import "@openzeppelin/contracts-upgradeable/token/ERC20/ERC20Upgradeable.sol";
+import "@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol";
+{{ access.import }}
+import "@openzeppelin/contracts-upgradeable/proxy/utils/UUPSUpgradeable.sol";
+
+{{ natspec }}contract {{ name }} is Initializable, ERC20Upgradeable, {{ access.contract }}, UUPSUpgradeable {
+{%- for role in access.roles %}
+    {{ role }}
+{%- endfor %}
+{%- if access.roles %}
+{% endif %}
+    /// @custom:oz-upgrades-unsafe-allow constructor
+    constructor() {
+        _disableInitializers();
+    }
+
+    function initialize(uint256 initialSupply{% if access.parameter %}, {{ access.parameter }}{% endif %}) public initializer {
+        __ERC20_init("{{ token_name }}", "{{ symbol }}");
+        {{ access.initializer }}
+        __UUPSUpgradeable_init();
+{%- for grant in access.grants %}
+        {{ grant }}
+{%- endfor %}
+        
+        _mint(msg.sender, initialSupply);
+    }
+{%- if decimals != 18 %}
+
+    function decimals() public view virtual override returns (uint8) {
+        return {{ decimals }};
+    }
+{%- endif %}
+
+    function _authorizeUpgrade(address newImplementation) internal override {{ access.only_upgrader }} {}
+}
\ No newline at end of file
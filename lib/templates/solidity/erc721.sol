@@ -0,0 +1,40 @@
+{% include "solidity/header.sol" %}
+
+import "@openzeppelin/contracts/token/ERC721/ERC721.sol";
+{%- if access.import %}
+{{ access.import }}
+{%- endif %}
+
+{{ natspec }}contract {{ name }} is ERC721{% if access.contract %}, {{ access.contract }}{% endif %} {
+{%- for role in access.roles %}
+    {{ role }}
+{%- endfor %}
+{%- if access.roles %}
+{% endif %}
+    uint256 private _tokenIdCounter;
+
+    constructor({{ access.parameter }}) ERC721("{{ token_name }}", "{{ symbol }}"){{ access.constructor }} {
+{%- for grant in access.grants %}
+        {{ grant }}
+{%- endfor %}
+{%- if access.grants %}
+    {% endif %}}
+
+
+    function safeMint(address to) public{% if access.only_minter %} {{ access.only_minter }}{% endif %} {
+        uint256 tokenId = _tokenIdCounter++;
+        _safeMint(to, tokenId);
+    }
+{%- if base_uri %}
+
+    function _baseURI() internal pure override returns (string memory) {
+        return "{{ base_uri }}";
+    }
+{%- endif %}
+{%- if access.model == "access-control" %}
+
+    function supportsInterface(bytes4 interfaceId) public view override(ERC721, AccessControl) returns (bool) {
+        return super.supportsInterface(interfaceId);
+    }
+{%- endif %}
+}
\ No newline at end of file
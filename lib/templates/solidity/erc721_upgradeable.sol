@@ -0,0 +1,48 @@
+{% include "solidity/header.sol" %}
+
+import "@openzeppelin/contracts-upgradeable/token/ERC721/ERC721Upgradeable.sol";
+import "@openzeppelin/contracts-upgradeable/proxy/utils/Initializable.sol";
+{{ access.import }}
+import "@openzeppelin/contracts-upgradeable/proxy/utils/UUPSUpgradeable.sol";
+
+{{ natspec }}contract {{ name }} is Initializable, ERC721Upgradeable, {{ access.contract }}, UUPSUpgradeable {
+{%- for role in access.roles %}
+    {{ role }}
+{%- endfor %}
+{%- if access.roles %}
+{% endif %}
+    uint256 private _tokenIdCounter;
+
+    /// @custom:oz-upgrades-unsafe-allow constructor
+    constructor() {
+        _disableInitializers();
+    }
+
+    function initialize({{ access.parameter }}) public initializer {
+        __ERC721_init("{{ token_name }}", "{{ symbol }}");
+        {{ access.initializer }}
+        __UUPSUpgradeable_init();
+{%- for grant in access.grants %}
+        {{ grant }}
+{%- endfor %}
+    }
+
+    function safeMint(address to) public {{ access.only_minter }} {
+        uint256 tokenId = _tokenIdCounter++;
+        _safeMint(to, tokenId);
+    }
+{%- if base_uri %}
+
+    function _baseURI() internal pure override returns (string memory) {
+        return "{{ base_uri }}";
+    }
+{%- endif %}
+{%- if access.model == "access-control" %}
+
+    function supportsInterface(bytes4 interfaceId) public view override(ERC721Upgradeable, AccessControlUpgradeable) returns (bool) {
+        return super.supportsInterface(interfaceId);
+    }
+{%- endif %}
+
+    function _authorizeUpgrade(address newImplementation) internal override {{ access.only_upgrader }} {}
+}
\ No newline at end of file
@@ -0,0 +1,2 @@
+// SPDX-License-Identifier: {{ license }}
+pragma solidity ^{{ pragma }};
\ No newline at end of file
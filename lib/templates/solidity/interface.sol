@@ -0,0 +1,11 @@
+{% include "solidity/header.sol" %}
+
+{{ natspec }}interface I{{ name }} {
+{%- for function in functions | default([]) %}
+    {{ function }}
+{%- else %}
+    // Add your function signatures here
+    // Example:
+    // function exampleFunction(uint256 value) external returns (bool);
+{%- endfor %}
+}
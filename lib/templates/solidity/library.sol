@@ -0,0 +1,24 @@
+{% include "solidity/header.sol" %}
+
+{{ natspec }}library {{ name }} {
+    /// @notice Example function - replace with your own
+    /// @param value The input value
+    /// @return The processed result
+    function exampleFunction(uint256 value) internal pure returns (uint256) {
+        return value * 2;
+    }
+    
+    /// @notice Example struct for data organization
+    struct Data {
+        uint256 id;
+        address owner;
+        bool isActive;
+    }
+    
+    /// @notice Example function that works with structs
+    /// @param data The input data struct
+    /// @return Whether the data is valid
+    function validateData(Data memory data) internal pure returns (bool) {
+        return data.owner != address(0) && data.isActive;
+    }
+}
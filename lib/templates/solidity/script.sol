@@ -0,0 +1,37 @@
+{% include "solidity/header.sol" %}
+{%- set deploys_manager = access.model == "access-managed" %}
+
+import "forge-std/Script.sol";
+{%- if deploys_manager %}
+import "@openzeppelin/contracts/access/manager/AccessManager.sol";
+{%- endif %}
+{%- if upgradeable %}
+import "@openzeppelin/contracts/proxy/ERC1967/ERC1967Proxy.sol";
+{%- endif %}
+import "{{ src_path }}/{{ name }}.sol";
+
+contract Deploy{{ name }} is Script {
+    function run() external returns ({{ name }}) {
+        uint256 deployerPrivateKey = vm.envUint("PRIVATE_KEY");
+        
+        vm.startBroadcast(deployerPrivateKey);
+{%- if deploys_manager %}
+
+        AccessManager manager = new AccessManager(vm.addr(deployerPrivateKey));
+{%- endif %}
+{%- if upgradeable %}
+        
+        {{ name }} implementation = new {{ name }}();
+        {{ name }} instance = {{ name }}(
+            address(new ERC1967Proxy(address(implementation), abi.encodeCall({{ name }}.initialize, {{ constructor_args }})))
+        );
+{%- else %}
+        
+        {{ name }} instance = new {{ name }}{{ constructor_args }};
+{%- endif %}
+        
+        vm.stopBroadcast();
+        
+        return instance;
+    }
+}
\ No newline at end of file
@@ -0,0 +1,46 @@
+{% include "solidity/header.sol" %}
+{%- set deploys_manager = access.model == "access-managed" %}
+
+import "forge-std/Test.sol";
+{%- if deploys_manager %}
+import "@openzeppelin/contracts/access/manager/AccessManager.sol";
+{%- endif %}
+{%- if upgradeable %}
+import "@openzeppelin/contracts/proxy/ERC1967/ERC1967Proxy.sol";
+{%- endif %}
+import "{{ src_path }}/{{ name }}.sol";
+
+contract {{ name }}Test is Test {
+    {{ name }} public instance;
+{%- if deploys_manager %}
+    AccessManager public manager;
+{%- endif %}
+
+    function setUp() public {
+{%- if deploys_manager %}
+        manager = new AccessManager(address(this));
+{%- endif %}
+{%- if upgradeable %}
+        {{ name }} implementation = new {{ name }}();
+        instance = {{ name }}(address(new ERC1967Proxy(address(implementation), abi.encodeCall({{ name }}.initialize, {{ constructor_args }}))));
+{%- else %}
+        instance = new {{ name }}{{ constructor_args }};
+{%- endif %}
+    }
+
+    function test_Deployment() public view {
+        assertNotEq(address(instance), address(0));
+    }
+{%- if access.contract %}
+
+    function test_DeployerHasAccess() public view {
+{%- if access.model == "access-control" %}
+        assertTrue(instance.hasRole(instance.DEFAULT_ADMIN_ROLE(), address(this)));
+{%- elif access.model == "access-managed" %}
+        assertEq(instance.authority(), address(manager));
+{%- else %}
+        assertEq(instance.owner(), address(this));
+{%- endif %}
+    }
+{%- endif %}
+}
\ No newline at end of file
@@ -0,0 +1,7 @@
+{% include "solidity/header.sol" %}
+
+{{ imports | join("\n") }}
+
+{{ natspec }}contract {{ name }} is {{ bases | join(", ") }} {
+{{ sections | join("\n\n") }}
+}
\ No newline at end of file
@@ -0,0 +1,51 @@
+//! {{ name }}: shared storage and behaviour for Stylus contracts to build on
+//!
+//! Stylus has no abstract contracts. A contract embeds `{{ name }}` in its storage
+//! with `#[borrow]`, lists it in `#[inherit(...)]` to expose its public methods,
+//! and implements `I{{ name }}` to get the trait's default methods.
+
+use stylus_sdk::{alloy_primitives::Address, msg, prelude::*, storage::StorageAddress};
+use alloc::vec::Vec;
+
+/// State shared by every contract built on {{ name }}
+#[storage]
+pub struct {{ name }} {
+    admin: StorageAddress,
+}
+
+/// Public methods of contracts that `#[inherit({{ name }})]`
+#[public]
+impl {{ name }} {
+    /// The account allowed to call admin-only methods
+    pub fn admin(&self) -> Address {
+        self.admin.get()
+    }
+}
+
+impl {{ name }} {
+    /// Make `admin` the admin; call it from the contract's `init`
+    pub fn _init(&mut self, admin: Address) {
+        self.admin.set(admin);
+    }
+}
+
+/// Behaviour for contracts built on {{ name }}: implement the required methods,
+/// override the default ones as needed
+pub trait I{{ name }} {
+    /// The {{ name }} state the contract embeds
+    fn {{ snake_name }}(&self) -> &{{ name }};
+
+    /// Mutable access to the {{ name }} state
+    fn {{ snake_name }}_mut(&mut self) -> &mut {{ name }};
+
+    // Methods every contract must provide go here, e.g.
+    // fn fee(&self) -> U256;
+
+    /// Fail unless the caller is the admin
+    fn only_admin(&self) -> Result<(), Vec<u8>> {
+        if msg::sender() != self.{{ snake_name }}().admin.get() {
+            return Err(b"caller is not the admin".to_vec());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use stylus_sdk::{alloy_primitives::Address, msg, prelude::*, storage::StorageAddress};
+use alloc::vec::Vec;
+
+/// Entrypoint for the {{ name }} contract
+#[entrypoint]
+#[storage]
+pub struct {{ name }} {
+    owner: StorageAddress,
+}
+
+#[public]
+impl {{ name }} {
+    /// Constructor
+    pub fn init(&mut self) -> Result<(), Vec<u8>> {
+        self.owner.set(msg::sender());
+        Ok(())
+    }
+    
+    /// Get the owner
+    pub fn owner(&self) -> Address {
+        self.owner.get()
+    }
+}
+{%- if with_test %}
+
+#[cfg(test)]
+mod tests;
+{%- endif %}
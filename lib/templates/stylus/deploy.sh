@@ -0,0 +1,50 @@
+#!/usr/bin/env bash
+# cargo stylus, with RPC_URL and PRIVATE_KEY from .env (see .env.example)
+#
+#   scripts/stylus.sh check             check the contract can be activated on RPC_URL
+#   scripts/stylus.sh deploy [EXAMPLE]  check, build, then deploy, activate and call init
+#                                       with examples/EXAMPLE.rs (the only deploy_* example
+#                                       by default); upgradeable contracts deploy the
+#                                       proxy in proxy/ and initialize through it
+#   scripts/stylus.sh deploy-only       cargo stylus deploy, without calling init; this
+#                                       deploys an upgradeable contract's implementation
+set -euo pipefail
+cd "$(dirname "$0")/.."
+
+if [ -f .env ]; then
+    set -a
+    . ./.env
+    set +a
+fi
+: "${RPC_URL:?is not set; copy .env.example to .env}"
+
+case "${1:-}" in
+    check)
+        cargo stylus check --endpoint "$RPC_URL"
+        ;;
+    deploy)
+        example="${2:-}"
+        if [ -z "$example" ]; then
+            examples=(examples/deploy_*.rs)
+            if [ ! -f "${examples[0]}" ] || [ -n "${examples[1]:-}" ]; then
+                echo "Name the deploy example to run: scripts/stylus.sh deploy deploy_<contract>" >&2
+                exit 1
+            fi
+            example="$(basename "${examples[0]}" .rs)"
+        fi
+        cargo stylus check --endpoint "$RPC_URL"
+        cargo build --release --lib --target wasm32-unknown-unknown
+        if [ -f proxy/Cargo.toml ]; then
+            (cd proxy && cargo build --release --lib --target wasm32-unknown-unknown)
+        fi
+        cargo run --example "$example"
+        ;;
+    deploy-only)
+        : "${PRIVATE_KEY:?is not set; copy .env.example to .env}"
+        cargo stylus deploy --endpoint "$RPC_URL" --private-key "$PRIVATE_KEY"
+        ;;
+    *)
+        echo "Usage: scripts/stylus.sh check | deploy [EXAMPLE] | deploy-only" >&2
+        exit 1
+        ;;
+esac
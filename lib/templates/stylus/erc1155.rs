@@ -0,0 +1,365 @@
+{#- Minting, pausing, token URIs and upgrades are restricted to an owner #}
+use openzeppelin_stylus::{
+    access::ownable::{IOwnable, Ownable},
+{%- if upgradeable %}
+    proxy::utils::{
+        erc1822::IErc1822Proxiable,
+        uups_upgradeable::{IUUPSUpgradeable, UUPSUpgradeable},
+    },
+{%- endif %}
+    token::erc1155::{
+{%- if "burnable" in extensions and "supply" in extensions %}
+        self,
+{%- endif %}
+        extensions::{
+            Erc1155MetadataUri, IErc1155MetadataUri,
+{%- if "burnable" in extensions %}
+            IErc1155Burnable,
+{%- endif %}
+{%- if "supply" in extensions %}
+            Erc1155Supply, IErc1155Supply,
+{%- endif %}
+{%- if "uristorage" in extensions %}
+            Erc1155UriStorage,
+{%- endif %}
+        },
+{%- if "supply" in extensions %}
+        IErc1155,
+{%- else %}
+        Erc1155, IErc1155,
+{%- endif %}
+    },
+    utils::{
+        introspection::erc165::IErc165,
+{%- if "pausable" in extensions %}
+        pausable::{IPausable, Pausable},
+{%- endif %}
+    },
+};
+use stylus_sdk::{
+    abi::Bytes,
+{%- if upgradeable %}
+    alloy_primitives::{aliases::B32, Address, B256, U256},
+{%- else %}
+    alloy_primitives::{aliases::B32, Address, U256},
+{%- endif %}
+    msg,
+    prelude::*,
+    storage::StorageBool,
+};
+use alloc::string::String;
+use alloc::vec::Vec;
+{#- Erc1155Supply wraps the Erc1155 it tracks, so token calls go through it #}
+{%- set token = "self.supply" if "supply" in extensions else "self.erc1155" %}
+
+/// {{ name }} Multi-Token Contract
+#[entrypoint]
+#[storage]
+struct {{ name }} {
+{%- if "supply" in extensions %}
+    supply: Erc1155Supply,
+{%- else %}
+    erc1155: Erc1155,
+{%- endif %}
+    metadata_uri: Erc1155MetadataUri,
+{%- if "pausable" in extensions %}
+    pausable: Pausable,
+{%- endif %}
+{%- if "uristorage" in extensions %}
+    uri_storage: Erc1155UriStorage,
+{%- endif %}
+    ownable: Ownable,
+{%- if upgradeable %}
+    uups: UUPSUpgradeable,
+{%- endif %}
+    initialized: StorageBool,
+}
+
+#[public]
+#[implements(IErc1155<Error = Vec<u8>>, IErc1155MetadataUri{% if "burnable" in extensions %}, IErc1155Burnable<Error = Vec<u8>>{% endif %}{% if "supply" in extensions %}, IErc1155Supply{% endif %}{% if "pausable" in extensions %}, IPausable{% endif %}, IOwnable{% if upgradeable %}, IUUPSUpgradeable, IErc1822Proxiable{% endif %}, IErc165)]
+impl {{ name }} {
+{%- if upgradeable %}
+    /// Mark this deployment as the implementation, which upgrades only through a proxy
+    #[constructor]
+    pub fn constructor(&mut self) {
+        self.uups.constructor();
+    }
+{% endif %}
+    /// Initialize the multi-token contract
+    ///
+{%- if upgradeable %}
+    /// Runs once, through the proxy; the caller becomes the owner, who may mint and upgrade
+{%- else %}
+    /// Runs once; the caller becomes the owner, who may mint
+{%- endif %}
+    pub fn init(&mut self{% if not base_uri %}, uri: String{% endif %}) -> Result<(), Vec<u8>> {
+        if self.initialized.get() {
+            return Err(b"already initialized".to_vec());
+        }
+        self.initialized.set(true);
+{%- if upgradeable %}
+        self.uups.set_version()?;
+{%- endif %}
+        self.ownable.constructor(msg::sender())?;
+        self.metadata_uri.constructor({% if base_uri %}String::from("{{ base_uri }}"){% else %}uri{% endif %});
+        Ok(())
+    }
+
+    /// Mint tokens; only the owner may mint
+    pub fn mint(
+        &mut self,
+        to: Address,
+        id: U256,
+        amount: U256,
+        data: Bytes,
+    ) -> Result<(), Vec<u8>> {
+        self.ownable.only_owner()?;
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+        {{ token }}._mint(to, id, amount, &data)?;
+        Ok(())
+    }
+
+    /// Mint batch of tokens; only the owner may mint
+    pub fn mint_batch(
+        &mut self,
+        to: Address,
+        ids: Vec<U256>,
+        amounts: Vec<U256>,
+        data: Bytes,
+    ) -> Result<(), Vec<u8>> {
+        self.ownable.only_owner()?;
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+        {{ token }}._mint_batch(to, ids, amounts, &data)?;
+        Ok(())
+    }
+{%- if "pausable" in extensions %}
+
+    /// Stop transfers, mints and burns; only the owner may pause
+    pub fn pause(&mut self) -> Result<(), Vec<u8>> {
+        self.ownable.only_owner()?;
+        Ok(self.pausable.pause()?)
+    }
+
+    /// Resume transfers, mints and burns; only the owner may unpause
+    pub fn unpause(&mut self) -> Result<(), Vec<u8>> {
+        self.ownable.only_owner()?;
+        Ok(self.pausable.unpause()?)
+    }
+{%- endif %}
+{%- if "uristorage" in extensions %}
+
+    /// Set the URI of token `id`; only the owner may set it
+    pub fn set_token_uri(&mut self, id: U256, token_uri: String) -> Result<(), Vec<u8>> {
+        self.ownable.only_owner()?;
+        self.uri_storage.set_token_uri(id, token_uri, &self.metadata_uri);
+        Ok(())
+    }
+{%- endif %}
+{%- if "burnable" in extensions and "supply" in extensions %}
+
+    /// Fail unless the caller is `account` or approved for all of its tokens
+    fn only_approved(&self, account: Address) -> Result<(), Vec<u8>> {
+        let sender = msg::sender();
+        if account != sender && !self.supply.is_approved_for_all(account, sender) {
+            return Err(erc1155::Error::MissingApprovalForAll(erc1155::ERC1155MissingApprovalForAll {
+                operator: sender,
+                owner: account,
+            }))?;
+        }
+        Ok(())
+    }
+{%- endif %}
+}
+
+#[public]
+impl IErc1155 for {{ name }} {
+    type Error = Vec<u8>;
+
+    fn balance_of(&self, account: Address, id: U256) -> U256 {
+        {{ token }}.balance_of(account, id)
+    }
+
+    fn balance_of_batch(&self, accounts: Vec<Address>, ids: Vec<U256>) -> Result<Vec<U256>, Self::Error> {
+        Ok({{ token }}.balance_of_batch(accounts, ids)?)
+    }
+
+    fn set_approval_for_all(&mut self, operator: Address, approved: bool) -> Result<(), Self::Error> {
+        Ok({{ token }}.set_approval_for_all(operator, approved)?)
+    }
+
+    fn is_approved_for_all(&self, account: Address, operator: Address) -> bool {
+        {{ token }}.is_approved_for_all(account, operator)
+    }
+
+    /// Transfer `value` of `id` tokens{% if "pausable" in extensions %}, unless paused{% endif %}
+    fn safe_transfer_from(
+        &mut self,
+        from: Address,
+        to: Address,
+        id: U256,
+        value: U256,
+        data: Bytes,
+    ) -> Result<(), Self::Error> {
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+        Ok({{ token }}.safe_transfer_from(from, to, id, value, data)?)
+    }
+
+    /// Transfer `values` of `ids` tokens{% if "pausable" in extensions %}, unless paused{% endif %}
+    fn safe_batch_transfer_from(
+        &mut self,
+        from: Address,
+        to: Address,
+        ids: Vec<U256>,
+        values: Vec<U256>,
+        data: Bytes,
+    ) -> Result<(), Self::Error> {
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+        Ok({{ token }}.safe_batch_transfer_from(from, to, ids, values, data)?)
+    }
+}
+
+#[public]
+impl IErc1155MetadataUri for {{ name }} {
+{%- if "uristorage" in extensions %}
+    /// The URI set for `id`, appended to the base URI, or the shared URI when none is set
+    fn uri(&self, id: U256) -> String {
+        self.uri_storage.uri(id, &self.metadata_uri)
+    }
+{%- else %}
+    fn uri(&self, id: U256) -> String {
+        self.metadata_uri.uri(id)
+    }
+{%- endif %}
+}
+{%- if "burnable" in extensions %}
+
+#[public]
+impl IErc1155Burnable for {{ name }} {
+    type Error = Vec<u8>;
+
+    /// Destroy `value` of `account`'s `id` tokens; callers must be `account` or approved
+    fn burn(&mut self, account: Address, token_id: U256, value: U256) -> Result<(), Self::Error> {
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+{%- if "supply" in extensions %}
+        self.only_approved(account)?;
+        Ok(self.supply._burn(account, token_id, value)?)
+{%- else %}
+        Ok(self.erc1155.burn(account, token_id, value)?)
+{%- endif %}
+    }
+
+    /// Destroy `values` of `account`'s `token_ids` tokens
+    fn burn_batch(&mut self, account: Address, token_ids: Vec<U256>, values: Vec<U256>) -> Result<(), Self::Error> {
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+{%- if "supply" in extensions %}
+        self.only_approved(account)?;
+        Ok(self.supply._burn_batch(account, token_ids, values)?)
+{%- else %}
+        Ok(self.erc1155.burn_batch(account, token_ids, values)?)
+{%- endif %}
+    }
+}
+{%- endif %}
+{%- if "supply" in extensions %}
+
+#[public]
+impl IErc1155Supply for {{ name }} {
+    fn total_supply(&self, id: U256) -> U256 {
+        self.supply.total_supply(id)
+    }
+
+    #[selector(name = "totalSupply")]
+    fn total_supply_all(&self) -> U256 {
+        self.supply.total_supply_all()
+    }
+
+    fn exists(&self, id: U256) -> bool {
+        self.supply.exists(id)
+    }
+}
+{%- endif %}
+{%- if "pausable" in extensions %}
+
+#[public]
+impl IPausable for {{ name }} {
+    fn paused(&self) -> bool {
+        self.pausable.paused()
+    }
+}
+{%- endif %}
+
+#[public]
+impl IOwnable for {{ name }} {
+    fn owner(&self) -> Address {
+        self.ownable.owner()
+    }
+
+    fn transfer_ownership(&mut self, new_owner: Address) -> Result<(), Vec<u8>> {
+        Ok(self.ownable.transfer_ownership(new_owner)?)
+    }
+
+    fn renounce_ownership(&mut self) -> Result<(), Vec<u8>> {
+        Ok(self.ownable.renounce_ownership()?)
+    }
+}
+{%- if upgradeable %}
+
+#[public]
+impl IUUPSUpgradeable for {{ name }} {
+    /// Version of the UUPS upgrade interface
+    #[selector(name = "UPGRADE_INTERFACE_VERSION")]
+    fn upgrade_interface_version(&self) -> String {
+        self.uups.upgrade_interface_version()
+    }
+
+    /// Point the proxy at `new_implementation` and call it with `data`, if any;
+    /// only the owner may upgrade
+    #[payable]
+    fn upgrade_to_and_call(&mut self, new_implementation: Address, data: Bytes) -> Result<(), Vec<u8>> {
+        self.ownable.only_owner()?;
+        self.uups.upgrade_to_and_call(new_implementation, data)
+    }
+}
+
+#[public]
+impl IErc1822Proxiable for {{ name }} {
+    /// The ERC-1967 implementation slot; reverts when called through the proxy
+    #[selector(name = "proxiableUUID")]
+    fn proxiable_uuid(&self) -> Result<B256, Vec<u8>> {
+        self.uups.proxiable_uuid()
+    }
+}
+{%- endif %}
+
+#[public]
+impl IErc165 for {{ name }} {
+    fn supports_interface(&self, interface_id: B32) -> bool {
+        <Self as IErc1155>::interface_id() == interface_id
+            || <Self as IErc1155MetadataUri>::interface_id() == interface_id
+{%- if "burnable" in extensions %}
+            || <Self as IErc1155Burnable>::interface_id() == interface_id
+{%- endif %}
+{%- if "supply" in extensions %}
+            || <Self as IErc1155Supply>::interface_id() == interface_id
+{%- endif %}
+            || <Self as IOwnable>::interface_id() == interface_id
+            || <Self as IErc165>::interface_id() == interface_id
+    }
+}
+{%- if with_test %}
+
+#[cfg(test)]
+mod tests;
+{%- endif %}
@@ -0,0 +1,356 @@
+{#- Minting, pausing and upgrades are restricted to an owner #}
+use openzeppelin_stylus::{
+    access::ownable::{IOwnable, Ownable},
+{%- if upgradeable %}
+    proxy::utils::{
+        erc1822::IErc1822Proxiable,
+        uups_upgradeable::{IUUPSUpgradeable, UUPSUpgradeable},
+    },
+{%- endif %}
+    token::erc20::{
+        extensions::{
+            Erc20Metadata, IErc20Metadata,
+{%- if "burnable" in extensions %}
+            IErc20Burnable,
+{%- endif %}
+{%- if "capped" in extensions %}
+            capped, Capped, ICapped,
+{%- endif %}
+{%- if "permit" in extensions %}
+            Erc20Permit, IErc20Permit,
+{%- endif %}
+        },
+        Erc20, IErc20,
+    },
+    utils::{
+        introspection::erc165::IErc165,
+{%- if "pausable" in extensions %}
+        pausable::{IPausable, Pausable},
+{%- endif %}
+{%- if "permit" in extensions %}
+        cryptography::eip712::IEip712,
+        nonces::{INonces, Nonces},
+{%- endif %}
+    },
+};
+use stylus_sdk::{
+{%- if upgradeable %}
+    abi::Bytes,
+{%- endif %}
+{%- if upgradeable or "permit" in extensions %}
+    alloy_primitives::{aliases::B32, Address, B256, U256, U8},
+{%- else %}
+    alloy_primitives::{aliases::B32, Address, U256, U8},
+{%- endif %}
+    msg,
+    prelude::*,
+    storage::StorageBool,
+};
+use alloc::string::String;
+use alloc::vec::Vec;
+{%- if "permit" in extensions %}
+
+/// EIP-712 domain the permit signatures are checked against
+#[storage]
+struct Eip712 {}
+
+impl IEip712 for Eip712 {
+    const NAME: &'static str = "{{ token_name }}";
+    const VERSION: &'static str = "1";
+}
+{%- endif %}
+
+/// {{ name }} ERC20 Token
+#[entrypoint]
+#[storage]
+struct {{ name }} {
+    erc20: Erc20,
+    metadata: Erc20Metadata,
+{%- if "capped" in extensions %}
+    capped: Capped,
+{%- endif %}
+{%- if "pausable" in extensions %}
+    pausable: Pausable,
+{%- endif %}
+{%- if "permit" in extensions %}
+    permit: Erc20Permit<Eip712>,
+    nonces: Nonces,
+{%- endif %}
+    ownable: Ownable,
+{%- if upgradeable %}
+    uups: UUPSUpgradeable,
+{%- endif %}
+    initialized: StorageBool,
+}
+
+#[public]
+#[implements(IErc20<Error = Vec<u8>>, IErc20Metadata{% if "burnable" in extensions %}, IErc20Burnable<Error = Vec<u8>>{% endif %}{% if "capped" in extensions %}, ICapped{% endif %}{% if "pausable" in extensions %}, IPausable{% endif %}{% if "permit" in extensions %}, IErc20Permit<Error = Vec<u8>>, INonces{% endif %}, IOwnable{% if upgradeable %}, IUUPSUpgradeable, IErc1822Proxiable{% endif %}, IErc165)]
+impl {{ name }} {
+{%- if upgradeable %}
+    /// Mark this deployment as the implementation, which upgrades only through a proxy
+    #[constructor]
+    pub fn constructor(&mut self) {
+        self.uups.constructor();
+    }
+{% endif %}
+    /// Initialize the contract with an initial supply
+    ///
+{%- if upgradeable %}
+    /// Runs once, through the proxy; the caller becomes the owner, who may mint and upgrade
+{%- else %}
+    /// Runs once; the caller becomes the owner, who may mint
+{%- endif %}
+    pub fn init(&mut self, initial_supply: U256{% if "capped" in extensions %}, cap: U256{% endif %}) -> Result<(), Vec<u8>> {
+        if self.initialized.get() {
+            return Err(b"already initialized".to_vec());
+        }
+        self.initialized.set(true);
+{%- if upgradeable %}
+        self.uups.set_version()?;
+{%- endif %}
+        self.ownable.constructor(msg::sender())?;
+        self.metadata.constructor(String::from("{{ token_name }}"), String::from("{{ symbol }}"));
+{%- if "capped" in extensions %}
+        self.capped.constructor(cap)?;
+{%- endif %}
+
+        // Mint initial supply to deployer
+        let deployer = msg::sender();
+        self.erc20._mint(deployer, initial_supply)?;
+
+        Ok(())
+    }
+
+    /// Mint new tokens; only the owner may mint
+    pub fn mint(&mut self, to: Address, amount: U256) -> Result<(), Vec<u8>> {
+        self.ownable.only_owner()?;
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+{%- if "capped" in extensions %}
+        let max_supply = self.capped.cap();
+        // A supply past U256::MAX exceeds any cap
+        let supply = self.erc20.total_supply().checked_add(amount);
+        if supply.is_none_or(|supply| supply > max_supply) {
+            return Err(capped::Error::ExceededCap(capped::ERC20ExceededCap {
+                increased_supply: supply.unwrap_or(U256::MAX),
+                cap: max_supply,
+            }))?;
+        }
+{%- endif %}
+        self.erc20._mint(to, amount)?;
+        Ok(())
+    }
+{%- if "pausable" in extensions %}
+
+    /// Stop transfers, mints and burns; only the owner may pause
+    pub fn pause(&mut self) -> Result<(), Vec<u8>> {
+        self.ownable.only_owner()?;
+        Ok(self.pausable.pause()?)
+    }
+
+    /// Resume transfers, mints and burns; only the owner may unpause
+    pub fn unpause(&mut self) -> Result<(), Vec<u8>> {
+        self.ownable.only_owner()?;
+        Ok(self.pausable.unpause()?)
+    }
+{%- endif %}
+}
+
+#[public]
+impl IErc20 for {{ name }} {
+    type Error = Vec<u8>;
+
+    fn total_supply(&self) -> U256 {
+        self.erc20.total_supply()
+    }
+
+    fn balance_of(&self, account: Address) -> U256 {
+        self.erc20.balance_of(account)
+    }
+
+    /// Transfer tokens{% if "pausable" in extensions %}, unless paused{% endif %}
+    fn transfer(&mut self, to: Address, value: U256) -> Result<bool, Self::Error> {
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+        Ok(self.erc20.transfer(to, value)?)
+    }
+
+    fn allowance(&self, owner: Address, spender: Address) -> U256 {
+        self.erc20.allowance(owner, spender)
+    }
+
+    fn approve(&mut self, spender: Address, value: U256) -> Result<bool, Self::Error> {
+        Ok(self.erc20.approve(spender, value)?)
+    }
+
+    /// Transfer tokens using an allowance{% if "pausable" in extensions %}, unless paused{% endif %}
+    fn transfer_from(&mut self, from: Address, to: Address, value: U256) -> Result<bool, Self::Error> {
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+        Ok(self.erc20.transfer_from(from, to, value)?)
+    }
+}
+
+#[public]
+impl IErc20Metadata for {{ name }} {
+    fn name(&self) -> String {
+        self.metadata.name()
+    }
+
+    fn symbol(&self) -> String {
+        self.metadata.symbol()
+    }
+{%- if decimals != 18 %}
+
+    /// Token decimals, in place of the default 18
+    fn decimals(&self) -> U8 {
+        U8::from({{ decimals }})
+    }
+{%- else %}
+
+    fn decimals(&self) -> U8 {
+        self.metadata.decimals()
+    }
+{%- endif %}
+}
+{%- if "burnable" in extensions %}
+
+#[public]
+impl IErc20Burnable for {{ name }} {
+    type Error = Vec<u8>;
+
+    /// Destroy `value` of the caller's tokens
+    fn burn(&mut self, value: U256) -> Result<(), Self::Error> {
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+        Ok(self.erc20.burn(value)?)
+    }
+
+    /// Destroy `value` of `account`'s tokens, spending the caller's allowance
+    fn burn_from(&mut self, account: Address, value: U256) -> Result<(), Self::Error> {
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+        Ok(self.erc20.burn_from(account, value)?)
+    }
+}
+{%- endif %}
+{%- if "capped" in extensions %}
+
+#[public]
+impl ICapped for {{ name }} {
+    fn cap(&self) -> U256 {
+        self.capped.cap()
+    }
+}
+{%- endif %}
+{%- if "pausable" in extensions %}
+
+#[public]
+impl IPausable for {{ name }} {
+    fn paused(&self) -> bool {
+        self.pausable.paused()
+    }
+}
+{%- endif %}
+{%- if "permit" in extensions %}
+
+#[public]
+impl IErc20Permit for {{ name }} {
+    type Error = Vec<u8>;
+
+    #[selector(name = "DOMAIN_SEPARATOR")]
+    fn domain_separator(&self) -> B256 {
+        self.permit.domain_separator()
+    }
+
+    /// Approve `spender` for `value` of `owner`'s tokens with `owner`'s signature
+    fn permit(
+        &mut self,
+        owner: Address,
+        spender: Address,
+        value: U256,
+        deadline: U256,
+        v: u8,
+        r: B256,
+        s: B256,
+    ) -> Result<(), Self::Error> {
+        Ok(self.permit.permit(owner, spender, value, deadline, v, r, s, &mut self.erc20, &mut self.nonces)?)
+    }
+}
+
+#[public]
+impl INonces for {{ name }} {
+    fn nonces(&self, owner: Address) -> U256 {
+        self.nonces.nonces(owner)
+    }
+}
+{%- endif %}
+
+#[public]
+impl IOwnable for {{ name }} {
+    fn owner(&self) -> Address {
+        self.ownable.owner()
+    }
+
+    fn transfer_ownership(&mut self, new_owner: Address) -> Result<(), Vec<u8>> {
+        Ok(self.ownable.transfer_ownership(new_owner)?)
+    }
+
+    fn renounce_ownership(&mut self) -> Result<(), Vec<u8>> {
+        Ok(self.ownable.renounce_ownership()?)
+    }
+}
+{%- if upgradeable %}
+
+#[public]
+impl IUUPSUpgradeable for {{ name }} {
+    /// Version of the UUPS upgrade interface
+    #[selector(name = "UPGRADE_INTERFACE_VERSION")]
+    fn upgrade_interface_version(&self) -> String {
+        self.uups.upgrade_interface_version()
+    }
+
+    /// Point the proxy at `new_implementation` and call it with `data`, if any;
+    /// only the owner may upgrade
+    #[payable]
+    fn upgrade_to_and_call(&mut self, new_implementation: Address, data: Bytes) -> Result<(), Vec<u8>> {
+        self.ownable.only_owner()?;
+        self.uups.upgrade_to_and_call(new_implementation, data)
+    }
+}
+
+#[public]
+impl IErc1822Proxiable for {{ name }} {
+    /// The ERC-1967 implementation slot; reverts when called through the proxy
+    #[selector(name = "proxiableUUID")]
+    fn proxiable_uuid(&self) -> Result<B256, Vec<u8>> {
+        self.uups.proxiable_uuid()
+    }
+}
+{%- endif %}
+
+#[public]
+impl IErc165 for {{ name }} {
+    fn supports_interface(&self, interface_id: B32) -> bool {
+        <Self as IErc20>::interface_id() == interface_id
+            || <Self as IErc20Metadata>::interface_id() == interface_id
+{%- if "capped" in extensions %}
+            || <Self as ICapped>::interface_id() == interface_id
+{%- endif %}
+{%- if "permit" in extensions %}
+            || <Self as IErc20Permit>::interface_id() == interface_id
+{%- endif %}
+            || <Self as IOwnable>::interface_id() == interface_id
+            || <Self as IErc165>::interface_id() == interface_id
+    }
+}
+{%- if with_test %}
+
+#[cfg(test)]
+mod tests;
+{%- endif %}
@@ -0,0 +1,341 @@
+{#- Minting, pausing, token URIs and upgrades are restricted to an owner #}
+use openzeppelin_stylus::{
+    access::ownable::{IOwnable, Ownable},
+{%- if upgradeable %}
+    proxy::utils::{
+        erc1822::IErc1822Proxiable,
+        uups_upgradeable::{IUUPSUpgradeable, UUPSUpgradeable},
+    },
+{%- endif %}
+    token::erc721::{
+        extensions::{
+            Erc721Metadata, IErc721Metadata,
+{%- if "burnable" in extensions %}
+            IErc721Burnable,
+{%- endif %}
+{%- if "enumerable" in extensions %}
+            Erc721Enumerable, IErc721Enumerable,
+{%- endif %}
+{%- if "uristorage" in extensions %}
+            Erc721UriStorage,
+{%- endif %}
+        },
+        Erc721, IErc721,
+    },
+    utils::{
+        introspection::erc165::IErc165,
+{%- if "pausable" in extensions %}
+        pausable::{IPausable, Pausable},
+{%- endif %}
+    },
+};
+use stylus_sdk::{
+    abi::Bytes,
+{%- if upgradeable %}
+    alloy_primitives::{aliases::B32, Address, B256, U256},
+{%- else %}
+    alloy_primitives::{aliases::B32, Address, U256},
+{%- endif %}
+    msg,
+    prelude::*,
+    storage::{StorageBool, StorageU256},
+};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// {{ name }} NFT Collection
+#[entrypoint]
+#[storage]
+struct {{ name }} {
+    erc721: Erc721,
+    metadata: Erc721Metadata,
+{%- if "enumerable" in extensions %}
+    enumerable: Erc721Enumerable,
+{%- endif %}
+{%- if "pausable" in extensions %}
+    pausable: Pausable,
+{%- endif %}
+{%- if "uristorage" in extensions %}
+    uri_storage: Erc721UriStorage,
+{%- endif %}
+    next_token_id: StorageU256,
+    ownable: Ownable,
+{%- if upgradeable %}
+    uups: UUPSUpgradeable,
+{%- endif %}
+    initialized: StorageBool,
+}
+
+#[public]
+#[implements(IErc721<Error = Vec<u8>>, IErc721Metadata<Error = Vec<u8>>{% if "burnable" in extensions %}, IErc721Burnable<Error = Vec<u8>>{% endif %}{% if "enumerable" in extensions %}, IErc721Enumerable<Error = Vec<u8>>{% endif %}{% if "pausable" in extensions %}, IPausable{% endif %}, IOwnable{% if upgradeable %}, IUUPSUpgradeable, IErc1822Proxiable{% endif %}, IErc165)]
+impl {{ name }} {
+{%- if upgradeable %}
+    /// Mark this deployment as the implementation, which upgrades only through a proxy
+    #[constructor]
+    pub fn constructor(&mut self) {
+        self.uups.constructor();
+    }
+{% endif %}
+    /// Initialize the NFT collection
+    ///
+{%- if upgradeable %}
+    /// Runs once, through the proxy; the caller becomes the owner, who may mint and upgrade
+{%- else %}
+    /// Runs once; the caller becomes the owner, who may mint
+{%- endif %}
+    pub fn init(&mut self) -> Result<(), Vec<u8>> {
+        if self.initialized.get() {
+            return Err(b"already initialized".to_vec());
+        }
+        self.initialized.set(true);
+{%- if upgradeable %}
+        self.uups.set_version()?;
+{%- endif %}
+        self.ownable.constructor(msg::sender())?;
+        self.metadata.constructor(String::from("{{ token_name }}"), String::from("{{ symbol }}"));
+{%- if base_uri %}
+        self.metadata.base_uri.set_str("{{ base_uri }}");
+{%- endif %}
+        self.next_token_id.set(U256::from(1));
+        Ok(())
+    }
+
+    /// Mint a new NFT; only the owner may mint
+    pub fn mint(&mut self, to: Address) -> Result<U256, Vec<u8>> {
+        self.ownable.only_owner()?;
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+        let token_id = self.next_token_id.get();
+        self.erc721._mint(to, token_id)?;
+{%- if "enumerable" in extensions %}
+        self.enumerable._add_token_to_all_tokens_enumeration(token_id);
+        self.enumerable._add_token_to_owner_enumeration(to, token_id, &self.erc721)?;
+{%- endif %}
+        self.next_token_id.set(token_id + U256::from(1));
+        Ok(token_id)
+    }
+{%- if "pausable" in extensions %}
+
+    /// Stop transfers, mints and burns; only the owner may pause
+    pub fn pause(&mut self) -> Result<(), Vec<u8>> {
+        self.ownable.only_owner()?;
+        Ok(self.pausable.pause()?)
+    }
+
+    /// Resume transfers, mints and burns; only the owner may unpause
+    pub fn unpause(&mut self) -> Result<(), Vec<u8>> {
+        self.ownable.only_owner()?;
+        Ok(self.pausable.unpause()?)
+    }
+{%- endif %}
+{%- if "uristorage" in extensions %}
+
+    /// Set the URI of `token_id`; only the owner may set it
+    pub fn set_token_uri(&mut self, token_id: U256, uri: String) -> Result<(), Vec<u8>> {
+        self.ownable.only_owner()?;
+        self.erc721.owner_of(token_id)?;
+        self.uri_storage._set_token_uri(token_id, uri);
+        Ok(())
+    }
+{%- endif %}
+}
+
+#[public]
+impl IErc721 for {{ name }} {
+    type Error = Vec<u8>;
+
+    fn balance_of(&self, owner: Address) -> Result<U256, Self::Error> {
+        Ok(self.erc721.balance_of(owner)?)
+    }
+
+    fn owner_of(&self, token_id: U256) -> Result<Address, Self::Error> {
+        Ok(self.erc721.owner_of(token_id)?)
+    }
+
+    /// Transfer `token_id`, checking that a contract recipient accepts it
+    fn safe_transfer_from(&mut self, from: Address, to: Address, token_id: U256) -> Result<(), Self::Error> {
+        self.safe_transfer_from_with_data(from, to, token_id, Vec::new().into())
+    }
+
+    /// Transfer `token_id` with `data` for the recipient's `onERC721Received`
+    #[selector(name = "safeTransferFrom")]
+    fn safe_transfer_from_with_data(&mut self, from: Address, to: Address, token_id: U256, data: Bytes) -> Result<(), Self::Error> {
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+        self.erc721.safe_transfer_from_with_data(from, to, token_id, data)?;
+{%- if "enumerable" in extensions %}
+        self.enumerable._remove_token_from_owner_enumeration(from, token_id, &self.erc721)?;
+        self.enumerable._add_token_to_owner_enumeration(to, token_id, &self.erc721)?;
+{%- endif %}
+        Ok(())
+    }
+
+    /// Transfer `token_id` from `from` to `to`
+    fn transfer_from(&mut self, from: Address, to: Address, token_id: U256) -> Result<(), Self::Error> {
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+        self.erc721.transfer_from(from, to, token_id)?;
+{%- if "enumerable" in extensions %}
+        self.enumerable._remove_token_from_owner_enumeration(from, token_id, &self.erc721)?;
+        self.enumerable._add_token_to_owner_enumeration(to, token_id, &self.erc721)?;
+{%- endif %}
+        Ok(())
+    }
+
+    fn approve(&mut self, to: Address, token_id: U256) -> Result<(), Self::Error> {
+        Ok(self.erc721.approve(to, token_id)?)
+    }
+
+    fn set_approval_for_all(&mut self, operator: Address, approved: bool) -> Result<(), Self::Error> {
+        Ok(self.erc721.set_approval_for_all(operator, approved)?)
+    }
+
+    fn get_approved(&self, token_id: U256) -> Result<Address, Self::Error> {
+        Ok(self.erc721.get_approved(token_id)?)
+    }
+
+    fn is_approved_for_all(&self, owner: Address, operator: Address) -> bool {
+        self.erc721.is_approved_for_all(owner, operator)
+    }
+}
+
+#[public]
+impl IErc721Metadata for {{ name }} {
+    type Error = Vec<u8>;
+
+    fn name(&self) -> String {
+        self.metadata.name()
+    }
+
+    fn symbol(&self) -> String {
+        self.metadata.symbol()
+    }
+
+    /// The URI of `token_id`, under the base URI when there is one
+    #[selector(name = "tokenURI")]
+    fn token_uri(&self, token_id: U256) -> Result<String, Self::Error> {
+{%- if "uristorage" in extensions %}
+        Ok(self.uri_storage.token_uri(token_id, &self.erc721, &self.metadata)?)
+{%- else %}
+        Ok(self.metadata.token_uri(token_id, &self.erc721)?)
+{%- endif %}
+    }
+}
+{%- if "burnable" in extensions %}
+
+#[public]
+impl IErc721Burnable for {{ name }} {
+    type Error = Vec<u8>;
+
+    /// Destroy `token_id`; callers must own it or be approved for it
+    fn burn(&mut self, token_id: U256) -> Result<(), Self::Error> {
+{%- if "pausable" in extensions %}
+        self.pausable.when_not_paused()?;
+{%- endif %}
+{%- if "enumerable" in extensions %}
+        let owner = self.erc721.owner_of(token_id)?;
+{%- endif %}
+        self.erc721.burn(token_id)?;
+{%- if "enumerable" in extensions %}
+        self.enumerable._remove_token_from_owner_enumeration(owner, token_id, &self.erc721)?;
+        self.enumerable._remove_token_from_all_tokens_enumeration(token_id);
+{%- endif %}
+        Ok(())
+    }
+}
+{%- endif %}
+{%- if "enumerable" in extensions %}
+
+#[public]
+impl IErc721Enumerable for {{ name }} {
+    type Error = Vec<u8>;
+
+    fn token_of_owner_by_index(&self, owner: Address, index: U256) -> Result<U256, Self::Error> {
+        Ok(self.enumerable.token_of_owner_by_index(owner, index)?)
+    }
+
+    fn total_supply(&self) -> U256 {
+        self.enumerable.total_supply()
+    }
+
+    fn token_by_index(&self, index: U256) -> Result<U256, Self::Error> {
+        Ok(self.enumerable.token_by_index(index)?)
+    }
+}
+{%- endif %}
+{%- if "pausable" in extensions %}
+
+#[public]
+impl IPausable for {{ name }} {
+    fn paused(&self) -> bool {
+        self.pausable.paused()
+    }
+}
+{%- endif %}
+
+#[public]
+impl IOwnable for {{ name }} {
+    fn owner(&self) -> Address {
+        self.ownable.owner()
+    }
+
+    fn transfer_ownership(&mut self, new_owner: Address) -> Result<(), Vec<u8>> {
+        Ok(self.ownable.transfer_ownership(new_owner)?)
+    }
+
+    fn renounce_ownership(&mut self) -> Result<(), Vec<u8>> {
+        Ok(self.ownable.renounce_ownership()?)
+    }
+}
+{%- if upgradeable %}
+
+#[public]
+impl IUUPSUpgradeable for {{ name }} {
+    /// Version of the UUPS upgrade interface
+    #[selector(name = "UPGRADE_INTERFACE_VERSION")]
+    fn upgrade_interface_version(&self) -> String {
+        self.uups.upgrade_interface_version()
+    }
+
+    /// Point the proxy at `new_implementation` and call it with `data`, if any;
+    /// only the owner may upgrade
+    #[payable]
+    fn upgrade_to_and_call(&mut self, new_implementation: Address, data: Bytes) -> Result<(), Vec<u8>> {
+        self.ownable.only_owner()?;
+        self.uups.upgrade_to_and_call(new_implementation, data)
+    }
+}
+
+#[public]
+impl IErc1822Proxiable for {{ name }} {
+    /// The ERC-1967 implementation slot; reverts when called through the proxy
+    #[selector(name = "proxiableUUID")]
+    fn proxiable_uuid(&self) -> Result<B256, Vec<u8>> {
+        self.uups.proxiable_uuid()
+    }
+}
+{%- endif %}
+
+#[public]
+impl IErc165 for {{ name }} {
+    fn supports_interface(&self, interface_id: B32) -> bool {
+        <Self as IErc721>::interface_id() == interface_id
+            || <Self as IErc721Metadata>::interface_id() == interface_id
+{%- if "burnable" in extensions %}
+            || <Self as IErc721Burnable>::interface_id() == interface_id
+{%- endif %}
+{%- if "enumerable" in extensions %}
+            || <Self as IErc721Enumerable>::interface_id() == interface_id
+{%- endif %}
+            || <Self as IOwnable>::interface_id() == interface_id
+            || <Self as IErc165>::interface_id() == interface_id
+    }
+}
+{%- if with_test %}
+
+#[cfg(test)]
+mod tests;
+{%- endif %}
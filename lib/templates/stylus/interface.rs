@@ -0,0 +1,18 @@
+//! I{{ name }}: the Solidity interface of a contract Stylus contracts call
+//!
+//! `sol_interface!` generates an `I{{ name }}` type with one method per function;
+//! construct it with `I{{ name }}::new(address)` and call through it.
+
+use stylus_sdk::prelude::*;
+
+sol_interface! {
+    interface I{{ name }} {
+{%- for function in functions | default([]) %}
+        {{ function }}
+{%- else %}
+        // Add your function signatures here
+        // Example:
+        // function exampleFunction(uint256 value) external returns (bool);
+{%- endfor %}
+    }
+}
@@ -0,0 +1,85 @@
+//! {{ name }} Library
+//! 
+//! A reusable library for Stylus contracts providing utility functions.
+
+use stylus_sdk::alloy_primitives::{Address, U256};
+use alloc::vec::Vec;
+
+/// Example trait for common functionality
+pub trait {{ name }}Operations {
+    /// Example function - replace with your own
+    fn example_operation(&self, value: U256) -> U256;
+    
+    /// Validation function
+    fn is_valid(&self) -> bool;
+}
+
+/// Example data structure for library use
+#[derive(Default)]
+pub struct {{ name }}Data {
+    pub id: U256,
+    pub owner: Address,
+    pub is_active: bool,
+}
+
+impl {{ name }}Data {
+    /// Create new data instance
+    pub fn new(id: U256, owner: Address) -> Self {
+        Self {
+            id,
+            owner,
+            is_active: true,
+        }
+    }
+    
+    /// Validate data integrity
+    pub fn validate(&self) -> bool {
+        !self.owner.is_zero() && self.is_active
+    }
+    
+    /// Example transformation
+    pub fn transform(&mut self, multiplier: U256) {
+        self.id = self.id * multiplier;
+    }
+}
+
+/// Utility functions module
+pub mod {{ snake_name }} {
+    use super::*;
+    
+    /// Example pure function
+    pub fn double_value(value: U256) -> U256 {
+        value * U256::from(2)
+    }
+    
+    /// Example validation function
+    pub fn is_non_zero_address(addr: Address) -> bool {
+        !addr.is_zero()
+    }
+    
+    /// Example calculation function
+    pub fn calculate_percentage(value: U256, percentage: u8) -> U256 {
+        if percentage > 100 {
+            return U256::ZERO;
+        }
+        value * U256::from(percentage) / U256::from(100)
+    }
+}
+
+/// Error types for the library
+#[derive(Debug)]
+pub enum {{ name }}Error {
+    InvalidInput,
+    ZeroAddress,
+    InactiveData,
+}
+
+impl {{ name }}Error {
+    pub fn as_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::InvalidInput => b"Invalid input".to_vec(),
+            Self::ZeroAddress => b"Zero address".to_vec(),
+            Self::InactiveData => b"Inactive data".to_vec(),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! ERC-1967 proxy for the upgradeable contracts of {{ package }}
+//!
+//! Its constructor takes an implementation's address and the calldata of the
+//! implementation's own `init`, so deploy it through the StylusDeployer contract
+//! (as `cargo stylus deploy --constructor-args` does), which creates it and runs
+//! the constructor in one transaction. Upgrades are UUPS: the implementation
+//! decides who may call `upgrade_to_and_call`, and stores the new address here.
+#![cfg_attr(not(any(test, feature = "export-abi")), no_main)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use openzeppelin_stylus::proxy::{erc1967::Erc1967Proxy, IProxy};
+use stylus_sdk::{abi::Bytes, alloy_primitives::Address, prelude::*, ArbResult};
+
+#[entrypoint]
+#[storage]
+struct Proxy {
+    erc1967: Erc1967Proxy,
+}
+
+#[public]
+impl Proxy {
+    /// Point the proxy at `implementation` and call it with `data`
+    #[constructor]
+    pub fn constructor(&mut self, implementation: Address, data: Bytes) -> Result<(), Vec<u8>> {
+        Ok(self.erc1967.constructor(implementation, &data)?)
+    }
+
+    /// The contract calls are delegated to
+    pub fn implementation(&self) -> Result<Address, Vec<u8>> {
+        self.erc1967.implementation()
+    }
+
+    /// Delegate every other call to the implementation
+    #[fallback]
+    fn fallback(&mut self, calldata: &[u8]) -> ArbResult {
+        unsafe { self.erc1967.do_fallback(calldata) }
+    }
+}
@@ -0,0 +1,296 @@
+//! Deploy {{ name }}: `cargo run --example deploy_{{ snake_name }}`, or `scripts/stylus.sh deploy`
+//!
+{%- if upgradeable %}
+//! Deploys the ERC-1967 proxy (`proxy/`, built by `cargo build --release --lib
+//! --target wasm32-unknown-unknown` there) through the StylusDeployer contract,
+//! which creates it and runs its constructor with the implementation's address and
+//! {{ name }}'s `init` calldata in one transaction. Deploy the implementation first with
+//! `scripts/stylus.sh deploy-only`, which runs its constructor, and set
+//! `{{ snake_name | upper }}_IMPLEMENTATION` to its address. `RPC_URL`, `PRIVATE_KEY` and the `init`
+{%- else %}
+//! Deploys the crate's WASM (built by `cargo build --release --lib --target
+//! wasm32-unknown-unknown`), activates it through the ArbWasm precompile and
+//! calls `init`. `RPC_URL`, `PRIVATE_KEY` and the `init`
+{%- endif %}
+//! arguments come from the environment or `.env` (see `.env.example`), which points
+//! at a local Nitro devnode by default. `cargo test --example deploy_{{ snake_name }} -- --include-ignored`
+//! runs the deployment against it.
+{%- set prefix = snake_name | upper %}
+{%- set capped = token_type == "erc20" and "capped" in extensions %}
+{%- set uri_arg = token_type == "erc1155" and not base_uri %}
+
+use alloy::{
+    network::TransactionBuilder,
+{%- if upgradeable %}
+    primitives::{address, keccak256, Address, Bytes, B256, U256},
+{%- else %}
+    primitives::{address, Address, U256},
+{%- endif %}
+    providers::{Provider, ProviderBuilder},
+    rpc::types::TransactionRequest,
+    signers::local::PrivateKeySigner,
+    sol,
+{%- if upgradeable %}
+    sol_types::{SolCall, SolValue},
+{%- endif %}
+};
+use eyre::{bail, eyre, Result, WrapErr};
+use std::io::Write;
+
+/// The precompile that activates Stylus programs
+const ARB_WASM: Address = address!("0000000000000000000000000000000000000071");
+{%- if upgradeable %}
+/// The StylusDeployer contract `cargo stylus deploy` uses, at the same address on
+/// Arbitrum chains and the Nitro devnode
+const STYLUS_DEPLOYER: Address = address!("cecba2f1dc234f70dd89f2041029807f8d03a990");
+{%- endif %}
+/// Marks Stylus code as brotli-compressed WASM without a dictionary
+const EOF_PREFIX_NO_DICT: [u8; 4] = [0xEF, 0xF0, 0x00, 0x00];
+
+sol! {
+    #[sol(rpc)]
+    interface IArbWasm {
+        function activateProgram(address program) external payable returns (uint16 version, uint256 dataFee);
+{%- if upgradeable %}
+        function codehashVersion(bytes32 codehash) external view returns (uint16 version);
+{%- endif %}
+    }
+
+    #[sol(rpc)]
+    interface I{{ name }} {
+{%- if token_type == "erc20" %}
+        function init(uint256 initial_supply{% if capped %}, uint256 cap{% endif %}) external;
+{%- elif uri_arg %}
+        function init(string uri) external;
+{%- else %}
+        function init() external;
+{%- endif %}
+    }
+{%- if upgradeable %}
+
+    #[sol(rpc)]
+    interface IStylusDeployer {
+        event ContractDeployed(address deployedContract);
+
+        function deploy(bytes bytecode, bytes initData, uint256 initValue, bytes32 salt) external payable returns (address);
+    }
+
+    #[sol(rpc)]
+    interface IProxy {
+        function implementation() external view returns (address);
+    }
+{%- endif %}
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+{%- if upgradeable %}
+    let implementation: Address = env("{{ prefix }}_IMPLEMENTATION")?.parse()?;
+    let address = deploy(&env("RPC_URL")?, &env("PRIVATE_KEY")?, &proxy_wasm()?, implementation).await?;
+    println!("{{ name }} deployed behind the proxy at {address}, running the implementation at {implementation}");
+{%- else %}
+    let address = deploy(&env("RPC_URL")?, &env("PRIVATE_KEY")?, &release_wasm()?).await?;
+    println!("{{ name }} deployed and initialized at {address}");
+{%- endif %}
+    Ok(())
+}
+{%- if upgradeable %}
+
+/// Deploy the proxy `wasm` through the StylusDeployer, which creates it and runs its
+/// constructor in one transaction, so that no one can point it elsewhere first; the
+/// constructor points it at `implementation` and initializes {{ name }} through it.
+/// Returns the proxy's address
+async fn deploy(rpc_url: &str, private_key: &str, wasm: &[u8], implementation: Address) -> Result<Address> {
+    let signer: PrivateKeySigner = private_key.parse().wrap_err("PRIVATE_KEY is not a valid private key")?;
+    let sender = signer.address();
+    let provider = ProviderBuilder::new().wallet(signer).connect_http(rpc_url.parse()?);
+    let code = contract_code(wasm)?;
+
+    // Activation is per code hash, so only the first proxy pays for it, through a
+    // bare deployment of the same code
+    let arb_wasm = IArbWasm::new(ARB_WASM, &provider);
+    if arb_wasm.codehashVersion(keccak256(&code)).call().await.is_err() {
+        let template = create(&provider, &code).await?;
+        activate(&provider, sender, template).await?;
+    }
+{%- if token_type == "erc20" %}
+
+    let initial_supply: U256 = env("{{ prefix }}_INITIAL_SUPPLY")?.parse()?;
+{%- if capped %}
+    let cap: U256 = env("{{ prefix }}_CAP")?.parse()?;
+{%- endif %}
+    let data = I{{ name }}::initCall { initial_supply{% if capped %}, cap{% endif %} }.abi_encode();
+{%- elif uri_arg %}
+
+    let data = I{{ name }}::initCall { uri: env("{{ prefix }}_URI")? }.abi_encode();
+{%- else %}
+
+    let data = I{{ name }}::initCall {}.abi_encode();
+{%- endif %}
+    // Stylus routes this selector to the `#[constructor]`, whatever its arguments
+    let mut constructor = keccak256("stylus_constructor()")[..4].to_vec();
+    constructor.extend((implementation, Bytes::from(data)).abi_encode_params());
+    let deployer = IStylusDeployer::new(STYLUS_DEPLOYER, &provider);
+    // alloy names the contract's `deploy` `deploy_call`, keeping `deploy` for its own constructor helper
+    let deployment = deployer.deploy_call(code.into(), constructor.into(), U256::ZERO, B256::ZERO);
+    let receipt = deployment.send().await?.get_receipt().await?;
+    if !receipt.status() {
+        bail!("deployment transaction {} reverted", receipt.transaction_hash);
+    }
+    let deployed = receipt
+        .decoded_log::<IStylusDeployer::ContractDeployed>()
+        .ok_or_else(|| eyre!("the deployer created no contract"))?;
+    Ok(deployed.deployedContract)
+}
+{%- else %}
+
+/// Deploy `wasm`, activate it and call `init`, returning the contract's address
+async fn deploy(rpc_url: &str, private_key: &str, wasm: &[u8]) -> Result<Address> {
+    let signer: PrivateKeySigner = private_key.parse().wrap_err("PRIVATE_KEY is not a valid private key")?;
+    let sender = signer.address();
+    let provider = ProviderBuilder::new().wallet(signer).connect_http(rpc_url.parse()?);
+
+    let address = create(&provider, &contract_code(wasm)?).await?;
+    activate(&provider, sender, address).await?;
+
+    let contract = I{{ name }}::new(address, &provider);
+{%- if token_type == "erc20" %}
+    let initial_supply: U256 = env("{{ prefix }}_INITIAL_SUPPLY")?.parse()?;
+{%- if capped %}
+    let cap: U256 = env("{{ prefix }}_CAP")?.parse()?;
+{%- endif %}
+    let init = contract.init(initial_supply{% if capped %}, cap{% endif %});
+{%- elif uri_arg %}
+    let init = contract.init(env("{{ prefix }}_URI")?);
+{%- else %}
+    let init = contract.init();
+{%- endif %}
+    let receipt = init.send().await?.get_receipt().await?;
+    if !receipt.status() {
+        bail!("init transaction {} reverted", receipt.transaction_hash);
+    }
+    Ok(address)
+}
+{%- endif %}
+
+/// Deploy `code` in a plain creation transaction, returning the contract's address
+async fn create(provider: &impl Provider, code: &[u8]) -> Result<Address> {
+    let deployment = TransactionRequest::default().with_deploy_code(deployment_calldata(code));
+    let receipt = provider.send_transaction(deployment).await?.get_receipt().await?;
+    if !receipt.status() {
+        bail!("deployment transaction {} reverted", receipt.transaction_hash);
+    }
+    receipt.contract_address.ok_or_else(|| eyre!("the deployment created no contract"))
+}
+
+/// Activate the program at `address` through the ArbWasm precompile
+async fn activate(provider: &impl Provider, sender: Address, address: Address) -> Result<()> {
+    // Activation charges a data fee; estimate it, then pay it with some headroom
+    let arb_wasm = IArbWasm::new(ARB_WASM, provider);
+    let balance = provider.get_balance(sender).await?;
+    let estimate = arb_wasm.activateProgram(address).value(balance).call().await?;
+    let fee = estimate.dataFee * U256::from(120) / U256::from(100);
+    arb_wasm.activateProgram(address).value(fee).send().await?.get_receipt().await?;
+    Ok(())
+}
+
+/// The contract code for `wasm`: compressed, behind the Stylus prefix
+fn contract_code(wasm: &[u8]) -> Result<Vec<u8>> {
+    let mut code = EOF_PREFIX_NO_DICT.to_vec();
+    {
+        let mut compressor = brotli::CompressorWriter::new(&mut code, 4096, 11, 22);
+        compressor.write_all(wasm)?;
+    }
+    Ok(code)
+}
+
+/// Creation code returning `code` as the contract's code
+fn deployment_calldata(code: &[u8]) -> Vec<u8> {
+    let mut calldata = vec![0x7f]; // PUSH32 the code length
+    calldata.extend(U256::from(code.len()).to_be_bytes::<32>());
+    calldata.extend([
+        0x80, // DUP1
+        0x60, 0x2b, // PUSH1 the offset of the code: this prelude and the version byte
+        0x60, 0x00, // PUSH1 0
+        0x39, // CODECOPY
+        0x60, 0x00, // PUSH1 0
+        0xf3, // RETURN
+        0x00, // Stylus version
+    ]);
+    calldata.extend(code);
+    calldata
+}
+
+{%- if upgradeable %}
+
+/// The proxy crate's release build
+fn proxy_wasm() -> Result<Vec<u8>> {
+    let path = format!("proxy/target/wasm32-unknown-unknown/release/{}_proxy.wasm", env!("CARGO_PKG_NAME").replace('-', "_"));
+    std::fs::read(&path).wrap_err_with(|| {
+        format!("{path} is missing; run `cargo build --release --lib --target wasm32-unknown-unknown` in proxy/")
+    })
+}
+{%- else %}
+
+/// The crate's release build
+fn release_wasm() -> Result<Vec<u8>> {
+    let path = format!("target/wasm32-unknown-unknown/release/{}.wasm", env!("CARGO_PKG_NAME").replace('-', "_"));
+    std::fs::read(&path)
+        .wrap_err_with(|| format!("{path} is missing; run `cargo build --release --lib --target wasm32-unknown-unknown`"))
+}
+{%- endif %}
+
+fn env(key: &str) -> Result<String> {
+    std::env::var(key).wrap_err_with(|| format!("{key} is not set; copy .env.example to .env"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn deployment_calldata_returns_the_compressed_wasm() {
+        let wasm = b"\0asm\x01\0\0\0";
+        let calldata = deployment_calldata(&contract_code(wasm).unwrap());
+
+        let code = &calldata[43..];
+        assert_eq!(U256::from_be_slice(&calldata[1..33]), U256::from(code.len()));
+        assert_eq!(code[..4], EOF_PREFIX_NO_DICT);
+        let mut decompressed = Vec::new();
+        brotli::Decompressor::new(&code[4..], 4096).read_to_end(&mut decompressed).unwrap();
+        assert_eq!(decompressed, wasm);
+    }
+
+{%- if upgradeable %}
+
+    #[tokio::test]
+    #[ignore = "needs the proxy's release WASM, the node in RPC_URL and an implementation deployed there"]
+    async fn deploys_and_initializes() {
+        dotenvy::dotenv().ok();
+        let rpc_url = env("RPC_URL").unwrap();
+        let implementation: Address = env("{{ prefix }}_IMPLEMENTATION").unwrap().parse().unwrap();
+
+        let address = deploy(&rpc_url, &env("PRIVATE_KEY").unwrap(), &proxy_wasm().unwrap(), implementation)
+            .await
+            .unwrap();
+
+        let provider = ProviderBuilder::new().connect_http(rpc_url.parse().unwrap());
+        assert_eq!(IProxy::new(address, &provider).implementation().call().await.unwrap(), implementation);
+    }
+{%- else %}
+
+    #[tokio::test]
+    #[ignore = "needs the release WASM and the node in RPC_URL, e.g. a local Nitro devnode"]
+    async fn deploys_and_initializes() {
+        dotenvy::dotenv().ok();
+        let rpc_url = env("RPC_URL").unwrap();
+
+        let address = deploy(&rpc_url, &env("PRIVATE_KEY").unwrap(), &release_wasm().unwrap()).await.unwrap();
+
+        let provider = ProviderBuilder::new().connect_http(rpc_url.parse().unwrap());
+        assert!(!provider.get_code_at(address).await.unwrap().is_empty());
+    }
+{%- endif %}
+}
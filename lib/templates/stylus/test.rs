@@ -0,0 +1,204 @@
+//! Unit tests for {{ name }}, run by `cargo test` on the motsu test harness
+
+use motsu::prelude::*;
+{%- if token_type in ["erc20", "erc721", "erc1155"] %}
+use stylus_sdk::alloy_primitives::{uint, Address, U256};
+{%- else %}
+use stylus_sdk::alloy_primitives::Address;
+{%- endif %}
+
+use super::*;
+{%- if token_type == "erc20" %}
+
+const INITIAL_SUPPLY: U256 = uint!({{ initial_supply }}_U256);
+{%- set init_args = "INITIAL_SUPPLY, U256::MAX" if "capped" in extensions else "INITIAL_SUPPLY" %}
+
+#[motsu::test]
+fn deploys_with_initial_supply(contract: Contract<{{ name }}>, alice: Address) {
+    contract.sender(alice).init({{ init_args }}).motsu_unwrap();
+
+    assert_eq!(contract.sender(alice).erc20.total_supply(), INITIAL_SUPPLY);
+    assert_eq!(contract.sender(alice).erc20.balance_of(alice), INITIAL_SUPPLY);
+}
+
+#[motsu::test]
+fn mints(contract: Contract<{{ name }}>, alice: Address, bob: Address) {
+    contract.sender(alice).init({{ init_args }}).motsu_unwrap();
+    let amount = uint!(1000_U256);
+
+    contract.sender(alice).mint(bob, amount).motsu_unwrap();
+
+    assert_eq!(contract.sender(alice).erc20.balance_of(bob), amount);
+    assert_eq!(contract.sender(alice).erc20.total_supply(), INITIAL_SUPPLY + amount);
+}
+
+#[motsu::test]
+fn transfers(contract: Contract<{{ name }}>, alice: Address, bob: Address) {
+    contract.sender(alice).init({{ init_args }}).motsu_unwrap();
+    let amount = INITIAL_SUPPLY / uint!(2_U256);
+
+    contract.sender(alice).transfer(bob, amount).motsu_unwrap();
+
+    assert_eq!(contract.sender(alice).erc20.balance_of(bob), amount);
+    assert_eq!(contract.sender(alice).erc20.balance_of(alice), INITIAL_SUPPLY - amount);
+}
+{%- if "pausable" in extensions %}
+
+#[motsu::test]
+fn pausing_blocks_transfers(contract: Contract<{{ name }}>, alice: Address, bob: Address) {
+    contract.sender(alice).init({{ init_args }}).motsu_unwrap();
+
+    contract.sender(alice).pause().motsu_unwrap();
+
+    assert!(contract.sender(alice).transfer(bob, uint!(1_U256)).is_err());
+}
+{%- endif %}
+{%- elif token_type == "erc721" %}
+
+#[motsu::test]
+fn deploys(contract: Contract<{{ name }}>, alice: Address) {
+    contract.sender(alice).init().motsu_unwrap();
+
+    assert_eq!(contract.sender(alice).erc721.balance_of(alice).motsu_unwrap(), U256::ZERO);
+}
+
+#[motsu::test]
+fn mints(contract: Contract<{{ name }}>, alice: Address) {
+    contract.sender(alice).init().motsu_unwrap();
+
+    let token_id = contract.sender(alice).mint(alice).motsu_unwrap();
+
+    assert_eq!(contract.sender(alice).erc721.owner_of(token_id).motsu_unwrap(), alice);
+    assert_eq!(contract.sender(alice).erc721.balance_of(alice).motsu_unwrap(), uint!(1_U256));
+}
+
+#[motsu::test]
+fn transfers(contract: Contract<{{ name }}>, alice: Address, bob: Address) {
+    contract.sender(alice).init().motsu_unwrap();
+    let token_id = contract.sender(alice).mint(alice).motsu_unwrap();
+
+    contract.sender(alice).transfer_from(alice, bob, token_id).motsu_unwrap();
+
+    assert_eq!(contract.sender(alice).erc721.owner_of(token_id).motsu_unwrap(), bob);
+}
+{%- if "pausable" in extensions %}
+
+#[motsu::test]
+fn pausing_blocks_minting(contract: Contract<{{ name }}>, alice: Address) {
+    contract.sender(alice).init().motsu_unwrap();
+
+    contract.sender(alice).pause().motsu_unwrap();
+
+    assert!(contract.sender(alice).mint(alice).is_err());
+}
+{%- endif %}
+{%- elif token_type == "erc1155" %}
+{%- set token = "supply" if "supply" in extensions else "erc1155" %}
+{%- set init_args = "" if base_uri else 'String::from("https://example.com/{id}.json")' %}
+
+const TOKEN_ID: U256 = uint!(1_U256);
+
+#[motsu::test]
+fn deploys(contract: Contract<{{ name }}>, alice: Address) {
+    contract.sender(alice).init({{ init_args }}).motsu_unwrap();
+
+    assert_eq!(contract.sender(alice).{{ token }}.balance_of(alice, TOKEN_ID), U256::ZERO);
+}
+
+#[motsu::test]
+fn mints(contract: Contract<{{ name }}>, alice: Address) {
+    contract.sender(alice).init({{ init_args }}).motsu_unwrap();
+    let amount = uint!(100_U256);
+
+    contract.sender(alice).mint(alice, TOKEN_ID, amount, Vec::new().into()).motsu_unwrap();
+
+    assert_eq!(contract.sender(alice).{{ token }}.balance_of(alice, TOKEN_ID), amount);
+}
+
+#[motsu::test]
+fn transfers(contract: Contract<{{ name }}>, alice: Address, bob: Address) {
+    contract.sender(alice).init({{ init_args }}).motsu_unwrap();
+    let amount = uint!(100_U256);
+    contract.sender(alice).mint(alice, TOKEN_ID, amount, Vec::new().into()).motsu_unwrap();
+
+    contract
+        .sender(alice)
+        .safe_transfer_from(alice, bob, TOKEN_ID, amount, Vec::new().into())
+        .motsu_unwrap();
+
+    assert_eq!(contract.sender(alice).{{ token }}.balance_of(alice, TOKEN_ID), U256::ZERO);
+    assert_eq!(contract.sender(alice).{{ token }}.balance_of(bob, TOKEN_ID), amount);
+}
+{%- if "pausable" in extensions %}
+
+#[motsu::test]
+fn pausing_blocks_minting(contract: Contract<{{ name }}>, alice: Address) {
+    contract.sender(alice).init({{ init_args }}).motsu_unwrap();
+
+    contract.sender(alice).pause().motsu_unwrap();
+
+    assert!(contract.sender(alice).mint(alice, TOKEN_ID, uint!(1_U256), Vec::new().into()).is_err());
+}
+{%- endif %}
+{%- else %}
+
+#[motsu::test]
+fn deploys(contract: Contract<{{ name }}>, alice: Address) {
+    contract.sender(alice).init().motsu_unwrap();
+
+    assert_eq!(contract.sender(alice).owner(), alice);
+}
+{%- endif %}
+{%- if token_type in ["erc20", "erc721", "erc1155"] %}
+{%- set init_args = init_args | default("") %}
+
+#[motsu::test]
+fn initializes_once(contract: Contract<{{ name }}>, alice: Address, bob: Address) {
+    contract.sender(alice).init({{ init_args }}).motsu_unwrap();
+
+    assert!(contract.sender(bob).init({{ init_args }}).is_err());
+    assert_eq!(contract.sender(alice).owner(), alice);
+}
+{%- if "pausable" in extensions %}
+
+#[motsu::test]
+fn only_the_owner_pauses(contract: Contract<{{ name }}>, alice: Address, bob: Address) {
+    contract.sender(alice).init({{ init_args }}).motsu_unwrap();
+
+    assert!(contract.sender(bob).pause().is_err());
+}
+{%- endif %}
+{%- set mint_args = {"erc20": "bob, uint!(1_U256)", "erc721": "bob", "erc1155": "bob, TOKEN_ID, uint!(1_U256), Vec::new().into()"}[token_type] %}
+
+#[motsu::test]
+fn only_the_owner_mints(contract: Contract<{{ name }}>, alice: Address, bob: Address) {
+    contract.sender(alice).init({{ init_args }}).motsu_unwrap();
+
+    assert!(contract.sender(bob).mint({{ mint_args }}).is_err());
+}
+{%- if "uristorage" in extensions %}
+
+#[motsu::test]
+fn only_the_owner_sets_token_uris(contract: Contract<{{ name }}>, alice: Address, bob: Address) {
+    contract.sender(alice).init({{ init_args }}).motsu_unwrap();
+{%- if token_type == "erc721" %}
+    let token_id = contract.sender(alice).mint(bob).motsu_unwrap();
+{%- else %}
+    let token_id = TOKEN_ID;
+{%- endif %}
+
+    assert!(contract.sender(bob).set_token_uri(token_id, String::from("token.json")).is_err());
+    contract.sender(alice).set_token_uri(token_id, String::from("token.json")).motsu_unwrap();
+}
+{%- endif %}
+{%- endif %}
+{%- if upgradeable %}
+{%- set init_args = init_args | default("") %}
+
+#[motsu::test]
+fn only_the_owner_upgrades(contract: Contract<{{ name }}>, alice: Address, bob: Address) {
+    contract.sender(alice).init({{ init_args }}).motsu_unwrap();
+
+    assert!(contract.sender(bob).upgrade_to_and_call(bob, Vec::new().into()).is_err());
+}
+{%- endif %}
@@ -4,8 +4,8 @@ use anyhow::Result;
 use clap::Command;
 use colored::*;
 use gramr::{
-    ContractType, GenericContractGenerator, LibraryGenerator, ProjectType, ScriptGenerator,
-    TestGenerator,
+    ContractType, GenericContractGenerator, LibraryGenerator, OverwritePolicy, ProjectType,
+    ScriptGenerator, TestGenerator,
 };
 use wizard::{ContractWizard, WizardState};
 
@@ -40,6 +40,9 @@ fn generate_from_state(state: WizardState) -> Result<()> {
     // Detect project
     let project = ProjectType::detect(&state.language)?;
 
+    // The wizard is interactive, so ask before replacing existing files
+    let overwrite = OverwritePolicy::Prompt;
+
     match state.resource_type.as_str() {
         "contract" => {
             let base_contract_type = state.contract_type.unwrap_or(ContractType::Basic);
@@ -63,7 +66,10 @@ fn generate_from_state(state: WizardState) -> Result<()> {
                 state.with_script,
                 Some(state.pragma),
                 Some(state.license),
-            );
+            )
+            .with_overwrite_policy(overwrite)
+            .with_defaults(state.defaults)
+            .with_token_metadata(state.token_metadata);
             generator.generate()?;
         }
 
@@ -74,14 +80,17 @@ fn generate_from_state(state: WizardState) -> Result<()> {
                 state.name,
                 Some(state.pragma),
                 Some(state.license),
-            );
+            )
+            .with_overwrite_policy(overwrite)
+            .with_defaults(state.defaults);
             generator.generate()?;
         }
 
         "script" => match project {
             ProjectType::Foundry(foundry_project) => {
                 let generator =
-                    ScriptGenerator::new(foundry_project, state.name, state.pragma, state.license);
+                    ScriptGenerator::new(foundry_project, state.name, state.pragma, state.license)
+                        .with_overwrite_policy(overwrite);
                 generator.generate()?;
             }
             _ => {
@@ -94,7 +103,8 @@ fn generate_from_state(state: WizardState) -> Result<()> {
         "test" => match project {
             ProjectType::Foundry(foundry_project) => {
                 let generator =
-                    TestGenerator::new(foundry_project, state.name, state.pragma, state.license);
+                    TestGenerator::new(foundry_project, state.name, state.pragma, state.license)
+                        .with_overwrite_policy(overwrite);
                 generator.generate()?;
             }
             _ => {
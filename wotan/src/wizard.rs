@@ -1,6 +1,6 @@
 use anyhow::Result;
 use colored::*;
-use gramr::{ContractType, Language, ProjectType, TokenExtension};
+use gramr::{AccessControl, ContractType, Defaults, ExtensionSpec, Language, OpenZeppelinVersion, ProjectType, StylusTemplate, TokenExtension, TokenMetadata};
 use inquire::{validator::Validation, Confirm, MultiSelect, Select, Text};
 use std::fs;
 use std::process::Command;
@@ -11,24 +11,29 @@ pub struct WizardState {
     pub language: Language,
     pub contract_type: Option<ContractType>,
     pub extensions: Vec<TokenExtension>,
+    pub token_metadata: TokenMetadata,
     pub with_test: bool,
     pub with_script: bool,
     pub pragma: String,
     pub license: String,
+    /// User and project defaults; the starting pragma and license come from here
+    pub defaults: Defaults,
 }
 
 impl WizardState {
-    pub fn new() -> Self {
+    pub fn new(defaults: Defaults) -> Self {
         Self {
             resource_type: String::new(),
             name: String::new(),
             language: Language::Solidity,
             contract_type: None,
             extensions: Vec::new(),
+            token_metadata: TokenMetadata::default(),
             with_test: false,
             with_script: false,
-            pragma: "0.8.30".to_string(),
-            license: "UNLICENSED".to_string(),
+            pragma: defaults.pragma.clone(),
+            license: defaults.license.clone(),
+            defaults,
         }
     }
 }
@@ -43,7 +48,7 @@ impl ContractWizard {
     pub fn run(&self) -> Result<WizardState> {
         self.print_welcome();
 
-        let mut state = WizardState::new();
+        let mut state = WizardState::new(Defaults::load()?);
 
         // Step 1: Choose resource type
         state.resource_type = self.choose_resource_type()?;
@@ -90,10 +95,7 @@ impl ContractWizard {
                 .bold()
                 .cyan()
         );
-        println!(
-            "{}",
-            "⚔️  Let's forge your smart contract step by step...\n"
-        );
+        println!("⚔️  Let's forge your smart contract step by step...\n");
     }
 
     fn choose_resource_type(&self) -> Result<String> {
@@ -171,17 +173,12 @@ impl ContractWizard {
             _ => unreachable!(),
         };
 
-        // Check for upgradeable (Solidity only)
+        // Tokens can be upgradeable; Stylus ones deploy behind the crate's ERC-1967 proxy
         let mut is_upgradeable = false;
-        if state.language == Language::Solidity && base_type != ContractType::Basic {
+        if base_type != ContractType::Basic {
             is_upgradeable = Confirm::new("Make this contract upgradeable?")
                 .with_default(false)
                 .prompt()?;
-        } else if state.language == Language::RustStylus && base_type != ContractType::Basic {
-            println!(
-                "{}",
-                "ℹ️  Upgradeable contracts are not yet supported for Rust/Stylus".yellow()
-            );
         }
 
         // Set contract type
@@ -193,46 +190,158 @@ impl ContractWizard {
             _ => unreachable!(),
         });
 
-        // Choose extensions (Solidity only for now)
-        if state.language == Language::Solidity && base_type != ContractType::Basic {
+        if base_type != ContractType::Basic {
             self.choose_extensions(state, &base_type)?;
-        } else if state.language == Language::RustStylus && base_type != ContractType::Basic {
-            println!(
-                "{}",
-                "ℹ️  Token extensions are not yet supported for Rust/Stylus".yellow()
-            );
+        }
+
+        if state.language == Language::Solidity && base_type != ContractType::Basic {
+            self.choose_access_control(state)?;
+        }
+
+        if base_type != ContractType::Basic {
+            self.configure_token_metadata(state, &base_type)?;
         }
 
         Ok(())
     }
 
-    fn choose_extensions(&self, state: &mut WizardState, base_type: &ContractType) -> Result<()> {
-        let available_extensions = match base_type {
-            ContractType::ERC20 => vec![
-                "burnable - Token burning capability",
-                "pausable - Emergency pause functionality",
-                "permit - Gasless approvals (EIP-2612)",
-                "capped - Maximum supply limit",
-                "votes - On-chain voting & delegation",
-                "wrapper - Wrap other ERC20 tokens",
-                "flashmint - Flash loan support",
-            ],
-            ContractType::ERC721 => vec![
-                "burnable - NFT burning capability",
-                "pausable - Emergency pause functionality",
-                "enumerable - Token enumeration",
-                "uristorage - Dynamic metadata URIs",
-                "royalty - ERC2981 royalty standard",
-                "votes - NFT-based voting",
-            ],
-            ContractType::ERC1155 => vec![
-                "burnable - Multi-token burning",
-                "pausable - Emergency pause functionality",
-                "supply - Track token supplies",
-                "uristorage - Per-token URI storage",
-            ],
-            _ => vec![],
+    fn choose_access_control(&self, state: &mut WizardState) -> Result<()> {
+        // Someone has to authorize upgrades and call the extensions' restricted functions
+        let base_type = state.contract_type.clone().unwrap_or(ContractType::Basic);
+        let contract_type = if state.extensions.is_empty() {
+            base_type
+        } else {
+            ContractType::MultiInheritance { base_type: Box::new(base_type), extensions: state.extensions.clone() }
+        };
+        let models: Vec<AccessControl> = AccessControl::ALL
+            .into_iter()
+            .filter(|access| {
+                access.check(&contract_type, &state.defaults.extensions, OpenZeppelinVersion::default()).is_ok()
+            })
+            .collect();
+        let options: Vec<String> = models
+            .iter()
+            .map(|access| format!("{} - {}", access, access.description()))
+            .collect();
+        let starting_cursor = models
+            .iter()
+            .position(|access| *access == state.defaults.access_control)
+            .unwrap_or(0);
+
+        let answer = Select::new("Who may mint, pause and upgrade?", options.clone())
+            .with_starting_cursor(starting_cursor)
+            .prompt()?;
+        let index = options.iter().position(|option| *option == answer).unwrap();
+        state.defaults.access_control = models[index];
+        Ok(())
+    }
+
+    fn configure_token_metadata(&self, state: &mut WizardState, base_type: &ContractType) -> Result<()> {
+        let details = match base_type {
+            ContractType::ERC20 => "name, symbol, decimals, initial supply",
+            ContractType::ERC721 => "name, symbol, base URI",
+            _ => "URI",
         };
+        let customize = Confirm::new(&format!("Customize token details ({})?", details))
+            .with_default(false)
+            .prompt()?;
+        if !customize {
+            return Ok(());
+        }
+
+        let metadata = &mut state.token_metadata;
+        if *base_type != ContractType::ERC1155 {
+            let name = Text::new("Token name:")
+                .with_default(&state.name)
+                .with_validator(|input: &str| -> Result<Validation, Box<dyn std::error::Error + Send + Sync>> {
+                    if input.is_empty() || input.contains(['"', '\\']) {
+                        Ok(Validation::Invalid("Name cannot be empty or contain quotes or backslashes".into()))
+                    } else {
+                        Ok(Validation::Valid)
+                    }
+                })
+                .prompt()?;
+            if name != state.name {
+                metadata.name = Some(name);
+            }
+
+            let derived_symbol = metadata.symbol(&state.name);
+            let symbol = Text::new("Token symbol:")
+                .with_default(&derived_symbol)
+                .with_validator(|input: &str| -> Result<Validation, Box<dyn std::error::Error + Send + Sync>> {
+                    if input.is_empty() || input.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+                        Ok(Validation::Invalid("Symbol cannot be empty or contain whitespace, quotes or backslashes".into()))
+                    } else {
+                        Ok(Validation::Valid)
+                    }
+                })
+                .prompt()?;
+            if symbol != derived_symbol {
+                metadata.symbol = Some(symbol);
+            }
+        }
+
+        if *base_type == ContractType::ERC20 {
+            let decimals = Text::new("Decimals:")
+                .with_default("18")
+                .with_validator(|input: &str| -> Result<Validation, Box<dyn std::error::Error + Send + Sync>> {
+                    match input.parse::<u8>() {
+                        Ok(_) => Ok(Validation::Valid),
+                        Err(_) => Ok(Validation::Invalid("Enter a number from 0 to 255".into())),
+                    }
+                })
+                .prompt()?;
+            metadata.decimals = decimals.parse().ok().filter(|decimals| *decimals != 18);
+
+            let supply = Text::new("Initial supply (whole tokens):")
+                .with_default(gramr::templates::DEFAULT_INITIAL_SUPPLY)
+                .with_validator(|input: &str| -> Result<Validation, Box<dyn std::error::Error + Send + Sync>> {
+                    let probe = TokenMetadata { initial_supply: Some(input.to_string()), ..Default::default() };
+                    match probe.check(&ContractType::ERC20) {
+                        Ok(()) => Ok(Validation::Valid),
+                        Err(_) => Ok(Validation::Invalid("Enter a whole number, e.g. 1000000 or 21e6".into())),
+                    }
+                })
+                .prompt()?;
+            if supply != gramr::templates::DEFAULT_INITIAL_SUPPLY {
+                metadata.initial_supply = Some(supply);
+            }
+        } else {
+            let prompt = if *base_type == ContractType::ERC721 { "Base URI:" } else { "URI:" };
+            let uri = Text::new(prompt)
+                .with_placeholder("e.g., https://api.example.com/tokens/{id}.json")
+                .with_help_message("Leave empty to fill it in later")
+                .with_validator(|input: &str| -> Result<Validation, Box<dyn std::error::Error + Send + Sync>> {
+                    if input.contains(['"', '\\']) {
+                        Ok(Validation::Invalid("URI cannot contain quotes or backslashes".into()))
+                    } else {
+                        Ok(Validation::Valid)
+                    }
+                })
+                .prompt()?;
+            if !uri.is_empty() {
+                metadata.base_uri = Some(uri);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn choose_extensions(&self, state: &mut WizardState, base_type: &ContractType) -> Result<()> {
+        let mut available = state.defaults.extensions.available(base_type);
+        if state.language == Language::RustStylus {
+            available.retain(|spec| StylusTemplate::supports_extension(&spec.id));
+        }
+        let available_extensions: Vec<String> = available
+            .iter()
+            .map(|spec| {
+                let name = spec.name.as_deref().unwrap_or(&spec.id);
+                match &spec.description {
+                    Some(description) => format!("{} - {}", name, description),
+                    None => name.to_string(),
+                }
+            })
+            .collect();
 
         if available_extensions.is_empty() {
             return Ok(());
@@ -243,12 +352,17 @@ impl ContractWizard {
             .prompt()?;
 
         if add_extensions {
-            let selected = MultiSelect::new("Select extensions:", available_extensions).prompt()?;
+            let selected = MultiSelect::new("Select extensions:", available_extensions.clone()).prompt()?;
 
             for extension in selected {
-                let ext_name = extension.split(" - ").next().unwrap();
-                if let Ok(parsed_ext) = self.parse_extension(ext_name, base_type) {
-                    state.extensions.push(parsed_ext);
+                if let Some(index) = available_extensions.iter().position(|option| *option == extension) {
+                    let spec = available[index];
+                    // Stylus parameters are `init` arguments, with no test or script to pass them
+                    let args = match state.language {
+                        Language::Solidity => self.choose_extension_args(spec)?,
+                        Language::RustStylus => Vec::new(),
+                    };
+                    state.extensions.push(TokenExtension::from_id(&spec.id).with_args(args));
                 }
             }
         }
@@ -256,41 +370,55 @@ impl ContractWizard {
         Ok(())
     }
 
-    fn parse_extension(&self, name: &str, base_type: &ContractType) -> Result<TokenExtension> {
-        use TokenExtension::*;
-
-        let extension = match (name, base_type) {
-            ("burnable", ContractType::ERC20) => ERC20Burnable,
-            ("pausable", ContractType::ERC20) => ERC20Pausable,
-            ("permit", ContractType::ERC20) => ERC20Permit,
-            ("capped", ContractType::ERC20) => ERC20Capped,
-            ("votes", ContractType::ERC20) => ERC20Votes,
-            ("wrapper", ContractType::ERC20) => ERC20Wrapper,
-            ("flashmint", ContractType::ERC20) => ERC20FlashMint,
-
-            ("burnable", ContractType::ERC721) => ERC721Burnable,
-            ("pausable", ContractType::ERC721) => ERC721Pausable,
-            ("enumerable", ContractType::ERC721) => ERC721Enumerable,
-            ("uristorage", ContractType::ERC721) => ERC721URIStorage,
-            ("royalty", ContractType::ERC721) => ERC721Royalty,
-            ("votes", ContractType::ERC721) => ERC721Votes,
-
-            ("burnable", ContractType::ERC1155) => ERC1155Burnable,
-            ("pausable", ContractType::ERC1155) => ERC1155Pausable,
-            ("supply", ContractType::ERC1155) => ERC1155Supply,
-            ("uristorage", ContractType::ERC1155) => ERC1155URIStorage,
-
-            _ => return Err(anyhow::anyhow!("Unknown extension: {}", name)),
-        };
+    /// Values for the extension's parameters, or none to deploy with the defaults
+    fn choose_extension_args(&self, spec: &ExtensionSpec) -> Result<Vec<String>> {
+        if spec.parameters.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        Ok(extension)
+        let defaults: Vec<String> = spec
+            .parameters
+            .iter()
+            .map(|parameter| format!("{} = {}", parameter.name, parameter.default))
+            .collect();
+        let set_values = Confirm::new(&format!(
+            "Set {} parameters? (otherwise tests and scripts use {})",
+            spec.name.as_deref().unwrap_or(&spec.id),
+            defaults.join(", ")
+        ))
+        .with_default(false)
+        .prompt()?;
+        if !set_values {
+            return Ok(Vec::new());
+        }
+
+        let mut args = Vec::new();
+        for parameter in &spec.parameters {
+            let prompt = format!("{}:", parameter.description.as_deref().unwrap_or(&parameter.name));
+            let is_address = parameter.solidity_type == "address";
+            let checked = parameter.clone();
+            let validator = move |input: &str| -> Result<Validation, Box<dyn std::error::Error + Send + Sync>> {
+                if checked.normalize(input.trim()).is_some() {
+                    Ok(Validation::Valid)
+                } else if is_address {
+                    Ok(Validation::Invalid("Enter a 0x-prefixed, 40-digit hex address".into()))
+                } else {
+                    Ok(Validation::Invalid("Enter a whole number, e.g. 500 or 1000000e18".into()))
+                }
+            };
+
+            let value = Text::new(&prompt)
+                .with_validator(validator)
+                .with_placeholder(&parameter.solidity_type)
+                .prompt()?;
+            args.push(value.trim().to_string());
+        }
+
+        Ok(spec.check_args(&args)?)
     }
 
     fn configure_library(&self, _state: &mut WizardState) -> Result<()> {
-        println!(
-            "{}",
-            "ℹ️  Libraries will contain basic utility functions and data structures"
-        );
+        println!("ℹ️  Libraries will contain basic utility functions and data structures");
         Ok(())
     }
 
@@ -300,7 +428,7 @@ impl ContractWizard {
                 "Scripts and tests are only supported for Solidity projects"
             ));
         }
-        println!("{}", "ℹ️  Basic script/test file will be generated");
+        println!("ℹ️  Basic script/test file will be generated");
         Ok(())
     }
 
@@ -313,8 +441,13 @@ impl ContractWizard {
             state.with_script = Confirm::new("Generate deployment script?")
                 .with_default(false)
                 .prompt()?;
-        } else if state.language == Language::RustStylus {
-            println!("{}", "ℹ️  Test and script generation not supported for Rust/Stylus (use cargo test and stylus deploy)".yellow());
+        } else if state.language == Language::RustStylus && state.resource_type == "contract" {
+            state.with_test = Confirm::new("Generate motsu unit tests?")
+                .with_default(true)
+                .prompt()?;
+            state.with_script = Confirm::new("Generate a deploy program (alloy example, .env.example, cargo stylus wrapper)?")
+                .with_default(false)
+                .prompt()?;
         }
 
         Ok(())
@@ -344,8 +477,15 @@ impl ContractWizard {
                 "Custom...",
             ];
 
-            let license_choice =
-                Select::new("SPDX License Identifier:", license_options).prompt()?;
+            // Start on the configured license when it is one of the listed ones
+            let starting_cursor = license_options
+                .iter()
+                .position(|license| *license == state.license)
+                .unwrap_or(0);
+
+            let license_choice = Select::new("SPDX License Identifier:", license_options)
+                .with_starting_cursor(starting_cursor)
+                .prompt()?;
 
             if license_choice == "Custom..." {
                 state.license = Text::new("Enter custom license:")
@@ -370,10 +510,40 @@ impl ContractWizard {
         }
 
         if !state.extensions.is_empty() {
-            println!("  {} {:?}", "Extensions:".bold(), state.extensions);
+            let extensions: Vec<String> = state
+                .extensions
+                .iter()
+                .map(|e| match e.args() {
+                    [] => e.to_string(),
+                    args => format!("{}={}", e, args.join(":")),
+                })
+                .collect();
+            println!("  {} {}", "Extensions:".bold(), extensions.join(", "));
+        }
+
+        let metadata = &state.token_metadata;
+        if metadata.name.is_some() || metadata.symbol.is_some() {
+            println!(
+                "  {} {} ({})",
+                "Token:".bold(),
+                metadata.token_name(&state.name),
+                metadata.symbol(&state.name)
+            );
+        }
+        if let Some(decimals) = metadata.decimals {
+            println!("  {} {}", "Decimals:".bold(), decimals);
+        }
+        if let Some(supply) = &metadata.initial_supply {
+            println!("  {} {}", "Initial Supply:".bold(), supply);
+        }
+        if let Some(uri) = &metadata.base_uri {
+            println!("  {} {}", "Base URI:".bold(), uri);
         }
 
         if state.language == Language::Solidity {
+            if state.contract_type.as_ref().is_some_and(|t| *t != ContractType::Basic) {
+                println!("  {} {}", "Access Control:".bold(), state.defaults.access_control);
+            }
             println!("  {} {}", "Pragma:".bold(), state.pragma);
             println!("  {} {}", "License:".bold(), state.license);
 
@@ -383,6 +553,13 @@ impl ContractWizard {
             if state.with_script {
                 println!("  {} Yes", "Generate Script:".bold());
             }
+        } else {
+            if state.with_test {
+                println!("  {} Yes (motsu)", "Generate Test:".bold());
+            }
+            if state.with_script {
+                println!("  {} Yes (alloy example)", "Generate Deploy Program:".bold());
+            }
         }
 
         let confirmed = Confirm::new("\nGenerate files?")
@@ -439,7 +616,7 @@ impl ContractWizard {
         }
 
         // Try forge init first
-        let output = Command::new("forge").args(&["init", "."]).output()?;
+        let output = Command::new("forge").args(["init", "."]).output()?;
 
         if output.status.success() {
             println!(
@@ -471,7 +648,7 @@ impl ContractWizard {
 
             // Try forge init --force
             let force_output = Command::new("forge")
-                .args(&["init", "--force", "."])
+                .args(["init", "--force", "."])
                 .output()?;
 
             if !force_output.status.success() {